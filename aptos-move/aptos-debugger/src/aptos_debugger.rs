@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::fork::ForkedState;
 use anyhow::{format_err, Result};
 use aptos_gas_meter::{StandardGasAlgebra, StandardGasMeter};
 use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
@@ -70,6 +71,15 @@ impl AptosDebugger {
         .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))
     }
 
+    /// Creates a local fork of the state right before `version`, on which transactions can be
+    /// simulated one after the other.
+    pub fn fork_at_version(&self, version: Version) -> ForkedState {
+        ForkedState::new(
+            DebuggerStateView::new(self.debugger.clone(), version),
+            version,
+        )
+    }
+
     pub fn execute_transaction_at_version_with_gas_profiler(
         &self,
         version: Version,
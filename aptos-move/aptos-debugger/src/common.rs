@@ -3,8 +3,10 @@
 
 use crate::{execute_past_transactions, execute_pending_block};
 use anyhow::Result;
+use aptos_block_executor::contention::{ContentionReportFileDumper, CONTENTION_REPORT_LIMIT};
+use aptos_vm::AptosVM;
 use clap::Parser;
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 #[derive(Parser)]
 #[clap(group(clap::ArgGroup::new("target")
@@ -29,6 +31,24 @@ pub struct Opts {
 
     #[clap(long, default_value_t = 1)]
     pub(crate) concurrency_level: usize,
+
+    /// Append a Block-STM contention report (top conflicting keys, incarnations and
+    /// dependency chains) for every block executed in parallel to this file.
+    #[clap(long)]
+    pub(crate) contention_report_path: Option<PathBuf>,
+}
+
+impl Opts {
+    pub(crate) fn init_vm(&self) -> Result<()> {
+        AptosVM::set_concurrency_level_once(self.concurrency_level);
+        if let Some(path) = &self.contention_report_path {
+            AptosVM::set_contention_report_hook_once(Arc::new(ContentionReportFileDumper::new(
+                path,
+                CONTENTION_REPORT_LIMIT,
+            )?));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Parser)]
@@ -4,7 +4,6 @@
 use crate::{aptos_debugger::AptosDebugger, common::Opts};
 use anyhow::Result;
 use aptos_rest_client::Client;
-use aptos_vm::AptosVM;
 use clap::Parser;
 use url::Url;
 
@@ -25,7 +24,7 @@ pub struct Command {
 
 impl Command {
     pub async fn run(self) -> Result<()> {
-        AptosVM::set_concurrency_level_once(self.opts.concurrency_level);
+        self.opts.init_vm()?;
 
         let debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
             AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
@@ -0,0 +1,382 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{format_err, Result};
+use aptos_crypto::hash::CryptoHash;
+use aptos_state_view::TStateView;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    block_executor::config::BlockExecutorConfigFromOnchain,
+    chain_id::ChainId,
+    on_chain_config::OnChainConfig,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::{
+        signature_verified_transaction::SignatureVerifiedTransaction, SignedTransaction,
+        Transaction, TransactionOutput, TransactionStatus, Version,
+    },
+    vm_status::VMStatus,
+    write_set::{TransactionWrite, WriteSet},
+};
+use aptos_validator_interface::DebuggerStateView;
+use aptos_vm::{data_cache::AsMoveResolver, AptosSimulationVM, AptosVM, VMExecutor};
+use move_core_types::move_resource::MoveResource;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Subdirectory of a persisted fork caching the state read from the base.
+const STATE_CACHE_DIR: &str = "state_cache";
+/// Subdirectory of a persisted fork holding the overlay.
+const OVERLAY_DIR: &str = "overlay";
+
+/// A local fork of the chain state at some version. Reads fall through to the (remote) state at
+/// that version, while the writes of the transactions executed on the fork are kept in a local
+/// overlay, so that each transaction sees the effects of the previous ones.
+pub struct ForkedState {
+    base: DebuggerStateView,
+    version: Version,
+    /// `None` marks a state value deleted on the fork.
+    overlay: HashMap<StateKey, Option<StateValue>>,
+    /// Directory the fork is persisted in, if any. See `persist_in`.
+    dir: Option<PathBuf>,
+}
+
+impl ForkedState {
+    pub fn new(base: DebuggerStateView, version: Version) -> Self {
+        Self {
+            base,
+            version,
+            overlay: HashMap::new(),
+            dir: None,
+        }
+    }
+
+    /// Persists the fork in `dir`: the state read from the base is cached there so that it is
+    /// fetched only once, and the overlay is written there, so that a fork persisted in the same
+    /// directory before picks up where it left off.
+    pub fn persist_in(mut self, dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(dir.join(STATE_CACHE_DIR))?;
+        fs::create_dir_all(dir.join(OVERLAY_DIR))?;
+        for entry in fs::read_dir(dir.join(OVERLAY_DIR))? {
+            let path = entry?.path();
+            if path.extension().is_none() {
+                let (state_key, value) = bcs::from_bytes(&fs::read(path)?)?;
+                self.overlay.insert(state_key, value);
+            }
+        }
+        self.dir = Some(dir);
+        Ok(self)
+    }
+
+    /// The version the fork was created at.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Number of state values written on the fork so far.
+    pub fn num_overlay_writes(&self) -> usize {
+        self.overlay.len()
+    }
+
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) -> Result<()> {
+        for (state_key, write_op) in write_set.iter() {
+            self.write(state_key.clone(), write_op.as_state_value())?;
+        }
+        Ok(())
+    }
+
+    /// Simulates a transaction on the fork, without verifying its signature, like the
+    /// `/transactions/simulate` API does. If the transaction is kept, its writes are applied to
+    /// the fork.
+    pub fn simulate_transaction(
+        &mut self,
+        txn: &SignedTransaction,
+    ) -> Result<(VMStatus, TransactionOutput)> {
+        let (vm_status, output) =
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(txn, &*self);
+        if let TransactionStatus::Keep(_) = output.status() {
+            self.apply_write_set(output.write_set())?;
+        }
+        Ok((vm_status, output))
+    }
+
+    /// Executes a transaction on the fork like a node would, in a block of its own and with its
+    /// signature verified. If the transaction is kept, its writes are applied to the fork.
+    pub fn execute_transaction(&mut self, txn: SignedTransaction) -> Result<TransactionOutput> {
+        let txns = [SignatureVerifiedTransaction::from(
+            Transaction::UserTransaction(txn),
+        )];
+        let output = AptosVM::execute_block(
+            &txns,
+            &*self,
+            BlockExecutorConfigFromOnchain::new_no_block_limit(),
+        )
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format_err!("No output for the transaction"))?;
+        if let TransactionStatus::Keep(_) = output.status() {
+            self.apply_write_set(output.write_set())?;
+        }
+        Ok(output)
+    }
+
+    pub fn chain_id(&self) -> Result<ChainId> {
+        ChainId::fetch_config(&self.as_move_resolver())
+            .ok_or_else(|| format_err!("ChainId not found on the fork"))
+    }
+
+    pub fn get_resource<T: MoveResource>(&self, address: AccountAddress) -> Result<Option<T>> {
+        self.get_state_value_bytes(&Self::resource_key::<T>(address)?)?
+            .map(|bytes| bcs::from_bytes(&bytes))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Replaces the authentication key of an existing account on the fork, so that transactions
+    /// can be simulated on behalf of the account without its private key.
+    pub fn set_authentication_key(
+        &mut self,
+        address: AccountAddress,
+        authentication_key: Vec<u8>,
+    ) -> Result<()> {
+        let mut account = self
+            .get_resource::<AccountResource>(address)?
+            .ok_or_else(|| format_err!("Account {} not found on the fork", address))?;
+        account.set_authentication_key(authentication_key);
+        self.write(
+            Self::resource_key::<AccountResource>(address)?,
+            Some(StateValue::from(bcs::to_bytes(&account)?)),
+        )
+    }
+
+    fn write(&mut self, state_key: StateKey, value: Option<StateValue>) -> Result<()> {
+        if let Some(dir) = &self.dir {
+            write_file(
+                &dir.join(OVERLAY_DIR).join(state_key.hash().to_hex()),
+                &bcs::to_bytes(&(&state_key, &value))?,
+            )?;
+        }
+        self.overlay.insert(state_key, value);
+        Ok(())
+    }
+
+    fn resource_key<T: MoveResource>(address: AccountAddress) -> Result<StateKey> {
+        Ok(StateKey::access_path(AccessPath::resource_access_path(
+            address,
+            T::struct_tag(),
+        )?))
+    }
+}
+
+impl TStateView for ForkedState {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if let Some(value) = self.overlay.get(state_key) {
+            return Ok(value.clone());
+        }
+        let cache_path = self
+            .dir
+            .as_ref()
+            .map(|dir| dir.join(STATE_CACHE_DIR).join(state_key.hash().to_hex()));
+        match cache_path {
+            Some(path) if path.exists() => Ok(bcs::from_bytes(&fs::read(path)?)?),
+            Some(path) => {
+                let value = self.base.get_state_value(state_key)?;
+                write_file(&path, &bcs::to_bytes(&value)?)?;
+                Ok(value)
+            },
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
+
+/// Writes a file through a temporary file, so that readers never see it partially written, even
+/// if several threads write it at once.
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+    let tmp_path = path.with_extension(format!(
+        "tmp{}",
+        NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_storage_interface::DbReader;
+    use aptos_types::{
+        account_config::{aptos_test_root_address, CoinStoreResource},
+        transaction::{ExecutionStatus, RawTransaction},
+    };
+    use aptos_validator_interface::DBDebuggerInterface;
+    use aptos_vm_genesis::{test_genesis_change_set_and_validators, GENESIS_KEYPAIR};
+    use std::sync::{Arc, Mutex};
+
+    /// The genesis state, recording every read.
+    struct GenesisDb {
+        state: HashMap<StateKey, StateValue>,
+        reads: Mutex<Vec<(StateKey, Version)>>,
+    }
+
+    impl GenesisDb {
+        fn new() -> Self {
+            let (change_set, _) = test_genesis_change_set_and_validators(Some(1));
+            let state = change_set
+                .write_set()
+                .iter()
+                .filter_map(|(state_key, write_op)| {
+                    write_op
+                        .as_state_value()
+                        .map(|value| (state_key.clone(), value))
+                })
+                .collect();
+            Self {
+                state,
+                reads: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    impl DbReader for GenesisDb {
+        fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> Result<Option<StateValue>> {
+            self.reads
+                .lock()
+                .unwrap()
+                .push((state_key.clone(), version));
+            Ok(self.state.get(state_key).cloned())
+        }
+    }
+
+    /// Forks the genesis state, i.e. the state right after version 0.
+    fn fork(db: &Arc<GenesisDb>) -> ForkedState {
+        let version = 1;
+        ForkedState::new(
+            DebuggerStateView::new(Arc::new(DBDebuggerInterface::new(db.clone())), version),
+            version,
+        )
+    }
+
+    fn transfer(
+        fork: &ForkedState,
+        sequence_number: u64,
+        receiver: AccountAddress,
+        amount: u64,
+    ) -> SignedTransaction {
+        let (private_key, public_key) = &*GENESIS_KEYPAIR;
+        RawTransaction::new(
+            aptos_test_root_address(),
+            sequence_number,
+            aptos_stdlib::aptos_account_transfer(receiver, amount),
+            1_000_000,
+            100,
+            u64::MAX,
+            fork.chain_id().unwrap(),
+        )
+        .sign(private_key, public_key.clone())
+        .unwrap()
+        .into_inner()
+    }
+
+    fn assert_success(output: &TransactionOutput) {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_writes_are_visible_to_next_transaction() {
+        let db = Arc::new(GenesisDb::new());
+        let mut fork = fork(&db);
+        let receiver = AccountAddress::random();
+
+        // The second transfer is only valid if the sequence number bumped by the first one is
+        // read from the overlay, and it adds to the account created by the first one.
+        let output = fork
+            .execute_transaction(transfer(&fork, 0, receiver, 100))
+            .unwrap();
+        assert_success(&output);
+        let output = fork
+            .execute_transaction(transfer(&fork, 1, receiver, 200))
+            .unwrap();
+        assert_success(&output);
+
+        let coin_store = fork
+            .get_resource::<CoinStoreResource>(receiver)
+            .unwrap()
+            .unwrap();
+        assert_eq!(coin_store.coin(), 300);
+        let root = fork
+            .get_resource::<AccountResource>(aptos_test_root_address())
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.sequence_number(), 2);
+
+        // A transaction that reuses a sequence number is discarded and writes nothing.
+        let num_overlay_writes = fork.num_overlay_writes();
+        let output = fork
+            .execute_transaction(transfer(&fork, 1, receiver, 400))
+            .unwrap();
+        assert!(output.status().is_discarded());
+        assert_eq!(fork.num_overlay_writes(), num_overlay_writes);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_base_state_is_only_read_through() {
+        let db = Arc::new(GenesisDb::new());
+        let mut fork = fork(&db);
+        let receiver = AccountAddress::random();
+
+        let output = fork
+            .execute_transaction(transfer(&fork, 0, receiver, 100))
+            .unwrap();
+        assert_success(&output);
+
+        // The base is read at the version the fork was created at, and is left as it is.
+        let reads = db.reads.lock().unwrap().drain(..).collect::<Vec<_>>();
+        assert!(!reads.is_empty());
+        assert!(reads.iter().all(|(_, version)| *version == 0));
+        let receiver_key = ForkedState::resource_key::<CoinStoreResource>(receiver).unwrap();
+        assert!(!db.state.contains_key(&receiver_key));
+        let root_key =
+            ForkedState::resource_key::<AccountResource>(aptos_test_root_address()).unwrap();
+        let base_root: AccountResource = bcs::from_bytes(db.state[&root_key].bytes()).unwrap();
+        assert_eq!(base_root.sequence_number(), 0);
+
+        // State written on the fork is served by the overlay, without reading the base.
+        assert!(fork.get_state_value(&receiver_key).unwrap().is_some());
+        let root = fork
+            .get_resource::<AccountResource>(aptos_test_root_address())
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.sequence_number(), 1);
+        assert!(db.reads.lock().unwrap().is_empty());
+
+        // State not written on the fork is read through.
+        let other_key =
+            ForkedState::resource_key::<AccountResource>(AccountAddress::random()).unwrap();
+        assert_eq!(fork.get_state_value(&other_key).unwrap(), None);
+        assert_eq!(*db.reads.lock().unwrap(), vec![(other_key, 0)]);
+    }
+}
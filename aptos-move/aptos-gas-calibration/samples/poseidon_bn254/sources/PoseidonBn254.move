@@ -0,0 +1,57 @@
+module poseidon_bn254_sample::poseidon_bn254_sample {
+    use aptos_std::poseidon_bn254;
+    use std::vector;
+
+    /// Hashes `num_inputs` inputs 10 times per iteration.
+    fun calibrate_hash_impl(num_inputs: u64, num_iterations: u64) {
+        let inputs = vector[];
+        let j = 0;
+        while (j < num_inputs) {
+            vector::push_back(&mut inputs, x"0100000000000000000000000000000000000000000000000000000000000000");
+            j = j + 1;
+        };
+
+        let i = 0;
+        while (i < num_iterations) {
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            poseidon_bn254::hash(inputs);
+            i = i + 1;
+        }
+    }
+
+    public entry fun calibrate_poseidon_bn254_1_input_x100() {
+        calibrate_hash_impl(1, 10);
+    }
+
+    public entry fun calibrate_poseidon_bn254_1_input_x500() {
+        calibrate_hash_impl(1, 50);
+    }
+
+    public entry fun calibrate_poseidon_bn254_2_inputs_x100() {
+        calibrate_hash_impl(2, 10);
+    }
+
+    public entry fun calibrate_poseidon_bn254_4_inputs_x100() {
+        calibrate_hash_impl(4, 10);
+    }
+
+    public entry fun calibrate_poseidon_bn254_8_inputs_x100() {
+        calibrate_hash_impl(8, 10);
+    }
+
+    public entry fun calibrate_poseidon_bn254_12_inputs_x100() {
+        calibrate_hash_impl(12, 10);
+    }
+
+    public entry fun calibrate_poseidon_bn254_12_inputs_x500() {
+        calibrate_hash_impl(12, 50);
+    }
+}
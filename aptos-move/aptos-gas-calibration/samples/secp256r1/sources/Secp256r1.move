@@ -0,0 +1,75 @@
+module secp256r1_sample::secp256r1_sample {
+    use aptos_std::secp256r1;
+    use std::vector;
+
+    /// Created using sk = x"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+    const PUBLIC_KEY: vector<u8> = x"04d8cd12ea5c67f2f8a00c1124893edcfa6754c4d6cede6be13bdf2295c810a97fa5a89d2d2a360c0ca9a4d6c7c9ed4b28d3e199d6627f2e696d689c310a5b0f48";
+
+    /// A signature on "test aptos secp256r1" under the key above, in canonical (low-S) form.
+    const SIGNATURE: vector<u8> = x"c1a6b162403b227eee8033fdf7be4b99cc056b52d6749cf34c6fc5caf1db82fa0fbb271e9a97950ebe460da192574e0b0ffd3728320c1e974e2782c8570e4560";
+
+    /// The same signature with a high S, which fails to deserialize.
+    const HIGH_S_SIGNATURE: vector<u8> = x"c1a6b162403b227eee8033fdf7be4b99cc056b52d6749cf34c6fc5caf1db82faf044d8e065686af241b9f25e6da8b1f4ace9c385750b7feda59247faa554dff1";
+
+    /// Not a point on the curve, which fails to deserialize.
+    const INVALID_PUBLIC_KEY: vector<u8> = x"0400000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+
+    /// Verifies the signature on a message of `msg_len` bytes 10 times per iteration.
+    fun calibrate_verify_impl(pk: vector<u8>, sig: vector<u8>, msg_len: u64, num_iterations: u64) {
+        let pk = secp256r1::ecdsa_raw_public_key_from_65_bytes(pk);
+        let sig = secp256r1::ecdsa_signature_from_bytes(sig);
+        let msg = vector[];
+        let j = 0;
+        while (j < msg_len) {
+            vector::push_back(&mut msg, 0x61);
+            j = j + 1;
+        };
+
+        let i = 0;
+        while (i < num_iterations) {
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            secp256r1::ecdsa_verify(msg, &pk, &sig);
+            i = i + 1;
+        }
+    }
+
+    public entry fun calibrate_secp256r1_verify_x100() {
+        calibrate_verify_impl(PUBLIC_KEY, SIGNATURE, 20, 10);
+    }
+
+    public entry fun calibrate_secp256r1_verify_x500() {
+        calibrate_verify_impl(PUBLIC_KEY, SIGNATURE, 20, 50);
+    }
+
+    public entry fun calibrate_secp256r1_verify_long_msg_x100() {
+        calibrate_verify_impl(PUBLIC_KEY, SIGNATURE, 10000, 10);
+    }
+
+    public entry fun calibrate_secp256r1_verify_long_msg_x500() {
+        calibrate_verify_impl(PUBLIC_KEY, SIGNATURE, 10000, 50);
+    }
+
+    public entry fun calibrate_secp256r1_high_s_sig_x100() {
+        calibrate_verify_impl(PUBLIC_KEY, HIGH_S_SIGNATURE, 20, 10);
+    }
+
+    public entry fun calibrate_secp256r1_high_s_sig_x500() {
+        calibrate_verify_impl(PUBLIC_KEY, HIGH_S_SIGNATURE, 20, 50);
+    }
+
+    public entry fun calibrate_secp256r1_invalid_pk_x100() {
+        calibrate_verify_impl(INVALID_PUBLIC_KEY, SIGNATURE, 20, 10);
+    }
+
+    public entry fun calibrate_secp256r1_invalid_pk_x500() {
+        calibrate_verify_impl(INVALID_PUBLIC_KEY, SIGNATURE, 20, 50);
+    }
+}
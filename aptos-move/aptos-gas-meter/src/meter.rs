@@ -502,4 +502,10 @@ where
             .charge_execution(MIN_TRANSACTION_GAS_UNITS + INTRINSIC_GAS_PER_BYTE * excess)
             .map_err(|e| e.finish(Location::Undefined))
     }
+
+    fn charge_webauthn_signature_verification(&mut self, num_signatures: NumArgs) -> VMResult<()> {
+        self.algebra
+            .charge_execution(WEBAUTHN_SIGNATURE_VERIFICATION * num_signatures)
+            .map_err(|e| e.finish(Location::Undefined))
+    }
 }
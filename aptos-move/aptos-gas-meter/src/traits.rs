@@ -12,7 +12,7 @@ use aptos_vm_types::{
     },
 };
 use move_binary_format::errors::{Location, PartialVMResult, VMResult};
-use move_core_types::gas_algebra::{InternalGas, InternalGasUnit, NumBytes};
+use move_core_types::gas_algebra::{InternalGas, InternalGasUnit, NumArgs, NumBytes};
 use move_vm_types::gas::GasMeter as MoveGasMeter;
 use std::fmt::Debug;
 
@@ -107,6 +107,9 @@ pub trait AptosGasMeter: MoveGasMeter {
     /// for bigger ones.
     fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
 
+    /// Charges the cost of verifying the WebAuthn signatures in the transaction authenticator.
+    fn charge_webauthn_signature_verification(&mut self, num_signatures: NumArgs) -> VMResult<()>;
+
     /// Charges IO gas for an item in the write set.
     ///
     /// This is to be differentiated from the storage fee, which is meant to cover the long-term
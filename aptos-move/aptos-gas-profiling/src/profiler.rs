@@ -588,6 +588,17 @@ where
 
         res
     }
+
+    fn charge_webauthn_signature_verification(&mut self, num_signatures: NumArgs) -> VMResult<()> {
+        let (cost, res) = self
+            .delegate_charge(|base| base.charge_webauthn_signature_verification(num_signatures));
+
+        // The signature verification is reported as part of the intrinsic cost.
+        self.intrinsic_cost = Some(self.intrinsic_cost.unwrap_or_else(|| 0.into()) + cost);
+        self.total_exec_io += cost;
+
+        res
+    }
 }
 
 impl<G> GasProfiler<G>
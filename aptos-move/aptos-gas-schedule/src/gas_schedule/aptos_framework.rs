@@ -186,6 +186,20 @@ crate::gas_schedule::macros::define_gas_parameters!(
         [secp256k1_base: InternalGas, "secp256k1.base", 3000],
         [secp256k1_ecdsa_recover: InternalGasPerArg, "secp256k1.ecdsa_recover", 32200000],
 
+        // Calibrated with the secp256r1 samples of aptos-gas-calibration. The base is always
+        // charged along with the public key deserialization, so only their sum is calibrated.
+        [secp256r1_base: InternalGas, { 14.. => "secp256r1.base" }, 3000],
+        [secp256r1_per_pubkey_deserialize: InternalGasPerArg, { 14.. => "secp256r1.per_pubkey_deserialize" }, 555_000],
+        [secp256r1_per_sig_deserialize: InternalGasPerArg, { 14.. => "secp256r1.per_sig_deserialize" }, 26_000],
+        [secp256r1_per_sig_verify: InternalGasPerArg, { 14.. => "secp256r1.per_sig_verify" }, 39_100_000],
+        // Using SHA2-256's cost, as the calibration cannot tell it apart from copying the message
+        [secp256r1_per_msg_byte_hashing: InternalGasPerByte, { 14.. => "secp256r1.per_msg_byte_hashing" }, 1_000], // 50 * 20
+
+        // Calibrated with the poseidon_bn254 samples of aptos-gas-calibration, which fit a
+        // negligible base
+        [poseidon_bn254_base: InternalGas, { 14.. => "poseidon_bn254.base" }, 0],
+        [poseidon_bn254_per_input: InternalGasPerArg, { 14.. => "poseidon_bn254.per_input" }, 4_800_000],
+
         [ristretto255_basepoint_mul: InternalGasPerArg, "ristretto255.basepoint_mul", 2560000],
         [ristretto255_basepoint_double_mul: InternalGasPerArg, "ristretto255.basepoint_double_mul", 8800000],
 
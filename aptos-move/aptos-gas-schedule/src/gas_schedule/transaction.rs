@@ -175,6 +175,15 @@ crate::gas_schedule::macros::define_gas_parameters!(
             max_storage_fee: Fee,
             { 7.. => "max_storage_fee" },
             2_0000_0000, // 2 APT
+        ],
+        // The cost of verifying a WebAuthn signature (i.e., a secp256r1 signature over the
+        // authenticator data and client data) in the transaction authenticator. This is charged
+        // in addition to the intrinsic gas, as the authenticator is not part of the transaction
+        // size.
+        [
+            webauthn_signature_verification: InternalGasPerArg,
+            { 14.. => "webauthn_signature_verification" },
+            32_200_000,
         ]
     ]
 );
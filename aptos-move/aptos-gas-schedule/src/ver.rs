@@ -8,6 +8,9 @@
 ///   - Changing how gas is calculated in any way
 ///
 /// Change log:
+/// - V14
+///   - Gas for verifying WebAuthn signatures in transaction authenticators.
+///   - Added secp256r1 ECDSA verification and Poseidon (BN254) hashing natives.
 /// - V12
 ///   - Making resource group charge on first read independent of BTreeMap serialization.
 ///   - Added BN254 operations.
@@ -45,4 +48,4 @@
 ///       global operations.
 /// - V1
 ///   - TBA
-pub const LATEST_GAS_FEATURE_VERSION: u64 = 13;
+pub const LATEST_GAS_FEATURE_VERSION: u64 = 14;
@@ -475,5 +475,10 @@ where
         ) -> PartialVMResult<()>;
 
         fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+
+        fn charge_webauthn_signature_verification(
+            &mut self,
+            num_signatures: NumArgs,
+        ) -> VMResult<()>;
     }
 }
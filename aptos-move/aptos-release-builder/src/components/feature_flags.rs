@@ -94,6 +94,9 @@ pub enum FeatureFlag {
     ResourceGroupsChargeAsSizeSum,
     CommissionChangeDelegationPool,
     BN254Structures,
+    WebAuthnSignature,
+    Secp256r1Natives,
+    PoseidonBn254Natives,
 }
 
 fn generate_features_blob(writer: &CodeWriter, data: &[u64]) {
@@ -246,6 +249,9 @@ impl From<FeatureFlag> for AptosFeatureFlag {
                 AptosFeatureFlag::COMMISSION_CHANGE_DELEGATION_POOL
             },
             FeatureFlag::BN254Structures => AptosFeatureFlag::BN254_STRUCTURES,
+            FeatureFlag::WebAuthnSignature => AptosFeatureFlag::WEBAUTHN_SIGNATURE,
+            FeatureFlag::Secp256r1Natives => AptosFeatureFlag::SECP256R1_NATIVES,
+            FeatureFlag::PoseidonBn254Natives => AptosFeatureFlag::POSEIDON_BN254_NATIVES,
         }
     }
 }
@@ -321,6 +327,9 @@ impl From<AptosFeatureFlag> for FeatureFlag {
                 FeatureFlag::CommissionChangeDelegationPool
             },
             AptosFeatureFlag::BN254_STRUCTURES => FeatureFlag::BN254Structures,
+            AptosFeatureFlag::WEBAUTHN_SIGNATURE => FeatureFlag::WebAuthnSignature,
+            AptosFeatureFlag::SECP256R1_NATIVES => FeatureFlag::Secp256r1Natives,
+            AptosFeatureFlag::POSEIDON_BN254_NATIVES => FeatureFlag::PoseidonBn254Natives,
         }
     }
 }
@@ -18,7 +18,10 @@ use crate::{
     transaction_validation, verifier, VMExecutor, VMValidator,
 };
 use anyhow::{anyhow, Result};
-use aptos_block_executor::txn_commit_hook::NoOpTransactionCommitHook;
+use aptos_block_executor::{
+    contention::{ConflictKey, ContentionReportHook},
+    txn_commit_hook::NoOpTransactionCommitHook,
+};
 use aptos_crypto::HashValue;
 use aptos_framework::{natives::code::PublishRequest, RuntimeModuleMetadataV1};
 use aptos_gas_algebra::Gas;
@@ -31,6 +34,7 @@ use aptos_state_view::StateView;
 use aptos_types::{
     account_config,
     account_config::new_block_event_key,
+    aggregator::DelayedFieldID,
     block_executor::{
         config::{BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig},
         partitioner::PartitionedTransactions,
@@ -42,6 +46,7 @@ use aptos_types::{
         new_epoch_event_key, ConfigurationResource, FeatureFlag, Features, OnChainConfig,
         TimedFeatureOverride, TimedFeatures, TimedFeaturesBuilder,
     },
+    state_store::state_key::StateKey,
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction,
         EntryFunction, ExecutionError, ExecutionStatus, ModuleBundle, Multisig,
@@ -104,6 +109,10 @@ static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
+static CONTENTION_REPORT_HOOK: OnceCell<
+    Arc<dyn ContentionReportHook<ConflictKey<StateKey, DelayedFieldID>>>,
+> = OnceCell::new();
+static HINT_BASED_SCHEDULING: OnceCell<bool> = OnceCell::new();
 
 // TODO: Don't expose this in AptosVM, and use only in BlockAptosVM!
 pub static RAYON_EXEC_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
@@ -310,6 +319,35 @@ impl AptosVM {
         }
     }
 
+    /// Sets the hook receiving per-block contention reports from parallel execution, when
+    /// invoked the first time.
+    pub fn set_contention_report_hook_once(
+        hook: Arc<dyn ContentionReportHook<ConflictKey<StateKey, DelayedFieldID>>>,
+    ) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CONTENTION_REPORT_HOOK.set(hook).ok();
+    }
+
+    pub fn get_contention_report_hook(
+    ) -> Option<Arc<dyn ContentionReportHook<ConflictKey<StateKey, DelayedFieldID>>>> {
+        CONTENTION_REPORT_HOOK.get().cloned()
+    }
+
+    /// Sets whether parallel execution uses read/write hints to schedule transactions, when
+    /// invoked the first time.
+    pub fn set_hint_based_scheduling_once(enable: bool) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        HINT_BASED_SCHEDULING.set(enable).ok();
+    }
+
+    /// Get whether parallel execution uses read/write hints to schedule transactions.
+    pub fn get_hint_based_scheduling() -> bool {
+        match HINT_BASED_SCHEDULING.get() {
+            Some(value) => *value,
+            None => false,
+        }
+    }
+
     /// Returns the internal gas schedule if it has been loaded, or an error if it hasn't.
     #[cfg(any(test, feature = "testing"))]
     pub fn gas_params(&self) -> Result<&AptosGasParameters, VMStatus> {
@@ -568,6 +606,7 @@ impl AptosVM {
         // Run the execution logic
         {
             gas_meter.charge_intrinsic_gas_for_transaction(txn_data.transaction_size())?;
+            gas_meter.charge_webauthn_signature_verification(txn_data.num_webauthn_signatures())?;
 
             match payload {
                 TransactionPayload::Script(script) => {
@@ -738,6 +777,7 @@ impl AptosVM {
         });
 
         gas_meter.charge_intrinsic_gas_for_transaction(txn_data.transaction_size())?;
+        gas_meter.charge_webauthn_signature_verification(txn_data.num_webauthn_signatures())?;
 
         // Step 1: Obtain the payload. If any errors happen here, the entire transaction should fail
         let invariant_violation_error = || {
@@ -1066,6 +1106,7 @@ impl AptosVM {
         });
 
         gas_meter.charge_intrinsic_gas_for_transaction(txn_data.transaction_size())?;
+        gas_meter.charge_webauthn_signature_verification(txn_data.num_webauthn_signatures())?;
 
         Self::verify_module_bundle(&mut session, modules)?;
         session.publish_module_bundle_with_compat_config(
@@ -2035,6 +2076,16 @@ impl VMValidator for AptosVM {
             }
         }
 
+        if !self.features.is_enabled(FeatureFlag::WEBAUTHN_SIGNATURE)
+            && transaction
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .any(|signer| signer.number_of_webauthn_signatures() > 0)
+        {
+            return VMValidatorResult::error(StatusCode::FEATURE_UNDER_GATING);
+        }
+
         let txn = match transaction.check_signature() {
             Ok(t) => t,
             _ => {
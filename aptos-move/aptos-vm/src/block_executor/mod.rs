@@ -7,15 +7,18 @@ pub(crate) mod vm_wrapper;
 use crate::{
     block_executor::vm_wrapper::AptosExecutorTask,
     counters::{BLOCK_EXECUTOR_CONCURRENCY, BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS},
+    AptosVM,
 };
 use aptos_aggregator::{
     delayed_change::DelayedChange, delta_change_set::DeltaOp, resolver::TAggregatorV1View,
     types::DelayedFieldID,
 };
 use aptos_block_executor::{
-    errors::Error, executor::BlockExecutor,
+    errors::Error,
+    executor::BlockExecutor,
     task::TransactionOutput as BlockExecutorTransactionOutput,
-    txn_commit_hook::TransactionCommitHook, types::InputOutputKey,
+    txn_commit_hook::TransactionCommitHook,
+    types::{InputOutputKey, ReadWriteHints},
 };
 use aptos_infallible::Mutex;
 use aptos_state_view::{StateView, StateViewId};
@@ -26,8 +29,9 @@ use aptos_types::{
     fee_statement::FeeStatement,
     state_store::state_key::StateKey,
     transaction::{
-        signature_verified_transaction::SignatureVerifiedTransaction, TransactionOutput,
-        TransactionStatus,
+        analyzed_transaction::{try_get_read_write_hints, StorageLocation},
+        signature_verified_transaction::SignatureVerifiedTransaction,
+        TransactionOutput, TransactionStatus,
     },
     write_set::WriteOp,
 };
@@ -398,15 +402,26 @@ impl BlockAptosVM {
         }
 
         BLOCK_EXECUTOR_CONCURRENCY.set(config.local.concurrency_level as i64);
-        let executor = BlockExecutor::<
+        let mut executor = BlockExecutor::<
             SignatureVerifiedTransaction,
             AptosExecutorTask<S>,
             S,
             L,
             ExecutableTestType,
         >::new(config, executor_thread_pool, transaction_commit_listener);
+        if let Some(hook) = AptosVM::get_contention_report_hook() {
+            executor = executor.with_contention_report_hook(hook);
+        }
+
+        let read_write_hints = AptosVM::get_hint_based_scheduling()
+            .then(|| Self::read_write_hints(signature_verified_block));
 
-        let ret = executor.execute_block(state_view, signature_verified_block, state_view);
+        let ret = executor.execute_block_with_hints(
+            state_view,
+            signature_verified_block,
+            read_write_hints.as_deref(),
+            state_view,
+        );
         match ret {
             Ok(outputs) => {
                 let output_vec: Vec<TransactionOutput> = outputs
@@ -434,4 +449,31 @@ impl BlockAptosVM {
             Err(Error::UserError(err)) => Err(err),
         }
     }
+
+    /// Best-effort read/write hints used for scheduling. Transactions whose hints cannot be
+    /// derived get empty hints, and wildcard locations are ignored.
+    fn read_write_hints(
+        signature_verified_block: &[SignatureVerifiedTransaction],
+    ) -> Vec<ReadWriteHints<StateKey>> {
+        let specific_keys = |locations: Vec<StorageLocation>| -> Vec<StateKey> {
+            locations
+                .into_iter()
+                .filter_map(|location| match location {
+                    StorageLocation::Specific(state_key) => Some(state_key),
+                    StorageLocation::WildCardStruct(_) | StorageLocation::WildCardTable(_) => None,
+                })
+                .collect()
+        };
+
+        signature_verified_block
+            .iter()
+            .map(|txn| match try_get_read_write_hints(txn) {
+                Some((reads, writes)) => ReadWriteHints {
+                    reads: specific_keys(reads),
+                    writes: specific_keys(writes),
+                },
+                None => ReadWriteHints::empty(),
+            })
+            .collect()
+    }
 }
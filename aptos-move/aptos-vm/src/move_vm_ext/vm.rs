@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::move_vm_ext::{warm_vm_cache::WarmVmCache, AptosMoveResolver, SessionExt, SessionId};
+use crate::{
+    move_vm_ext::{warm_vm_cache::WarmVmCache, AptosMoveResolver, SessionExt, SessionId},
+    natives::aptos_natives_with_builder,
+};
 use aptos_framework::natives::{
     aggregator_natives::NativeAggregatorContext,
     code::NativeCodeContext,
@@ -109,30 +112,34 @@ impl MoveVmExt {
             features.clone(),
         );
 
-        if let Some(hook) = gas_hook {
-            builder.set_gas_hook(hook);
-        }
+        let vm_config = VMConfig {
+            verifier: verifier_config,
+            deserializer_config: DeserializerConfig::new(
+                max_binary_format_version,
+                max_identifier_size,
+            ),
+            paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
+            enable_invariant_violation_check_in_swap_loc,
+            type_size_limit,
+            max_value_nest_depth: Some(128),
+            type_max_cost,
+            type_base_cost,
+            type_byte_cost,
+            aggregator_v2_type_tagging,
+        };
+
+        // The gas hook is not part of the key of the warm VM cache, so a VM with a hook is
+        // created from scratch instead of being shared.
+        let inner = match gas_hook {
+            Some(hook) => {
+                builder.set_gas_hook(hook);
+                MoveVM::new_with_config(aptos_natives_with_builder(&mut builder), vm_config)?
+            },
+            None => WarmVmCache::get_warm_vm(builder, vm_config, resolver)?,
+        };
 
         Ok(Self {
-            inner: WarmVmCache::get_warm_vm(
-                builder,
-                VMConfig {
-                    verifier: verifier_config,
-                    deserializer_config: DeserializerConfig::new(
-                        max_binary_format_version,
-                        max_identifier_size,
-                    ),
-                    paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
-                    enable_invariant_violation_check_in_swap_loc,
-                    type_size_limit,
-                    max_value_nest_depth: Some(128),
-                    type_max_cost,
-                    type_base_cost,
-                    type_byte_cost,
-                    aggregator_v2_type_tagging,
-                },
-                resolver,
-            )?,
+            inner,
             chain_id,
             features: Arc::new(features),
         })
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey};
-use aptos_gas_algebra::{FeePerGasUnit, Gas, NumBytes};
+use aptos_gas_algebra::{FeePerGasUnit, Gas, NumArgs, NumBytes};
 use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
@@ -26,6 +26,7 @@ pub struct TransactionMetadata {
     pub chain_id: ChainId,
     pub script_hash: Vec<u8>,
     pub script_size: NumBytes,
+    pub num_webauthn_signatures: NumArgs,
 }
 
 impl TransactionMetadata {
@@ -63,6 +64,13 @@ impl TransactionMetadata {
                 TransactionPayload::Script(s) => (s.code().len() as u64).into(),
                 _ => NumBytes::zero(),
             },
+            num_webauthn_signatures: (txn
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .map(|signer| signer.number_of_webauthn_signatures())
+                .sum::<usize>() as u64)
+                .into(),
         }
     }
 
@@ -108,6 +116,10 @@ impl TransactionMetadata {
         self.transaction_size
     }
 
+    pub fn num_webauthn_signatures(&self) -> NumArgs {
+        self.num_webauthn_signatures
+    }
+
     pub fn expiration_timestamp_secs(&self) -> u64 {
         self.expiration_timestamp_secs
     }
@@ -141,6 +153,7 @@ impl Default for TransactionMetadata {
             chain_id: ChainId::test(),
             script_hash: vec![],
             script_size: NumBytes::zero(),
+            num_webauthn_signatures: NumArgs::zero(),
         }
     }
 }
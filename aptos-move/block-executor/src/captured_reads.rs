@@ -543,29 +543,9 @@ impl<T: Transaction> CapturedReads<T> {
             return false;
         }
 
-        use MVDataError::*;
-        use MVDataOutput::*;
-        self.data_reads.iter().all(|(k, r)| {
-            match data_map.fetch_data(k, idx_to_validate) {
-                Ok(Versioned(version, v)) => {
-                    matches!(
-                        DataRead::from_value_with_layout(version, v).contains(r),
-                        DataReadComparison::Contains
-                    )
-                },
-                Ok(Resolved(value)) => matches!(
-                    DataRead::Resolved(value).contains(r),
-                    DataReadComparison::Contains
-                ),
-                // Dependency implies a validation failure, and if the original read were to
-                // observe an unresolved delta, it would set the aggregator base value in the
-                // multi-versioned data-structure, resolve, and record the resolved value.
-                Err(Dependency(_))
-                | Err(Unresolved(_))
-                | Err(DeltaApplicationFailure)
-                | Err(Uninitialized) => false,
-            }
-        })
+        self.data_reads
+            .iter()
+            .all(|(k, r)| Self::validate_data_read(data_map, k, r, idx_to_validate).is_ok())
     }
 
     pub(crate) fn validate_group_reads(
@@ -573,46 +553,116 @@ impl<T: Transaction> CapturedReads<T> {
         group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
         idx_to_validate: TxnIndex,
     ) -> bool {
-        use MVGroupError::*;
-
         if self.speculative_failure {
             return false;
         }
 
         self.group_reads.iter().all(|(key, group)| {
-            let mut ret = true;
-            if let Some(size) = group.collected_size {
-                ret &= Ok(size) == group_map.get_group_size(key, idx_to_validate);
-            }
+            Self::validate_group_read(group_map, key, group, idx_to_validate).is_ok()
+        })
+    }
 
-            ret && group.inner_reads.iter().all(|(tag, r)| {
-                match group_map.fetch_tagged_data(key, tag, idx_to_validate) {
-                    Ok((version, v)) => {
-                        matches!(
-                            DataRead::from_value_with_layout(version, v).contains(r),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(TagNotFound) => {
-                        let sentinel_deletion =
-                            Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
-                        assert!(sentinel_deletion.is_deletion());
-                        matches!(
-                            DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None)
-                                .contains(r),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(Dependency(_)) => false,
-                    Err(Uninitialized) => {
-                        unreachable!("May not be uninitialized if captured for validation");
-                    },
-                    Err(TagSerializationError) => {
-                        unreachable!("Should not require tag serialization");
-                    },
-                }
+    /// Returns the keys of all data and group reads that no longer pass validation, each
+    /// together with the index of the transaction whose write (or estimate) is observed
+    /// at the key instead, if any. Used for contention analytics after a failed validation.
+    pub(crate) fn invalid_reads(
+        &self,
+        data_map: &VersionedData<T::Key, T::Value>,
+        group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> Vec<(T::Key, Option<TxnIndex>)> {
+        self.data_reads
+            .iter()
+            .filter_map(|(k, r)| {
+                Self::validate_data_read(data_map, k, r, idx_to_validate)
+                    .err()
+                    .map(|writer| (k.clone(), writer))
             })
-        })
+            .chain(self.group_reads.iter().filter_map(|(k, group)| {
+                Self::validate_group_read(group_map, k, group, idx_to_validate)
+                    .err()
+                    .map(|writer| (k.clone(), writer))
+            }))
+            .collect()
+    }
+
+    // On failure, returns the index of the transaction that wrote the observed value, if any.
+    fn validate_data_read(
+        data_map: &VersionedData<T::Key, T::Value>,
+        k: &T::Key,
+        r: &DataRead<T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> Result<(), Option<TxnIndex>> {
+        use MVDataError::*;
+        use MVDataOutput::*;
+        match data_map.fetch_data(k, idx_to_validate) {
+            Ok(Versioned(version, v)) => {
+                let writer = version.as_ref().ok().map(|(idx, _)| *idx);
+                match DataRead::from_value_with_layout(version, v).contains(r) {
+                    DataReadComparison::Contains => Ok(()),
+                    _ => Err(writer),
+                }
+            },
+            Ok(Resolved(value)) => match DataRead::Resolved(value).contains(r) {
+                DataReadComparison::Contains => Ok(()),
+                _ => Err(None),
+            },
+            // Dependency implies a validation failure, and if the original read were to
+            // observe an unresolved delta, it would set the aggregator base value in the
+            // multi-versioned data-structure, resolve, and record the resolved value.
+            Err(Dependency(dep_idx)) => Err(Some(dep_idx)),
+            Err(Unresolved(_)) | Err(DeltaApplicationFailure) | Err(Uninitialized) => Err(None),
+        }
+    }
+
+    // On failure, returns the index of the transaction that wrote the observed value, if any.
+    fn validate_group_read(
+        group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
+        key: &T::Key,
+        group: &GroupRead<T>,
+        idx_to_validate: TxnIndex,
+    ) -> Result<(), Option<TxnIndex>> {
+        use MVGroupError::*;
+
+        if let Some(size) = group.collected_size {
+            if Ok(size) != group_map.get_group_size(key, idx_to_validate) {
+                return Err(None);
+            }
+        }
+
+        for (tag, r) in group.inner_reads.iter() {
+            match group_map.fetch_tagged_data(key, tag, idx_to_validate) {
+                Ok((version, v)) => {
+                    let writer = version.as_ref().ok().map(|(idx, _)| *idx);
+                    if !matches!(
+                        DataRead::from_value_with_layout(version, v).contains(r),
+                        DataReadComparison::Contains
+                    ) {
+                        return Err(writer);
+                    }
+                },
+                Err(TagNotFound) => {
+                    let sentinel_deletion =
+                        Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
+                    assert!(sentinel_deletion.is_deletion());
+                    if !matches!(
+                        DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None)
+                            .contains(r),
+                        DataReadComparison::Contains
+                    ) {
+                        return Err(None);
+                    }
+                },
+                Err(Dependency(dep_idx)) => return Err(Some(dep_idx)),
+                Err(Uninitialized) => {
+                    unreachable!("May not be uninitialized if captured for validation");
+                },
+                Err(TagSerializationError) => {
+                    unreachable!("Should not require tag serialization");
+                },
+            }
+        }
+        Ok(())
     }
 
     // This validation needs to be called at commit time
@@ -626,41 +676,58 @@ impl<T: Transaction> CapturedReads<T> {
             return Ok(false);
         }
 
-        use MVDelayedFieldsError::*;
         for (id, read_value) in &self.delayed_field_reads {
-            match delayed_fields.read_latest_committed_value(
-                id,
-                idx_to_validate,
-                ReadPosition::BeforeCurrentTxn,
-            ) {
-                Ok(current_value) => match read_value {
-                    DelayedFieldRead::Value { value, .. } => {
-                        if value != &current_value {
-                            return Ok(false);
-                        }
-                    },
-                    DelayedFieldRead::HistoryBounded {
-                        restriction,
-                        max_value,
-                        ..
-                    } => match restriction.validate_against_base_value(
-                        current_value.into_aggregator_value()?,
-                        *max_value,
-                    ) {
-                        Ok(_) => {},
-                        Err(_) => {
-                            return Ok(false);
-                        },
-                    },
-                },
-                Err(NotFound) | Err(Dependency(_)) | Err(DeltaApplicationFailure) => {
-                    return Ok(false);
-                },
+            if !Self::validate_delayed_field_read(delayed_fields, id, read_value, idx_to_validate)?
+            {
+                return Ok(false);
             }
         }
         Ok(true)
     }
 
+    /// Returns the delayed fields whose reads no longer pass commit-time validation. Used for
+    /// contention analytics after a failed validation.
+    pub(crate) fn invalid_delayed_field_reads(
+        &self,
+        delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+        idx_to_validate: TxnIndex,
+    ) -> Result<Vec<T::Identifier>, PanicError> {
+        let mut invalid_reads = vec![];
+        for (id, read_value) in &self.delayed_field_reads {
+            if !Self::validate_delayed_field_read(delayed_fields, id, read_value, idx_to_validate)?
+            {
+                invalid_reads.push(*id);
+            }
+        }
+        Ok(invalid_reads)
+    }
+
+    fn validate_delayed_field_read(
+        delayed_fields: &dyn TVersionedDelayedFieldView<T::Identifier>,
+        id: &T::Identifier,
+        read_value: &DelayedFieldRead,
+        idx_to_validate: TxnIndex,
+    ) -> Result<bool, PanicError> {
+        use MVDelayedFieldsError::*;
+        match delayed_fields.read_latest_committed_value(
+            id,
+            idx_to_validate,
+            ReadPosition::BeforeCurrentTxn,
+        ) {
+            Ok(current_value) => match read_value {
+                DelayedFieldRead::Value { value, .. } => Ok(value == &current_value),
+                DelayedFieldRead::HistoryBounded {
+                    restriction,
+                    max_value,
+                    ..
+                } => Ok(restriction
+                    .validate_against_base_value(current_value.into_aggregator_value()?, *max_value)
+                    .is_ok()),
+            },
+            Err(NotFound) | Err(Dependency(_)) | Err(DeltaApplicationFailure) => Ok(false),
+        }
+    }
+
     pub(crate) fn get_read_summary(
         &self,
     ) -> HashSet<InputOutputKey<T::Key, T::Tag, T::Identifier>> {
@@ -0,0 +1,253 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_mvhashmap::types::{Incarnation, TxnIndex};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    hash::Hash,
+    io::Write,
+    path::Path,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// Default number of conflicting keys, re-executed transactions and dependency chains included
+/// in a report summary.
+pub const CONTENTION_REPORT_LIMIT: usize = 10;
+
+/// What a failed validation is attributed to: a key whose read was invalidated, or a delayed
+/// field (e.g. an aggregator) whose read or update could not be validated at commit time.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ConflictKey<K, I> {
+    Key(K),
+    DelayedField(I),
+}
+
+/// Per-block summary of the contention observed by parallel execution: which keys caused
+/// validation failures, how many incarnations each transaction needed, and which transactions
+/// had to be re-executed because of writes by (lower) other transactions.
+#[derive(Clone, Debug)]
+pub struct ContentionReport<K> {
+    /// Number of incarnations that were executed, per transaction index.
+    incarnations: Vec<u32>,
+    /// Keys whose reads failed validation, with the number of failures, most contended first.
+    conflicting_keys: Vec<(K, usize)>,
+    /// For each transaction that failed validation, the transactions whose writes it observed
+    /// as conflicting (all of which have a lower index).
+    dependencies: BTreeMap<TxnIndex, BTreeSet<TxnIndex>>,
+}
+
+impl<K> ContentionReport<K> {
+    pub fn num_txns(&self) -> usize {
+        self.incarnations.len()
+    }
+
+    pub fn incarnations(&self) -> &[u32] {
+        &self.incarnations
+    }
+
+    /// Total number of executions beyond the first incarnation of every transaction.
+    pub fn num_re_executions(&self) -> u64 {
+        self.incarnations
+            .iter()
+            .map(|i| i.saturating_sub(1) as u64)
+            .sum()
+    }
+
+    pub fn top_conflicting_keys(&self, limit: usize) -> &[(K, usize)] {
+        &self.conflicting_keys[..limit.min(self.conflicting_keys.len())]
+    }
+
+    pub fn dependencies(&self) -> &BTreeMap<TxnIndex, BTreeSet<TxnIndex>> {
+        &self.dependencies
+    }
+
+    /// Returns up to 'limit' longest dependency chains, each ordered from the lowest
+    /// transaction index to the highest. Every transaction appears in at most one chain.
+    pub fn longest_dependency_chains(&self, limit: usize) -> Vec<Vec<TxnIndex>> {
+        // Dependencies always point to lower indices, so the graph is a DAG and processing
+        // the transactions in increasing order computes the longest chain ending at each.
+        let mut longest: BTreeMap<TxnIndex, (usize, Option<TxnIndex>)> = BTreeMap::new();
+        for (txn_idx, deps) in &self.dependencies {
+            let best = deps
+                .iter()
+                .map(|dep| (longest.get(dep).map_or(1, |(len, _)| *len), *dep))
+                .max();
+            longest.insert(
+                *txn_idx,
+                best.map_or((1, None), |(len, dep)| (len + 1, Some(dep))),
+            );
+        }
+
+        let mut tips: Vec<(usize, TxnIndex)> = longest
+            .iter()
+            .map(|(txn_idx, (len, _))| (*len, *txn_idx))
+            .collect();
+        tips.sort_by(|a, b| b.cmp(a));
+
+        let mut used = BTreeSet::new();
+        let mut chains = Vec::new();
+        for (len, tip) in tips {
+            if chains.len() == limit || len < 2 {
+                break;
+            }
+            if used.contains(&tip) {
+                continue;
+            }
+
+            let mut chain = vec![tip];
+            let mut cur = tip;
+            while let Some((_, Some(prev))) = longest.get(&cur) {
+                chain.push(*prev);
+                cur = *prev;
+            }
+            if chain.iter().any(|txn_idx| used.contains(txn_idx)) {
+                continue;
+            }
+            used.extend(chain.iter().cloned());
+            chain.reverse();
+            chains.push(chain);
+        }
+        chains
+    }
+}
+
+impl<K: Debug> ContentionReport<K> {
+    /// Human readable summary, with at most 'limit' conflicting keys and dependency chains.
+    pub fn summary(&self, limit: usize) -> String {
+        ContentionSummary {
+            report: self,
+            limit,
+        }
+        .to_string()
+    }
+}
+
+struct ContentionSummary<'a, K> {
+    report: &'a ContentionReport<K>,
+    limit: usize,
+}
+
+impl<'a, K: Debug> fmt::Display for ContentionSummary<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = self.report;
+        writeln!(
+            f,
+            "ContentionReport: {} txns, {} re-executions, max incarnations {}",
+            report.num_txns(),
+            report.num_re_executions(),
+            report.incarnations.iter().max().unwrap_or(&0),
+        )?;
+        writeln!(f, "top conflicting keys:")?;
+        for (key, count) in report.top_conflicting_keys(self.limit) {
+            writeln!(f, "    {:>6} {:?}", count, key)?;
+        }
+        writeln!(f, "most re-executed txns:")?;
+        let mut by_incarnations: Vec<(u32, TxnIndex)> = report
+            .incarnations
+            .iter()
+            .enumerate()
+            .filter(|(_, incarnations)| **incarnations > 1)
+            .map(|(txn_idx, incarnations)| (*incarnations, txn_idx as TxnIndex))
+            .collect();
+        by_incarnations.sort_by(|a, b| b.cmp(a));
+        for (incarnations, txn_idx) in by_incarnations.into_iter().take(self.limit) {
+            writeln!(f, "    txn {:>6}: {} incarnations", txn_idx, incarnations)?;
+        }
+        writeln!(f, "longest dependency chains:")?;
+        for chain in report.longest_dependency_chains(self.limit) {
+            writeln!(f, "    {:?}", chain)?;
+        }
+        Ok(())
+    }
+}
+
+/// An interface for receiving the contention report of every block executed in parallel.
+pub trait ContentionReportHook<K>: Send + Sync {
+    fn on_block_executed(&self, report: ContentionReport<K>);
+}
+
+/// Appends a human readable summary of every received report to a file.
+pub struct ContentionReportFileDumper {
+    file: Mutex<File>,
+    limit: usize,
+}
+
+impl ContentionReportFileDumper {
+    pub fn new(path: &Path, limit: usize) -> std::io::Result<Self> {
+        Ok(Self {
+            file: Mutex::new(OpenOptions::new().create(true).append(true).open(path)?),
+            limit,
+        })
+    }
+}
+
+impl<K: Debug> ContentionReportHook<K> for ContentionReportFileDumper {
+    fn on_block_executed(&self, report: ContentionReport<K>) {
+        let summary = report.summary(self.limit);
+        // Analytics are best effort and must never affect execution.
+        let mut file = self.file.lock();
+        let _ = writeln!(file, "{}", summary);
+    }
+}
+
+/// Concurrently collects contention information during parallel execution of a block.
+pub(crate) struct ContentionRecorder<K> {
+    incarnations: Vec<AtomicU32>,
+    conflicting_keys: DashMap<K, usize>,
+    dependencies: Mutex<BTreeMap<TxnIndex, BTreeSet<TxnIndex>>>,
+}
+
+impl<K: Clone + Debug + Eq + Hash + Ord> ContentionRecorder<K> {
+    pub(crate) fn new(num_txns: TxnIndex) -> Self {
+        Self {
+            incarnations: (0..num_txns).map(|_| AtomicU32::new(0)).collect(),
+            conflicting_keys: DashMap::new(),
+            dependencies: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn record_execution(&self, txn_idx: TxnIndex, incarnation: Incarnation) {
+        self.incarnations[txn_idx as usize].fetch_max(incarnation + 1, Ordering::Relaxed);
+    }
+
+    /// Records the keys that failed validation for txn_idx, each together with the index of
+    /// the transaction whose write (or estimate) was observed instead, if any.
+    pub(crate) fn record_conflicts(
+        &self,
+        txn_idx: TxnIndex,
+        conflicts: impl IntoIterator<Item = (K, Option<TxnIndex>)>,
+    ) {
+        let mut writers = BTreeSet::new();
+        for (key, writer) in conflicts {
+            *self.conflicting_keys.entry(key).or_insert(0) += 1;
+            writers.extend(writer);
+        }
+        if !writers.is_empty() {
+            self.dependencies
+                .lock()
+                .entry(txn_idx)
+                .or_default()
+                .extend(writers);
+        }
+    }
+
+    pub(crate) fn into_report(self) -> ContentionReport<K> {
+        let mut conflicting_keys: Vec<(K, usize)> = self.conflicting_keys.into_iter().collect();
+        conflicting_keys.sort_by(|(k1, c1), (k2, c2)| c2.cmp(c1).then_with(|| k1.cmp(k2)));
+
+        ContentionReport {
+            incarnations: self
+                .incarnations
+                .into_iter()
+                .map(AtomicU32::into_inner)
+                .collect(),
+            conflicting_keys,
+            dependencies: self.dependencies.into_inner(),
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    contention::{ConflictKey, ContentionRecorder, ContentionReportHook},
     counters,
     counters::{
         PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS,
@@ -15,7 +16,7 @@ use crate::{
     task::{ExecutionStatus, ExecutorTask, TransactionOutput},
     txn_commit_hook::TransactionCommitHook,
     txn_last_input_output::{KeyKind, TxnLastInputOutput},
-    types::ReadWriteSummary,
+    types::{ReadWriteHints, ReadWriteSummary},
     view::{LatestView, ParallelState, SequentialState, ViewState},
 };
 use aptos_aggregator::{
@@ -56,12 +57,15 @@ use std::{
     sync::{atomic::AtomicU32, Arc},
 };
 
-pub struct BlockExecutor<T, E, S, L, X> {
+pub struct BlockExecutor<T: Transaction, E, S, L, X> {
     // Number of active concurrent tasks, corresponding to the maximum number of rayon
     // threads that may be concurrently participating in parallel execution.
     config: BlockExecutorConfig,
     executor_thread_pool: Arc<ThreadPool>,
     transaction_commit_hook: Option<L>,
+    // If set, contention during parallel execution is recorded and reported per block.
+    contention_report_hook:
+        Option<Arc<dyn ContentionReportHook<ConflictKey<T::Key, T::Identifier>>>>,
     phantom: PhantomData<(T, E, S, L, X)>,
 }
 
@@ -89,10 +93,20 @@ where
             config,
             executor_thread_pool,
             transaction_commit_hook,
+            contention_report_hook: None,
             phantom: PhantomData,
         }
     }
 
+    /// Report the contention observed during parallel execution of every block to the hook.
+    pub fn with_contention_report_hook(
+        mut self,
+        contention_report_hook: Arc<dyn ContentionReportHook<ConflictKey<T::Key, T::Identifier>>>,
+    ) -> Self {
+        self.contention_report_hook = Some(contention_report_hook);
+        self
+    }
+
     fn execute(
         idx_to_execute: TxnIndex,
         incarnation: Incarnation,
@@ -355,10 +369,27 @@ where
         last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         scheduler: &Scheduler,
+        contention_recorder: Option<&ContentionRecorder<ConflictKey<T::Key, T::Identifier>>>,
     ) -> SchedulerTask {
         let aborted = !valid && scheduler.try_abort(txn_idx, incarnation);
 
         if aborted {
+            if let Some(recorder) = contention_recorder {
+                let read_set = last_input_output
+                    .read_set(txn_idx)
+                    .expect("[BlockSTM]: Prior read-set must be recorded");
+                recorder.record_conflicts(
+                    txn_idx,
+                    read_set
+                        .invalid_reads(
+                            versioned_cache.data(),
+                            versioned_cache.group_data(),
+                            txn_idx,
+                        )
+                        .into_iter()
+                        .map(|(key, writer)| (ConflictKey::Key(key), writer)),
+                );
+            }
             Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
             scheduler.finish_abort(txn_idx, incarnation)
         } else {
@@ -404,6 +435,28 @@ where
         Ok(execution_still_valid)
     }
 
+    /// Delayed fields that failed validate_commit_ready for txn_idx: the reads that no longer
+    /// hold or, if all reads are still valid, the delayed fields whose updates couldn't be
+    /// applied to the latest committed values.
+    fn invalid_delayed_fields(
+        txn_idx: TxnIndex,
+        versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
+        last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
+    ) -> ::std::result::Result<Vec<T::Identifier>, PanicError> {
+        let read_set = last_input_output
+            .read_set(txn_idx)
+            .expect("Read set must be recorded");
+
+        let invalid_reads =
+            read_set.invalid_delayed_field_reads(versioned_cache.delayed_fields(), txn_idx)?;
+        if !invalid_reads.is_empty() {
+            return Ok(invalid_reads);
+        }
+        Ok(last_input_output
+            .delayed_field_keys(txn_idx)
+            .map_or_else(Vec::new, |delayed_field_ids| delayed_field_ids.collect()))
+    }
+
     /// This method may be executed by different threads / workers, but is guaranteed to be executed
     /// non-concurrently by the scheduling in parallel executor. This allows to perform light logic
     /// related to committing a transaction in a simple way and without excessive synchronization
@@ -428,6 +481,7 @@ where
         shared_counter: &AtomicU32,
         executor: &E,
         block: &[T],
+        contention_recorder: Option<&ContentionRecorder<ConflictKey<T::Key, T::Identifier>>>,
     ) -> ::std::result::Result<(), PanicOr<IntentionalFallbackToSequential>> {
         let mut shared_commit_state_guard = shared_commit_state.acquire();
         let (block_limit_processor, shared_maybe_error) =
@@ -436,6 +490,15 @@ where
         while let Some((txn_idx, incarnation)) = scheduler.try_commit() {
             if !Self::validate_commit_ready(txn_idx, versioned_cache, last_input_output)? {
                 // Transaction needs to be re-executed, one final time.
+                if let Some(recorder) = contention_recorder {
+                    recorder.record_conflicts(
+                        txn_idx,
+                        Self::invalid_delayed_fields(txn_idx, versioned_cache, last_input_output)?
+                            .into_iter()
+                            .map(|id| (ConflictKey::DelayedField(id), None)),
+                    );
+                    recorder.record_execution(txn_idx, incarnation + 1);
+                }
 
                 Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
                 // We are going to skip reducing validation index here, as we
@@ -933,6 +996,7 @@ where
             Option<Error<E::Error>>,
         )>,
         final_results: &ExplicitSyncWrapper<Vec<E::Output>>,
+        contention_recorder: Option<&ContentionRecorder<ConflictKey<T::Key, T::Identifier>>>,
     ) -> ::std::result::Result<(), PanicOr<IntentionalFallbackToSequential>> {
         // Make executor for each task. TODO: fast concurrent executor.
         let init_timer = VM_INIT_SECONDS.start_timer();
@@ -974,6 +1038,7 @@ where
                     shared_counter,
                     &executor,
                     block,
+                    contention_recorder,
                 )?;
                 scheduler.queueing_commits_mark_done();
             }
@@ -991,6 +1056,7 @@ where
                         last_input_output,
                         versioned_cache,
                         scheduler,
+                        contention_recorder,
                     )
                 },
                 SchedulerTask::ExecutionTask(
@@ -998,6 +1064,9 @@ where
                     incarnation,
                     ExecutionTaskType::Execution,
                 ) => {
+                    if let Some(recorder) = contention_recorder {
+                        recorder.record_execution(txn_idx, incarnation);
+                    }
                     let updates_outside = Self::execute(
                         txn_idx,
                         incarnation,
@@ -1034,11 +1103,29 @@ where
         }
     }
 
+    #[cfg(any(test, feature = "fuzzing"))]
     pub(crate) fn execute_transactions_parallel(
         &self,
         executor_initial_arguments: E::Argument,
         signature_verified_block: &[T],
         base_view: &S,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        self.execute_transactions_parallel_with_hints(
+            executor_initial_arguments,
+            signature_verified_block,
+            None,
+            base_view,
+        )
+    }
+
+    /// If read/write hints are provided, the first execution of transactions that are predicted
+    /// to conflict with a lower transaction is deferred until that transaction is executed.
+    pub(crate) fn execute_transactions_parallel_with_hints(
+        &self,
+        executor_initial_arguments: E::Argument,
+        signature_verified_block: &[T],
+        read_write_hints: Option<&[ReadWriteHints<T::Key>]>,
+        base_view: &S,
     ) -> Result<Vec<E::Output>, E::Error> {
         let _timer = PARALLEL_EXECUTION_SECONDS.start_timer();
         // Using parallel execution with 1 thread currently will not work as it
@@ -1076,7 +1163,17 @@ where
         let num_txns = num_txns as u32;
 
         let last_input_output = TxnLastInputOutput::new(num_txns);
-        let scheduler = Scheduler::new(num_txns);
+        let scheduler = match read_write_hints {
+            Some(hints) => Scheduler::new_with_predicted_dependencies(
+                num_txns,
+                &ReadWriteHints::predict_dependencies(hints),
+            ),
+            None => Scheduler::new(num_txns),
+        };
+        let contention_recorder = self
+            .contention_report_hook
+            .as_ref()
+            .map(|_| ContentionRecorder::new(num_txns));
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
         self.executor_thread_pool.scope(|s| {
@@ -1093,6 +1190,7 @@ where
                         &shared_counter,
                         &shared_commit_state,
                         &final_results,
+                        contention_recorder.as_ref(),
                     ) {
                         if scheduler.halt() {
                             let mut shared_commit_state_guard = shared_commit_state.acquire();
@@ -1107,6 +1205,12 @@ where
         // Explicit async drops.
         DEFAULT_DROPPER.schedule_drop((last_input_output, scheduler, versioned_cache));
         let (_, maybe_error) = shared_commit_state.into_inner();
+        // Contention is only meaningful if parallel execution ran to completion.
+        if let (Some(hook), Some(recorder)) = (&self.contention_report_hook, contention_recorder) {
+            if !matches!(maybe_error, Some(Error::FallbackToSequential(_))) {
+                hook.on_block_executed(recorder.into_report());
+            }
+        }
         match maybe_error {
             Some(err) => Err(err),
             None => Ok(final_results.into_inner()),
@@ -1412,15 +1516,37 @@ where
         signature_verified_block: &[T],
         base_view: &S,
     ) -> Result<Vec<E::Output>, E::Error> {
+        self.execute_block_with_hints(
+            executor_arguments,
+            signature_verified_block,
+            None,
+            base_view,
+        )
+    }
+
+    /// Same as execute_block, but parallel execution uses the provided read/write hints (one
+    /// per transaction) to schedule transactions. Hints do not affect the output.
+    pub fn execute_block_with_hints(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: &[T],
+        read_write_hints: Option<&[ReadWriteHints<T::Key>]>,
+        base_view: &S,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        if let Some(hints) = read_write_hints {
+            assert_eq!(hints.len(), signature_verified_block.len());
+        }
+
         let dynamic_change_set_optimizations_enabled = signature_verified_block.len() != 1
             || E::is_transaction_dynamic_change_set_capable(&signature_verified_block[0]);
 
         let mut ret = if self.config.local.concurrency_level > 1
             && dynamic_change_set_optimizations_enabled
         {
-            self.execute_transactions_parallel(
+            self.execute_transactions_parallel_with_hints(
                 executor_arguments,
                 signature_verified_block,
+                read_write_hints,
                 base_view,
             )
         } else {
@@ -140,6 +140,7 @@ subsequent incarnation to finish.
 extern crate scopeguard;
 
 mod captured_reads;
+pub mod contention;
 pub mod counters;
 pub mod errors;
 pub mod executor;
@@ -113,12 +113,22 @@ pub enum SchedulerTask {
 /// to 'Ready(incarnation + 1)', allowing the scheduler to create an execution
 /// task for the next incarnation of the transaction.
 ///
+/// 'Deferred' is the initial status of a transaction that is predicted (based on read/write hints)
+/// to depend on a lower transaction that has not yet finished its first execution. Instead of
+/// speculatively executing it and likely aborting, the first incarnation is deferred until the
+/// predicted dependency finishes executing, at which point the status becomes 'Ready'. This allows
+/// the workers to first execute the transactions with no predicted conflicts. The serialization
+/// order (transaction indices) is not affected.
+///
 /// 'ExecutionHalted' is a transaction status marking that parallel execution is halted, due to
 /// reasons such as module r/w intersection or exceeding per-block gas limit. It is safe to ignore
 /// this status during the transaction invariant checks, e.g., suspend(), resume(), set_executed_status().
 ///
 /// Status transition diagram:
-/// Ready(i)                                                                               ---
+/// Deferred(0)                                                                            ---
+///    |  resume (predicted dependency executed)                                             |
+///    ↓                                                                                     |
+/// Ready(i)                                                                                 |
 ///    |  try_incarnate (incarnate successfully)                                             |
 ///    |                                                                                     |
 ///    ↓         suspend (waiting on dependency)                resume                       |
@@ -134,6 +144,7 @@ pub enum SchedulerTask {
 ///
 #[derive(Debug)]
 enum ExecutionStatus {
+    Deferred(Incarnation),
     Ready(Incarnation, ExecutionTaskType),
     Executing(Incarnation),
     Suspended(Incarnation, DependencyCondvar),
@@ -149,7 +160,8 @@ impl PartialEq for ExecutionStatus {
     fn eq(&self, other: &Self) -> bool {
         use ExecutionStatus::*;
         match (self, other) {
-            (&Ready(ref a, _), &Ready(ref b, _))
+            (&Deferred(ref a), &Deferred(ref b))
+            | (&Ready(ref a, _), &Ready(ref b, _))
             | (&Executing(ref a), &Executing(ref b))
             | (&Suspended(ref a, _), &Suspended(ref b, _))
             | (&Executed(ref a), &Executed(ref b))
@@ -308,6 +320,33 @@ impl Scheduler {
         }
     }
 
+    /// Creates a scheduler where the first execution of each transaction with a predicted
+    /// dependency (on a lower transaction index) is deferred until the dependency is executed.
+    pub fn new_with_predicted_dependencies(
+        num_txns: TxnIndex,
+        predicted_dependencies: &[Option<TxnIndex>],
+    ) -> Self {
+        assert_eq!(predicted_dependencies.len(), num_txns as usize);
+        let scheduler = Self::new(num_txns);
+
+        for (txn_idx, maybe_dep_idx) in predicted_dependencies.iter().enumerate() {
+            if let Some(dep_idx) = maybe_dep_idx {
+                assert!(
+                    (*dep_idx as usize) < txn_idx,
+                    "Predicted dependency {} of txn {} must have a lower index",
+                    dep_idx,
+                    txn_idx
+                );
+                *scheduler.txn_status[txn_idx].0.write() = ExecutionStatus::Deferred(0);
+                // Will be resumed by wake_dependencies_after_execution of dep_idx.
+                scheduler.txn_dependency[*dep_idx as usize]
+                    .lock()
+                    .push(txn_idx as TxnIndex);
+            }
+        }
+        scheduler
+    }
+
     pub fn num_txns(&self) -> TxnIndex {
         self.num_txns
     }
@@ -786,12 +825,13 @@ impl Scheduler {
     }
 
     /// Returns true iff no incarnation (even the 0-th one) has set the executed status, i.e.
-    /// iff the execution status is READY_TO_EXECUTE/EXECUTING/SUSPENDED for incarnation 0.
+    /// iff the execution status is DEFERRED/READY_TO_EXECUTE/EXECUTING/SUSPENDED for incarnation 0.
     fn never_executed(&self, txn_idx: TxnIndex) -> bool {
         let status = self.txn_status[txn_idx as usize].0.read();
         matches!(
             *status,
-            ExecutionStatus::Ready(0, _)
+            ExecutionStatus::Deferred(0)
+                | ExecutionStatus::Ready(0, _)
                 | ExecutionStatus::Executing(0)
                 | ExecutionStatus::Suspended(0, _)
         )
@@ -877,21 +917,23 @@ impl Scheduler {
 
     /// When a dependency is resolved, mark the transaction as Ready with an
     /// incremented incarnation number.
-    /// The caller must ensure that the transaction is in the Suspended state.
+    /// The caller must ensure that the transaction is in the Suspended or Deferred state.
     fn resume(&self, txn_idx: TxnIndex) {
         let mut status = self.txn_status[txn_idx as usize].0.write();
 
-        if matches!(*status, ExecutionStatus::ExecutionHalted) {
-            return;
-        }
-
-        if let ExecutionStatus::Suspended(incarnation, dep_condvar) = &*status {
-            *status = ExecutionStatus::Ready(
-                *incarnation,
-                ExecutionTaskType::Wakeup(dep_condvar.clone()),
-            );
-        } else {
-            unreachable!();
+        match &*status {
+            ExecutionStatus::ExecutionHalted => {},
+            ExecutionStatus::Suspended(incarnation, dep_condvar) => {
+                *status = ExecutionStatus::Ready(
+                    *incarnation,
+                    ExecutionTaskType::Wakeup(dep_condvar.clone()),
+                );
+            },
+            // No execution is waiting, so a regular execution task needs to be created.
+            ExecutionStatus::Deferred(incarnation) => {
+                *status = ExecutionStatus::Ready(*incarnation, ExecutionTaskType::Execution);
+            },
+            _ => unreachable!(),
         }
     }
 
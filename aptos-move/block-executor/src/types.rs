@@ -1,8 +1,13 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_mvhashmap::types::TxnIndex;
 use aptos_types::transaction::BlockExecutableTransaction as Transaction;
-use std::{collections::HashSet, fmt};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+};
 
 #[derive(Eq, Hash, PartialEq, Debug)]
 pub enum InputOutputKey<K, T, I> {
@@ -55,3 +60,44 @@ impl<T: Transaction> fmt::Debug for ReadWriteSummary<T> {
         Ok(())
     }
 }
+
+/// Keys that a transaction is expected to read and write. Hints may be inaccurate: they are only
+/// used to guide scheduling, while correctness is always ensured by Block-STM validation.
+#[derive(Clone, Debug)]
+pub struct ReadWriteHints<K> {
+    pub reads: Vec<K>,
+    pub writes: Vec<K>,
+}
+
+impl<K> ReadWriteHints<K> {
+    pub fn empty() -> Self {
+        Self {
+            reads: vec![],
+            writes: vec![],
+        }
+    }
+}
+
+impl<K: Eq + Hash> ReadWriteHints<K> {
+    /// For every transaction, predicts the closest lower transaction it conflicts with, i.e. the
+    /// highest index of a prior transaction that writes a key the transaction reads or writes.
+    pub fn predict_dependencies(hints: &[Self]) -> Vec<Option<TxnIndex>> {
+        let mut last_writer: HashMap<&K, TxnIndex> = HashMap::new();
+        hints
+            .iter()
+            .enumerate()
+            .map(|(txn_idx, hints)| {
+                let dependency = hints
+                    .reads
+                    .iter()
+                    .chain(hints.writes.iter())
+                    .filter_map(|key| last_writer.get(key).cloned())
+                    .max();
+                for key in &hints.writes {
+                    last_writer.insert(key, txn_idx as TxnIndex);
+                }
+                dependency
+            })
+            .collect()
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    contention::{ConflictKey, ContentionRecorder, ContentionReport, ContentionReportHook},
     executor::BlockExecutor,
     proptest_types::{
         baseline::BaselineOutput,
@@ -15,6 +16,7 @@ use crate::{
         DependencyResult, ExecutionTaskType, Scheduler, SchedulerTask, TWaitForDependency,
     },
     txn_commit_hook::NoOpTransactionCommitHook,
+    types::ReadWriteHints,
 };
 use aptos_aggregator::{
     bounded_math::SignedU128,
@@ -23,6 +25,7 @@ use aptos_aggregator::{
 };
 use aptos_mvhashmap::types::TxnIndex;
 use aptos_types::{
+    aggregator::DelayedFieldID,
     block_executor::config::BlockExecutorConfig,
     contract_event::TransactionEvent,
     executable::{ExecutableTestType, ModulePath},
@@ -30,7 +33,12 @@ use aptos_types::{
 use claims::assert_matches;
 use rand::{prelude::*, random};
 use std::{
-    cmp::min, collections::BTreeMap, fmt::Debug, hash::Hash, marker::PhantomData, sync::Arc,
+    cmp::min,
+    collections::BTreeMap,
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
 };
 
 // TODO: add unit test for block gas limit!
@@ -38,6 +46,16 @@ fn run_and_assert<K, E>(transactions: Vec<MockTransaction<K, E>>)
 where
     K: PartialOrd + Ord + Send + Sync + Clone + Hash + Eq + ModulePath + Debug + 'static,
     E: Send + Sync + Debug + Clone + TransactionEvent + 'static,
+{
+    run_and_assert_with_hints(transactions, None)
+}
+
+fn run_and_assert_with_hints<K, E>(
+    transactions: Vec<MockTransaction<K, E>>,
+    read_write_hints: Option<Vec<ReadWriteHints<K>>>,
+) where
+    K: PartialOrd + Ord + Send + Sync + Clone + Hash + Eq + ModulePath + Debug + 'static,
+    E: Send + Sync + Debug + Clone + TransactionEvent + 'static,
 {
     let data_view = DeltaDataView::<K> {
         phantom: PhantomData,
@@ -61,7 +79,12 @@ where
         executor_thread_pool,
         None,
     )
-    .execute_transactions_parallel((), &transactions, &data_view);
+    .execute_transactions_parallel_with_hints(
+        (),
+        &transactions,
+        read_write_hints.as_deref(),
+        &data_view,
+    );
 
     let baseline = BaselineOutput::generate(&transactions, None);
     baseline.assert_output(&output);
@@ -204,6 +227,44 @@ fn cycle_transactions() {
     run_and_assert(transactions)
 }
 
+#[test]
+fn hinted_cycle_transactions() {
+    let mut transactions = vec![];
+    let mut hints = vec![];
+    let keys: Vec<KeyType<[u8; 32]>> = (0..TOTAL_KEY_NUM)
+        .map(|_| KeyType(random::<[u8; 32]>(), false))
+        .collect();
+    // Interleave the writes to different keys, so each transaction is predicted to depend
+    // on the transaction TOTAL_KEY_NUM positions earlier.
+    for _ in 0..WRITES_PER_KEY {
+        for key in &keys {
+            transactions.push(MockTransaction::from_behavior(MockIncarnation::<
+                KeyType<[u8; 32]>,
+                MockEvent,
+            >::new(
+                vec![*key],                        // reads
+                vec![(*key, random_value(false))], // writes
+                vec![],
+                vec![],
+                1, // gas
+            )));
+            hints.push(ReadWriteHints {
+                reads: vec![*key],
+                writes: vec![*key],
+            });
+        }
+    }
+    // Hints for some transactions are missing or wrong, which must not affect the output.
+    let mut rng = thread_rng();
+    for _ in 0..TOTAL_KEY_NUM {
+        let idx = rng.gen_range(0, hints.len());
+        hints[idx] = ReadWriteHints::empty();
+        let idx = rng.gen_range(0, hints.len());
+        hints[idx].writes = vec![keys[rng.gen_range(0, keys.len())]];
+    }
+    run_and_assert_with_hints(transactions, Some(hints))
+}
+
 const NUM_BLOCKS: u64 = 10;
 const TXN_PER_BLOCK: u64 = 100;
 
@@ -518,6 +579,77 @@ fn scheduler_dependency() {
     ));
 }
 
+#[test]
+fn scheduler_predicted_dependencies() {
+    let predicted_dependencies = ReadWriteHints::predict_dependencies(&[
+        ReadWriteHints {
+            reads: vec![],
+            writes: vec![1],
+        },
+        ReadWriteHints {
+            reads: vec![2],
+            writes: vec![3],
+        },
+        ReadWriteHints {
+            reads: vec![1],
+            writes: vec![4],
+        },
+        ReadWriteHints {
+            reads: vec![1, 3],
+            writes: vec![4],
+        },
+    ]);
+    assert_eq!(predicted_dependencies, vec![None, None, Some(0), Some(2)]);
+
+    let s = Scheduler::new_with_predicted_dependencies(4, &predicted_dependencies);
+
+    // Transactions without predicted dependencies are executed first.
+    for i in 0..2 {
+        assert!(matches!(
+            s.next_task(),
+            SchedulerTask::ExecutionTask(j, 0, ExecutionTaskType::Execution) if j == i
+        ));
+    }
+    assert!(matches!(s.next_task(), SchedulerTask::NoTask));
+
+    assert!(matches!(
+        s.finish_execution(1, 0, false),
+        SchedulerTask::NoTask
+    ));
+    assert!(matches!(s.next_task(), SchedulerTask::NoTask));
+
+    // Executing transaction 0 allows transaction 2 to be executed.
+    assert!(matches!(
+        s.finish_execution(0, 0, false),
+        SchedulerTask::NoTask
+    ));
+    for i in 0..2 {
+        assert!(matches!(
+            s.next_task(),
+            SchedulerTask::ValidationTask(j, 0, 0) if j == i
+        ));
+    }
+    // Deferred transaction is executed as a regular first incarnation.
+    assert!(matches!(
+        s.next_task(),
+        SchedulerTask::ExecutionTask(2, 0, ExecutionTaskType::Execution)
+    ));
+    assert!(matches!(s.next_task(), SchedulerTask::NoTask));
+
+    assert!(matches!(
+        s.finish_execution(2, 0, false),
+        SchedulerTask::NoTask
+    ));
+    assert!(matches!(
+        s.next_task(),
+        SchedulerTask::ValidationTask(2, 0, 0)
+    ));
+    assert!(matches!(
+        s.next_task(),
+        SchedulerTask::ExecutionTask(3, 0, ExecutionTaskType::Execution)
+    ));
+}
+
 // Will return a scheduler in a state where all transactions are scheduled for
 // for execution, validation index = num_txns, and wave = 0.
 fn incarnation_one_scheduler(num_txns: TxnIndex) -> Scheduler {
@@ -896,3 +1028,104 @@ fn no_conflict_task_count() {
         assert!(matches!(s.next_task(), SchedulerTask::Done));
     }
 }
+
+#[test]
+fn contention_report_chains() {
+    let recorder = ContentionRecorder::<u32>::new(6);
+    for txn_idx in 0..6 {
+        recorder.record_execution(txn_idx, 0);
+    }
+    // 1 <- 2 <- 4 and 3 <- 5, all through key 7 except for 3 <- 5.
+    recorder.record_execution(2, 1);
+    recorder.record_conflicts(2, vec![(7, Some(1))]);
+    recorder.record_execution(4, 1);
+    recorder.record_execution(4, 2);
+    recorder.record_conflicts(4, vec![(7, Some(2)), (8, None)]);
+    recorder.record_execution(5, 1);
+    recorder.record_conflicts(5, vec![(9, Some(3))]);
+
+    let report = recorder.into_report();
+    assert_eq!(report.num_txns(), 6);
+    assert_eq!(report.incarnations(), &[1, 1, 2, 1, 3, 2]);
+    assert_eq!(report.num_re_executions(), 4);
+    assert_eq!(report.top_conflicting_keys(1), &[(7, 2)]);
+    assert_eq!(report.top_conflicting_keys(10).len(), 3);
+    assert_eq!(report.longest_dependency_chains(10), vec![
+        vec![1, 2, 4],
+        vec![3, 5]
+    ]);
+    assert_eq!(report.longest_dependency_chains(1), vec![vec![1, 2, 4]]);
+}
+
+type MockConflictKey = ConflictKey<KeyType<[u8; 32]>, DelayedFieldID>;
+
+#[derive(Default)]
+struct CollectingContentionReportHook {
+    reports: Mutex<Vec<ContentionReport<MockConflictKey>>>,
+}
+
+impl ContentionReportHook<MockConflictKey> for CollectingContentionReportHook {
+    fn on_block_executed(&self, report: ContentionReport<MockConflictKey>) {
+        self.reports.lock().unwrap().push(report);
+    }
+}
+
+#[test]
+fn contention_report_hot_key() {
+    let num_threads = num_cpus::get();
+    if num_threads < 2 {
+        // Parallel execution requires at least two threads to observe any contention.
+        return;
+    }
+
+    let key = KeyType(random::<[u8; 32]>(), false);
+    let transactions: Vec<_> = (0..100)
+        .map(|_| {
+            MockTransaction::from_behavior(MockIncarnation::<KeyType<[u8; 32]>, MockEvent>::new(
+                vec![key],                        // reads
+                vec![(key, random_value(false))], // writes
+                vec![],
+                vec![],
+                1, // gas
+            ))
+        })
+        .collect();
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap(),
+    );
+    let hook = Arc::new(CollectingContentionReportHook::default());
+    let block_executor = BlockExecutor::<
+        MockTransaction<KeyType<[u8; 32]>, MockEvent>,
+        MockTask<KeyType<[u8; 32]>, MockEvent>,
+        DeltaDataView<KeyType<[u8; 32]>>,
+        NoOpTransactionCommitHook<MockOutput<KeyType<[u8; 32]>, MockEvent>, usize>,
+        ExecutableTestType,
+    >::new(
+        BlockExecutorConfig::new_no_block_limit(num_threads),
+        executor_thread_pool,
+        None,
+    )
+    .with_contention_report_hook(hook.clone());
+
+    // Whether validation fails depends on the interleaving, so retry until it does.
+    for _ in 0..100 {
+        block_executor
+            .execute_transactions_parallel((), &transactions, &data_view)
+            .unwrap();
+        let report = hook.reports.lock().unwrap().pop().unwrap();
+        assert_eq!(report.num_txns(), transactions.len());
+        if report.num_re_executions() > 0 {
+            // The hot key is the only key read, so every failed validation is attributed to it.
+            assert_eq!(report.top_conflicting_keys(10).len(), 1);
+            assert_eq!(report.top_conflicting_keys(10)[0].0, ConflictKey::Key(key));
+            return;
+        }
+    }
+    panic!("No contention observed in 100 blocks");
+}
@@ -0,0 +1,97 @@
+/// This module implements the Poseidon hash function over the scalar field of the BN254 elliptic curve, using the
+/// same parameters as circomlib's `Poseidon` template. This makes it possible to recompute, on chain, hashes that
+/// are committed to inside circom-based zero-knowledge proofs.
+
+module aptos_std::poseidon_bn254 {
+    use std::features;
+
+    /// An input is not a canonical 32-byte little-endian BN254 scalar, or the number of inputs is not supported.
+    const E_INVALID_INPUT: u64 = 1;
+
+    /// The native functions have not been rolled out yet.
+    const E_NATIVE_FUN_NOT_AVAILABLE: u64 = 2;
+
+    /// The maximum number of inputs that can be hashed at once.
+    /// NOTE: This must match the constant in the Rust implementation
+    const MAX_NUM_INPUTS: u64 = 12;
+
+    /// Returns the Poseidon hash of `inputs`, where each input, as well as the output, is the canonical 32-byte
+    /// little-endian encoding of a BN254 scalar.
+    ///
+    /// Aborts with `E_INVALID_INPUT` if there are no inputs, more than `MAX_NUM_INPUTS` inputs, or if an input is
+    /// not exactly 32 bytes or not a canonical BN254 scalar.
+    public fun hash(inputs: vector<vector<u8>>): vector<u8> {
+        if (!features::poseidon_bn254_natives_enabled()) {
+            abort(std::error::invalid_state(E_NATIVE_FUN_NOT_AVAILABLE))
+        };
+
+        let num_inputs = std::vector::length(&inputs);
+        assert!(num_inputs > 0 && num_inputs <= MAX_NUM_INPUTS, std::error::invalid_argument(E_INVALID_INPUT));
+
+        hash_internal(inputs)
+    }
+
+    //
+    // Native functions
+    //
+
+    native fun hash_internal(inputs: vector<vector<u8>>): vector<u8>;
+
+    //
+    // Tests
+    //
+
+    #[test(fx = @std)]
+    fun test_hash(fx: signer) {
+        features::change_feature_flags(&fx, vector[features::get_poseidon_bn254_natives_feature()], vector[]);
+
+        // Test vector from circomlib: poseidon([1, 2]).
+        let inputs = vector[
+            x"0100000000000000000000000000000000000000000000000000000000000000",
+            x"0200000000000000000000000000000000000000000000000000000000000000",
+        ];
+        assert!(hash(inputs) == x"9a1817447a60199e51453274f217362acfe962966b4cf63d4190d6e7f5c05c11", 1);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 65537, location = Self)]
+    fun test_hash_no_inputs(fx: signer) {
+        features::change_feature_flags(&fx, vector[features::get_poseidon_bn254_natives_feature()], vector[]);
+
+        hash(vector[]);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 65537, location = Self)]
+    fun test_hash_non_canonical_input(fx: signer) {
+        features::change_feature_flags(&fx, vector[features::get_poseidon_bn254_natives_feature()], vector[]);
+
+        // The BN254 scalar field modulus.
+        hash(vector[x"010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430"]);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 65537, location = Self)]
+    fun test_hash_short_input(fx: signer) {
+        features::change_feature_flags(&fx, vector[features::get_poseidon_bn254_natives_feature()], vector[]);
+
+        hash(vector[x"01"]);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 65537, location = Self)]
+    fun test_hash_long_input(fx: signer) {
+        features::change_feature_flags(&fx, vector[features::get_poseidon_bn254_natives_feature()], vector[]);
+
+        // A valid encoding of 1 followed by a trailing byte.
+        hash(vector[x"010000000000000000000000000000000000000000000000000000000000000000"]);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 196610, location = Self)]
+    fun test_hash_aborts_if_disabled(fx: signer) {
+        features::change_feature_flags(&fx, vector[], vector[features::get_poseidon_bn254_natives_feature()]);
+
+        hash(vector[x"0100000000000000000000000000000000000000000000000000000000000000"]);
+    }
+}
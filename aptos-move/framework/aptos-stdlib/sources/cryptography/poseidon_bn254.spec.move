@@ -0,0 +1,10 @@
+spec aptos_std::poseidon_bn254 {
+    spec hash_internal(inputs: vector<vector<u8>>): vector<u8> {
+        pragma opaque;
+        aborts_if spec_hash_internal_abort_condition(inputs);
+        ensures result == spec_hash_internal(inputs);
+    }
+
+    spec fun spec_hash_internal_abort_condition(inputs: vector<vector<u8>>): bool;
+    spec fun spec_hash_internal(inputs: vector<vector<u8>>): vector<u8>;
+}
@@ -0,0 +1,125 @@
+/// This module implements ECDSA signature verification over the NIST P-256 (a.k.a. secp256r1) elliptic curve, which
+/// is used by WebAuthn authenticators such as device passkeys.
+
+module aptos_std::secp256r1 {
+    use std::features;
+
+    /// An error occurred while deserializing, for example due to wrong input size.
+    const E_DESERIALIZE: u64 = 1;
+
+    /// The native functions have not been rolled out yet.
+    const E_NATIVE_FUN_NOT_AVAILABLE: u64 = 2;
+
+    /// The size of a secp256r1-based ECDSA public key in the uncompressed SEC1 format (i.e., `0x04 || x || y`), in bytes.
+    const RAW_PUBLIC_KEY_NUM_BYTES: u64 = 65;
+
+    /// The size of a secp256r1-based ECDSA signature (i.e., `r || s`), in bytes.
+    const SIGNATURE_NUM_BYTES: u64 = 64;
+
+    /// A 65-byte ECDSA public key in the uncompressed SEC1 format.
+    struct ECDSARawPublicKey has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// A 64-byte ECDSA signature.
+    struct ECDSASignature has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// Constructs an ECDSASignature struct from the given 64 bytes.
+    public fun ecdsa_signature_from_bytes(bytes: vector<u8>): ECDSASignature {
+        assert!(std::vector::length(&bytes) == SIGNATURE_NUM_BYTES, std::error::invalid_argument(E_DESERIALIZE));
+        ECDSASignature { bytes }
+    }
+
+    /// Constructs an ECDSARawPublicKey struct, given a 65-byte uncompressed SEC1 representation.
+    public fun ecdsa_raw_public_key_from_65_bytes(bytes: vector<u8>): ECDSARawPublicKey {
+        assert!(std::vector::length(&bytes) == RAW_PUBLIC_KEY_NUM_BYTES, std::error::invalid_argument(E_DESERIALIZE));
+        ECDSARawPublicKey { bytes }
+    }
+
+    /// Serializes an ECDSARawPublicKey struct to 65-bytes.
+    public fun ecdsa_raw_public_key_to_bytes(pk: &ECDSARawPublicKey): vector<u8> {
+        pk.bytes
+    }
+
+    /// Serializes an ECDSASignature struct to 64-bytes.
+    public fun ecdsa_signature_to_bytes(sig: &ECDSASignature): vector<u8> {
+        sig.bytes
+    }
+
+    /// Returns `true` if `signature` verifies on `message` under `public_key`, where `message` is hashed with SHA2-256
+    /// as part of the verification.
+    ///
+    /// To prevent signature malleability, only signatures in canonical form (i.e., with a low `s`) are accepted.
+    public fun ecdsa_verify(
+        message: vector<u8>,
+        public_key: &ECDSARawPublicKey,
+        signature: &ECDSASignature,
+    ): bool {
+        if (!features::secp256r1_natives_enabled()) {
+            abort(std::error::invalid_state(E_NATIVE_FUN_NOT_AVAILABLE))
+        };
+
+        ecdsa_verify_internal(message, public_key.bytes, signature.bytes)
+    }
+
+    //
+    // Native functions
+    //
+
+    /// Returns `true` if `signature` verifies on `message` under `public_key` and returns `false` otherwise.
+    native fun ecdsa_verify_internal(
+        message: vector<u8>,
+        public_key: vector<u8>,
+        signature: vector<u8>
+    ): bool;
+
+    //
+    // Tests
+    //
+
+    #[test_only]
+    /// Created using sk = x"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+    const TEST_PUBLIC_KEY: vector<u8> = x"04d8cd12ea5c67f2f8a00c1124893edcfa6754c4d6cede6be13bdf2295c810a97fa5a89d2d2a360c0ca9a4d6c7c9ed4b28d3e199d6627f2e696d689c310a5b0f48";
+
+    #[test_only]
+    /// A signature on "test aptos secp256r1" under the key above, in canonical (low-S) form.
+    const TEST_SIGNATURE: vector<u8> = x"c1a6b162403b227eee8033fdf7be4b99cc056b52d6749cf34c6fc5caf1db82fa0fbb271e9a97950ebe460da192574e0b0ffd3728320c1e974e2782c8570e4560";
+
+    #[test(fx = @std)]
+    fun test_ecdsa_verify(fx: signer) {
+        features::change_feature_flags(&fx, vector[features::get_secp256r1_natives_feature()], vector[]);
+
+        let pk = ecdsa_raw_public_key_from_65_bytes(TEST_PUBLIC_KEY);
+        let sig = ecdsa_signature_from_bytes(TEST_SIGNATURE);
+        assert!(ecdsa_verify(b"test aptos secp256r1", &pk, &sig), 1);
+
+        // Wrong message.
+        assert!(!ecdsa_verify(b"test aptos secp256k1", &pk, &sig), 2);
+
+        // The same signature with a high S.
+        let high_s_sig = ecdsa_signature_from_bytes(x"c1a6b162403b227eee8033fdf7be4b99cc056b52d6749cf34c6fc5caf1db82faf044d8e065686af241b9f25e6da8b1f4ace9c385750b7feda59247faa554dff1");
+        assert!(!ecdsa_verify(b"test aptos secp256r1", &pk, &high_s_sig), 3);
+
+        // Not a point on the curve.
+        let bad_pk = ecdsa_raw_public_key_from_65_bytes(x"0400000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000");
+        assert!(!ecdsa_verify(b"test aptos secp256r1", &bad_pk, &sig), 4);
+    }
+
+    #[test(fx = @std)]
+    #[expected_failure(abort_code = 196610, location = Self)]
+    fun test_ecdsa_verify_aborts_if_disabled(fx: signer) {
+        features::change_feature_flags(&fx, vector[], vector[features::get_secp256r1_natives_feature()]);
+
+        let pk = ecdsa_raw_public_key_from_65_bytes(TEST_PUBLIC_KEY);
+        let sig = ecdsa_signature_from_bytes(TEST_SIGNATURE);
+        ecdsa_verify(b"test aptos secp256r1", &pk, &sig);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 65537, location = Self)]
+    fun test_ecdsa_raw_public_key_wrong_size() {
+        ecdsa_raw_public_key_from_65_bytes(x"0102");
+    }
+}
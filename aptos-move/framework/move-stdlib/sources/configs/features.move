@@ -332,6 +332,35 @@ module std::features {
         is_enabled(BN254_STRUCTURES)
     }
 
+    /// Whether WebAuthn signatures over secp256r1 (e.g., from device passkeys) are accepted in
+    /// transaction authenticators. This is needed because of the introduction of new signature
+    /// and public key variants in `AnySignature` and `AnyPublicKey`.
+    ///
+    /// Lifetime: transient
+    const WEBAUTHN_SIGNATURE: u64 = 44;
+
+    /// Whether the secp256r1 ECDSA signature verification native is enabled.
+    /// This is needed because of the introduction of new native function(s).
+    /// Lifetime: transient
+    const SECP256R1_NATIVES: u64 = 45;
+
+    public fun get_secp256r1_natives_feature(): u64 { SECP256R1_NATIVES }
+
+    public fun secp256r1_natives_enabled(): bool acquires Features {
+        is_enabled(SECP256R1_NATIVES)
+    }
+
+    /// Whether the Poseidon hash function native over the BN254 scalar field is enabled.
+    /// This is needed because of the introduction of new native function(s).
+    /// Lifetime: transient
+    const POSEIDON_BN254_NATIVES: u64 = 46;
+
+    public fun get_poseidon_bn254_natives_feature(): u64 { POSEIDON_BN254_NATIVES }
+
+    public fun poseidon_bn254_natives_enabled(): bool acquires Features {
+        is_enabled(POSEIDON_BN254_NATIVES)
+    }
+
     // ============================================================================================
     // Feature Flag Implementation
 
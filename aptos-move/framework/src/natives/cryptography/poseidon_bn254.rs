@@ -0,0 +1,166 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_gas_schedule::gas_params::natives::aptos_framework::*;
+use aptos_native_interface::{
+    safely_pop_vec_arg, RawSafeNative, SafeNativeBuilder, SafeNativeContext, SafeNativeError,
+    SafeNativeResult,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use light_poseidon::{Poseidon, PoseidonHasher};
+use move_core_types::gas_algebra::NumArgs;
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use smallvec::{smallvec, SmallVec};
+use std::collections::VecDeque;
+
+/// The maximum number of inputs supported by the circom-compatible Poseidon parameters.
+/// NOTE: This must match the constant in the Move implementation
+pub const MAX_NUM_INPUTS: usize = 12;
+
+/// The size of the encoding of a BN254 scalar, in bytes.
+const SCALAR_NUM_BYTES: usize = 32;
+
+/// Abort code when an input is not a canonical 32-byte encoding of a BN254 scalar, or when the
+/// number of inputs is not supported (0x01 == INVALID_ARGUMENT).
+/// NOTE: This must match the code in the Move implementation
+pub mod abort_codes {
+    pub const NFE_INVALID_INPUT: u64 = 0x01_0001;
+}
+
+/***************************************************************************************************
+ * native fun hash_internal
+ *
+ *   gas cost: base_cost + per_input_cost * |inputs|
+ *
+ **************************************************************************************************/
+fn native_hash(
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 1);
+
+    let inputs = safely_pop_vec_arg!(arguments, Vec<u8>);
+
+    context.charge(
+        POSEIDON_BN254_BASE + POSEIDON_BN254_PER_INPUT * NumArgs::new(inputs.len() as u64),
+    )?;
+
+    match poseidon_hash(&inputs) {
+        Some(hash) => Ok(smallvec![Value::vector_u8(hash)]),
+        None => Err(SafeNativeError::Abort {
+            abort_code: abort_codes::NFE_INVALID_INPUT,
+        }),
+    }
+}
+
+/// Returns the circom-compatible Poseidon hash of the inputs, where each input, as well as the
+/// output, is the canonical, 32-byte little-endian encoding of a BN254 scalar. Returns `None` if
+/// the number of inputs is not supported or an input is not such an encoding.
+fn poseidon_hash(inputs: &[Vec<u8>]) -> Option<Vec<u8>> {
+    if inputs.is_empty() || inputs.len() > MAX_NUM_INPUTS {
+        return None;
+    }
+
+    // NOTE: Deserialization would ignore any trailing bytes, so the length is checked explicitly.
+    let scalars = inputs
+        .iter()
+        .map(|bytes| {
+            if bytes.len() != SCALAR_NUM_BYTES {
+                return None;
+            }
+            ark_bn254::Fr::deserialize_uncompressed(bytes.as_slice()).ok()
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let hash = Poseidon::<ark_bn254::Fr>::new_circom(scalars.len())
+        .and_then(|mut poseidon| poseidon.hash(&scalars))
+        .ok()?;
+
+    let mut output = Vec::with_capacity(SCALAR_NUM_BYTES);
+    hash.serialize_uncompressed(&mut output)
+        .expect("Serializing a BN254 scalar must succeed");
+    Some(output)
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+pub fn make_all(
+    builder: &SafeNativeBuilder,
+) -> impl Iterator<Item = (String, NativeFunction)> + '_ {
+    let natives = [("hash_internal", native_hash as RawSafeNative)];
+
+    builder.make_named_natives(natives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a small integer as a BN254 scalar.
+    fn scalar(value: u64) -> Vec<u8> {
+        let mut bytes = value.to_le_bytes().to_vec();
+        bytes.resize(SCALAR_NUM_BYTES, 0);
+        bytes
+    }
+
+    /// Decodes a big-endian hex string, as printed by circom, into a little-endian scalar.
+    fn from_circom_hex(hex: &str) -> Vec<u8> {
+        let mut bytes = hex::decode(hex.trim_start_matches("0x")).unwrap();
+        bytes.reverse();
+        bytes
+    }
+
+    #[test]
+    fn test_poseidon_hash_matches_circom() {
+        // Test vectors of circomlib's Poseidon implementation.
+        let vectors = [
+            (
+                vec![1],
+                "0x29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133",
+            ),
+            (
+                vec![1, 2],
+                "0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+            ),
+            (
+                vec![1, 2, 3, 4],
+                "0x299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465",
+            ),
+        ];
+        for (inputs, expected) in vectors {
+            let inputs = inputs.into_iter().map(scalar).collect::<Vec<_>>();
+            assert_eq!(poseidon_hash(&inputs), Some(from_circom_hex(expected)));
+        }
+    }
+
+    #[test]
+    fn test_poseidon_hash_supports_up_to_max_inputs() {
+        assert_eq!(poseidon_hash(&[]), None);
+        for num_inputs in 1..=MAX_NUM_INPUTS {
+            let inputs = (0..num_inputs as u64).map(scalar).collect::<Vec<_>>();
+            assert_eq!(poseidon_hash(&inputs).unwrap().len(), SCALAR_NUM_BYTES);
+        }
+        let inputs = (0..=MAX_NUM_INPUTS as u64).map(scalar).collect::<Vec<_>>();
+        assert_eq!(poseidon_hash(&inputs), None);
+    }
+
+    #[test]
+    fn test_poseidon_hash_rejects_invalid_scalars() {
+        // Too short, even if the value would fit.
+        assert_eq!(poseidon_hash(&[vec![1]]), None);
+        assert_eq!(poseidon_hash(&[vec![]]), None);
+        // Too long, even if the trailing byte is zero.
+        let mut long = scalar(1);
+        long.push(0);
+        assert_eq!(poseidon_hash(&[long]), None);
+        // The BN254 scalar field modulus is not a canonical encoding.
+        let modulus =
+            from_circom_hex("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+        assert_eq!(poseidon_hash(&[scalar(1), modulus]), None);
+    }
+}
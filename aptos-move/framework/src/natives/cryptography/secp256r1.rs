@@ -0,0 +1,80 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::{p256_ecdsa, traits::Signature};
+use aptos_gas_schedule::gas_params::natives::aptos_framework::*;
+use aptos_native_interface::{
+    safely_pop_arg, RawSafeNative, SafeNativeBuilder, SafeNativeContext, SafeNativeResult,
+};
+use move_core_types::gas_algebra::{NumArgs, NumBytes};
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use smallvec::{smallvec, SmallVec};
+use std::{collections::VecDeque, convert::TryFrom};
+
+/***************************************************************************************************
+ * native fun ecdsa_verify_internal
+ *
+ *   gas cost: base_cost + per_pubkey_deserialize_cost
+ *                       +? ( per_sig_deserialize_cost
+ *                            +? ( per_sig_verify_cost + per_msg_byte_hashing_cost * |msg| ) )
+ *
+ * where +? indicates that the expression stops evaluating there if the previous gas-charging step
+ * failed
+ **************************************************************************************************/
+fn native_ecdsa_verify(
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 3);
+
+    let signature = safely_pop_arg!(arguments, Vec<u8>);
+    let public_key = safely_pop_arg!(arguments, Vec<u8>);
+    let msg = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(SECP256R1_BASE + SECP256R1_PER_PUBKEY_DESERIALIZE * NumArgs::one())?;
+
+    // NOTE(Gas): O(1) cost: checks that the point is on the curve
+    let pk = match p256_ecdsa::PublicKey::try_from(public_key.as_slice()) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return Ok(smallvec![Value::bool(false)]);
+        },
+    };
+
+    context.charge(SECP256R1_PER_SIG_DESERIALIZE * NumArgs::one())?;
+
+    // NOTE: Deserialization rejects non-canonical (high S) signatures.
+    let sig = match p256_ecdsa::Signature::try_from(signature.as_slice()) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return Ok(smallvec![Value::bool(false)]);
+        },
+    };
+
+    // NOTE(Gas): SHA2-256 hashing of the message and a size-2 multi-scalar multiplication
+    context.charge(
+        SECP256R1_PER_SIG_VERIFY * NumArgs::one()
+            + SECP256R1_PER_MSG_BYTE_HASHING * NumBytes::new(msg.len() as u64),
+    )?;
+
+    let verify_result = sig.verify_arbitrary_msg(msg.as_slice(), &pk).is_ok();
+    Ok(smallvec![Value::bool(verify_result)])
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+pub fn make_all(
+    builder: &SafeNativeBuilder,
+) -> impl Iterator<Item = (String, NativeFunction)> + '_ {
+    let natives = [(
+        "ecdsa_verify_internal",
+        native_ecdsa_verify as RawSafeNative,
+    )];
+
+    builder.make_named_natives(natives)
+}
@@ -441,6 +441,9 @@ pub fn default_features() -> Vec<FeatureFlag> {
         FeatureFlag::LIMIT_MAX_IDENTIFIER_LENGTH,
         FeatureFlag::OPERATOR_BENEFICIARY_CHANGE,
         FeatureFlag::BN254_STRUCTURES,
+        FeatureFlag::WEBAUTHN_SIGNATURE,
+        FeatureFlag::SECP256R1_NATIVES,
+        FeatureFlag::POSEIDON_BN254_NATIVES,
     ]
 }
 
@@ -558,7 +558,7 @@ pub fn setup_environment_and_start_node(
     let admin_service = services::start_admin_service(&node_config);
 
     // Set up the storage database and any RocksDB checkpoints
-    let (aptos_db, db_rw, backup_service, genesis_waypoint) =
+    let (aptos_db, db_rw, backup_service, genesis_waypoint, backup_restorer) =
         storage::initialize_database_and_checkpoints(&mut node_config)?;
 
     admin_service.set_aptos_db(db_rw.clone().into());
@@ -617,6 +617,7 @@ pub fn setup_environment_and_start_node(
             genesis_waypoint,
             event_subscription_service,
             db_rw.clone(),
+            backup_restorer,
         )?;
 
     // Start the node inspection service
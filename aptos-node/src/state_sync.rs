@@ -20,6 +20,7 @@ use aptos_network::application::{
     storage::PeersAndMetadata,
 };
 use aptos_state_sync_driver::{
+    backup_restorer::BackupRestorer,
     driver_factory::{DriverFactory, StateSyncRuntimes},
     metadata_storage::PersistentMetadataStorage,
 };
@@ -80,6 +81,7 @@ pub fn start_state_sync_and_get_notification_handles(
     waypoint: Waypoint,
     event_subscription_service: EventSubscriptionService,
     db_rw: DbReaderWriter,
+    backup_restorer: Option<Arc<dyn BackupRestorer>>,
 ) -> anyhow::Result<(
     AptosDataClient,
     StateSyncRuntimes,
@@ -140,6 +142,7 @@ pub fn start_state_sync_and_get_notification_handles(
         aptos_data_client.clone(),
         streaming_service_client,
         TimeService::real(),
+        backup_restorer,
     );
 
     // Create a new state sync runtime handle
@@ -2,16 +2,37 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, Result};
+use aptos_backup_cli::{
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata::cache::MetadataCacheOpt,
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        s3::{config::S3Config, S3Storage},
+        BackupStorage,
+    },
+    utils::{GlobalRestoreOptions, RestoreRunMode},
+};
 use aptos_backup_service::start_backup_service;
-use aptos_config::{config::NodeConfig, utils::get_genesis_txn};
-use aptos_db::{fast_sync_storage_wrapper::FastSyncStorageWrapper, AptosDB};
+use aptos_config::{
+    config::{BackupRestoreConfig, BackupStorageConfig, NodeConfig},
+    utils::get_genesis_txn,
+};
+use aptos_db::{
+    fast_sync_storage_wrapper::FastSyncStorageWrapper, get_restore_handler::GetRestoreHandler,
+    AptosDB,
+};
 use aptos_executor::db_bootstrapper::maybe_bootstrap;
 use aptos_logger::{debug, info};
+use aptos_state_sync_driver::backup_restorer::BackupRestorer;
 use aptos_storage_interface::{DbReader, DbReaderWriter};
-use aptos_types::{ledger_info::LedgerInfoWithSignatures, waypoint::Waypoint};
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, transaction::Version, waypoint::Waypoint,
+};
 use aptos_vm::AptosVM;
+use async_trait::async_trait;
 use either::Either;
-use std::{fs, path::Path, sync::Arc, time::Instant};
+use std::{collections::HashMap, fs, path::Path, sync::Arc, time::Instant};
 use tokio::runtime::Runtime;
 
 pub(crate) fn maybe_apply_genesis(
@@ -29,11 +50,107 @@ pub(crate) fn maybe_apply_genesis(
     }
 }
 
+/// Restores the DB from backups (as configured in `storage.backup_restore`),
+/// for nodes that bootstrap by restoring from backups.
+struct DbBackupRestorer {
+    // The backups to restore from
+    config: BackupRestoreConfig,
+    // The DB to restore into
+    db: Arc<AptosDB>,
+    // The wrapper holding the DB (if the DB was empty on startup)
+    fast_sync_db_wrapper: Option<Arc<FastSyncStorageWrapper>>,
+    // The waypoint the restored epoch history is verified against
+    waypoint: Waypoint,
+}
+
+impl DbBackupRestorer {
+    async fn init_backup_storage(&self) -> Result<Arc<dyn BackupStorage>> {
+        Ok(match &self.config.backup_storage {
+            BackupStorageConfig::LocalFs { dir } => Arc::new(LocalFs::new(dir.clone())),
+            BackupStorageConfig::CommandAdapter { config } => Arc::new(CommandAdapter::new(
+                CommandAdapterConfig::load_from_file(config).await?,
+            )),
+            BackupStorageConfig::S3 { config } => {
+                Arc::new(S3Storage::new(S3Config::load_from_file(config).await?)?)
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl BackupRestorer for DbBackupRestorer {
+    async fn restore(&self) -> Result<Version> {
+        let backup_storage = self.init_backup_storage().await?;
+        let global_opt = GlobalRestoreOptions {
+            target_version: Version::MAX,
+            trusted_waypoints: Arc::new(HashMap::from([(self.waypoint.version(), self.waypoint)])),
+            run_mode: Arc::new(RestoreRunMode::Restore {
+                restore_handler: self.db.get_restore_handler(),
+            }),
+            concurrent_downloads: self
+                .config
+                .concurrent_downloads
+                .unwrap_or_else(num_cpus::get),
+            replay_concurrency_level: self
+                .config
+                .replay_concurrency_level
+                .unwrap_or_else(num_cpus::get),
+        };
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::new(self.config.metadata_cache_dir.as_ref()),
+                replay_all: false,
+                ledger_history_start_version: None,
+                skip_epoch_endings: false,
+            },
+            global_opt,
+            backup_storage,
+        )
+        .run()
+        .await?;
+
+        // From now on, read from and write to the restored DB
+        if let Some(fast_sync_db_wrapper) = &self.fast_sync_db_wrapper {
+            fast_sync_db_wrapper.finish_restore_from_backup();
+        }
+        self.db.get_latest_version()
+    }
+}
+
+/// Creates the restorer of the DB from backups, if state sync
+/// bootstraps by restoring from backups.
+fn create_backup_restorer(
+    node_config: &NodeConfig,
+    db: Arc<AptosDB>,
+    fast_sync_db_wrapper: Option<Arc<FastSyncStorageWrapper>>,
+) -> Option<Arc<dyn BackupRestorer>> {
+    if !node_config
+        .state_sync
+        .state_sync_driver
+        .bootstrapping_mode
+        .is_restore_from_backup()
+    {
+        return None;
+    }
+    let config = node_config.storage.backup_restore.clone()?;
+    Some(Arc::new(DbBackupRestorer {
+        config,
+        db,
+        fast_sync_db_wrapper,
+        waypoint: node_config.base.waypoint.genesis_waypoint(),
+    }))
+}
+
 #[cfg(not(feature = "consensus-only-perf-test"))]
 pub(crate) fn bootstrap_db(
     node_config: &NodeConfig,
-) -> Result<(Arc<dyn DbReader>, DbReaderWriter, Option<Runtime>)> {
-    let (aptos_db_reader, db_rw, backup_service) =
+) -> Result<(
+    Arc<dyn DbReader>,
+    DbReaderWriter,
+    Option<Runtime>,
+    Option<Arc<dyn BackupRestorer>>,
+)> {
+    let (aptos_db_reader, db_rw, backup_service, backup_restorer) =
         match FastSyncStorageWrapper::initialize_dbs(node_config)? {
             Either::Left(db) => {
                 let (db_arc, db_rw) = DbReaderWriter::wrap(db);
@@ -42,7 +159,13 @@ pub(crate) fn bootstrap_db(
                     db_arc.clone(),
                 );
                 maybe_apply_genesis(&db_rw, node_config)?;
-                (db_arc as Arc<dyn DbReader>, db_rw, Some(db_backup_service))
+                let backup_restorer = create_backup_restorer(node_config, db_arc.clone(), None);
+                (
+                    db_arc as Arc<dyn DbReader>,
+                    db_rw,
+                    Some(db_backup_service),
+                    backup_restorer,
+                )
             },
             Either::Right(fast_sync_db_wrapper) => {
                 let temp_db = fast_sync_db_wrapper.get_temporary_db_with_genesis();
@@ -65,14 +188,23 @@ pub(crate) fn bootstrap_db(
                     fast_sync_db.commit_genesis_ledger_info(&ledger_info)?;
                 }
 
-                let db_backup_service =
-                    start_backup_service(node_config.storage.backup_service_address, fast_sync_db);
+                let db_backup_service = start_backup_service(
+                    node_config.storage.backup_service_address,
+                    fast_sync_db.clone(),
+                );
+                let backup_restorer =
+                    create_backup_restorer(node_config, fast_sync_db, Some(db_arc.clone()));
 
-                (db_arc as Arc<dyn DbReader>, db_rw, Some(db_backup_service))
+                (
+                    db_arc as Arc<dyn DbReader>,
+                    db_rw,
+                    Some(db_backup_service),
+                    backup_restorer,
+                )
             },
         };
 
-    Ok((aptos_db_reader, db_rw, backup_service))
+    Ok((aptos_db_reader, db_rw, backup_service, backup_restorer))
 }
 
 /// In consensus-only mode, return a in-memory based [FakeAptosDB] and
@@ -80,7 +212,12 @@ pub(crate) fn bootstrap_db(
 #[cfg(feature = "consensus-only-perf-test")]
 pub(crate) fn bootstrap_db(
     node_config: &NodeConfig,
-) -> Result<(Arc<dyn DbReader>, DbReaderWriter, Option<Runtime>)> {
+) -> Result<(
+    Arc<dyn DbReader>,
+    DbReaderWriter,
+    Option<Runtime>,
+    Option<Arc<dyn BackupRestorer>>,
+)> {
     use aptos_db::fake_aptosdb::FakeAptosDB;
 
     let aptos_db = AptosDB::open(
@@ -95,7 +232,7 @@ pub(crate) fn bootstrap_db(
     .map_err(|err| anyhow!("DB failed to open {}", err))?;
     let (aptos_db, db_rw) = DbReaderWriter::wrap(FakeAptosDB::new(aptos_db));
     maybe_apply_genesis(&db_rw, node_config)?;
-    Ok((aptos_db, db_rw, None))
+    Ok((aptos_db, db_rw, None, None))
 }
 
 /// Creates a RocksDb checkpoint for the consensus_db, state_sync_db,
@@ -137,10 +274,16 @@ fn create_rocksdb_checkpoint_and_change_working_dir(
 
 /// Creates any rocksdb checkpoints, opens the storage database,
 /// starts the backup service, handles genesis initialization and returns
-/// the various handles.
+/// the various handles (including any restorer of the DB from backups).
 pub fn initialize_database_and_checkpoints(
     node_config: &mut NodeConfig,
-) -> Result<(Arc<dyn DbReader>, DbReaderWriter, Option<Runtime>, Waypoint)> {
+) -> Result<(
+    Arc<dyn DbReader>,
+    DbReaderWriter,
+    Option<Runtime>,
+    Waypoint,
+    Option<Arc<dyn BackupRestorer>>,
+)> {
     // If required, create RocksDB checkpoints and change the working directory.
     // This is test-only.
     if let Some(working_dir) = node_config.base.working_dir.clone() {
@@ -149,7 +292,7 @@ pub fn initialize_database_and_checkpoints(
 
     // Open the database
     let instant = Instant::now();
-    let (aptos_db, db_rw, backup_service) = bootstrap_db(node_config)?;
+    let (aptos_db, db_rw, backup_service, backup_restorer) = bootstrap_db(node_config)?;
 
     // Log the duration to open storage
     debug!(
@@ -162,5 +305,6 @@ pub fn initialize_database_and_checkpoints(
         db_rw,
         backup_service,
         node_config.base.waypoint.genesis_waypoint(),
+        backup_restorer,
     ))
 }
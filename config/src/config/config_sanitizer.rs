@@ -6,7 +6,8 @@ use crate::config::{
     utils::{are_failpoints_enabled, get_config_name},
     AdminServiceConfig, ApiConfig, BaseConfig, ConsensusConfig, DagConsensusConfig, Error,
     ExecutionConfig, IndexerGrpcConfig, InspectionServiceConfig, LoggerConfig, MempoolConfig,
-    NetbenchConfig, NodeConfig, PeerMonitoringServiceConfig, StateSyncConfig, StorageConfig,
+    NetbenchConfig, NetworkConfig, NodeConfig, PeerMonitoringServiceConfig, StateSyncConfig,
+    StorageConfig, OUTBOUND_PRIORITY_PROTOCOLS,
 };
 use aptos_types::chain_id::ChainId;
 use std::collections::HashSet;
@@ -142,6 +143,9 @@ fn sanitize_fullnode_network_configs(
                 ),
             ));
         }
+
+        // Verify the outbound protocol priorities
+        sanitize_outbound_protocol_priorities(fullnode_network_config, &sanitizer_name)?;
     }
 
     Ok(())
@@ -189,6 +193,30 @@ fn sanitize_validator_network_config(
                 "Mutual authentication must be enabled for the validator network!".into(),
             ));
         }
+
+        // Verify the outbound protocol priorities
+        sanitize_outbound_protocol_priorities(validator_network_config, &sanitizer_name)?;
+    }
+
+    Ok(())
+}
+
+/// Sanitize the outbound protocol priorities of a network config, which must only name known
+/// protocols
+fn sanitize_outbound_protocol_priorities(
+    network_config: &NetworkConfig,
+    sanitizer_name: &str,
+) -> Result<(), Error> {
+    for protocol in network_config.outbound_protocol_priorities.keys() {
+        if !OUTBOUND_PRIORITY_PROTOCOLS.contains(&protocol.as_str()) {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name.to_string(),
+                format!(
+                    "Unknown protocol in the outbound protocol priorities of network {}: {}",
+                    network_config.network_id, protocol
+                ),
+            ));
+        }
     }
 
     Ok(())
@@ -365,4 +393,64 @@ mod tests {
         .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_outbound_protocol_priorities() {
+        // Create a fullnode config with valid outbound protocol priorities
+        let mut node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                network_id: NetworkId::Public,
+                outbound_protocol_priorities: [
+                    ("ConsensusRpcBcs".to_string(), 2),
+                    ("StorageServiceRpc".to_string(), 1),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it succeeds
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+
+        // Add an unknown protocol, and verify that sanitization fails
+        node_config.full_node_networks[0]
+            .outbound_protocol_priorities
+            .insert("Consensus".to_string(), 3);
+        let error = sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Create a validator config with an unknown protocol
+        let node_config = NodeConfig {
+            validator_network: Some(NetworkConfig {
+                network_id: NetworkId::Validator,
+                mutual_authentication: true,
+                outbound_protocol_priorities: [("consensus_rpc".to_string(), 1)]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error = sanitize_validator_network_config(
+            &node_config,
+            NodeType::Validator,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
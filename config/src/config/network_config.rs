@@ -26,6 +26,7 @@ use std::{
     collections::{HashMap, HashSet},
     convert::TryFrom,
     fmt,
+    net::SocketAddr,
     path::PathBuf,
     string::ToString,
 };
@@ -53,6 +54,25 @@ pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; /* 64 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+/// The names of the protocols that can be given an outbound priority. See
+/// [`aptos_network::protocols::wire::handshake::v1::ProtocolId`](../../network/protocols/wire/handshake/v1/enum.ProtocolId.html).
+pub const OUTBOUND_PRIORITY_PROTOCOLS: &[&str] = &[
+    "ConsensusRpcBcs",
+    "ConsensusDirectSendBcs",
+    "MempoolDirectSend",
+    "StateSyncDirectSend",
+    "DiscoveryDirectSend",
+    "HealthCheckerRpc",
+    "ConsensusDirectSendJson",
+    "ConsensusRpcJson",
+    "StorageServiceRpc",
+    "MempoolRpc",
+    "PeerMonitoringServiceRpc",
+    "ConsensusRpcCompressed",
+    "ConsensusDirectSendCompressed",
+    "NetbenchDirectSend",
+    "NetbenchRpc",
+];
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -125,6 +145,11 @@ pub struct NetworkConfig {
     pub max_message_size: usize,
     /// The maximum number of parallel message deserialization tasks that can run (per application)
     pub max_parallel_deserialization_tasks: Option<usize>,
+    /// The priorities of outbound messages, keyed by protocol name (one of
+    /// `OUTBOUND_PRIORITY_PROTOCOLS`). Queued messages of higher priority protocols are sent
+    /// first (lower priorities are still served regularly, to avoid starvation). Unlisted
+    /// protocols have priority 0, so an empty map serves all protocols round robin.
+    pub outbound_protocol_priorities: HashMap<String, u8>,
 }
 
 impl Default for NetworkConfig {
@@ -166,6 +191,7 @@ impl NetworkConfig {
             outbound_rx_buffer_size_bytes: None,
             outbound_tx_buffer_size_bytes: None,
             max_parallel_deserialization_tasks: None,
+            outbound_protocol_priorities: HashMap::new(),
         };
 
         // Configure the number of parallel deserialization tasks
@@ -348,6 +374,7 @@ pub enum DiscoveryMethod {
     Onchain,
     File(FileDiscovery),
     Rest(RestDiscovery),
+    Dns(DnsDiscovery),
     None,
 }
 
@@ -365,6 +392,20 @@ pub struct RestDiscovery {
     pub interval_secs: u64,
 }
 
+/// Discovers peers from DNS: the SRV records of `name` list the peer hosts and ports, and the
+/// TXT records of every SRV target hold the peer's x25519 public key (`noise=<hex>`) and,
+/// optionally, its peer id (`peer_id=<hex>`).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DnsDiscovery {
+    /// Name of the SRV records, e.g. `_aptos._tcp.seeds.example.com`
+    pub name: String,
+    pub interval_secs: u64,
+    /// The nameserver to query, defaults to the first nameserver in `/etc/resolv.conf`
+    #[serde(default)]
+    pub nameserver: Option<SocketAddr>,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RateLimitConfig {
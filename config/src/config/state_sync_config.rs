@@ -44,6 +44,10 @@ pub enum BootstrappingMode {
     ExecuteTransactionsFromGenesis,
     /// Executes transactions or applies outputs from genesis (whichever is faster)
     ExecuteOrApplyFromGenesis,
+    /// Restores the epoch ending ledger infos, the latest state snapshot and the
+    /// transactions after it from backups (see `StorageConfig::backup_restore`),
+    /// and then executes transactions or applies outputs from the restored version
+    RestoreFromBackup,
 }
 
 impl BootstrappingMode {
@@ -57,6 +61,7 @@ impl BootstrappingMode {
                 "execute_transactions_from_genesis"
             },
             BootstrappingMode::ExecuteOrApplyFromGenesis => "execute_or_apply_from_genesis",
+            BootstrappingMode::RestoreFromBackup => "restore_from_backup",
         }
     }
 
@@ -64,6 +69,11 @@ impl BootstrappingMode {
     pub fn is_fast_sync(&self) -> bool {
         *self == BootstrappingMode::DownloadLatestStates
     }
+
+    /// Returns true iff the bootstrapping mode restores from backups
+    pub fn is_restore_from_backup(&self) -> bool {
+        *self == BootstrappingMode::RestoreFromBackup
+    }
 }
 
 /// The continuous syncing mode determines how the node will stay up-to-date
@@ -317,6 +327,33 @@ impl Default for AptosLatencyFilteringConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AptosPeerReputationConfig {
+    /// Whether or not to persist peer reputations (across node restarts)
+    pub enable_peer_reputation_persistence: bool,
+    /// Maximum number of recent response latencies to track for each peer
+    pub max_num_latency_samples_per_peer: u64,
+    /// Maximum time (in seconds) to remember the reputation of a disconnected peer
+    pub max_reputation_age_secs: u64,
+    /// The interval (in seconds) at which to persist the peer reputations
+    pub persistence_interval_secs: u64,
+    /// The half-life (in seconds) of a disconnected peer's score deviation from the starting score
+    pub score_decay_half_life_secs: u64,
+}
+
+impl Default for AptosPeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            enable_peer_reputation_persistence: true,
+            max_num_latency_samples_per_peer: 20,
+            max_reputation_age_secs: 604_800, // 7 days
+            persistence_interval_secs: 60,    // 1 minute
+            score_decay_half_life_secs: 3600, // 1 hour
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct AptosDataClientConfig {
@@ -344,6 +381,8 @@ pub struct AptosDataClientConfig {
     pub max_transaction_output_chunk_size: u64,
     /// Timeout (in ms) when waiting for an optimistic fetch response
     pub optimistic_fetch_timeout_ms: u64,
+    /// The peer reputation config for the data client
+    pub peer_reputation_config: AptosPeerReputationConfig,
     /// First timeout (in ms) when waiting for a response
     pub response_timeout_ms: u64,
     /// Timeout (in ms) when waiting for a subscription response
@@ -366,7 +405,8 @@ impl Default for AptosDataClientConfig {
             max_subscription_lag_secs: 30, // 30 seconds
             max_transaction_chunk_size: MAX_TRANSACTION_CHUNK_SIZE,
             max_transaction_output_chunk_size: MAX_TRANSACTION_OUTPUT_CHUNK_SIZE,
-            optimistic_fetch_timeout_ms: 5000,        // 5 seconds
+            optimistic_fetch_timeout_ms: 5000, // 5 seconds
+            peer_reputation_config: AptosPeerReputationConfig::default(),
             response_timeout_ms: 10_000,              // 10 seconds
             subscription_response_timeout_ms: 20_000, // 20 seconds (must be longer than a regular timeout because of pre-fetching)
             use_compression: true,
@@ -405,6 +445,20 @@ impl ConfigSanitizer for StateSyncDriverConfig {
             ));
         }
 
+        // Verify that nodes restoring from backups know where the backups are
+        if state_sync_driver_config
+            .bootstrapping_mode
+            .is_restore_from_backup()
+            && node_config.storage.backup_restore.is_none()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The backups to restore from must be configured (in storage.backup_restore) \
+                for nodes that are restoring from backups!"
+                    .to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -492,6 +546,7 @@ impl ConfigOptimizer for DataStreamingServiceConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{BackupRestoreConfig, BackupStorageConfig};
 
     #[test]
     fn test_optimize_bootstrapping_mode_devnet_vfn() {
@@ -710,6 +765,40 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_restore_from_backup_without_backups() {
+        // Create a node config that restores from backups,
+        // without configuring the backups.
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::RestoreFromBackup,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Configure the backups and verify that sanitization succeeds
+        node_config.storage.backup_restore = Some(BackupRestoreConfig {
+            backup_storage: BackupStorageConfig::LocalFs {
+                dir: "/opt/aptos/backups".into(),
+            },
+            metadata_cache_dir: None,
+            concurrent_downloads: None,
+            replay_concurrency_level: None,
+        });
+        StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+            .unwrap();
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
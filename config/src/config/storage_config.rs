@@ -174,6 +174,38 @@ pub struct StorageConfig {
     /// If not specificed, will use `dir` as default.
     /// Only allowed when sharding is enabled.
     pub db_path_overrides: Option<DbPathConfig>,
+    /// The backups to restore the DB from, when state sync bootstraps by
+    /// restoring from backups (see `BootstrappingMode::RestoreFromBackup`).
+    pub backup_restore: Option<BackupRestoreConfig>,
+}
+
+/// The backup storage and the options with which a node restores its DB from backups
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackupRestoreConfig {
+    /// The backup storage holding the backups
+    pub backup_storage: BackupStorageConfig,
+    /// Directory to cache the backup metadata in. Defaults to a temporary directory.
+    #[serde(default)]
+    pub metadata_cache_dir: Option<PathBuf>,
+    /// Number of concurrent downloads from the backup storage. Defaults to the number of CPUs.
+    #[serde(default)]
+    pub concurrent_downloads: Option<usize>,
+    /// Number of threads used to replay transactions. Defaults to the number of CPUs.
+    #[serde(default)]
+    pub replay_concurrency_level: Option<usize>,
+}
+
+/// The backup storage types, as supported by the backup CLI
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStorageConfig {
+    /// A directory on the local file system, used mainly for tests
+    LocalFs { dir: PathBuf },
+    /// A command adapter config file, describing the shell commands used to access the storage
+    CommandAdapter { config: PathBuf },
+    /// An S3 compatible object store, configured by the given config file
+    S3 { config: PathBuf },
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -321,6 +353,7 @@ impl Default for StorageConfig {
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
             db_path_overrides: None,
+            backup_restore: None,
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
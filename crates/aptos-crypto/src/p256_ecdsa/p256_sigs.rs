@@ -42,6 +42,16 @@ impl P256Signature {
         }
     }
 
+    /// Deserialize an P256Signature from its ASN.1 DER encoding, e.g., as returned by WebAuthn
+    /// authenticators. The signature is normalized to its canonical (low S) form, see
+    /// `make_canonical`.
+    pub fn from_der(bytes: &[u8]) -> std::result::Result<P256Signature, CryptoMaterialError> {
+        match p256::ecdsa::Signature::from_der(bytes) {
+            Ok(p256_signature) => Ok(P256Signature(p256_signature).make_canonical()),
+            Err(_) => Err(CryptoMaterialError::DeserializationError),
+        }
+    }
+
     /// return an all-zero signature (for test only)
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn dummy_signature() -> Self {
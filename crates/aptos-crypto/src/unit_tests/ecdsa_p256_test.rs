@@ -140,6 +140,29 @@ proptest! {
         prop_assert!(deserialized.verify(&hashable, &keypair.public_key).is_ok());
     }
 
+    #[test]
+    fn test_signature_from_der(
+        msg in vec(proptest::num::u8::ANY, 1..128),
+        keypair in uniform_keypair_strategy::<P256PrivateKey, P256PublicKey>()
+    ) {
+        let signature = keypair.private_key.sign_arbitrary_message(&msg);
+        let der = signature.0.to_der();
+        let deserialized = P256Signature::from_der(der.as_bytes()).unwrap();
+        prop_assert_eq!(&deserialized, &signature);
+        prop_assert!(deserialized.verify_arbitrary_msg(&msg, &keypair.public_key).is_ok());
+
+        // A non-canonical signature is normalized when parsed from DER.
+        let malleable_s = NonZeroScalar::new(-*signature.0.s()).unwrap();
+        let malleable = p256::ecdsa::Signature::from_scalars(signature.0.r(), malleable_s).unwrap();
+        let deserialized = P256Signature::from_der(malleable.to_der().as_bytes()).unwrap();
+        prop_assert_eq!(&deserialized, &signature);
+
+        prop_assert_eq!(
+            P256Signature::from_der(&signature.to_bytes()),
+            Err(CryptoMaterialError::DeserializationError)
+        );
+    }
+
 
     // Check for canonical S.
     #[test]
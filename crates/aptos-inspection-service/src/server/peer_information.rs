@@ -248,6 +248,13 @@ fn display_state_sync_metadata(
                 "\t- Peer: {}, score: {}, bucket ID: {}",
                 peer, peer_score, peer_bucket_id
             ));
+            if let Some(ignore_reason) = peer_state_entry.get_ignore_reason() {
+                peer_information_output.push(format!("\t\t- Ignored: {}", ignore_reason));
+            }
+            peer_information_output.push(format!(
+                "\t\t- Recent response latencies (ms): {:?}",
+                peer_state_entry.get_recent_response_latencies_ms()
+            ));
             peer_information_output.push(format!(
                 "\t\t- Advertised storage summary: {:?}",
                 peer_storage_summary
@@ -268,6 +275,25 @@ fn display_state_sync_metadata(
             ));
         }
     }
+
+    // Fetch and display the remembered reputations of disconnected peers
+    peer_information_output.push("Peer reputations (for disconnected peers):".into());
+    let disconnected_peer_reputations = aptos_data_client
+        .get_peer_states()
+        .get_disconnected_peer_reputations();
+    for (peer, reputation) in disconnected_peer_reputations {
+        peer_information_output.push(format!(
+            "\t- Peer: {}, decayed score: {}, last updated (unix secs): {}",
+            peer, reputation.score, reputation.last_updated_unix_secs
+        ));
+        if let Some(ignore_reason) = reputation.ignore_reason {
+            peer_information_output.push(format!("\t\t- Ignored: {}", ignore_reason));
+        }
+        peer_information_output.push(format!(
+            "\t\t- Recent response latencies (ms): {:?}",
+            reputation.recent_response_latencies_ms
+        ));
+    }
 }
 
 /// Displays the entire set of trusted peers
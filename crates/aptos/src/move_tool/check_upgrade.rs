@@ -0,0 +1,232 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, RestOptions,
+        },
+        utils::get_feature_flag,
+    },
+    move_tool::{stored_package::CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{natives::code::UpgradePolicy, BuildOptions, BuiltPackage};
+use aptos_types::{account_address::AccountAddress, on_chain_config::FeatureFlag};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, IncompatibilityKind},
+    normalized, CompiledModule,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Checks whether the package can be published as an upgrade of the one on chain
+///
+/// The package is built locally and compared with the package of the same name published at
+/// `--account`, with the checks `0x1::code::publish_package` and the VM apply when publishing:
+/// the on-chain upgrade policy must allow upgrades and must not be weakened, no module may be
+/// removed, and each module must stay compatible with its published version, i.e. keep its
+/// public and entry functions, struct layouts and abilities, and friend functions and
+/// declarations. Every violation is reported.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account the package is published at
+    ///
+    /// Defaults to the account of the profile
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) account: Option<AccountAddress>,
+
+    /// Artifacts to be generated when building this package.
+    #[clap(long, default_value_t = IncludedArtifacts::Sparse)]
+    pub(crate) included_artifacts: IncludedArtifacts,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// The outcome of a successful upgrade check.
+#[derive(Debug, Serialize)]
+pub struct UpgradeCheck {
+    pub package: String,
+    /// The upgrade number of the published package, if the package is published.
+    pub upgrade_number: Option<u64>,
+    pub upgrade_policy: String,
+    /// The ledger version the published package was read at.
+    pub version: u64,
+}
+
+#[async_trait]
+impl CliCommand<UpgradeCheck> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeCheck> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            bytecode_version: self.move_options.bytecode_version,
+            ..self.included_artifacts.build_options(
+                self.move_options.dev,
+                self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.named_addresses(),
+                self.move_options.bytecode_version,
+                self.move_options.compiler_version,
+                self.move_options.skip_attribute_checks,
+                self.move_options.check_test_code,
+            )
+        };
+        let pack = BuiltPackage::build(self.move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let new_policy = pack.extract_metadata()?.upgrade_policy;
+        let new_modules: BTreeMap<String, &CompiledModule> = pack
+            .modules()
+            .map(|module| (module.self_id().name().to_string(), module))
+            .collect();
+
+        let account = match self.account {
+            Some(account) => account,
+            None => self.profile_options.account_address()?,
+        };
+        let url = self.rest_options.url(&self.profile_options)?;
+        let client = self.rest_options.client(&self.profile_options)?;
+        let registry = CachedPackageRegistry::create(url, account).await?;
+
+        let mut violations = vec![];
+        // Mirrors `0x1::code::publish_package`.
+        if new_policy == UpgradePolicy::arbitrary() {
+            violations.push(
+                "The package has upgrade policy `arbitrary`, which can no longer be published"
+                    .to_string(),
+            );
+        }
+        for other_name in registry.package_names() {
+            if other_name == pack.name() {
+                continue;
+            }
+            let other = registry.get_package(other_name).await?;
+            for module_name in other.module_names() {
+                if new_modules.contains_key(module_name) {
+                    violations.push(format!(
+                        "Module `{}` is already published by package `{}` at {}",
+                        module_name,
+                        other_name,
+                        account.to_hex_literal()
+                    ));
+                }
+            }
+        }
+
+        let package = match registry.get_package(pack.name()).await {
+            Ok(package) => package,
+            // A new package: there is nothing to upgrade.
+            Err(_) => {
+                return finish(
+                    UpgradeCheck {
+                        package: pack.name().to_string(),
+                        upgrade_number: None,
+                        upgrade_policy: new_policy.to_string(),
+                        version: registry.version(),
+                    },
+                    violations,
+                )
+            },
+        };
+        let old_policy = package.upgrade_policy();
+        if old_policy == UpgradePolicy::immutable() {
+            violations.push(format!(
+                "The published package has upgrade policy `{}`, so it cannot be upgraded",
+                old_policy
+            ));
+        }
+        if old_policy.policy > new_policy.policy {
+            violations.push(format!(
+                "The upgrade policy is weakened from `{}` to `{}`, but it can only be kept or \
+                 strengthened",
+                old_policy, new_policy
+            ));
+        }
+        for module_name in package.module_names() {
+            if !new_modules.contains_key(module_name) {
+                violations.push(format!(
+                    "Module `{}` was removed, but an upgrade must keep all published modules",
+                    module_name
+                ));
+            }
+        }
+
+        // Mirrors the compatibility check of the VM when publishing modules.
+        let treat_friend_as_private =
+            get_feature_flag(&client, FeatureFlag::TREAT_FRIEND_AS_PRIVATE).await?;
+        let compatibility = Compatibility::new(true, true, !treat_friend_as_private);
+        let old_bytecode = registry.get_package_bytecode(&package).await?;
+        for (module_name, bytecode) in package.module_names().into_iter().zip(old_bytecode) {
+            let new_module = match new_modules.get(module_name) {
+                Some(new_module) => new_module,
+                None => continue,
+            };
+            let old_module = CompiledModule::deserialize(&bytecode).map_err(|err| {
+                CliError::UnexpectedError(format!(
+                    "Unable to deserialize published module `{}`: {}",
+                    module_name, err
+                ))
+            })?;
+            for incompatibility in compatibility.incompatibilities(
+                &normalized::Module::new(&old_module),
+                &normalized::Module::new(new_module),
+            ) {
+                violations.push(format!(
+                    "Module `{}`: {}. {}",
+                    module_name,
+                    incompatibility.description,
+                    explain(incompatibility.kind)
+                ));
+            }
+        }
+
+        finish(
+            UpgradeCheck {
+                package: pack.name().to_string(),
+                upgrade_number: Some(package.upgrade_number()),
+                upgrade_policy: old_policy.to_string(),
+                version: registry.version(),
+            },
+            violations,
+        )
+    }
+}
+
+fn finish(check: UpgradeCheck, violations: Vec<String>) -> CliTypedResult<UpgradeCheck> {
+    if violations.is_empty() {
+        return Ok(check);
+    }
+    Err(CliError::UnexpectedError(format!(
+        "Package `{}` cannot be published as an upgrade, found {} violation(s):\n{}",
+        check.package,
+        violations.len(),
+        violations
+            .iter()
+            .map(|violation| format!("- {}", violation))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )))
+}
+
+fn explain(kind: IncompatibilityKind) -> &'static str {
+    match kind {
+        IncompatibilityKind::StructAndPubFunctionLinking => {
+            "Modules depending on the published version could no longer link against it"
+        },
+        IncompatibilityKind::StructLayout => {
+            "Values stored on chain by the published version could no longer be read"
+        },
+        IncompatibilityKind::FriendLinking => {
+            "Friend modules of the published version could no longer link against it"
+        },
+    }
+}
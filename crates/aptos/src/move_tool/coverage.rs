@@ -4,14 +4,22 @@
 use crate::common::types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir};
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    coverage_map::CoverageMap,
+    format_csv_summary, format_human_summary,
+    line_coverage::{format_cobertura, format_lcov, ModuleLineCoverage},
+    source_coverage::SourceCoverageBuilder,
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -142,6 +150,82 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CoverageFormat {
+    /// LCOV tracefile, e.g. for Codecov or Coveralls
+    Lcov,
+    /// Cobertura XML report, e.g. for GitLab or Jenkins
+    Cobertura,
+}
+
+/// Export the line coverage of all modules in a package for coverage services and CI dashboards
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// Format of the coverage report
+    #[clap(long, value_enum, default_value_t = CoverageFormat::Lcov)]
+    pub format: CoverageFormat,
+    /// File to write the coverage report to
+    ///
+    /// Defaults to `lcov.info` or `cobertura.xml` in the package directory
+    #[clap(long, value_parser)]
+    pub output_file: Option<PathBuf>,
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<PathBuf> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<PathBuf> {
+        let path = self.move_options.get_package_path()?;
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let coverage_map = coverage_map.to_unified_exec_map();
+        let modules = package
+            .root_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(NamedCompiledModule {
+                    module, source_map, ..
+                }) => Some(
+                    ModuleLineCoverage::new(module, source_map, &unit.source_path, &coverage_map)
+                        .map_err(|err| {
+                            CliError::UnexpectedError(format!(
+                                "Failed to get coverage of {}: {:#}",
+                                unit.unit.name(),
+                                err
+                            ))
+                        }),
+                ),
+                _ => None,
+            })
+            .collect::<CliTypedResult<Vec<_>>>()?;
+
+        let output_file = self.output_file.unwrap_or_else(|| {
+            path.join(match self.format {
+                CoverageFormat::Lcov => "lcov.info",
+                CoverageFormat::Cobertura => "cobertura.xml",
+            })
+        });
+        let file = File::create(&output_file)
+            .map_err(|err| CliError::IO(output_file.display().to_string(), err))?;
+        let mut writer = BufWriter::new(file);
+        match self.format {
+            CoverageFormat::Lcov => format_lcov(&modules, &mut writer),
+            CoverageFormat::Cobertura => format_cobertura(
+                package.compiled_package_info.package_name.as_str(),
+                &path,
+                &modules,
+                &mut writer,
+            ),
+        }
+        .and_then(|_| writer.flush())
+        .map_err(|err| CliError::IO(output_file.display().to_string(), err))?;
+        Ok(output_file)
+    }
+}
+
 fn compile_coverage(
     move_options: MovePackageDir,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -178,6 +262,7 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
 }
 
 impl CoveragePackage {
@@ -186,6 +271,7 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -0,0 +1,253 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult, PromptOptions},
+    utils::{
+        check_if_file_exists, create_dir_if_not_exist, dir_default_to_current, read_from_file,
+        write_to_user_only_file,
+    },
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_command_line_common::files::{
+    extension_equals, find_filenames, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION,
+};
+use move_disassembler::decompiler::Decompiler;
+use std::path::{Path, PathBuf};
+
+/// Decompile Move bytecode into Move source
+///
+/// Reconstructs the struct definitions and functions of modules, including their control flow
+/// and locals, e.g. to read a module published without its source code:
+/// 1. Download the package with its bytecode - aptos move download --bytecode
+/// 2. Decompile the modules - aptos move decompile --bytecode-path ./<package>/bytecode_modules
+#[derive(Debug, Parser)]
+pub struct Decompile {
+    /// The path to the bytecode file of a module to decompile, or to a directory of them
+    #[clap(long)]
+    pub bytecode_path: PathBuf,
+
+    /// Output directory for the decompiled modules. Defaults to the current directory.
+    #[clap(long, value_parser)]
+    pub(crate) output_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for Decompile {
+    fn command_name(&self) -> &'static str {
+        "Decompile"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let bytecode_path = self.bytecode_path.as_path();
+        if bytecode_path.is_file() && !extension_equals(bytecode_path, MOVE_COMPILED_EXTENSION) {
+            return Err(CliError::UnexpectedError(format!(
+                "Bad bytecode file extension for {}; expected {}",
+                bytecode_path.display(),
+                MOVE_COMPILED_EXTENSION
+            )));
+        }
+        let files = find_filenames(&[bytecode_path], |path| {
+            extension_equals(path, MOVE_COMPILED_EXTENSION)
+        })?;
+        if files.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "No bytecode files found in {}",
+                bytecode_path.display()
+            )));
+        }
+
+        let output_dir = dir_default_to_current(self.output_dir.clone())?;
+        create_dir_if_not_exist(output_dir.as_path())?;
+        let mut decompiled_files = vec![];
+        for file in files {
+            let (name, source) = decompile_file(Path::new(&file))?;
+            let file_name = format!("{}.{}", name, MOVE_EXTENSION);
+            let decompiled_file = output_dir.join(&file_name);
+            check_if_file_exists(decompiled_file.as_path(), self.prompt_options)?;
+            write_to_user_only_file(decompiled_file.as_path(), &file_name, source.as_bytes())?;
+            decompiled_files.push(decompiled_file.display().to_string());
+        }
+        Ok(decompiled_files)
+    }
+}
+
+/// Decompiles the module in the bytecode file, returning its name and source.
+fn decompile_file(path: &Path) -> CliTypedResult<(String, String)> {
+    let bytecode = read_from_file(path)?;
+    let module = CompiledModule::deserialize(&bytecode)
+        .with_context(|| format!("Module blob {} can't be deserialized", path.display()))?;
+    let source = Decompiler::new(&module).decompile().map_err(|err| {
+        CliError::UnexpectedError(format!("Unable to decompile {}: {}", path.display(), err))
+    })?;
+    Ok((module.self_id().name().to_string(), source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use move_command_line_common::address::NumericalAddress;
+    use move_compiler::{
+        compiled_unit::{CompiledUnit, NamedCompiledModule},
+        shared::Flags,
+        Compiler,
+    };
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        fs,
+    };
+
+    const SOURCE: &str = r#"
+        module 0x42::first {
+            public fun max(a: u64, b: u64): u64 {
+                if (a > b) a else b
+            }
+        }
+
+        module 0x42::second {
+            struct Counter has key {
+                value: u64,
+            }
+        }
+    "#;
+
+    fn temp_dir() -> TempPath {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        dir
+    }
+
+    /// Compiles `SOURCE`, writing the bytecode of its modules to `dir`.
+    fn compile_modules(dir: &Path) -> Vec<CompiledModule> {
+        let source_dir = temp_dir();
+        let source_path = source_dir.path().join("modules.move");
+        fs::write(&source_path, SOURCE).unwrap();
+        let (_, units) = Compiler::from_files(
+            vec![source_path.display().to_string()],
+            vec![],
+            BTreeMap::<String, NumericalAddress>::new(),
+            Flags::empty(),
+            &BTreeSet::new(),
+        )
+        .build_and_report()
+        .unwrap();
+
+        fs::create_dir_all(dir).unwrap();
+        units
+            .into_iter()
+            .map(|unit| match unit.into_compiled_unit() {
+                CompiledUnit::Module(NamedCompiledModule { name, module, .. }) => {
+                    let mut bytecode = vec![];
+                    module.serialize(&mut bytecode).unwrap();
+                    fs::write(
+                        dir.join(format!("{}.{}", name, MOVE_COMPILED_EXTENSION)),
+                        bytecode,
+                    )
+                    .unwrap();
+                    module
+                },
+                CompiledUnit::Script(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn decompile(bytecode_path: &Path, output_dir: &Path, assume_no: bool) -> Decompile {
+        Decompile {
+            bytecode_path: bytecode_path.to_path_buf(),
+            output_dir: Some(output_dir.to_path_buf()),
+            prompt_options: PromptOptions {
+                assume_yes: !assume_no,
+                assume_no,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompile_directory() {
+        let bytecode_dir = temp_dir();
+        let modules = compile_modules(bytecode_dir.path());
+        fs::write(bytecode_dir.path().join("README.md"), "not bytecode").unwrap();
+        let output_dir = temp_dir();
+
+        let mut files = decompile(bytecode_dir.path(), output_dir.path(), false)
+            .execute()
+            .await
+            .unwrap();
+        files.sort();
+        assert_eq!(files, vec![
+            output_dir.path().join("first.move").display().to_string(),
+            output_dir.path().join("second.move").display().to_string(),
+        ]);
+        for module in &modules {
+            let path =
+                output_dir
+                    .path()
+                    .join(format!("{}.{}", module.self_id().name(), MOVE_EXTENSION));
+            assert_eq!(
+                fs::read_to_string(path).unwrap(),
+                Decompiler::new(module).decompile().unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompile_file() {
+        let bytecode_dir = temp_dir();
+        compile_modules(bytecode_dir.path());
+        let bytecode_path = bytecode_dir.path().join("first.mv");
+        let output_dir = temp_dir();
+
+        let files = decompile(&bytecode_path, output_dir.path(), true)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(files, vec![output_dir
+            .path()
+            .join("first.move")
+            .display()
+            .to_string()]);
+        let source = fs::read_to_string(output_dir.path().join("first.move")).unwrap();
+        assert!(source.starts_with("module 0x42::first {"));
+
+        // Existing files are only overwritten when confirmed.
+        assert!(decompile(&bytecode_path, output_dir.path(), true)
+            .execute()
+            .await
+            .is_err());
+        assert!(decompile(&bytecode_path, output_dir.path(), false)
+            .execute()
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_decompile_without_bytecode_fails() {
+        let dir = temp_dir();
+        let output_dir = temp_dir();
+        assert!(decompile(dir.path(), output_dir.path(), false)
+            .execute()
+            .await
+            .is_err());
+
+        let source_path = dir.path().join("first.move");
+        fs::write(&source_path, SOURCE).unwrap();
+        assert!(decompile(&source_path, output_dir.path(), false)
+            .execute()
+            .await
+            .is_err());
+
+        let invalid_path = dir.path().join("invalid.mv");
+        fs::write(&invalid_path, [0xA1, 0x1C]).unwrap();
+        assert!(decompile(&invalid_path, output_dir.path(), false)
+            .execute()
+            .await
+            .is_err());
+    }
+}
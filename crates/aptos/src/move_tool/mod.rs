@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aptos_debug_natives;
+mod check_upgrade;
 pub mod coverage;
+mod decompiler;
 mod disassembler;
 mod manifest;
 pub mod package_hooks;
 mod show;
+mod simulate;
 pub mod stored_package;
 
 use crate::{
@@ -26,6 +29,7 @@ use crate::{
     governance::CompileScriptFunction,
     move_tool::{
         coverage::SummaryCoverage,
+        decompiler::Decompile,
         disassembler::Disassemble,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
     },
@@ -51,7 +55,8 @@ use move_cli::{self, base::test::UnitTestResult};
 use move_command_line_common::env::MOVE_HOME;
 use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::U256};
 use move_package::{
-    source_package::layout::SourcePackageLayout, BuildConfig, CompilerConfig, CompilerVersion,
+    resolution::lockfile::LockedDependency, source_package::layout::SourcePackageLayout,
+    BuildConfig, CompilerConfig, CompilerVersion,
 };
 use move_unit_test::UnitTestingConfig;
 pub use package_hooks::*;
@@ -74,12 +79,14 @@ use tokio::task;
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(check_upgrade::CheckUpgrade),
     Clean(CleanPackage),
     Compile(CompilePackage),
     CompileScript(CompileScript),
     #[clap(subcommand)]
     Coverage(coverage::CoveragePackage),
     CreateResourceAccountAndPublishPackage(CreateResourceAccountAndPublishPackage),
+    Decompile(Decompile),
     Disassemble(Disassemble),
     Document(DocumentPackage),
     Download(DownloadPackage),
@@ -91,7 +98,9 @@ pub enum MoveTool {
     RunScript(RunScript),
     #[clap(subcommand, hide = true)]
     Show(show::ShowTool),
+    Simulate(simulate::SimulateTransactions),
     Test(TestPackage),
+    UpdateDeps(UpdateDeps),
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
 }
@@ -100,6 +109,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
             MoveTool::CompileScript(tool) => tool.execute_serialized().await,
@@ -107,6 +117,7 @@ impl MoveTool {
             MoveTool::CreateResourceAccountAndPublishPackage(tool) => {
                 tool.execute_serialized_success().await
             },
+            MoveTool::Decompile(tool) => tool.execute_serialized().await,
             MoveTool::Disassemble(tool) => tool.execute_serialized().await,
             MoveTool::Document(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
@@ -117,7 +128,9 @@ impl MoveTool {
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::RunScript(tool) => tool.execute_serialized().await,
             MoveTool::Show(tool) => tool.execute_serialized().await,
+            MoveTool::Simulate(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
+            MoveTool::UpdateDeps(tool) => tool.execute_serialized().await,
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
         }
@@ -439,6 +452,13 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// Write a JSON report of the tests to this file, for CI dashboards
+    ///
+    /// The report gives for each test whether it passed, its failure if it did not, the gas it
+    /// used and how long it took.
+    #[clap(long, value_parser)]
+    pub json_report: Option<PathBuf>,
 }
 
 #[async_trait]
@@ -474,6 +494,7 @@ impl CliCommand<&'static str> for TestPackage {
                 report_stacktrace_on_abort: true,
                 report_storage_on_error: self.dump_state,
                 ignore_compile_warnings: self.ignore_compile_warnings,
+                json_report: self.json_report.clone(),
                 ..UnitTestingConfig::default_with_bound(None)
             },
             // TODO(Gas): we may want to switch to non-zero costs in the future
@@ -981,6 +1002,11 @@ pub struct DownloadPackage {
     /// Print metadata of the package
     #[clap(long)]
     pub print_metadata: bool,
+
+    /// Also save the bytecode of the modules, e.g. to decompile modules published without their
+    /// source code with `aptos move decompile`
+    #[clap(long)]
+    pub bytecode: bool,
 }
 
 #[async_trait]
@@ -1012,6 +1038,14 @@ impl CliCommand<&'static str> for DownloadPackage {
         package
             .save_package_to_disk(package_path.as_path())
             .map_err(|e| CliError::UnexpectedError(format!("Failed to save package: {}", e)))?;
+        if self.bytecode {
+            let bytecode = registry.get_package_bytecode(&package).await?;
+            package
+                .save_bytecode_to_disk(package_path.as_path(), &bytecode)
+                .map_err(|e| {
+                    CliError::UnexpectedError(format!("Failed to save bytecode: {}", e))
+                })?;
+        }
         println!(
             "Saved package with {} module(s) to `{}`",
             package.module_names().len(),
@@ -1201,6 +1235,42 @@ impl CliCommand<&'static str> for CleanPackage {
     }
 }
 
+/// Updates the lockfile of a package to the latest versions of its dependencies
+///
+/// The lockfile, `Move.lock`, pins each git dependency of the package, transitively, to a commit,
+/// and each on-chain dependency to an upgrade number and a hash of its bytecode. When a package
+/// has a lockfile, builds check out the pinned commits rather than the latest ones, and fail if a
+/// dependency doesn't match the lockfile, e.g. if an on-chain dependency was upgraded.
+///
+/// This resolves the dependencies afresh and writes the lockfile, creating it if needed.
+#[derive(Parser)]
+pub struct UpdateDeps {
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<BTreeMap<String, LockedDependency>> for UpdateDeps {
+    fn command_name(&self) -> &'static str {
+        "UpdateDeps"
+    }
+
+    async fn execute(self) -> CliTypedResult<BTreeMap<String, LockedDependency>> {
+        let config = BuildConfig {
+            additional_named_addresses: self.move_options.named_addresses(),
+            install_dir: self.move_options.output_dir.clone(),
+            ..Default::default()
+        };
+        let lockfile = config
+            .update_lockfile(
+                self.move_options.get_package_path()?.as_path(),
+                &mut std::io::stderr(),
+            )
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        Ok(lockfile.dependencies)
+    }
+}
+
 /// Run a Move function
 #[derive(Parser)]
 pub struct RunFunction {
@@ -2,10 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{common::types::load_account_arg, move_tool::CachedPackageRegistry};
+use aptos_crypto::HashValue;
 use aptos_framework::UPGRADE_POLICY_CUSTOM_FIELD;
 use futures::executor::block_on;
 use move_package::{
-    compilation::package_layout::CompiledPackageLayout, package_hooks::PackageHooks,
+    compilation::package_layout::CompiledPackageLayout,
+    package_hooks::{CustomDepVersion, PackageHooks},
     source_package::parsed_manifest::CustomDepInfo,
 };
 use move_symbol_pool::Symbol;
@@ -33,6 +35,13 @@ impl PackageHooks for AptosPackageHooks {
     ) -> anyhow::Result<()> {
         block_on(maybe_download_package(info))
     }
+
+    fn custom_dependency_version(
+        &self,
+        info: &CustomDepInfo,
+    ) -> anyhow::Result<Option<CustomDepVersion>> {
+        block_on(package_version(info)).map(Some)
+    }
 }
 
 async fn maybe_download_package(info: &CustomDepInfo) -> anyhow::Result<()> {
@@ -52,3 +61,19 @@ async fn maybe_download_package(info: &CustomDepInfo) -> anyhow::Result<()> {
         Ok(())
     }
 }
+
+/// The upgrade number of an on-chain package, and the hash of its bytecode, read at the same
+/// ledger version.
+async fn package_version(info: &CustomDepInfo) -> anyhow::Result<CustomDepVersion> {
+    let registry = CachedPackageRegistry::create(
+        Url::parse(info.node_url.as_str())?,
+        load_account_arg(info.package_address.as_str())?,
+    )
+    .await?;
+    let package = registry.get_package(info.package_name).await?;
+    let bytecode = registry.get_package_bytecode(&package).await?;
+    Ok(CustomDepVersion {
+        version: package.upgrade_number(),
+        bytecode_hash: HashValue::sha3_256_of(&bcs::to_bytes(&bytecode)?).to_hex(),
+    })
+}
@@ -0,0 +1,397 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{
+        load_account_arg, CliCommand, CliError, CliTypedResult, EntryFunctionArguments,
+        EntryFunctionArgumentsJSON, GasOptions, TransactionOptions, TransactionSummary,
+    },
+    utils::parse_json_file,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    PrivateKey,
+};
+use aptos_keygen::KeyGen;
+use aptos_move_debugger::{aptos_debugger::AptosDebugger, fork::ForkedState};
+use aptos_rest_client::Client;
+use aptos_sdk::transaction_builder::TransactionFactory;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{AccountResource, CoinStoreResource},
+    transaction::{
+        authenticator::AuthenticationKey, EntryFunction, SignedTransaction, TransactionPayload,
+        TransactionStatus,
+    },
+};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf};
+
+const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+const DEFAULT_MAX_GAS: u64 = 2_000_000;
+
+/// Simulate transactions locally against a fork of the on-chain state
+///
+/// The transactions are executed one after the other on a local overlay of the state at
+/// `--version`, which is fetched on demand from a fullnode or from a local database. Each
+/// transaction sees the writes of the previous ones, and nothing is submitted to the network.
+/// This can be used to rehearse multi-step flows, e.g. a governance proposal and its
+/// resolution, against mainnet state.
+///
+/// Transactions are sent by the account of the profile, or by the `sender` given in the JSON
+/// file. Since signatures are not verified, a sender's authentication key is replaced on the
+/// fork when its private key is not available.
+#[derive(Parser)]
+pub struct SimulateTransactions {
+    /// URL of a fullnode's REST API, or path to a database directory, to fork the state from
+    ///
+    /// Defaults to the URL of the profile
+    #[clap(long)]
+    pub(crate) fork: Option<String>,
+
+    /// Version of the state to fork, i.e. the state right after the transaction at this version
+    ///
+    /// Defaults to the latest version
+    #[clap(long)]
+    pub(crate) version: Option<u64>,
+
+    /// The entry function to call, or with `--json-file`, the calls to simulate in order
+    ///
+    /// The JSON file is either a single call, in the format of `aptos move run --json-file`,
+    /// or an array of them. A call may have an additional `sender` field, to be sent by an
+    /// account other than the profile's.
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+#[derive(Deserialize)]
+struct SimulatedCallJSON {
+    sender: Option<String>,
+    #[serde(flatten)]
+    call: EntryFunctionArgumentsJSON,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SimulatedCallsJSON {
+    One(SimulatedCallJSON),
+    Many(Vec<SimulatedCallJSON>),
+}
+
+#[async_trait]
+impl CliCommand<Vec<TransactionSummary>> for SimulateTransactions {
+    fn command_name(&self) -> &'static str {
+        "SimulateTransactions"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<TransactionSummary>> {
+        let signer = self.txn_options.get_key_and_address().ok();
+        let calls = Self::calls(
+            self.entry_function_args,
+            signer.as_ref().map(|(_, address)| *address),
+        )?;
+
+        let debugger = match &self.fork {
+            Some(fork) if fork.starts_with("http://") || fork.starts_with("https://") => {
+                let url = reqwest::Url::parse(fork)
+                    .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+                AptosDebugger::rest_client(Client::new(url))
+            },
+            Some(fork) => AptosDebugger::db(PathBuf::from(fork)),
+            None => AptosDebugger::rest_client(
+                self.txn_options
+                    .rest_options
+                    .client(&self.txn_options.profile_options)?,
+            ),
+        }
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to fork state: {}", err)))?;
+        let version = match self.version {
+            Some(version) => version,
+            None => debugger
+                .get_latest_version()
+                .await
+                .map_err(|err| CliError::ApiError(err.to_string()))?,
+        };
+        let mut fork = debugger.fork_at_version(version + 1);
+        simulate_calls(
+            &mut fork,
+            version,
+            calls,
+            signer,
+            &self.txn_options.gas_options,
+        )
+    }
+}
+
+/// Simulates the calls in order on the fork of the state at `version`, so that each one sees the
+/// writes of the previous ones.
+fn simulate_calls(
+    fork: &mut ForkedState,
+    version: u64,
+    calls: Vec<(AccountAddress, EntryFunction)>,
+    signer: Option<(Ed25519PrivateKey, AccountAddress)>,
+    gas_options: &GasOptions,
+) -> CliTypedResult<Vec<TransactionSummary>> {
+    let chain_id = fork
+        .chain_id()
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+
+    let gas_unit_price = gas_options.gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE);
+    // Signatures are not verified, so senders without a private key share a throwaway key.
+    let impersonation_key = KeyGen::from_os_rng().generate_ed25519_private_key();
+    let mut public_keys: HashMap<AccountAddress, Ed25519PublicKey> = HashMap::new();
+    if let Some((key, address)) = &signer {
+        public_keys.insert(*address, key.public_key());
+    }
+
+    let mut summaries = vec![];
+    for (sender, entry_function) in calls {
+        let public_key = match public_keys.get(&sender) {
+            Some(public_key) => public_key.clone(),
+            None => {
+                let public_key = impersonation_key.public_key();
+                fork.set_authentication_key(
+                    sender,
+                    AuthenticationKey::ed25519(&public_key).to_vec(),
+                )
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+                public_keys.insert(sender, public_key.clone());
+                public_key
+            },
+        };
+        let sequence_number = fork
+            .get_resource::<AccountResource>(sender)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .ok_or_else(|| {
+                CliError::UnexpectedError(format!("Account {} not found on the fork", sender))
+            })?
+            .sequence_number();
+        let max_gas = gas_options.max_gas.unwrap_or_else(|| {
+            let balance = fork
+                .get_resource::<CoinStoreResource>(sender)
+                .ok()
+                .flatten()
+                .map_or(0, |coin_store| coin_store.coin());
+            if gas_unit_price == 0 {
+                DEFAULT_MAX_GAS
+            } else {
+                std::cmp::min(balance / gas_unit_price, DEFAULT_MAX_GAS)
+            }
+        });
+
+        let raw_txn = TransactionFactory::new(chain_id)
+            .with_gas_unit_price(gas_unit_price)
+            .with_max_gas_amount(max_gas)
+            .with_transaction_expiration_time(gas_options.expiration_secs)
+            .payload(TransactionPayload::EntryFunction(entry_function))
+            .sender(sender)
+            .sequence_number(sequence_number)
+            .build();
+        let txn = SignedTransaction::new(
+            raw_txn,
+            public_key,
+            Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+        );
+        let hash = txn.clone().committed_hash();
+
+        let (vm_status, output) = fork
+            .simulate_transaction(&txn)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let success = match output.status() {
+            TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
+        };
+        summaries.push(TransactionSummary {
+            transaction_hash: hash.into(),
+            gas_used: Some(output.gas_used()),
+            gas_unit_price: Some(gas_unit_price),
+            pending: None,
+            sender: Some(sender),
+            sequence_number: Some(sequence_number),
+            success,
+            timestamp_us: None,
+            version: Some(version), // The version of the state the simulation forked.
+            vm_status: Some(vm_status.to_string()),
+        });
+    }
+    Ok(summaries)
+}
+
+impl SimulateTransactions {
+    /// The calls to simulate with their senders, from the command line or the JSON file.
+    fn calls(
+        entry_function_args: EntryFunctionArguments,
+        default_sender: Option<AccountAddress>,
+    ) -> CliTypedResult<Vec<(AccountAddress, EntryFunction)>> {
+        let sender_or_default = |sender: Option<String>| match sender {
+            Some(sender) => load_account_arg(&sender),
+            None => default_sender.ok_or_else(|| {
+                CliError::CommandArgumentError(
+                    "A sender is required: use a profile or '--private-key', or give a 'sender' \
+                     in the JSON file"
+                        .to_string(),
+                )
+            }),
+        };
+
+        let json_path = match entry_function_args.json_file.clone() {
+            Some(json_path) => json_path,
+            None => {
+                return Ok(vec![(
+                    sender_or_default(None)?,
+                    entry_function_args.try_into()?,
+                )])
+            },
+        };
+        let json_calls = match parse_json_file::<SimulatedCallsJSON>(&json_path)? {
+            SimulatedCallsJSON::One(call) => vec![call],
+            SimulatedCallsJSON::Many(calls) => calls,
+        };
+        json_calls
+            .into_iter()
+            .map(|SimulatedCallJSON { sender, call }| {
+                let entry_function_args: EntryFunctionArguments = call.try_into()?;
+                Ok((sender_or_default(sender)?, entry_function_args.try_into()?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_storage_interface::DbReader;
+    use aptos_types::{
+        access_path::AccessPath,
+        account_config::aptos_test_root_address,
+        state_store::{state_key::StateKey, state_value::StateValue},
+        transaction::Version,
+    };
+    use aptos_validator_interface::DBDebuggerInterface;
+    use aptos_vm_genesis::test_genesis_change_set_and_validators;
+    use move_core_types::move_resource::MoveStructType;
+    use std::sync::{Arc, Mutex};
+
+    /// The genesis state, recording every read.
+    struct GenesisDb {
+        state: HashMap<StateKey, StateValue>,
+        reads: Mutex<Vec<(StateKey, Version)>>,
+    }
+
+    impl DbReader for GenesisDb {
+        fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> anyhow::Result<Option<StateValue>> {
+            self.reads
+                .lock()
+                .unwrap()
+                .push((state_key.clone(), version));
+            Ok(self.state.get(state_key).cloned())
+        }
+    }
+
+    /// Forks the genesis state, i.e. the state at version 0.
+    fn fork_genesis() -> (Arc<GenesisDb>, ForkedState) {
+        let (change_set, _) = test_genesis_change_set_and_validators(Some(1));
+        let state = change_set
+            .write_set()
+            .iter()
+            .filter_map(|(state_key, write_op)| {
+                write_op
+                    .as_state_value()
+                    .map(|value| (state_key.clone(), value))
+            })
+            .collect();
+        let db = Arc::new(GenesisDb {
+            state,
+            reads: Mutex::new(vec![]),
+        });
+        let debugger = AptosDebugger::new(Arc::new(DBDebuggerInterface::new(db.clone())));
+        (db, debugger.fork_at_version(1))
+    }
+
+    fn transfer(
+        sender: AccountAddress,
+        receiver: AccountAddress,
+        amount: u64,
+    ) -> (AccountAddress, EntryFunction) {
+        match aptos_stdlib::aptos_account_transfer(receiver, amount) {
+            TransactionPayload::EntryFunction(entry_function) => (sender, entry_function),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_calls_see_writes_of_previous_calls() {
+        let (_, mut fork) = fork_genesis();
+        let root = aptos_test_root_address();
+        let account = AccountAddress::random();
+
+        // The account only exists on the fork, created by the first call.
+        let calls = vec![
+            transfer(root, account, 10_000_000),
+            transfer(account, root, 1_000),
+            transfer(root, account, 2_000),
+        ];
+        let summaries = simulate_calls(&mut fork, 0, calls, None, &GasOptions::default()).unwrap();
+
+        assert_eq!(summaries.len(), 3);
+        assert!(summaries
+            .iter()
+            .all(|summary| summary.success == Some(true) && summary.version == Some(0)));
+        assert_eq!(summaries[1].sequence_number, Some(0));
+        assert_eq!(summaries[2].sequence_number, Some(1));
+
+        let account_resource = fork
+            .get_resource::<AccountResource>(account)
+            .unwrap()
+            .unwrap();
+        assert_eq!(account_resource.sequence_number(), 1);
+        let coin_store = fork
+            .get_resource::<CoinStoreResource>(account)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            coin_store.coin(),
+            10_000_000 - 1_000 + 2_000 - summaries[1].gas_used.unwrap() * DEFAULT_GAS_UNIT_PRICE
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_base_state_is_only_read_through() {
+        let (db, mut fork) = fork_genesis();
+        let root = aptos_test_root_address();
+        let account = AccountAddress::random();
+
+        let summaries = simulate_calls(
+            &mut fork,
+            0,
+            vec![transfer(root, account, 10_000_000)],
+            None,
+            &GasOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(summaries[0].success, Some(true));
+
+        // The state at the forked version is read, and the writes stay on the fork.
+        let reads = db.reads.lock().unwrap();
+        assert!(!reads.is_empty());
+        assert!(reads.iter().all(|(_, version)| *version == 0));
+        let account_key = StateKey::access_path(
+            AccessPath::resource_access_path(account, AccountResource::struct_tag()).unwrap(),
+        );
+        assert!(!db.state.contains_key(&account_key));
+        assert!(fork
+            .get_resource::<AccountResource>(account)
+            .unwrap()
+            .is_some());
+    }
+}
@@ -18,6 +18,10 @@ use std::{fmt, fs, path::Path};
 /// Represents the package registry at a given account.
 pub struct CachedPackageRegistry {
     inner: PackageRegistry,
+    client: Client,
+    address: AccountAddress,
+    /// The ledger version the registry was read at.
+    version: u64,
 }
 
 /// Represents the package metadata found in an registry.
@@ -42,11 +46,21 @@ impl CachedPackageRegistry {
     pub async fn create(url: Url, addr: AccountAddress) -> anyhow::Result<Self> {
         let client = Client::new(url);
         // Need to use a different type to deserialize JSON
-        let inner = client
+        let response = client
             .get_account_resource_bcs::<PackageRegistry>(addr, "0x1::code::PackageRegistry")
-            .await?
-            .into_inner();
-        Ok(Self { inner })
+            .await?;
+        let version = response.state().version;
+        Ok(Self {
+            inner: response.into_inner(),
+            client,
+            address: addr,
+            version,
+        })
+    }
+
+    /// The ledger version the registry was read at.
+    pub fn version(&self) -> u64 {
+        self.version
     }
 
     /// Returns the list of packages in this registry by name.
@@ -87,6 +101,25 @@ impl CachedPackageRegistry {
         }
         bail!("package `{}` not found", name)
     }
+
+    /// Fetches the bytecode of the modules of a package in this registry, in the order of the
+    /// package metadata, at the ledger version the registry was read at.
+    pub async fn get_package_bytecode(
+        &self,
+        package: &CachedPackageMetadata<'_>,
+    ) -> anyhow::Result<Vec<Vec<u8>>> {
+        let mut bytecode = vec![];
+        for module in &package.metadata.modules {
+            bytecode.push(
+                self.client
+                    .get_account_module_bcs_at_version(self.address, &module.name, self.version)
+                    .await?
+                    .into_inner()
+                    .to_vec(),
+            );
+        }
+        Ok(bytecode)
+    }
 }
 
 impl<'a> CachedPackageMetadata<'a> {
@@ -149,6 +182,17 @@ impl<'a> CachedPackageMetadata<'a> {
         Ok(())
     }
 
+    /// Saves the bytecode of the modules, as returned by `get_package_bytecode`, to the
+    /// `bytecode_modules` directory of the package.
+    pub fn save_bytecode_to_disk(&self, path: &Path, bytecode: &[Vec<u8>]) -> anyhow::Result<()> {
+        let bytecode_dir = path.join(CompiledPackageLayout::CompiledModules.path());
+        fs::create_dir_all(&bytecode_dir)?;
+        for (module, bytecode) in self.metadata.modules.iter().zip(bytecode) {
+            fs::write(bytecode_dir.join(format!("{}.mv", module.name)), bytecode)?;
+        }
+        Ok(())
+    }
+
     pub fn verify(&self, package_metadata: &PackageMetadata) -> anyhow::Result<()> {
         let self_metadata = self.metadata;
 
@@ -213,3 +257,49 @@ impl<'a> CachedModuleMetadata<'a> {
         &self.metadata.source_map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_framework::natives::code::MoveOption;
+    use aptos_temppath::TempPath;
+
+    fn module_metadata(name: &str) -> ModuleMetadata {
+        ModuleMetadata {
+            name: name.to_string(),
+            source: vec![],
+            source_map: vec![],
+            extension: MoveOption::none(),
+        }
+    }
+
+    #[test]
+    fn test_save_bytecode_to_disk() {
+        let metadata = PackageMetadata {
+            name: "package".to_string(),
+            upgrade_policy: UpgradePolicy::compat(),
+            upgrade_number: 0,
+            source_digest: String::new(),
+            manifest: vec![],
+            modules: vec![module_metadata("first"), module_metadata("second")],
+            deps: vec![],
+            extension: MoveOption::none(),
+        };
+        let package = CachedPackageMetadata {
+            metadata: &metadata,
+        };
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+
+        // The bytecode is in the order of the modules in the metadata.
+        package
+            .save_bytecode_to_disk(dir.path(), &[vec![1, 2], vec![3]])
+            .unwrap();
+        let bytecode_dir = dir
+            .path()
+            .join(CompiledPackageLayout::CompiledModules.path());
+        assert_eq!(fs::read(bytecode_dir.join("first.mv")).unwrap(), vec![1, 2]);
+        assert_eq!(fs::read(bytecode_dir.join("second.mv")).unwrap(), vec![3]);
+        assert_eq!(fs::read_dir(&bytecode_dir).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,1218 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    health_checker::HealthChecker, traits::ServiceManager, utils::socket_addr_to_url,
+    RunLocalTestnet,
+};
+use anyhow::{ensure, format_err, Context, Result};
+use aptos_api_types::{
+    AccountData, Address, AptosError, AptosErrorCode, AsConverter, GasEstimation, GasEstimationBcs,
+    HexEncodedBytes, IndexResponse, IndexResponseBcs, LedgerInfo, MoveModuleBytecode,
+    MoveStructTag, SubmitTransactionRequest, Transaction as ApiTransaction, TransactionData,
+    TransactionOnChainData, ViewFunction, ViewRequest, X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID,
+    X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_OLDEST_BLOCK_HEIGHT,
+};
+use aptos_config::config::{ApiConfig, RoleType};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_move_debugger::{aptos_debugger::AptosDebugger, fork::ForkedState};
+use aptos_rest_client::Client;
+use aptos_state_view::TStateView;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::{AccountResource, CoinStoreResource},
+    chain_id::ChainId,
+    on_chain_config::{ConfigurationResource, CurrentTimeMicroseconds, OnChainConfig},
+    state_store::state_key::StateKey,
+    transaction::{
+        authenticator::AuthenticationKey, ExecutionStatus, RawTransaction, SignedTransaction,
+        Transaction, TransactionInfo, TransactionStatus, Version,
+    },
+    vm_status::VMStatus,
+};
+use aptos_vm::{data_cache::AsMoveResolver, AptosSimulationVM, AptosVM};
+use async_trait::async_trait;
+use clap::Parser;
+use maplit::hashset;
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    resolver::MoveResolver,
+};
+use poem::{
+    get, handler,
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    listener::TcpListener,
+    middleware::Tracing,
+    post,
+    web::{Data, Path, Query},
+    Body, EndpointExt, Response, Route, Server,
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use tracing::info;
+
+/// The directory in the test dir holding the fork.
+const FORK_FOLDER: &str = "fork";
+/// The file recording what the fork in the test dir was created from.
+const FORK_INFO_FILE: &str = "fork.json";
+/// The directory of the fork holding the transactions executed on it.
+const TRANSACTIONS_FOLDER: &str = "transactions";
+
+/// The port of the REST API of the fork, the same as a node's by default.
+const API_PORT: u16 = 8080;
+/// The gas unit price the fork estimates, the minimum on mainnet.
+const GAS_ESTIMATE: u64 = 100;
+
+const BCS: &str = aptos_api_types::mime_types::BCS;
+const BCS_SIGNED_TRANSACTION: &str = aptos_api_types::mime_types::BCS_SIGNED_TRANSACTION;
+const BCS_VIEW_FUNCTION: &str = aptos_api_types::mime_types::BCS_VIEW_FUNCTION;
+const JSON: &str = aptos_api_types::mime_types::JSON;
+
+/// Args related to running the local testnet as a fork of another network.
+#[derive(Debug, Parser)]
+pub struct ForkArgs {
+    /// Fork the network with this REST API URL instead of starting a new chain at genesis,
+    /// e.g. https://fullnode.mainnet.aptoslabs.com
+    ///
+    /// The local node serves the state of the network at `--fork-version`, fetched from the
+    /// network as it is read and cached in `--test-dir`. Transactions submitted to the local
+    /// node are executed right away, each in a block of its own, and only change the local copy
+    /// of the state. Both persist across restarts, until `--force-restart`.
+    ///
+    /// A fork serves a subset of the node API: the ledger info, accounts with their resources
+    /// and modules, submitting, simulating and looking up transactions, and view functions. The
+    /// time on the fork stays at the one of `--fork-version`. Since there is no faucet, accounts
+    /// of the network can be used by setting their authentication key with
+    /// `POST /v1/-/fork/set_authentication_key`, e.g. with the body
+    /// `{"address": "0x...", "authentication_key": "0x..."}`.
+    #[clap(long, conflicts_with = "with_indexer_api")]
+    pub fork_url: Option<Url>,
+
+    /// The version of the network to fork
+    ///
+    /// Defaults to the latest version, or to the version of the fork in `--test-dir`.
+    #[clap(long, requires = "fork_url")]
+    pub fork_version: Option<Version>,
+}
+
+/// What a fork was created from, to resume the same fork across restarts.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct ForkInfo {
+    url: Url,
+    version: Version,
+    block_height: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ForkManager {
+    url: Url,
+    version: Option<Version>,
+    api_address: SocketAddrV4,
+    dir: PathBuf,
+}
+
+impl ForkManager {
+    pub fn new(
+        args: &RunLocalTestnet,
+        bind_to: Ipv4Addr,
+        test_dir: PathBuf,
+        url: Url,
+    ) -> Result<Self> {
+        Ok(Self {
+            url,
+            version: args.fork_args.fork_version,
+            api_address: SocketAddrV4::new(bind_to, API_PORT),
+            dir: test_dir.join(FORK_FOLDER),
+        })
+    }
+
+    pub fn get_node_api_url(&self) -> Url {
+        socket_addr_to_url(&SocketAddr::V4(self.api_address), "http").unwrap()
+    }
+}
+
+#[async_trait]
+impl ServiceManager for ForkManager {
+    fn get_name(&self) -> String {
+        "Node API".to_string()
+    }
+
+    fn get_health_checkers(&self) -> HashSet<HealthChecker> {
+        hashset! {HealthChecker::NodeApi(self.get_node_api_url())}
+    }
+
+    fn get_prerequisite_health_checkers(&self) -> HashSet<&HealthChecker> {
+        // The fork doesn't depend on anything, we start it first.
+        hashset! {}
+    }
+
+    async fn run_service(self: Box<Self>) -> Result<()> {
+        let fork = Fork::open(self.url, self.version, self.dir).await?;
+        info!(
+            "Forked {} at version {}, with {} local transactions",
+            fork.info.url,
+            fork.info.version,
+            fork.transactions.len()
+        );
+        let app = Route::new()
+            .nest(
+                "/v1",
+                Route::new()
+                    .at("/", get(index))
+                    .at("/-/healthy", get(healthy))
+                    .at(
+                        "/-/fork/set_authentication_key",
+                        post(set_authentication_key),
+                    )
+                    .at("/accounts/:address", get(account))
+                    .at("/accounts/:address/resource/:resource_type", get(resource))
+                    .at("/accounts/:address/module/:module_name", get(module))
+                    .at("/estimate_gas_price", get(estimate_gas_price))
+                    .at("/transactions", post(submit_transaction))
+                    .at("/transactions/simulate", post(simulate_transaction))
+                    .at("/transactions/by_hash/:hash", get(transaction_by_hash))
+                    .at(
+                        "/transactions/by_version/:version",
+                        get(transaction_by_version),
+                    )
+                    .at("/view", post(view)),
+            )
+            .data(Arc::new(Mutex::new(fork)))
+            .with(Tracing);
+        Server::new(TcpListener::bind(self.api_address))
+            .name("fork-api")
+            .run(app)
+            .await?;
+        Err(format_err!("Fork API exited unexpectedly"))
+    }
+}
+
+/// The fork of a network served by the local testnet, persisted in a directory of the test dir.
+struct Fork {
+    info: ForkInfo,
+    state: ForkedState,
+    chain_id: ChainId,
+    /// The transactions executed on the fork, the first one at the version after the fork's.
+    transactions: Vec<TransactionOnChainData>,
+    transaction_versions: HashMap<HashValue, Version>,
+    dir: PathBuf,
+}
+
+impl Fork {
+    /// Opens the fork in `dir`, or creates it if there is none yet.
+    async fn open(url: Url, version: Option<Version>, dir: PathBuf) -> Result<Self> {
+        let client = Client::new(url.clone());
+        let info_path = dir.join(FORK_INFO_FILE);
+        let info = if info_path.exists() {
+            let info: ForkInfo = serde_json::from_slice(&fs::read(&info_path)?)?;
+            ensure!(
+                info.url == url && version.map_or(true, |version| version == info.version),
+                "The test dir holds a fork of {} at version {}, use --force-restart to fork again",
+                info.url,
+                info.version,
+            );
+            info
+        } else {
+            let version = match version {
+                Some(version) => version,
+                None => client.get_ledger_information().await?.into_inner().version,
+            };
+            let block_height = client
+                .get_block_by_version(version, false)
+                .await?
+                .into_inner()
+                .block_height
+                .0;
+            let info = ForkInfo {
+                url,
+                version,
+                block_height,
+            };
+            fs::create_dir_all(&dir)?;
+            fs::write(&info_path, serde_json::to_vec_pretty(&info)?)?;
+            info
+        };
+
+        // The debugger forks the state right before the given version.
+        let state = AptosDebugger::rest_client(client)?
+            .fork_at_version(info.version + 1)
+            .persist_in(dir.clone())
+            .context("Failed to open the state of the fork")?;
+        let chain_id = state.chain_id()?;
+
+        let transactions_dir = dir.join(TRANSACTIONS_FOLDER);
+        fs::create_dir_all(&transactions_dir)?;
+        let mut transactions = vec![];
+        for entry in fs::read_dir(&transactions_dir)? {
+            let txn: TransactionOnChainData = bcs::from_bytes(&fs::read(entry?.path())?)?;
+            transactions.push(txn);
+        }
+        transactions.sort_by_key(|txn| txn.version);
+        let transaction_versions = transactions
+            .iter()
+            .map(|txn| (txn.info.transaction_hash(), txn.version))
+            .collect();
+
+        Ok(Self {
+            info,
+            state,
+            chain_id,
+            transactions,
+            transaction_versions,
+            dir,
+        })
+    }
+
+    fn version(&self) -> Version {
+        self.info.version + self.transactions.len() as u64
+    }
+
+    fn ledger_info(&self) -> LedgerInfo {
+        let resolver = self.state.as_move_resolver();
+        let epoch =
+            ConfigurationResource::fetch_config(&resolver).map_or(0, |config| config.epoch());
+        let timestamp =
+            CurrentTimeMicroseconds::fetch_config(&resolver).map_or(0, |time| time.microseconds);
+        LedgerInfo {
+            chain_id: self.chain_id.id(),
+            epoch: epoch.into(),
+            ledger_version: self.version().into(),
+            oldest_ledger_version: self.info.version.into(),
+            // Each local transaction is in a block of its own.
+            block_height: (self.info.block_height + self.transactions.len() as u64).into(),
+            oldest_block_height: self.info.block_height.into(),
+            ledger_timestamp: timestamp.into(),
+        }
+    }
+
+    /// Only the latest state is available on the fork.
+    fn check_ledger_version(&self, ledger_version: Option<Version>) -> Result<(), ApiError> {
+        match ledger_version {
+            Some(version) if version != self.version() => Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                AptosErrorCode::VersionNotFound,
+                format!(
+                    "Only the latest version {} of the state is available on a fork",
+                    self.version()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn transaction(&self, version: Version) -> Option<&TransactionOnChainData> {
+        version
+            .checked_sub(self.info.version + 1)
+            .and_then(|index| self.transactions.get(index as usize))
+    }
+
+    /// Executes a transaction on the fork and records it, unless it's discarded.
+    fn execute(&mut self, txn: SignedTransaction) -> Result<Result<(), ApiError>> {
+        let output = self.state.execute_transaction(txn.clone())?;
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(status) => {
+                return Ok(Err(ApiError {
+                    status: StatusCode::BAD_REQUEST,
+                    error: AptosError::new_with_vm_status(
+                        format!("Invalid transaction: {:?}", status),
+                        AptosErrorCode::VmError,
+                        *status,
+                    ),
+                }))
+            },
+            TransactionStatus::Retry => {
+                return Ok(Err(ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    AptosErrorCode::InternalError,
+                    "Transaction was not executed, retry it",
+                )))
+            },
+        };
+
+        // The fork doesn't maintain the accumulator nor the state merkle tree, so like for
+        // simulated transactions, their hashes are zero.
+        let txn = Transaction::UserTransaction(txn);
+        let zero_hash = HashValue::zero();
+        let data = TransactionOnChainData {
+            version: self.version() + 1,
+            info: TransactionInfo::new(
+                txn.hash(),
+                zero_hash,
+                zero_hash,
+                None,
+                output.gas_used(),
+                status,
+            ),
+            transaction: txn,
+            events: output.events().to_vec(),
+            accumulator_root_hash: zero_hash,
+            changes: output.write_set().clone(),
+        };
+        fs::write(
+            self.dir
+                .join(TRANSACTIONS_FOLDER)
+                .join(data.version.to_string()),
+            bcs::to_bytes(&data)?,
+        )?;
+        self.transaction_versions
+            .insert(data.info.transaction_hash(), data.version);
+        self.transactions.push(data);
+        Ok(Ok(()))
+    }
+
+    fn render_transaction(&self, data: TransactionOnChainData) -> Result<ApiTransaction> {
+        self.state
+            .as_move_resolver()
+            .as_converter(no_table_info())
+            .try_into_onchain_transaction(self.ledger_info().timestamp(), data)
+    }
+}
+
+/// The fork has no table info to decode table items with.
+struct NoTableInfo;
+
+impl DbReader for NoTableInfo {
+    fn indexer_enabled(&self) -> bool {
+        false
+    }
+}
+
+fn no_table_info() -> Arc<dyn DbReader> {
+    Arc::new(NoTableInfo)
+}
+
+/// An error response of the fork API, in the format of the node API.
+struct ApiError {
+    status: StatusCode,
+    error: AptosError,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, error_code: AptosErrorCode, error: impl Display) -> Self {
+        Self {
+            status,
+            error: AptosError::new_with_error_code(error, error_code),
+        }
+    }
+
+    fn bad_request(error: impl Display) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, AptosErrorCode::InvalidInput, error)
+    }
+
+    fn not_found(error_code: AptosErrorCode, error: impl Display) -> Self {
+        Self::new(StatusCode::NOT_FOUND, error_code, error)
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            AptosErrorCode::InternalError,
+            error,
+        )
+    }
+}
+
+type SharedFork = Arc<Mutex<Fork>>;
+
+/// Runs `f` on the fork on a blocking thread, since reading the state may wait for it to be
+/// fetched, and adds the ledger info headers to its response.
+async fn with_fork<F>(fork: &SharedFork, f: F) -> Response
+where
+    F: FnOnce(&mut Fork) -> Result<Response, ApiError> + Send + 'static,
+{
+    let fork = fork.clone();
+    let response = tokio::task::spawn_blocking(move || {
+        let mut fork = fork.lock().unwrap();
+        let response = f(&mut fork).unwrap_or_else(|err| {
+            Response::builder()
+                .status(err.status)
+                .content_type(JSON)
+                .body(serde_json::to_vec(&err.error).unwrap_or_default())
+        });
+        (response, fork.ledger_info())
+    })
+    .await;
+    match response {
+        Ok((mut response, ledger_info)) => {
+            let headers = response.headers_mut();
+            for (name, value) in [
+                (X_APTOS_CHAIN_ID, ledger_info.chain_id.to_string()),
+                (
+                    X_APTOS_LEDGER_VERSION,
+                    ledger_info.ledger_version.to_string(),
+                ),
+                (
+                    X_APTOS_LEDGER_OLDEST_VERSION,
+                    ledger_info.oldest_ledger_version.to_string(),
+                ),
+                (
+                    X_APTOS_LEDGER_TIMESTAMP,
+                    ledger_info.ledger_timestamp.to_string(),
+                ),
+                (X_APTOS_EPOCH, ledger_info.epoch.to_string()),
+                (X_APTOS_BLOCK_HEIGHT, ledger_info.block_height.to_string()),
+                (
+                    X_APTOS_OLDEST_BLOCK_HEIGHT,
+                    ledger_info.oldest_block_height.to_string(),
+                ),
+            ] {
+                headers.insert(name, value.parse().unwrap());
+            }
+            response
+        },
+        Err(err) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(err.to_string()),
+    }
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Result<Response, ApiError> {
+    Ok(Response::builder()
+        .status(status)
+        .content_type(JSON)
+        .body(serde_json::to_vec(value).map_err(anyhow::Error::from)?))
+}
+
+fn bcs_response<T: Serialize>(status: StatusCode, value: &T) -> Result<Response, ApiError> {
+    Ok(Response::builder()
+        .status(status)
+        .content_type(BCS)
+        .body(bcs::to_bytes(value).map_err(anyhow::Error::from)?))
+}
+
+fn accepts_bcs(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map_or(false, |accept| accept.starts_with(BCS))
+}
+
+fn content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or(JSON)
+        .to_string()
+}
+
+fn parse<T: FromStr>(value: &str, what: &str) -> Result<T, ApiError>
+where
+    T::Err: Display,
+{
+    T::from_str(value).map_err(|err| ApiError::bad_request(format!("Invalid {}: {}", what, err)))
+}
+
+#[derive(Deserialize)]
+struct LedgerVersionQuery {
+    ledger_version: Option<Version>,
+}
+
+#[derive(Deserialize)]
+struct SimulateQuery {
+    estimate_max_gas_amount: Option<bool>,
+    estimate_gas_unit_price: Option<bool>,
+    estimate_prioritized_gas_unit_price: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SetAuthenticationKeyRequest {
+    address: Address,
+    authentication_key: HexEncodedBytes,
+}
+
+#[handler]
+async fn index(fork: Data<&SharedFork>, headers: &HeaderMap) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |fork| {
+        let ledger_info = fork.ledger_info();
+        if accept_bcs {
+            bcs_response(
+                StatusCode::OK,
+                &IndexResponseBcs::new(ledger_info, RoleType::FullNode),
+            )
+        } else {
+            json_response(
+                StatusCode::OK,
+                &IndexResponse::new(ledger_info, RoleType::FullNode, None),
+            )
+        }
+    })
+    .await
+}
+
+#[handler]
+async fn healthy(fork: Data<&SharedFork>) -> Response {
+    with_fork(fork.0, |_| {
+        json_response(
+            StatusCode::OK,
+            &serde_json::json!({"message": "aptos-node:ok"}),
+        )
+    })
+    .await
+}
+
+/// Replaces the authentication key of an account on the fork, so that transactions can be sent
+/// on behalf of the account with the corresponding key.
+#[handler]
+async fn set_authentication_key(fork: Data<&SharedFork>, body: Body) -> Response {
+    let body = body.into_vec().await.unwrap_or_default();
+    with_fork(fork.0, move |fork| {
+        let request: SetAuthenticationKeyRequest =
+            serde_json::from_slice(&body).map_err(ApiError::bad_request)?;
+        let authentication_key = AuthenticationKey::try_from(request.authentication_key.0)
+            .map_err(ApiError::bad_request)?;
+        fork.state
+            .set_authentication_key(request.address.into(), authentication_key.to_vec())
+            .map_err(|err| ApiError::not_found(AptosErrorCode::AccountNotFound, err))?;
+        json_response(StatusCode::OK, &())
+    })
+    .await
+}
+
+#[handler]
+async fn account(
+    fork: Data<&SharedFork>,
+    Path(address): Path<String>,
+    Query(query): Query<LedgerVersionQuery>,
+    headers: &HeaderMap,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |fork| {
+        fork.check_ledger_version(query.ledger_version)?;
+        let address: Address = parse(&address, "address")?;
+        let account = fork
+            .state
+            .get_resource::<AccountResource>(address.into())?
+            .ok_or_else(|| {
+                ApiError::not_found(
+                    AptosErrorCode::AccountNotFound,
+                    format!("Account not found: {}", address),
+                )
+            })?;
+        if accept_bcs {
+            bcs_response(StatusCode::OK, &account)
+        } else {
+            json_response(StatusCode::OK, &AccountData::from(account))
+        }
+    })
+    .await
+}
+
+#[handler]
+async fn resource(
+    fork: Data<&SharedFork>,
+    Path((address, resource_type)): Path<(String, String)>,
+    Query(query): Query<LedgerVersionQuery>,
+    headers: &HeaderMap,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |fork| {
+        fork.check_ledger_version(query.ledger_version)?;
+        let address: Address = parse(&address, "address")?;
+        let resource_type: MoveStructTag = parse(&resource_type, "resource type")?;
+        let resource_type = StructTag::try_from(resource_type).map_err(ApiError::bad_request)?;
+        let resolver = fork.state.as_move_resolver();
+        let bytes = resolver
+            .get_resource(&address.into(), &resource_type)?
+            .ok_or_else(|| {
+                ApiError::not_found(
+                    AptosErrorCode::ResourceNotFound,
+                    format!("Resource not found: {} at {}", resource_type, address),
+                )
+            })?;
+        if accept_bcs {
+            Ok(Response::builder().content_type(BCS).body(bytes.to_vec()))
+        } else {
+            let resource = resolver
+                .as_converter(no_table_info())
+                .try_into_resource(&resource_type, &bytes)?;
+            json_response(StatusCode::OK, &resource)
+        }
+    })
+    .await
+}
+
+#[handler]
+async fn module(
+    fork: Data<&SharedFork>,
+    Path((address, module_name)): Path<(String, String)>,
+    Query(query): Query<LedgerVersionQuery>,
+    headers: &HeaderMap,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |fork| {
+        fork.check_ledger_version(query.ledger_version)?;
+        let address: Address = parse(&address, "address")?;
+        let module_name = Identifier::new(module_name).map_err(ApiError::bad_request)?;
+        let module_id = ModuleId::new(address.into(), module_name);
+        let bytes = fork
+            .state
+            .get_state_value_bytes(&StateKey::access_path(AccessPath::code_access_path(
+                module_id.clone(),
+            )))?
+            .ok_or_else(|| {
+                ApiError::not_found(
+                    AptosErrorCode::ModuleNotFound,
+                    format!("Module not found: {}", module_id),
+                )
+            })?;
+        if accept_bcs {
+            Ok(Response::builder().content_type(BCS).body(bytes.to_vec()))
+        } else {
+            json_response(
+                StatusCode::OK,
+                &MoveModuleBytecode::new(bytes.to_vec()).try_parse_abi()?,
+            )
+        }
+    })
+    .await
+}
+
+#[handler]
+async fn estimate_gas_price(fork: Data<&SharedFork>, headers: &HeaderMap) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |_| {
+        if accept_bcs {
+            bcs_response(StatusCode::OK, &GasEstimationBcs {
+                gas_estimate: GAS_ESTIMATE,
+            })
+        } else {
+            json_response(StatusCode::OK, &GasEstimation {
+                deprioritized_gas_estimate: Some(GAS_ESTIMATE),
+                gas_estimate: GAS_ESTIMATE,
+                prioritized_gas_estimate: Some(GAS_ESTIMATE),
+            })
+        }
+    })
+    .await
+}
+
+/// Parses a transaction submitted in BCS or in JSON.
+fn parse_signed_transaction(
+    fork: &Fork,
+    content_type: &str,
+    body: &[u8],
+) -> Result<SignedTransaction, ApiError> {
+    if content_type.starts_with(BCS_SIGNED_TRANSACTION) {
+        bcs::from_bytes(body).map_err(ApiError::bad_request)
+    } else {
+        let request: SubmitTransactionRequest =
+            serde_json::from_slice(body).map_err(ApiError::bad_request)?;
+        fork.state
+            .as_move_resolver()
+            .as_converter(no_table_info())
+            .try_into_signed_transaction_poem(request, fork.chain_id)
+            .map_err(ApiError::bad_request)
+    }
+}
+
+#[handler]
+async fn submit_transaction(fork: Data<&SharedFork>, headers: &HeaderMap, body: Body) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    let content_type = content_type(headers);
+    let body = body.into_vec().await.unwrap_or_default();
+    with_fork(fork.0, move |fork| {
+        let txn = parse_signed_transaction(fork, &content_type, &body)?;
+        fork.execute(txn.clone())??;
+        if accept_bcs {
+            bcs_response(StatusCode::ACCEPTED, &())
+        } else {
+            let pending_txn = fork
+                .state
+                .as_move_resolver()
+                .as_converter(no_table_info())
+                .try_into_pending_transaction_poem(txn)?;
+            json_response(StatusCode::ACCEPTED, &pending_txn)
+        }
+    })
+    .await
+}
+
+/// Simulates a transaction on the latest state of the fork, like the node API does.
+#[handler]
+async fn simulate_transaction(
+    fork: Data<&SharedFork>,
+    Query(query): Query<SimulateQuery>,
+    headers: &HeaderMap,
+    body: Body,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    let content_type = content_type(headers);
+    let body = body.into_vec().await.unwrap_or_default();
+    with_fork(fork.0, move |fork| {
+        let txn = parse_signed_transaction(fork, &content_type, &body)?;
+        if txn.verify_signature().is_ok() {
+            return Err(ApiError::bad_request(
+                "Simulated transactions must not have a valid signature",
+            ));
+        }
+
+        let gas_unit_price = if query.estimate_gas_unit_price.unwrap_or_default()
+            || query
+                .estimate_prioritized_gas_unit_price
+                .unwrap_or_default()
+        {
+            GAS_ESTIMATE
+        } else {
+            txn.gas_unit_price()
+        };
+        let max_gas_amount = if query.estimate_max_gas_amount.unwrap_or_default() {
+            let balance = fork
+                .state
+                .get_resource::<CoinStoreResource>(txn.sender())?
+                .map_or(0, |coin_store| coin_store.coin());
+            match gas_unit_price {
+                0 => txn.max_gas_amount(),
+                _ => std::cmp::min(balance / gas_unit_price, txn.max_gas_amount()),
+            }
+        } else {
+            txn.max_gas_amount()
+        };
+        let txn = SignedTransaction::new_with_authenticator(
+            RawTransaction::new(
+                txn.sender(),
+                txn.sequence_number(),
+                txn.payload().clone(),
+                max_gas_amount,
+                gas_unit_price,
+                txn.expiration_timestamp_secs(),
+                txn.chain_id(),
+            ),
+            txn.authenticator(),
+        );
+
+        let (vm_status, output) =
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &fork.state);
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(status) => {
+                ExecutionStatus::MiscellaneousError(Some(*status))
+            },
+            TransactionStatus::Retry => ExecutionStatus::MiscellaneousError(None),
+        };
+        let txn = Transaction::UserTransaction(txn);
+        let zero_hash = HashValue::zero();
+        let data = TransactionOnChainData {
+            version: fork.version(),
+            info: TransactionInfo::new(
+                txn.hash(),
+                zero_hash,
+                zero_hash,
+                None,
+                output.gas_used(),
+                status,
+            ),
+            transaction: txn,
+            events: output.events().to_vec(),
+            accumulator_root_hash: zero_hash,
+            changes: output.write_set().clone(),
+        };
+        if accept_bcs {
+            return bcs_response(StatusCode::OK, &data);
+        }
+        match fork.render_transaction(data)? {
+            ApiTransaction::UserTransaction(txn) => {
+                let mut txn = *txn;
+                if let VMStatus::Error {
+                    message: Some(message),
+                    ..
+                }
+                | VMStatus::ExecutionFailure {
+                    message: Some(message),
+                    ..
+                } = &vm_status
+                {
+                    txn.info.vm_status += &format!("\nExecution failed with status: {}", message);
+                }
+                json_response(StatusCode::OK, &vec![txn])
+            },
+            _ => Err(format_err!("Simulation resulted in a non-user transaction").into()),
+        }
+    })
+    .await
+}
+
+fn transaction_response(
+    fork: &Fork,
+    data: Option<TransactionOnChainData>,
+    accept_bcs: bool,
+) -> Result<Response, ApiError> {
+    let data = data.ok_or_else(|| {
+        ApiError::not_found(
+            AptosErrorCode::TransactionNotFound,
+            "Transaction not found on the fork",
+        )
+    })?;
+    if accept_bcs {
+        bcs_response(StatusCode::OK, &TransactionData::OnChain(data))
+    } else {
+        json_response(StatusCode::OK, &fork.render_transaction(data)?)
+    }
+}
+
+#[handler]
+async fn transaction_by_hash(
+    fork: Data<&SharedFork>,
+    Path(hash): Path<String>,
+    headers: &HeaderMap,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |fork| {
+        let hash: aptos_api_types::HashValue = parse(&hash, "hash")?;
+        let data = fork
+            .transaction_versions
+            .get(&hash.into())
+            .and_then(|version| fork.transaction(*version))
+            .cloned();
+        transaction_response(fork, data, accept_bcs)
+    })
+    .await
+}
+
+#[handler]
+async fn transaction_by_version(
+    fork: Data<&SharedFork>,
+    Path(version): Path<String>,
+    headers: &HeaderMap,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    with_fork(fork.0, move |fork| {
+        let version: Version = parse(&version, "version")?;
+        let data = fork.transaction(version).cloned();
+        transaction_response(fork, data, accept_bcs)
+    })
+    .await
+}
+
+/// Executes a view function on the latest state of the fork, like the node API does.
+#[handler]
+async fn view(
+    fork: Data<&SharedFork>,
+    Query(query): Query<LedgerVersionQuery>,
+    headers: &HeaderMap,
+    body: Body,
+) -> Response {
+    let accept_bcs = accepts_bcs(headers);
+    let content_type = content_type(headers);
+    let body = body.into_vec().await.unwrap_or_default();
+    with_fork(fork.0, move |fork| {
+        fork.check_ledger_version(query.ledger_version)?;
+        let resolver = fork.state.as_move_resolver();
+        let converter = resolver.as_converter(no_table_info());
+        let view_function: ViewFunction = if content_type.starts_with(BCS_VIEW_FUNCTION) {
+            bcs::from_bytes(&body).map_err(ApiError::bad_request)?
+        } else {
+            let request: ViewRequest =
+                serde_json::from_slice(&body).map_err(ApiError::bad_request)?;
+            converter
+                .convert_view_function(request)
+                .map_err(ApiError::bad_request)?
+        };
+
+        let return_values = AptosVM::execute_view_function(
+            &fork.state,
+            view_function.module.clone(),
+            view_function.function.clone(),
+            view_function.ty_args.clone(),
+            view_function.args.clone(),
+            ApiConfig::default().max_gas_view_function,
+        )
+        .map_err(ApiError::bad_request)?;
+
+        if accept_bcs {
+            // The return values are BCS encoded already, so only their number is encoded here.
+            let mut bytes = vec![];
+            let mut len = return_values.len();
+            while len >= 0x80 {
+                bytes.push((len & 0x7F) as u8 | 0x80);
+                len >>= 7;
+            }
+            bytes.push(len as u8);
+            bytes.extend(return_values.into_iter().flatten());
+            return Ok(Response::builder().content_type(BCS).body(bytes));
+        }
+        let return_types = converter
+            .function_return_types(&view_function)
+            .and_then(|types| {
+                types
+                    .into_iter()
+                    .map(TypeTag::try_from)
+                    .collect::<Result<Vec<_>>>()
+            })?;
+        let values = return_values
+            .iter()
+            .zip(return_types.iter())
+            .map(|(value, ty)| converter.try_into_move_value(ty, value))
+            .collect::<Result<Vec<_>>>()?;
+        json_response(StatusCode::OK, &values)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::Block;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        account_address::AccountAddress, account_config::aptos_test_root_address,
+        state_store::state_value::StateValue,
+    };
+    use aptos_vm_genesis::{test_genesis_change_set_and_validators, GENESIS_KEYPAIR};
+    use move_core_types::move_resource::MoveStructType;
+    use poem::listener::{Acceptor, Listener};
+
+    /// The version of the remote network the tests fork.
+    const VERSION: Version = 0;
+
+    /// A remote node serving the genesis state through the subset of the node API a fork uses,
+    /// recording the state keys it is asked for.
+    struct MockNode {
+        state: HashMap<StateKey, StateValue>,
+        requested_keys: Mutex<Vec<StateKey>>,
+    }
+
+    impl MockNode {
+        /// Starts the node on a free port and returns its URL.
+        async fn start() -> (Url, Arc<Self>) {
+            let (change_set, _) = test_genesis_change_set_and_validators(Some(1));
+            let state = change_set
+                .write_set()
+                .iter()
+                .filter_map(|(state_key, write_op)| {
+                    write_op
+                        .as_state_value()
+                        .map(|value| (state_key.clone(), value))
+                })
+                .collect();
+            let node = Arc::new(Self {
+                state,
+                requested_keys: Mutex::new(vec![]),
+            });
+
+            let acceptor = TcpListener::bind("127.0.0.1:0")
+                .into_acceptor()
+                .await
+                .unwrap();
+            let address = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+            let app = Route::new()
+                .nest(
+                    "/v1",
+                    Route::new()
+                        .at("/blocks/by_version/:version", get(mock_block_by_version))
+                        .at("/experimental/state_values/raw", post(mock_raw_state_value)),
+                )
+                .data(node.clone());
+            tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+            (socket_addr_to_url(&address, "http").unwrap(), node)
+        }
+
+        /// Returns the state keys requested since the last call.
+        fn take_requested_keys(&self) -> Vec<StateKey> {
+            std::mem::take(&mut *self.requested_keys.lock().unwrap())
+        }
+    }
+
+    /// Adds the ledger info headers the REST client expects to a response of the mock node.
+    fn with_ledger_info(mut response: Response) -> Response {
+        let headers = response.headers_mut();
+        for (name, value) in [
+            (X_APTOS_CHAIN_ID, ChainId::test().id().to_string()),
+            (X_APTOS_LEDGER_VERSION, VERSION.to_string()),
+            (X_APTOS_LEDGER_OLDEST_VERSION, VERSION.to_string()),
+            (X_APTOS_LEDGER_TIMESTAMP, "0".to_string()),
+            (X_APTOS_EPOCH, "1".to_string()),
+            (X_APTOS_BLOCK_HEIGHT, "0".to_string()),
+            (X_APTOS_OLDEST_BLOCK_HEIGHT, "0".to_string()),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+        response
+    }
+
+    fn error_response(err: ApiError) -> Response {
+        Response::builder()
+            .status(err.status)
+            .content_type(JSON)
+            .body(serde_json::to_vec(&err.error).unwrap())
+    }
+
+    #[handler]
+    async fn mock_block_by_version(Path(version): Path<Version>) -> Response {
+        let block = Block {
+            block_height: 0.into(),
+            block_hash: HashValue::zero().into(),
+            block_timestamp: 0.into(),
+            first_version: version.into(),
+            last_version: version.into(),
+            transactions: None,
+        };
+        with_ledger_info(json_response(StatusCode::OK, &block).unwrap_or_else(error_response))
+    }
+
+    #[derive(Deserialize)]
+    struct RawStateValueRequest {
+        key: HexEncodedBytes,
+    }
+
+    #[handler]
+    async fn mock_raw_state_value(
+        node: Data<&Arc<MockNode>>,
+        Query(query): Query<LedgerVersionQuery>,
+        body: Body,
+    ) -> Response {
+        let response = async {
+            if query.ledger_version != Some(VERSION) {
+                return Err(ApiError::not_found(
+                    AptosErrorCode::VersionNotFound,
+                    "Only the forked version is available",
+                ));
+            }
+            let request: RawStateValueRequest =
+                serde_json::from_slice(&body.into_bytes().await.map_err(ApiError::bad_request)?)
+                    .map_err(ApiError::bad_request)?;
+            let state_key: StateKey =
+                bcs::from_bytes(request.key.inner()).map_err(ApiError::bad_request)?;
+            node.requested_keys.lock().unwrap().push(state_key.clone());
+            match node.state.get(&state_key) {
+                Some(value) => bcs_response(StatusCode::OK, value),
+                None => Err(ApiError::not_found(
+                    AptosErrorCode::StateValueNotFound,
+                    "State value not found",
+                )),
+            }
+        }
+        .await;
+        with_ledger_info(response.unwrap_or_else(error_response))
+    }
+
+    fn resource_key<T: MoveStructType>(address: AccountAddress) -> StateKey {
+        StateKey::access_path(AccessPath::resource_access_path(address, T::struct_tag()).unwrap())
+    }
+
+    fn transfer(
+        fork: &Fork,
+        sequence_number: u64,
+        receiver: AccountAddress,
+        amount: u64,
+    ) -> SignedTransaction {
+        let (private_key, public_key) = &*GENESIS_KEYPAIR;
+        RawTransaction::new(
+            aptos_test_root_address(),
+            sequence_number,
+            aptos_stdlib::aptos_account_transfer(receiver, amount),
+            1_000_000,
+            GAS_ESTIMATE,
+            u64::MAX,
+            fork.chain_id,
+        )
+        .sign(private_key, public_key.clone())
+        .unwrap()
+        .into_inner()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cache_miss_is_fetched_from_remote_once() {
+        let (url, node) = MockNode::start().await;
+        let dir = TempPath::new();
+        let fork = Fork::open(url, Some(VERSION), dir.path().to_path_buf())
+            .await
+            .unwrap();
+        assert_eq!(fork.chain_id, ChainId::test());
+        assert_eq!(fork.version(), VERSION);
+        node.take_requested_keys();
+
+        let root = aptos_test_root_address();
+        let root_key = resource_key::<AccountResource>(root);
+        let account = fork
+            .state
+            .get_resource::<AccountResource>(root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.sequence_number(), 0);
+        assert_eq!(node.take_requested_keys(), vec![root_key.clone()]);
+        assert!(fork
+            .state
+            .get_resource::<AccountResource>(root)
+            .unwrap()
+            .is_some());
+        assert!(node.take_requested_keys().is_empty());
+
+        // State missing on the remote is cached as well.
+        let missing = AccountAddress::random();
+        let missing_key = resource_key::<AccountResource>(missing);
+        for _ in 0..2 {
+            assert!(fork
+                .state
+                .get_resource::<AccountResource>(missing)
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(node.take_requested_keys(), vec![missing_key]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_fork_persists_across_restart() {
+        let (url, node) = MockNode::start().await;
+        let dir = TempPath::new();
+        let dir = dir.path().to_path_buf();
+        let root = aptos_test_root_address();
+        let receiver = AccountAddress::random();
+
+        let mut fork = Fork::open(url.clone(), Some(VERSION), dir.clone())
+            .await
+            .unwrap();
+        let txn = transfer(&fork, 0, receiver, 300);
+        let hash = Transaction::UserTransaction(txn.clone()).hash();
+        assert!(fork.execute(txn).unwrap().is_ok());
+        assert_eq!(fork.version(), VERSION + 1);
+        drop(fork);
+        assert!(!node.take_requested_keys().is_empty());
+
+        // The fork picks up where it left off, without fetching the state it read before again.
+        let fork = Fork::open(url.clone(), None, dir.clone()).await.unwrap();
+        assert_eq!(fork.version(), VERSION + 1);
+        assert_eq!(fork.transactions.len(), 1);
+        assert_eq!(fork.transaction_versions.get(&hash), Some(&(VERSION + 1)));
+        assert_eq!(
+            fork.transaction(VERSION + 1).unwrap().info.status(),
+            &ExecutionStatus::Success
+        );
+        let account = fork
+            .state
+            .get_resource::<AccountResource>(root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.sequence_number(), 1);
+        let coin_store = fork
+            .state
+            .get_resource::<CoinStoreResource>(receiver)
+            .unwrap()
+            .unwrap();
+        assert_eq!(coin_store.coin(), 300);
+        assert!(node.take_requested_keys().is_empty());
+        drop(fork);
+
+        // The test dir only holds a fork at its version.
+        assert!(Fork::open(url, Some(VERSION + 1), dir).await.is_err());
+    }
+}
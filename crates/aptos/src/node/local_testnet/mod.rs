@@ -3,6 +3,7 @@
 
 mod docker;
 mod faucet;
+mod fork;
 mod health_checker;
 mod indexer_api;
 mod logging;
@@ -15,6 +16,7 @@ mod utils;
 
 use self::{
     faucet::FaucetArgs,
+    fork::{ForkArgs, ForkManager},
     health_checker::HealthChecker,
     indexer_api::IndexerApiArgs,
     logging::ThreadNameMakeWriter,
@@ -57,6 +59,9 @@ const TESTNET_FOLDER: &str = "testnet";
 /// This local testnet will run it's own genesis and run as a single node network
 /// locally. A faucet and grpc transaction stream will run alongside the node unless
 /// you specify otherwise with --no-faucet and --no-txn-stream respectively.
+///
+/// With --fork-url, the local testnet instead serves a local fork of another network,
+/// e.g. mainnet, without a faucet nor a transaction stream.
 #[derive(Parser)]
 pub struct RunLocalTestnet {
     /// The directory to save all files for the node
@@ -76,6 +81,9 @@ pub struct RunLocalTestnet {
     #[clap(flatten)]
     node_args: NodeArgs,
 
+    #[clap(flatten)]
+    fork_args: ForkArgs,
+
     #[clap(flatten)]
     faucet_args: FaucetArgs,
 
@@ -248,19 +256,36 @@ impl CliCommand<()> for RunLocalTestnet {
 
         let mut managers: Vec<Box<dyn ServiceManager>> = Vec::new();
 
-        // Build the node manager. We do this unconditionally.
-        let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
-            .context("Failed to build node service manager")?;
+        // Build the node manager, or the manager of the fork when forking another
+        // network. We do this unconditionally. A fork has no txn stream.
+        let forking = self.fork_args.fork_url.is_some();
+        let (node_manager, node_api_url, data_service_url): (Box<dyn ServiceManager>, _, _) =
+            match self.fork_args.fork_url.clone() {
+                Some(fork_url) => {
+                    let fork_manager = ForkManager::new(&self, bind_to, test_dir.clone(), fork_url)
+                        .context("Failed to build fork service manager")?;
+                    let node_api_url = fork_manager.get_node_api_url();
+                    (Box::new(fork_manager), node_api_url, None)
+                },
+                None => {
+                    let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
+                        .context("Failed to build node service manager")?;
+                    let node_api_url = node_manager.get_node_api_url();
+                    let data_service_url = node_manager.get_data_service_url();
+                    (Box::new(node_manager), node_api_url, Some(data_service_url))
+                },
+            };
         let node_health_checkers = node_manager.get_health_checkers();
 
-        // If configured to do so, build the faucet manager.
-        if !self.faucet_args.no_faucet {
+        // If configured to do so, build the faucet manager. A fork has no faucet since
+        // there is no account that can mint coins on it.
+        if !self.faucet_args.no_faucet && !forking {
             let faucet_manager = FaucetManager::new(
                 &self,
                 node_health_checkers.clone(),
                 bind_to,
                 test_dir.clone(),
-                node_manager.get_node_api_url(),
+                node_api_url,
             )
             .context("Failed to build faucet service manager")?;
             managers.push(Box::new(faucet_manager));
@@ -280,7 +305,7 @@ impl CliCommand<()> for RunLocalTestnet {
             let processor_managers = ProcessorManager::many_new(
                 &self,
                 processor_preqrequisite_healthcheckers,
-                node_manager.get_data_service_url(),
+                data_service_url.context("The indexer API requires a txn stream")?,
                 self.postgres_args.get_connection_string(None, true),
             )
             .context("Failed to build processor service managers")?;
@@ -307,8 +332,8 @@ impl CliCommand<()> for RunLocalTestnet {
         }
 
         // We put the node manager into managers at the end just so we have access to
-        // it before this so we can call things like `node_manager.get_node_api_url()`.
-        managers.push(Box::new(node_manager));
+        // it before this so we can call things like `node_manager.get_health_checkers()`.
+        managers.push(node_manager);
 
         // Get the healthcheckers from all the managers. We'll pass to this
         // `wait_for_startup`.
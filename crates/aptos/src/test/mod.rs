@@ -850,6 +850,7 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            json_report: None,
         }
         .execute()
         .await
@@ -887,6 +888,7 @@ impl CliTestFramework {
             package,
             output_dir: Some(output_dir),
             print_metadata: false,
+            bytecode: false,
         }
         .execute()
         .await
@@ -12,7 +12,7 @@ use std::{
     convert::TryFrom,
     fs::File,
     io::{BufRead, BufReader},
-    path::Path,
+    path::{Path, PathBuf},
 };
 use url::Url;
 
@@ -153,6 +153,11 @@ pub struct EmitArgs {
     #[clap(long, num_args = 0..)]
     pub transaction_phases: Vec<usize>,
 
+    /// Instead of the transaction types above, replay a workload similar to the historical
+    /// transactions summarized in this trace profile (see `aptos-db-tool trace-profile`).
+    #[clap(long, value_parser, conflicts_with_all = &["transaction_weights", "transaction_phases"])]
+    pub trace_profile: Option<PathBuf>,
+
     #[clap(long)]
     pub gas_price: Option<u64>,
 
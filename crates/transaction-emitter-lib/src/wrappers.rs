@@ -14,9 +14,14 @@ use anyhow::{bail, Context, Result};
 use aptos_config::config::DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE;
 use aptos_logger::{error, info};
 use aptos_sdk::transaction_builder::TransactionFactory;
-use aptos_transaction_generator_lib::args::TransactionTypeArg;
+use aptos_transaction_generator_lib::{
+    args::TransactionTypeArg, trace::TraceProfile, TransactionType,
+};
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 pub async fn emit_transactions(
     cluster_args: &ClusterArgs,
@@ -81,13 +86,29 @@ pub async fn emit_transactions_with_cluster(
         StdRng::from_entropy(),
     );
 
-    let transaction_mix_per_phase = TransactionTypeArg::args_to_transaction_mix_per_phase(
-        &args.transaction_type,
-        &args.transaction_weights,
-        &args.transaction_phases,
-        args.module_working_set_size.unwrap_or(1),
-        args.sender_use_account_pool.unwrap_or(false),
-    );
+    let mut max_transactions_per_account = args.max_transactions_per_account;
+    let transaction_mix_per_phase = if let Some(trace_profile) = &args.trace_profile {
+        let profile = TraceProfile::load(trace_profile)?;
+        max_transactions_per_account.get_or_insert(
+            (profile.mean_transactions_per_sender_per_block().round() as usize).max(1),
+        );
+        vec![vec![(
+            TransactionType::Trace {
+                profile: Arc::new(profile),
+                num_modules: args.module_working_set_size.unwrap_or(1),
+                use_account_pool: args.sender_use_account_pool.unwrap_or(false),
+            },
+            1,
+        )]]
+    } else {
+        TransactionTypeArg::args_to_transaction_mix_per_phase(
+            &args.transaction_type,
+            &args.transaction_weights,
+            &args.transaction_phases,
+            args.module_working_set_size.unwrap_or(1),
+            args.sender_use_account_pool.unwrap_or(false),
+        )
+    };
     let mut emit_job_request =
         EmitJobRequest::new(cluster.all_instances().map(Instance::rest_client).collect())
             .mode(emitter_mode)
@@ -97,7 +118,7 @@ pub async fn emit_transactions_with_cluster(
                 args.coordination_delay_between_instances.unwrap_or(0),
             ));
 
-    if let Some(max_transactions_per_account) = args.max_transactions_per_account {
+    if let Some(max_transactions_per_account) = max_transactions_per_account {
         emit_job_request =
             emit_job_request.max_transactions_per_account(max_transactions_per_account);
     }
@@ -31,12 +31,14 @@ mod entry_points;
 mod p2p_transaction_generator;
 pub mod publish_modules;
 mod publishing;
+pub mod trace;
 mod transaction_mix_generator;
 use self::{
     account_generator::AccountGeneratorCreator,
     call_custom_modules::CustomModulesDelegationGeneratorCreator,
     p2p_transaction_generator::P2PTransactionGeneratorCreator,
     publish_modules::PublishPackageCreator,
+    trace::{TraceProfile, TraceTransactionGenerator},
     transaction_mix_generator::PhasedTxnMixGeneratorCreator,
 };
 use crate::{
@@ -48,7 +50,7 @@ pub use publishing::module_simple::EntryPoints;
 
 pub const SEND_AMOUNT: u64 = 1;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum TransactionType {
     NonConflictingCoinTransfer {
         invalid_transaction_ratio: usize,
@@ -74,6 +76,11 @@ pub enum TransactionType {
     BatchTransfer {
         batch_size: usize,
     },
+    Trace {
+        profile: Arc<TraceProfile>,
+        num_modules: usize,
+        use_account_pool: bool,
+    },
 }
 
 impl Default for TransactionType {
@@ -290,6 +297,29 @@ pub async fn create_txn_generator_creator(
                         *batch_size,
                     ))
                 },
+                TransactionType::Trace {
+                    profile,
+                    num_modules,
+                    use_account_pool,
+                } => wrap_accounts_pool(
+                    Box::new(
+                        CustomModulesDelegationGeneratorCreator::new(
+                            txn_factory.clone(),
+                            init_txn_factory.clone(),
+                            source_accounts,
+                            txn_executor,
+                            *num_modules,
+                            EntryPoints::Nop.package_name(),
+                            &mut TraceTransactionGenerator::new(
+                                profile.clone(),
+                                addresses_pool.clone(),
+                            ),
+                        )
+                        .await,
+                    ),
+                    *use_account_pool,
+                    accounts_pool.clone(),
+                ),
             };
             txn_generator_creator_mix.push((txn_generator_creator, *weight));
         }
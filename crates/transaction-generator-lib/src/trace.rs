@@ -0,0 +1,394 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Trace-driven workloads: a [`TraceProfile`] summarizes a range of historical transactions
+//! (the mix of entry functions, the size of their arguments, and how senders and touched
+//! addresses conflict within a block), and [`TraceTransactionGenerator`] replays a
+//! statistically similar workload from it.
+
+use crate::{
+    call_custom_modules::{TransactionGeneratorWorker, UserModuleTransactionGenerator},
+    publishing::publish_util::Package,
+    EntryPoints, ReliableTransactionSubmitter, SEND_AMOUNT,
+};
+use anyhow::{Context, Result};
+use aptos_infallible::RwLock;
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{
+        transaction::{SignedTransaction, Transaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use async_trait::async_trait;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+/// Entry functions that move APT between accounts, replayed as real transfers so that the
+/// receivers' balances are written just like in the original traffic.
+const TRANSFER_FUNCTIONS: &[&str] = &[
+    "0x1::aptos_account::transfer",
+    "0x1::aptos_account::transfer_coins",
+    "0x1::coin::transfer",
+];
+
+/// Number of addresses that conflicting transfers are sent to.
+const NUM_HOT_RECEIVERS: usize = 16;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EntryFunctionProfile {
+    /// Fully qualified name, e.g. `0x1::aptos_account::transfer`.
+    pub function: String,
+    pub num_ty_args: usize,
+    pub count: u64,
+    /// Histogram of the total BCS size of the arguments, bucketed to the next power of two.
+    pub arg_bytes: BTreeMap<usize, u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TraceProfile {
+    pub first_version: Option<u64>,
+    pub num_user_transactions: u64,
+    /// Entry function calls, sorted by decreasing count.
+    pub entry_functions: Vec<EntryFunctionProfile>,
+    /// Scripts, multisig and module publishing payloads, which are not replayed.
+    pub num_other_payloads: u64,
+    /// Histogram of the number of transactions a single sender had in a block.
+    pub transactions_per_sender_per_block: BTreeMap<usize, u64>,
+    /// Number of transactions with an address argument already touched (as a sender or an
+    /// argument) by an earlier transaction in the same block.
+    pub num_conflicting_transactions: u64,
+}
+
+impl TraceProfile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read trace profile {}", path.display()))?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse trace profile {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write trace profile {}", path.display()))
+    }
+
+    pub fn num_entry_function_calls(&self) -> u64 {
+        self.entry_functions.iter().map(|f| f.count).sum()
+    }
+
+    pub fn conflict_ratio(&self) -> f64 {
+        if self.num_user_transactions == 0 {
+            0.0
+        } else {
+            self.num_conflicting_transactions as f64 / self.num_user_transactions as f64
+        }
+    }
+
+    /// Average number of transactions a sender has in a block, to configure how many
+    /// transactions the load generators create per account.
+    pub fn mean_transactions_per_sender_per_block(&self) -> f64 {
+        let (num_txns, num_senders) = self.transactions_per_sender_per_block.iter().fold(
+            (0, 0),
+            |(txns, senders), (per_sender, count)| {
+                (txns + *per_sender as u64 * count, senders + count)
+            },
+        );
+        if num_senders == 0 {
+            1.0
+        } else {
+            num_txns as f64 / num_senders as f64
+        }
+    }
+}
+
+/// Builds a [`TraceProfile`] from transactions fed in version order.
+#[derive(Default)]
+pub struct TraceProfileBuilder {
+    profile: TraceProfile,
+    entry_functions: HashMap<String, EntryFunctionProfile>,
+    block_senders: HashMap<AccountAddress, usize>,
+    block_touched: HashSet<AccountAddress>,
+}
+
+impl TraceProfileBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_transaction(&mut self, version: u64, txn: &Transaction) {
+        self.profile.first_version.get_or_insert(version);
+        match txn {
+            Transaction::UserTransaction(signed_txn) => self.add_user_transaction(signed_txn),
+            Transaction::BlockMetadata(_) | Transaction::GenesisTransaction(_) => self.end_block(),
+            Transaction::StateCheckpoint(_) | Transaction::ValidatorTransaction(_) => (),
+        }
+    }
+
+    fn add_user_transaction(&mut self, txn: &SignedTransaction) {
+        self.profile.num_user_transactions += 1;
+        let sender = txn.sender();
+        *self.block_senders.entry(sender).or_default() += 1;
+
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => {
+                let function = format!(
+                    "{}::{}",
+                    entry_function.module().short_str_lossless(),
+                    entry_function.function()
+                );
+                let arg_bytes: usize = entry_function.args().iter().map(Vec::len).sum();
+                let profile = self
+                    .entry_functions
+                    .entry(function.clone())
+                    .or_insert_with(|| EntryFunctionProfile {
+                        function,
+                        num_ty_args: entry_function.ty_args().len(),
+                        ..Default::default()
+                    });
+                profile.count += 1;
+                *profile.arg_bytes.entry(size_bucket(arg_bytes)).or_default() += 1;
+
+                // 32-byte arguments are most likely addresses, whose resources get written.
+                let addresses = entry_function
+                    .args()
+                    .iter()
+                    .filter_map(|arg| AccountAddress::from_bytes(arg).ok())
+                    .collect::<Vec<_>>();
+                if addresses
+                    .iter()
+                    .any(|address| self.block_touched.contains(address))
+                {
+                    self.profile.num_conflicting_transactions += 1;
+                }
+                self.block_touched.extend(addresses);
+            },
+            _ => self.profile.num_other_payloads += 1,
+        }
+        self.block_touched.insert(sender);
+    }
+
+    fn end_block(&mut self) {
+        for (_, num_txns) in self.block_senders.drain() {
+            *self
+                .profile
+                .transactions_per_sender_per_block
+                .entry(num_txns)
+                .or_default() += 1;
+        }
+        self.block_touched.clear();
+    }
+
+    pub fn build(mut self) -> TraceProfile {
+        self.end_block();
+        let mut entry_functions = self.entry_functions.into_values().collect::<Vec<_>>();
+        entry_functions.sort_by(|a, b| b.count.cmp(&a.count).then(a.function.cmp(&b.function)));
+        self.profile.entry_functions = entry_functions;
+        self.profile
+    }
+}
+
+fn size_bucket(size: usize) -> usize {
+    if size == 0 {
+        0
+    } else {
+        size.next_power_of_two()
+    }
+}
+
+/// Replays a [`TraceProfile`]: transfers go to receivers drawn from the addresses pool (a
+/// small hot set of them for the fraction of conflicting transactions), and every other entry
+/// function is approximated by writing a resource of the same size as its arguments, or by
+/// modifying a shared resource of the module publisher when it conflicts.
+pub struct TraceTransactionGenerator {
+    profile: Arc<TraceProfile>,
+    addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
+}
+
+impl TraceTransactionGenerator {
+    pub fn new(
+        profile: Arc<TraceProfile>,
+        addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
+    ) -> Self {
+        assert!(
+            profile.num_entry_function_calls() > 0,
+            "Trace profile has no entry function calls to replay"
+        );
+        Self {
+            profile,
+            addresses_pool,
+        }
+    }
+}
+
+#[async_trait]
+impl UserModuleTransactionGenerator for TraceTransactionGenerator {
+    fn initialize_package(
+        &mut self,
+        _package: &Package,
+        _publisher: &mut LocalAccount,
+        _txn_factory: &TransactionFactory,
+        _rng: &mut StdRng,
+    ) -> Vec<SignedTransaction> {
+        vec![]
+    }
+
+    async fn create_generator_fn(
+        &self,
+        _init_accounts: &mut [LocalAccount],
+        _txn_factory: &TransactionFactory,
+        _txn_executor: &dyn ReliableTransactionSubmitter,
+        _rng: &mut StdRng,
+    ) -> Arc<TransactionGeneratorWorker> {
+        let functions = self
+            .profile
+            .entry_functions
+            .iter()
+            .map(|f| {
+                let sizes = f.arg_bytes.keys().copied().collect::<Vec<_>>();
+                let size_dist = WeightedIndex::new(f.arg_bytes.values()).unwrap();
+                (
+                    TRANSFER_FUNCTIONS.contains(&f.function.as_str()),
+                    sizes,
+                    size_dist,
+                )
+            })
+            .collect::<Vec<_>>();
+        let function_dist =
+            WeightedIndex::new(self.profile.entry_functions.iter().map(|f| f.count)).unwrap();
+        let conflict_ratio = self.profile.conflict_ratio();
+        let addresses_pool = self.addresses_pool.clone();
+
+        Arc::new(move |account, package, publisher, txn_factory, rng| {
+            let (is_transfer, sizes, size_dist) = &functions[function_dist.sample(rng)];
+            let conflicting = rng.gen_bool(conflict_ratio);
+            let payload = if *is_transfer {
+                let receiver = {
+                    let addresses = addresses_pool.read();
+                    let candidates = if conflicting {
+                        &addresses[..addresses.len().min(NUM_HOT_RECEIVERS)]
+                    } else {
+                        &addresses[..]
+                    };
+                    *candidates.choose(rng).unwrap()
+                };
+                aptos_stdlib::aptos_account_transfer(receiver, SEND_AMOUNT)
+            } else {
+                let entry_point = if conflicting {
+                    EntryPoints::StepDst
+                } else {
+                    match sizes[size_dist.sample(rng)] {
+                        0 => EntryPoints::Nop,
+                        data_length => EntryPoints::BytesMakeOrChange {
+                            data_length: Some(data_length),
+                        },
+                    }
+                };
+                entry_point.create_payload(
+                    package.get_module_id(entry_point.module_name()),
+                    Some(rng),
+                    Some(&publisher.address()),
+                )
+            };
+            account.sign_with_transaction_builder(txn_factory.payload(payload))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::{
+        crypto::HashValue,
+        move_types::{ident_str, language_storage::ModuleId},
+        types::{block_metadata::BlockMetadata, chain_id::ChainId, transaction::EntryFunction},
+    };
+    use rand::SeedableRng;
+
+    fn block() -> Transaction {
+        Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::zero(),
+            0,
+            0,
+            AccountAddress::ZERO,
+            vec![],
+            vec![],
+            0,
+        ))
+    }
+
+    #[test]
+    fn test_profile_builder() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let alice = LocalAccount::generate(&mut rng);
+        let bob = LocalAccount::generate(&mut rng);
+        let hot = AccountAddress::from_hex_literal("0xc").unwrap();
+        let txn_factory = TransactionFactory::new(ChainId::test());
+        let user_txn = |sender: &LocalAccount, payload: TransactionPayload| {
+            Transaction::UserTransaction(
+                sender.sign_with_transaction_builder(txn_factory.payload(payload)),
+            )
+        };
+        let transfer = |receiver: AccountAddress| aptos_stdlib::aptos_account_transfer(receiver, 1);
+        let custom = TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(hot, ident_str!("game").to_owned()),
+            ident_str!("play").to_owned(),
+            vec![],
+            vec![vec![0; 100]],
+        ));
+
+        let mut builder = TraceProfileBuilder::new();
+        for (version, txn) in [
+            block(),
+            user_txn(&alice, transfer(hot)),
+            user_txn(&bob, transfer(hot)),
+            user_txn(&alice, custom),
+            block(),
+            user_txn(&bob, transfer(alice.address())),
+        ]
+        .iter()
+        .enumerate()
+        {
+            builder.add_transaction(version as u64, txn);
+        }
+        let profile = builder.build();
+
+        assert_eq!(profile.first_version, Some(0));
+        assert_eq!(profile.num_user_transactions, 4);
+        assert_eq!(profile.num_other_payloads, 0);
+        // Only bob's transfer to the address alice already sent to in the same block.
+        assert_eq!(profile.num_conflicting_transactions, 1);
+        assert_eq!(profile.entry_functions.len(), 2);
+        assert_eq!(
+            profile.entry_functions[0].function,
+            "0x1::aptos_account::transfer"
+        );
+        assert_eq!(profile.entry_functions[0].count, 3);
+        assert_eq!(
+            profile.entry_functions[0].arg_bytes,
+            BTreeMap::from([(64, 3)])
+        );
+        assert_eq!(profile.entry_functions[1].function, "0xc::game::play");
+        assert_eq!(
+            profile.entry_functions[1].arg_bytes,
+            BTreeMap::from([(128, 1)])
+        );
+        // alice had 2 transactions in the first block, bob 1 in each.
+        assert_eq!(
+            profile.transactions_per_sender_per_block,
+            BTreeMap::from([(1, 2), (2, 1)])
+        );
+        assert!((profile.mean_transactions_per_sender_per_block() - 4.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -7,12 +7,13 @@ use crate::metrics::{
 };
 use anyhow::{bail, Context, Result};
 use aptos_indexer_grpc_utils::{
-    cache_operator::CacheOperator,
+    cache_operator::{CacheClient, CacheConnection, CacheOperator},
     config::IndexerGrpcFileStoreConfig,
     counters::{log_grpc_step, IndexerGrpcStep},
     create_grpc_client,
     file_store_operator::{
         FileStoreMetadata, FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator,
+        S3FileStoreOperator,
     },
     types::RedisUrl,
 };
@@ -38,8 +39,8 @@ const CACHE_WORKER_WAIT_FOR_FILE_STORE_IN_SECS: u64 = 1;
 const SERVICE_TYPE: &str = "cache_worker";
 
 pub struct Worker {
-    /// Redis client.
-    redis_client: redis::Client,
+    /// Cache client, either Redis or an embedded cache.
+    cache_client: CacheClient,
     /// Fullnode grpc address.
     fullnode_grpc_address: Url,
     /// File store config
@@ -82,12 +83,28 @@ impl Worker {
                     redis_main_instance_address
                 )
             })?;
-        Ok(Self {
-            redis_client,
+        Ok(Self::new_with_cache_client(
+            fullnode_grpc_address,
+            CacheClient::Redis(redis_client),
+            file_store,
+            enable_verbose_logging,
+        ))
+    }
+
+    /// Creates a worker that writes to the given cache, e.g., an embedded cache
+    /// that is served by a data service running in the same process.
+    pub fn new_with_cache_client(
+        fullnode_grpc_address: Url,
+        cache_client: CacheClient,
+        file_store: IndexerGrpcFileStoreConfig,
+        enable_verbose_logging: bool,
+    ) -> Self {
+        Self {
+            cache_client,
             file_store,
             fullnode_grpc_address,
             enable_verbose_logging,
-        })
+        }
     }
 
     /// The main loop of the worker is:
@@ -103,10 +120,10 @@ impl Worker {
         // Re-connect if lost.
         loop {
             let conn = self
-                .redis_client
-                .get_tokio_connection_manager()
+                .cache_client
+                .get_connection()
                 .await
-                .context("Get redis connection failed.")?;
+                .context("Get cache connection failed.")?;
             let mut rpc_client = create_grpc_client(self.fullnode_grpc_address.clone()).await;
 
             // 1. Fetch metadata.
@@ -122,6 +139,13 @@ impl Worker {
                 IndexerGrpcFileStoreConfig::LocalFileStore(local_file_store) => Box::new(
                     LocalFileStoreOperator::new(local_file_store.local_file_store_path.clone()),
                 ),
+                IndexerGrpcFileStoreConfig::S3FileStore(s3_file_store) => {
+                    Box::new(S3FileStoreOperator::new(
+                        s3_file_store.s3_file_store_bucket_name.clone(),
+                        s3_file_store.s3_file_store_region.clone(),
+                        s3_file_store.s3_file_store_endpoint.clone(),
+                    ))
+                },
             };
 
             file_store_operator.verify_storage_bucket_existence().await;
@@ -163,7 +187,7 @@ impl Worker {
 
 async fn process_transactions_from_node_response(
     response: TransactionsFromNodeResponse,
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache_operator: &mut CacheOperator<CacheConnection>,
     enable_verbose_logging: bool,
 ) -> Result<GrpcDataStatus> {
     let size_in_bytes = response.encoded_len();
@@ -252,13 +276,9 @@ async fn process_transactions_from_node_response(
 
 /// Setup the cache operator with init signal, includeing chain id and starting version from fullnode.
 async fn setup_cache_with_init_signal(
-    conn: redis::aio::ConnectionManager,
+    conn: CacheConnection,
     init_signal: TransactionsFromNodeResponse,
-) -> Result<(
-    CacheOperator<redis::aio::ConnectionManager>,
-    ChainID,
-    StartingVersion,
-)> {
+) -> Result<(CacheOperator<CacheConnection>, ChainID, StartingVersion)> {
     let (fullnode_chain_id, starting_version) = match init_signal
         .response
         .expect("[Indexer Cache] Response type does not exist.")
@@ -290,7 +310,7 @@ async fn setup_cache_with_init_signal(
 
 // Infinite streaming processing. Retry if error happens; crash if fatal.
 async fn process_streaming_response(
-    conn: redis::aio::ConnectionManager,
+    conn: CacheConnection,
     file_store_metadata: Option<FileStoreMetadata>,
     file_store_operator: Box<dyn FileStoreOperator>,
     mut resp_stream: impl futures_core::Stream<Item = Result<TransactionsFromNodeResponse, tonic::Status>>
@@ -3,15 +3,20 @@
 
 use crate::service::RawDataServerWrapper;
 use anyhow::{bail, Result};
+use aptos_indexer_grpc_cache_worker::worker::Worker;
+use aptos_indexer_grpc_file_store::processor::Processor;
 use aptos_indexer_grpc_server_framework::RunnableConfig;
-use aptos_indexer_grpc_utils::{config::IndexerGrpcFileStoreConfig, types::RedisUrl};
+use aptos_indexer_grpc_utils::{
+    cache_operator::CacheClient, config::IndexerGrpcFileStoreConfig, embedded_cache::EmbeddedCache,
+    types::RedisUrl,
+};
 use aptos_protos::{
     indexer::v1::FILE_DESCRIPTOR_SET as INDEXER_V1_FILE_DESCRIPTOR_SET,
     transaction::v1::FILE_DESCRIPTOR_SET as TRANSACTION_V1_TESTING_FILE_DESCRIPTOR_SET,
     util::timestamp::FILE_DESCRIPTOR_SET as UTIL_TIMESTAMP_FILE_DESCRIPTOR_SET,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, net::SocketAddr};
+use std::{collections::HashSet, net::SocketAddr, time::Duration};
 use tonic::{
     codec::CompressionEncoding,
     codegen::InterceptedService,
@@ -19,6 +24,7 @@ use tonic::{
     transport::Server,
     Request, Status,
 };
+use url::Url;
 
 pub const SERVER_NAME: &str = "idxdatasvc";
 
@@ -31,6 +37,10 @@ const DEFAULT_MAX_RESPONSE_CHANNEL_SIZE: usize = 3;
 const HTTP2_PING_INTERVAL_DURATION: std::time::Duration = std::time::Duration::from_secs(60);
 const HTTP2_PING_TIMEOUT_DURATION: std::time::Duration = std::time::Duration::from_secs(10);
 
+// With the embedded cache, the file store processor waits for the cache worker to
+// initialize the cache before it starts.
+const EMBEDDED_CACHE_INIT_RETRY_DURATION: Duration = Duration::from_secs(1);
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct TlsConfig {
@@ -47,6 +57,13 @@ pub struct NonTlsConfig {
     pub data_service_grpc_listen_address: SocketAddr,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmbeddedCacheConfig {
+    /// The fullnode to stream transactions from into the embedded cache.
+    pub fullnode_grpc_address: Url,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct IndexerGrpcDataServiceConfig {
@@ -65,7 +82,12 @@ pub struct IndexerGrpcDataServiceConfig {
     /// File store config.
     pub file_store_config: IndexerGrpcFileStoreConfig,
     /// Redis read replica address.
-    pub redis_read_replica_address: RedisUrl,
+    #[serde(default)]
+    pub redis_read_replica_address: Option<RedisUrl>,
+    /// If given, the data service serves from an in-process cache instead of Redis.
+    /// The cache worker and the file store processor then run in the same process.
+    #[serde(default)]
+    pub embedded_cache_config: Option<EmbeddedCacheConfig>,
     pub enable_verbose_logging: bool,
 }
 
@@ -88,7 +110,8 @@ impl IndexerGrpcDataServiceConfig {
             whitelisted_auth_tokens,
             disable_auth_check,
             file_store_config,
-            redis_read_replica_address,
+            redis_read_replica_address: Some(redis_read_replica_address),
+            embedded_cache_config: None,
             enable_verbose_logging: enable_verbose_logging.unwrap_or(false),
         }
     }
@@ -112,6 +135,11 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
         {
             bail!("At least one of data_service_grpc_non_tls_config and data_service_grpc_tls_config must be set");
         }
+        if self.redis_read_replica_address.is_some() == self.embedded_cache_config.is_some() {
+            bail!(
+                "Exactly one of redis_read_replica_address and embedded_cache_config must be set"
+            );
+        }
         Ok(())
     }
 
@@ -148,13 +176,33 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build reflection service: {}", e))?;
 
+        let mut tasks = vec![];
+        let server = match (
+            &self.redis_read_replica_address,
+            &self.embedded_cache_config,
+        ) {
+            (Some(redis_read_replica_address), _) => RawDataServerWrapper::new(
+                redis_read_replica_address.clone(),
+                self.file_store_config.clone(),
+                self.data_service_response_channel_size,
+                self.enable_verbose_logging,
+            )?,
+            (None, Some(embedded_cache_config)) => {
+                let cache_client = CacheClient::Embedded(EmbeddedCache::new());
+                tasks.extend(
+                    self.spawn_embedded_cache_tasks(embedded_cache_config, cache_client.clone()),
+                );
+                RawDataServerWrapper::new_with_cache_client(
+                    cache_client,
+                    self.file_store_config.clone(),
+                    self.data_service_response_channel_size,
+                    self.enable_verbose_logging,
+                )
+            },
+            (None, None) => bail!("No cache config provided"),
+        };
+
         // Add authentication interceptor.
-        let server = RawDataServerWrapper::new(
-            self.redis_read_replica_address.clone(),
-            self.file_store_config.clone(),
-            self.data_service_response_channel_size,
-            self.enable_verbose_logging,
-        )?;
         let svc = aptos_protos::indexer::v1::raw_data_server::RawDataServer::new(server)
             .send_compressed(CompressionEncoding::Gzip)
             .accept_compressed(CompressionEncoding::Gzip);
@@ -163,7 +211,7 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
         let svc_with_interceptor_clone = svc_with_interceptor.clone();
         let reflection_service_clone = reflection_service.clone();
 
-        let mut tasks = vec![];
+        let num_of_embedded_cache_tasks = tasks.len();
         if let Some(config) = &self.data_service_grpc_non_tls_config {
             let listen_address = config.data_service_grpc_listen_address;
             tracing::info!(
@@ -203,7 +251,7 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
             }));
         }
 
-        if tasks.is_empty() {
+        if tasks.len() == num_of_embedded_cache_tasks {
             return Err(anyhow::anyhow!("No grpc config provided"));
         }
 
@@ -216,6 +264,54 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
     }
 }
 
+impl IndexerGrpcDataServiceConfig {
+    /// Spawns the cache worker and the file store processor that fill the embedded
+    /// cache and the file store from the fullnode.
+    fn spawn_embedded_cache_tasks(
+        &self,
+        embedded_cache_config: &EmbeddedCacheConfig,
+        cache_client: CacheClient,
+    ) -> Vec<tokio::task::JoinHandle<Result<()>>> {
+        let mut worker = Worker::new_with_cache_client(
+            embedded_cache_config.fullnode_grpc_address.clone(),
+            cache_client.clone(),
+            self.file_store_config.clone(),
+            self.enable_verbose_logging,
+        );
+        let file_store_config = self.file_store_config.clone();
+        let enable_verbose_logging = self.enable_verbose_logging;
+        tracing::info!(
+            fullnode_grpc_address = embedded_cache_config.fullnode_grpc_address.as_str(),
+            "[Data Service] Starting the embedded cache worker and file store processor."
+        );
+        vec![
+            tokio::spawn(async move { worker.run().await }),
+            tokio::spawn(async move {
+                // The processor requires the chain id, which is set by the cache worker.
+                let mut processor = loop {
+                    match Processor::new_with_cache_client(
+                        cache_client.clone(),
+                        file_store_config.clone(),
+                        enable_verbose_logging,
+                    )
+                    .await
+                    {
+                        Ok(processor) => break processor,
+                        Err(e) => {
+                            tracing::info!(
+                                error = e.to_string(),
+                                "[Data Service] Embedded cache is not initialized yet; retrying."
+                            );
+                            tokio::time::sleep(EMBEDDED_CACHE_INIT_RETRY_DURATION).await;
+                        },
+                    }
+                };
+                processor.run().await
+            }),
+        ]
+    }
+}
+
 /// Build a set of whitelisted auth tokens. Invalid tokens are ignored.
 pub fn build_auth_token_set(whitelisted_auth_tokens: Vec<String>) -> HashSet<MetadataValue<Ascii>> {
     whitelisted_auth_tokens
@@ -6,5 +6,6 @@ mod grpc_response_stream;
 mod metrics;
 mod response_dispatcher;
 mod service;
+mod transactions_filter;
 
 pub use config::{IndexerGrpcDataServiceConfig, NonTlsConfig, SERVER_NAME};
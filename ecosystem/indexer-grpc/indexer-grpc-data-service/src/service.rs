@@ -1,16 +1,19 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::metrics::{
-    BYTES_READY_TO_TRANSFER_FROM_SERVER, CONNECTION_COUNT, ERROR_COUNT,
-    LATEST_PROCESSED_VERSION as LATEST_PROCESSED_VERSION_OLD, PROCESSED_BATCH_SIZE,
-    PROCESSED_LATENCY_IN_SECS, PROCESSED_LATENCY_IN_SECS_ALL, PROCESSED_VERSIONS_COUNT,
-    SHORT_CONNECTION_COUNT,
+use crate::{
+    metrics::{
+        BYTES_READY_TO_TRANSFER_FROM_SERVER, CONNECTION_COUNT, ERROR_COUNT,
+        LATEST_PROCESSED_VERSION as LATEST_PROCESSED_VERSION_OLD, PROCESSED_BATCH_SIZE,
+        PROCESSED_LATENCY_IN_SECS, PROCESSED_LATENCY_IN_SECS_ALL, PROCESSED_VERSIONS_COUNT,
+        SHORT_CONNECTION_COUNT,
+    },
+    transactions_filter::TransactionsFilter,
 };
 use anyhow::Context;
 use aptos_indexer_grpc_utils::{
     build_protobuf_encoded_transaction_wrappers,
-    cache_operator::{CacheBatchGetStatus, CacheOperator},
+    cache_operator::{CacheBatchGetStatus, CacheClient, CacheConnection, CacheOperator},
     chunk_transactions,
     config::IndexerGrpcFileStoreConfig,
     constants::{
@@ -18,14 +21,18 @@ use aptos_indexer_grpc_utils::{
         MESSAGE_SIZE_LIMIT,
     },
     counters::{log_grpc_step, IndexerGrpcStep},
-    file_store_operator::{FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator},
+    file_store_operator::{
+        FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator, S3FileStoreOperator,
+    },
     time_diff_since_pb_timestamp_in_secs,
     types::RedisUrl,
     EncodedTransactionWithVersion,
 };
 use aptos_moving_average::MovingAverage;
 use aptos_protos::{
-    indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
+    indexer::v1::{
+        raw_data_server::RawData, GetTransactionsRequest, ProcessedRange, TransactionsResponse,
+    },
     transaction::v1::Transaction,
 };
 use futures::Stream;
@@ -34,7 +41,6 @@ use std::{
     collections::HashMap,
     pin::Pin,
     str::FromStr,
-    sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::sync::mpsc::{channel, error::SendTimeoutError};
@@ -64,7 +70,7 @@ const RESPONSE_HEADER_APTOS_CONNECTION_ID_HEADER: &str = "x-aptos-connection-id"
 const SERVICE_TYPE: &str = "data_service";
 
 pub struct RawDataServerWrapper {
-    pub redis_client: Arc<redis::Client>,
+    pub cache_client: CacheClient,
     pub file_store_config: IndexerGrpcFileStoreConfig,
     pub data_service_response_channel_size: usize,
     pub enable_verbose_logging: bool,
@@ -77,16 +83,30 @@ impl RawDataServerWrapper {
         data_service_response_channel_size: usize,
         enable_verbose_logging: bool,
     ) -> anyhow::Result<Self> {
-        Ok(Self {
-            redis_client: Arc::new(
-                redis::Client::open(redis_address.0.clone()).with_context(|| {
-                    format!("Failed to create redis client for {}", redis_address)
-                })?,
-            ),
+        let redis_client = redis::Client::open(redis_address.0.clone())
+            .with_context(|| format!("Failed to create redis client for {}", redis_address))?;
+        Ok(Self::new_with_cache_client(
+            CacheClient::Redis(redis_client),
             file_store_config,
             data_service_response_channel_size,
             enable_verbose_logging,
-        })
+        ))
+    }
+
+    /// Creates a server that reads from the given cache, e.g., an embedded cache
+    /// that is filled by a cache worker running in the same process.
+    pub fn new_with_cache_client(
+        cache_client: CacheClient,
+        file_store_config: IndexerGrpcFileStoreConfig,
+        data_service_response_channel_size: usize,
+        enable_verbose_logging: bool,
+    ) -> Self {
+        Self {
+            cache_client,
+            file_store_config,
+            data_service_response_channel_size,
+            enable_verbose_logging,
+        }
     }
 }
 
@@ -111,8 +131,9 @@ impl RawData for RawDataServerWrapper {
     ///    1.2. If the data is not in cache, fetch the data from file store.
     ///    1.3. If the data is not in file store, stream connection will break.
     ///    1.4  If error happens, retry after a short sleep.
-    /// 2. Push data into channel to stream to the client.
-    ///    2.1. If the channel is full, do not fetch and retry after a short sleep.
+    /// 2. If the request has a transactions filter, drop the transactions that don't match it.
+    /// 3. Push data into channel to stream to the client.
+    ///    3.1. If the channel is full, do not fetch and retry after a short sleep.
     async fn get_transactions(
         &self,
         req: Request<GetTransactionsRequest>,
@@ -135,6 +156,13 @@ impl RawData for RawDataServerWrapper {
                 return Result::Err(Status::aborted("Starting version is not set"));
             },
         };
+        let transactions_filter = match &request.transactions_filter {
+            Some(transactions_filter) => match TransactionsFilter::new(transactions_filter) {
+                Ok(transactions_filter) => Some(transactions_filter),
+                Err(e) => return Result::Err(Status::invalid_argument(e)),
+            },
+            None => None,
+        };
 
         let file_store_operator: Box<dyn FileStoreOperator> = match &self.file_store_config {
             IndexerGrpcFileStoreConfig::GcsFileStore(gcs_file_store) => {
@@ -148,6 +176,13 @@ impl RawData for RawDataServerWrapper {
             IndexerGrpcFileStoreConfig::LocalFileStore(local_file_store) => Box::new(
                 LocalFileStoreOperator::new(local_file_store.local_file_store_path.clone()),
             ),
+            IndexerGrpcFileStoreConfig::S3FileStore(s3_file_store) => {
+                Box::new(S3FileStoreOperator::new(
+                    s3_file_store.s3_file_store_bucket_name.clone(),
+                    s3_file_store.s3_file_store_region.clone(),
+                    s3_file_store.s3_file_store_endpoint.clone(),
+                ))
+            },
         };
 
         // Adds tracing context for the request.
@@ -165,7 +200,7 @@ impl RawData for RawDataServerWrapper {
             Some(request_metadata.clone()),
         );
 
-        let redis_client = self.redis_client.clone();
+        let cache_client = self.cache_client.clone();
         let enable_verbose_logging = self.enable_verbose_logging;
         tokio::spawn({
             let request_metadata = request_metadata.clone();
@@ -173,8 +208,8 @@ impl RawData for RawDataServerWrapper {
                 let mut connection_start_time = Some(std::time::Instant::now());
                 let mut transactions_count = transactions_count;
 
-                // Establish redis connection
-                let conn = match redis_client.get_tokio_connection_manager().await {
+                // Establish cache connection
+                let conn = match cache_client.get_connection().await {
                     Ok(conn) => conn,
                     Err(e) => {
                         ERROR_COUNT
@@ -314,24 +349,22 @@ impl RawData for RawDataServerWrapper {
                             request_metadata.processor_name.as_str(),
                         ])
                         .inc_by(bytes_ready_to_transfer as u64);
-                    // 2. Push the data to the response channel, i.e. stream the data to the client.
+                    // 2. Filter the data (if requested) and push it to the response channel,
+                    // i.e. stream the data to the client.
                     let current_batch_size = transaction_data.as_slice().len();
                     let end_of_batch_version = transaction_data.as_slice().last().unwrap().1;
                     let resp_items = get_transactions_responses_builder(
                         transaction_data,
                         chain_id as u32,
+                        transactions_filter.as_ref(),
                         current_batch_start_time,
                         request_metadata.clone(),
                         enable_verbose_logging,
                     );
                     let data_latency_in_secs = resp_items
                         .last()
-                        .unwrap()
-                        .transactions
-                        .last()
-                        .unwrap()
-                        .timestamp
-                        .as_ref()
+                        .and_then(|resp_item| resp_item.transactions.last())
+                        .and_then(|transaction| transaction.timestamp.as_ref())
                         .map(time_diff_since_pb_timestamp_in_secs);
 
                     match channel_send_multiple_with_timeout(
@@ -390,7 +423,7 @@ impl RawData for RawDataServerWrapper {
                             break;
                         },
                     }
-                    // 3. Update the current version and record current tps.
+                    // 4. Update the current version and record current tps.
                     tps_calculator.tick_now(current_batch_size as u64);
                     current_version = end_of_batch_version + 1;
                 }
@@ -436,13 +469,17 @@ impl RawData for RawDataServerWrapper {
 }
 
 /// Builds the response for the get transactions request. Partial batch is ok, i.e., a batch with transactions < 1000.
+/// If a transactions filter is given, only the matching transactions are included in the responses.
 fn get_transactions_responses_builder(
     data: Vec<EncodedTransactionWithVersion>,
     chain_id: u32,
+    transactions_filter: Option<&TransactionsFilter>,
     current_batch_start_time: Instant,
     request_metadata: IndexerGrpcRequestMetadata,
     enable_logging: bool,
 ) -> Vec<TransactionsResponse> {
+    let first_version = data.first().unwrap().1;
+    let last_version = data.last().unwrap().1;
     let transactions: Vec<Transaction> = data
         .into_iter()
         .map(|(encoded, _)| {
@@ -450,26 +487,41 @@ fn get_transactions_responses_builder(
             let transaction = Transaction::decode(&*decoded_transaction);
             transaction.unwrap()
         })
+        .filter(|transaction| {
+            transactions_filter.map_or(true, |transactions_filter| {
+                transactions_filter.matches(transaction)
+            })
+        })
         .collect();
     let chunks = chunk_transactions(transactions, MESSAGE_SIZE_LIMIT);
-    let resp_items = chunks
-        .into_iter()
-        .map(|chunk| TransactionsResponse {
-            chain_id: Some(chain_id as u64),
-            transactions: chunk,
-        })
-        .collect::<Vec<TransactionsResponse>>();
+    let resp_items = match transactions_filter {
+        Some(_) => {
+            get_filtered_transactions_responses(chunks, chain_id, first_version, last_version)
+        },
+        None => chunks
+            .into_iter()
+            .map(|chunk| TransactionsResponse {
+                chain_id: Some(chain_id as u64),
+                transactions: chunk,
+                processed_range: None,
+            })
+            .collect::<Vec<TransactionsResponse>>(),
+    };
 
     let overall_size_in_bytes = resp_items
         .iter()
         .map(|resp_item| resp_item.encoded_len())
         .sum::<usize>();
-    let overall_start_txn = resp_items.first().unwrap().transactions.first().unwrap();
-    let overall_end_txn = resp_items.last().unwrap().transactions.last().unwrap();
-    let overall_start_version = overall_start_txn.version;
-    let overall_end_version = overall_end_txn.version;
-    let overall_start_txn_timestamp = overall_start_txn.clone().timestamp;
-    let overall_end_txn_timestamp = overall_end_txn.clone().timestamp;
+    let (overall_start_version, overall_end_version) =
+        get_responses_version_range(resp_items.as_slice());
+    let overall_start_txn_timestamp = resp_items
+        .first()
+        .and_then(|resp_item| resp_item.transactions.first())
+        .and_then(|transaction| transaction.timestamp.clone());
+    let overall_end_txn_timestamp = resp_items
+        .last()
+        .and_then(|resp_item| resp_item.transactions.last())
+        .and_then(|transaction| transaction.timestamp.clone());
     log_grpc_step(
         SERVICE_TYPE,
         IndexerGrpcStep::DataServiceTxnsDecoded,
@@ -486,11 +538,77 @@ fn get_transactions_responses_builder(
     resp_items
 }
 
+/// Builds the responses for a filtered request. Each response carries the range of versions it covers,
+/// i.e., all versions since the previous response, so that the responses cover the whole batch, and
+/// the client can keep track of the processed versions. If no transaction matches the filter, a single
+/// response without any transactions is returned.
+fn get_filtered_transactions_responses(
+    chunks: Vec<Vec<Transaction>>,
+    chain_id: u32,
+    first_version: u64,
+    last_version: u64,
+) -> Vec<TransactionsResponse> {
+    if chunks.is_empty() {
+        return vec![TransactionsResponse {
+            chain_id: Some(chain_id as u64),
+            transactions: vec![],
+            processed_range: Some(ProcessedRange {
+                first_version,
+                last_version,
+            }),
+        }];
+    }
+
+    let num_of_chunks = chunks.len();
+    let mut next_version = first_version;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            // The last response also covers the filtered out versions at the end of the batch.
+            let end_version = if index == num_of_chunks - 1 {
+                last_version
+            } else {
+                chunk.last().unwrap().version
+            };
+            let processed_range = ProcessedRange {
+                first_version: next_version,
+                last_version: end_version,
+            };
+            next_version = end_version + 1;
+            TransactionsResponse {
+                chain_id: Some(chain_id as u64),
+                transactions: chunk,
+                processed_range: Some(processed_range),
+            }
+        })
+        .collect()
+}
+
+/// Returns the range of versions covered by the response, i.e., the processed range for filtered
+/// requests, or the versions of the first and last transactions otherwise.
+fn get_response_version_range(resp_item: &TransactionsResponse) -> (u64, u64) {
+    match &resp_item.processed_range {
+        Some(processed_range) => (processed_range.first_version, processed_range.last_version),
+        None => (
+            resp_item.transactions.first().unwrap().version,
+            resp_item.transactions.last().unwrap().version,
+        ),
+    }
+}
+
+/// Returns the range of versions covered by all the (non-empty list of) responses.
+fn get_responses_version_range(resp_items: &[TransactionsResponse]) -> (u64, u64) {
+    let (start_version, _) = get_response_version_range(resp_items.first().unwrap());
+    let (_, end_version) = get_response_version_range(resp_items.last().unwrap());
+    (start_version, end_version)
+}
+
 /// Fetches data from cache or the file store. It returns the data if it is ready in the cache or file store.
 /// Otherwise, it returns the status of the data fetching.
 async fn data_fetch(
     starting_version: u64,
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache_operator: &mut CacheOperator<CacheConnection>,
     file_store_operator: &dyn FileStoreOperator,
     current_batch_start_time: Instant,
     request_metadata: IndexerGrpcRequestMetadata,
@@ -681,32 +799,29 @@ async fn channel_send_multiple_with_timeout(
         .iter()
         .map(|resp_item| resp_item.encoded_len())
         .sum::<usize>();
-    let overall_start_txn = resp_items.first().unwrap().transactions.first().unwrap();
-    let overall_end_txn = resp_items.last().unwrap().transactions.last().unwrap();
-    let overall_start_version = overall_start_txn.version;
-    let overall_end_version = overall_end_txn.version;
-    let overall_start_txn_timestamp = overall_start_txn.clone().timestamp;
-    let overall_end_txn_timestamp = overall_end_txn.clone().timestamp;
+    let (overall_start_version, overall_end_version) =
+        get_responses_version_range(resp_items.as_slice());
+    let overall_start_txn_timestamp = resp_items
+        .first()
+        .and_then(|resp_item| resp_item.transactions.first())
+        .and_then(|transaction| transaction.timestamp.clone());
+    let overall_end_txn_timestamp = resp_items
+        .last()
+        .and_then(|resp_item| resp_item.transactions.last())
+        .and_then(|transaction| transaction.timestamp.clone());
 
     for resp_item in resp_items {
         let response_size = resp_item.encoded_len();
         let num_of_transactions = resp_item.transactions.len();
-        let start_version = resp_item.transactions.first().unwrap().version;
-        let end_version = resp_item.transactions.last().unwrap().version;
+        let (start_version, end_version) = get_response_version_range(&resp_item);
         let start_version_txn_timestamp = resp_item
             .transactions
             .first()
-            .unwrap()
-            .timestamp
-            .as_ref()
-            .unwrap();
+            .and_then(|transaction| transaction.timestamp.clone());
         let end_version_txn_timestamp = resp_item
             .transactions
             .last()
-            .unwrap()
-            .timestamp
-            .as_ref()
-            .unwrap();
+            .and_then(|transaction| transaction.timestamp.clone());
 
         tx.send_timeout(
             Result::<TransactionsResponse, Status>::Ok(resp_item.clone()),
@@ -720,8 +835,8 @@ async fn channel_send_multiple_with_timeout(
             enable_logging,
             Some(start_version as i64),
             Some(end_version as i64),
-            Some(start_version_txn_timestamp),
-            Some(end_version_txn_timestamp),
+            start_version_txn_timestamp.as_ref(),
+            end_version_txn_timestamp.as_ref(),
             Some(current_batch_start_time.elapsed().as_secs_f64()),
             Some(response_size),
             Some(num_of_transactions as i64),
@@ -745,3 +860,48 @@ async fn channel_send_multiple_with_timeout(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_transactions(versions: &[u64]) -> Vec<Transaction> {
+        versions
+            .iter()
+            .map(|version| Transaction {
+                version: *version,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filtered_responses_cover_the_whole_batch() {
+        let chunks = vec![create_transactions(&[12, 15]), create_transactions(&[17])];
+        let responses = get_filtered_transactions_responses(chunks, 1, 10, 20);
+        assert_eq!(responses.len(), 2);
+        assert_eq!(
+            responses[0].processed_range,
+            Some(ProcessedRange {
+                first_version: 10,
+                last_version: 15,
+            })
+        );
+        assert_eq!(
+            responses[1].processed_range,
+            Some(ProcessedRange {
+                first_version: 16,
+                last_version: 20,
+            })
+        );
+        assert_eq!(get_responses_version_range(&responses), (10, 20));
+    }
+
+    #[test]
+    fn test_filtered_responses_without_matching_transactions() {
+        let responses = get_filtered_transactions_responses(vec![], 1, 10, 20);
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].transactions.is_empty());
+        assert_eq!(get_responses_version_range(&responses), (10, 20));
+    }
+}
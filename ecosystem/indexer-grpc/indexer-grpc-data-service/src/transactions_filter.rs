@@ -0,0 +1,564 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_protos::{
+    indexer::v1::TransactionsFilter as TransactionsFilterProto,
+    transaction::v1::{
+        multisig_transaction_payload, transaction::TxnData, transaction_payload,
+        write_set_change::Change, EntryFunctionPayload, Event, Transaction,
+    },
+};
+
+/// A validated and normalized version of the transactions filter sent by the client.
+/// Addresses are normalized (i.e., lowercase without leading zeros), so that "0x1" and
+/// "0x0000000000000000000000000000000000000000000000000000000000000001" are equivalent.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransactionsFilter {
+    senders: Vec<String>,
+    // Each entry is either "address::module" or "address::module::function".
+    entry_functions: Vec<String>,
+    event_types: Vec<String>,
+    success: Option<bool>,
+    write_set_resource_types: Vec<String>,
+}
+
+impl TransactionsFilter {
+    /// Validates and normalizes the given filter. Returns an error message
+    /// (suitable for the client) if any of the criteria are malformed.
+    pub fn new(filter: &TransactionsFilterProto) -> Result<Self, String> {
+        let senders = filter
+            .senders
+            .iter()
+            .map(|sender| {
+                normalize_address(sender)
+                    .ok_or_else(|| format!("Invalid sender address in filter: {}", sender))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let entry_functions = filter
+            .entry_functions
+            .iter()
+            .map(|entry_function| {
+                let num_parts = entry_function.split("::").count();
+                if !(2..=3).contains(&num_parts) || !starts_with_address(entry_function) {
+                    return Err(format!(
+                        "Invalid entry function in filter (expected \"address::module\" or \"address::module::function\"): {}",
+                        entry_function
+                    ));
+                }
+                Ok(normalize_type_str(entry_function))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let event_types = normalize_type_strs(&filter.event_types, "event type")?;
+        let write_set_resource_types =
+            normalize_type_strs(&filter.write_set_resource_types, "write set resource type")?;
+
+        Ok(Self {
+            senders,
+            entry_functions,
+            event_types,
+            success: filter.success,
+            write_set_resource_types,
+        })
+    }
+
+    /// Returns true iff the given transaction matches all of the criteria in the filter
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.matches_sender_and_entry_function(transaction)
+            && self.matches_event_types(transaction)
+            && self.matches_success(transaction)
+            && self.matches_write_set_resource_types(transaction)
+    }
+
+    fn matches_sender_and_entry_function(&self, transaction: &Transaction) -> bool {
+        if self.senders.is_empty() && self.entry_functions.is_empty() {
+            return true;
+        }
+
+        // Only user transactions have a sender and an entry function
+        let request = match &transaction.txn_data {
+            Some(TxnData::User(user_transaction)) => match &user_transaction.request {
+                Some(request) => request,
+                None => return false,
+            },
+            _ => return false,
+        };
+
+        if !self.senders.is_empty() {
+            let sender = normalize_address(&request.sender);
+            if !self.senders.iter().any(|s| Some(s) == sender.as_ref()) {
+                return false;
+            }
+        }
+
+        if !self.entry_functions.is_empty() {
+            let entry_function_id = match get_entry_function_payload(transaction) {
+                Some(payload) => normalize_type_str(&payload.entry_function_id_str),
+                None => return false,
+            };
+            if !self
+                .entry_functions
+                .iter()
+                .any(|entry_function| matches_entry_function(entry_function, &entry_function_id))
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn matches_event_types(&self, transaction: &Transaction) -> bool {
+        if self.event_types.is_empty() {
+            return true;
+        }
+        get_events(transaction).iter().any(|event| {
+            let event_type = normalize_type_str(&event.type_str);
+            self.event_types
+                .iter()
+                .any(|filter_type| matches_type(filter_type, &event_type))
+        })
+    }
+
+    fn matches_success(&self, transaction: &Transaction) -> bool {
+        match self.success {
+            Some(success) => transaction
+                .info
+                .as_ref()
+                .map_or(false, |info| info.success == success),
+            None => true,
+        }
+    }
+
+    fn matches_write_set_resource_types(&self, transaction: &Transaction) -> bool {
+        if self.write_set_resource_types.is_empty() {
+            return true;
+        }
+        let changes = match &transaction.info {
+            Some(info) => &info.changes,
+            None => return false,
+        };
+        changes.iter().any(|change| {
+            let type_str = match &change.change {
+                Some(Change::WriteResource(write_resource)) => &write_resource.type_str,
+                Some(Change::DeleteResource(delete_resource)) => &delete_resource.type_str,
+                _ => return false,
+            };
+            let resource_type = normalize_type_str(type_str);
+            self.write_set_resource_types
+                .iter()
+                .any(|filter_type| matches_type(filter_type, &resource_type))
+        })
+    }
+}
+
+/// Returns the entry function payload of the transaction (if any), including
+/// the entry function payload wrapped in a multisig transaction.
+fn get_entry_function_payload(transaction: &Transaction) -> Option<&EntryFunctionPayload> {
+    let payload = match &transaction.txn_data {
+        Some(TxnData::User(user_transaction)) => user_transaction
+            .request
+            .as_ref()?
+            .payload
+            .as_ref()?
+            .payload
+            .as_ref()?,
+        _ => return None,
+    };
+    match payload {
+        transaction_payload::Payload::EntryFunctionPayload(payload) => Some(payload),
+        transaction_payload::Payload::MultisigPayload(multisig_payload) => {
+            match multisig_payload
+                .transaction_payload
+                .as_ref()?
+                .payload
+                .as_ref()?
+            {
+                multisig_transaction_payload::Payload::EntryFunctionPayload(payload) => {
+                    Some(payload)
+                },
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Returns the events emitted by the transaction
+fn get_events(transaction: &Transaction) -> &[Event] {
+    match &transaction.txn_data {
+        Some(TxnData::User(user_transaction)) => &user_transaction.events,
+        Some(TxnData::Genesis(genesis_transaction)) => &genesis_transaction.events,
+        Some(TxnData::BlockMetadata(block_metadata_transaction)) => {
+            &block_metadata_transaction.events
+        },
+        _ => &[],
+    }
+}
+
+/// Returns true iff the (normalized) entry function id matches the filter, i.e., the
+/// filter is the same function, or the module containing the function.
+fn matches_entry_function(filter: &str, entry_function_id: &str) -> bool {
+    entry_function_id == filter
+        || entry_function_id
+            .strip_prefix(filter)
+            .map_or(false, |rest| rest.starts_with("::"))
+}
+
+/// Returns true iff the (normalized) type matches the filter. A filter without
+/// type arguments matches all instantiations of a generic type.
+fn matches_type(filter: &str, type_str: &str) -> bool {
+    if filter.contains('<') {
+        type_str == filter
+    } else {
+        type_str.split('<').next() == Some(filter)
+    }
+}
+
+fn normalize_type_strs(type_strs: &[String], name: &str) -> Result<Vec<String>, String> {
+    type_strs
+        .iter()
+        .map(|type_str| {
+            if type_str
+                .split('<')
+                .next()
+                .unwrap_or_default()
+                .split("::")
+                .count()
+                != 3
+                || !starts_with_address(type_str)
+            {
+                return Err(format!(
+                    "Invalid {} in filter (expected \"address::module::name\"): {}",
+                    name, type_str
+                ));
+            }
+            Ok(normalize_type_str(type_str))
+        })
+        .collect()
+}
+
+fn starts_with_address(type_str: &str) -> bool {
+    type_str
+        .split("::")
+        .next()
+        .and_then(normalize_address)
+        .is_some()
+}
+
+/// Normalizes an address, e.g., "0x00A1" becomes "0xa1". Returns None if the address is malformed.
+fn normalize_address(address: &str) -> Option<String> {
+    let hex = address.trim().strip_prefix("0x")?;
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let trimmed = hex.trim_start_matches('0').to_ascii_lowercase();
+    if trimmed.is_empty() {
+        Some("0x0".to_string())
+    } else {
+        Some(format!("0x{}", trimmed))
+    }
+}
+
+/// Normalizes all addresses in a type (or function) string and removes any whitespace,
+/// e.g., "0x01::coin::CoinStore< 0x1::aptos_coin::AptosCoin >" becomes
+/// "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>".
+fn normalize_type_str(type_str: &str) -> String {
+    fn flush_token(token: &mut String, normalized: &mut String) {
+        match normalize_address(token) {
+            Some(address) => normalized.push_str(&address),
+            None => normalized.push_str(token),
+        }
+        token.clear();
+    }
+
+    let mut normalized = String::with_capacity(type_str.len());
+    let mut token = String::new();
+    for c in type_str.chars() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            token.push(c);
+        } else {
+            flush_token(&mut token, &mut normalized);
+            normalized.push(c);
+        }
+    }
+    flush_token(&mut token, &mut normalized);
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::transaction::v1::{
+        DeleteResource, MultisigPayload, MultisigTransactionPayload, TransactionInfo,
+        TransactionPayload, UserTransaction, UserTransactionRequest, WriteResource, WriteSetChange,
+    };
+
+    fn create_user_transaction(
+        sender: &str,
+        entry_function_id: &str,
+        event_types: &[&str],
+        success: bool,
+        resource_types: &[&str],
+    ) -> Transaction {
+        Transaction {
+            txn_data: Some(TxnData::User(UserTransaction {
+                request: Some(UserTransactionRequest {
+                    sender: sender.to_string(),
+                    payload: Some(TransactionPayload {
+                        payload: Some(transaction_payload::Payload::EntryFunctionPayload(
+                            EntryFunctionPayload {
+                                entry_function_id_str: entry_function_id.to_string(),
+                                ..Default::default()
+                            },
+                        )),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                events: event_types
+                    .iter()
+                    .map(|event_type| Event {
+                        type_str: event_type.to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            })),
+            info: Some(TransactionInfo {
+                success,
+                changes: resource_types
+                    .iter()
+                    .map(|resource_type| WriteSetChange {
+                        change: Some(Change::WriteResource(WriteResource {
+                            type_str: resource_type.to_string(),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn create_filter(filter: TransactionsFilterProto) -> TransactionsFilter {
+        TransactionsFilter::new(&filter).unwrap()
+    }
+
+    fn create_coin_transfer() -> Transaction {
+        create_user_transaction(
+            "0x000000000000000000000000000000000000000000000000000000000000abcd",
+            "0x1::coin::transfer",
+            &[
+                "0x1::coin::WithdrawEvent",
+                "0x1::coin::DepositEvent",
+                "0x1::transaction_fee::FeeStatement",
+            ],
+            true,
+            &["0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"],
+        )
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = create_filter(TransactionsFilterProto::default());
+        assert!(filter.matches(&create_coin_transfer()));
+        assert!(filter.matches(&Transaction::default()));
+    }
+
+    #[test]
+    fn test_sender_filter() {
+        let transaction = create_coin_transfer();
+        let filter = create_filter(TransactionsFilterProto {
+            senders: vec!["0xABCD".to_string()],
+            ..Default::default()
+        });
+        assert!(filter.matches(&transaction));
+
+        let filter = create_filter(TransactionsFilterProto {
+            senders: vec!["0x1".to_string(), "0x2".to_string()],
+            ..Default::default()
+        });
+        assert!(!filter.matches(&transaction));
+
+        // Non-user transactions never match a sender filter
+        assert!(!filter.matches(&Transaction::default()));
+    }
+
+    #[test]
+    fn test_entry_function_filter() {
+        let transaction = create_coin_transfer();
+        for entry_function in [
+            "0x1::coin::transfer",
+            "0x01::coin",
+            "0x0000000000000000000000000000000000000000000000000000000000000001::coin",
+        ] {
+            let filter = create_filter(TransactionsFilterProto {
+                entry_functions: vec![entry_function.to_string()],
+                ..Default::default()
+            });
+            assert!(filter.matches(&transaction));
+        }
+        for entry_function in ["0x1::coin::transfer_coins", "0x1::co", "0x2::coin"] {
+            let filter = create_filter(TransactionsFilterProto {
+                entry_functions: vec![entry_function.to_string()],
+                ..Default::default()
+            });
+            assert!(!filter.matches(&transaction));
+        }
+    }
+
+    #[test]
+    fn test_entry_function_filter_multisig() {
+        let mut transaction = create_coin_transfer();
+        if let Some(TxnData::User(user_transaction)) = transaction.txn_data.as_mut() {
+            let payload = user_transaction
+                .request
+                .as_mut()
+                .unwrap()
+                .payload
+                .as_mut()
+                .unwrap();
+            let entry_function_payload = match payload.payload.take() {
+                Some(transaction_payload::Payload::EntryFunctionPayload(payload)) => payload,
+                _ => unreachable!(),
+            };
+            payload.payload = Some(transaction_payload::Payload::MultisigPayload(
+                MultisigPayload {
+                    multisig_address: "0x5".to_string(),
+                    transaction_payload: Some(MultisigTransactionPayload {
+                        payload: Some(multisig_transaction_payload::Payload::EntryFunctionPayload(
+                            entry_function_payload,
+                        )),
+                        ..Default::default()
+                    }),
+                },
+            ));
+        }
+        let filter = create_filter(TransactionsFilterProto {
+            entry_functions: vec!["0x1::coin::transfer".to_string()],
+            ..Default::default()
+        });
+        assert!(filter.matches(&transaction));
+    }
+
+    #[test]
+    fn test_event_type_filter() {
+        let transaction = create_coin_transfer();
+        let filter = create_filter(TransactionsFilterProto {
+            event_types: vec!["0x1::coin::DepositEvent".to_string()],
+            ..Default::default()
+        });
+        assert!(filter.matches(&transaction));
+
+        let filter = create_filter(TransactionsFilterProto {
+            event_types: vec!["0x1::fungible_asset::Deposit".to_string()],
+            ..Default::default()
+        });
+        assert!(!filter.matches(&transaction));
+    }
+
+    #[test]
+    fn test_success_filter() {
+        let transaction = create_coin_transfer();
+        let filter = create_filter(TransactionsFilterProto {
+            success: Some(true),
+            ..Default::default()
+        });
+        assert!(filter.matches(&transaction));
+
+        let filter = create_filter(TransactionsFilterProto {
+            success: Some(false),
+            ..Default::default()
+        });
+        assert!(!filter.matches(&transaction));
+    }
+
+    #[test]
+    fn test_write_set_resource_type_filter() {
+        let mut transaction = create_coin_transfer();
+
+        // Generic types without type arguments match all instantiations
+        for resource_type in [
+            "0x1::coin::CoinStore",
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            "0x1::coin::CoinStore< 0x0001::aptos_coin::AptosCoin >",
+        ] {
+            let filter = create_filter(TransactionsFilterProto {
+                write_set_resource_types: vec![resource_type.to_string()],
+                ..Default::default()
+            });
+            assert!(filter.matches(&transaction));
+        }
+        let filter = create_filter(TransactionsFilterProto {
+            write_set_resource_types: vec!["0x1::coin::CoinStore<0x2::coin::Coin>".to_string()],
+            ..Default::default()
+        });
+        assert!(!filter.matches(&transaction));
+
+        // Deleted resources match as well
+        transaction.info.as_mut().unwrap().changes = vec![WriteSetChange {
+            change: Some(Change::DeleteResource(DeleteResource {
+                type_str: "0x1::object::ObjectGroup".to_string(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }];
+        let filter = create_filter(TransactionsFilterProto {
+            write_set_resource_types: vec!["0x1::object::ObjectGroup".to_string()],
+            ..Default::default()
+        });
+        assert!(filter.matches(&transaction));
+    }
+
+    #[test]
+    fn test_all_criteria_must_match() {
+        let transaction = create_coin_transfer();
+        let filter = create_filter(TransactionsFilterProto {
+            senders: vec!["0xabcd".to_string()],
+            entry_functions: vec!["0x1::coin".to_string()],
+            event_types: vec!["0x1::coin::WithdrawEvent".to_string()],
+            success: Some(true),
+            write_set_resource_types: vec!["0x1::coin::CoinStore".to_string()],
+        });
+        assert!(filter.matches(&transaction));
+
+        let filter = create_filter(TransactionsFilterProto {
+            senders: vec!["0xabcd".to_string()],
+            success: Some(false),
+            ..Default::default()
+        });
+        assert!(!filter.matches(&transaction));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        let invalid_filters = [
+            TransactionsFilterProto {
+                senders: vec!["abcd".to_string()],
+                ..Default::default()
+            },
+            TransactionsFilterProto {
+                entry_functions: vec!["0x1".to_string()],
+                ..Default::default()
+            },
+            TransactionsFilterProto {
+                entry_functions: vec!["coin::transfer".to_string()],
+                ..Default::default()
+            },
+            TransactionsFilterProto {
+                event_types: vec!["0x1::coin".to_string()],
+                ..Default::default()
+            },
+            TransactionsFilterProto {
+                write_set_resource_types: vec!["0xzz::coin::CoinStore".to_string()],
+                ..Default::default()
+            },
+        ];
+        for filter in invalid_filters {
+            assert!(TransactionsFilter::new(&filter).is_err());
+        }
+    }
+}
@@ -7,11 +7,13 @@ use crate::metrics::{
 use anyhow::{bail, Context, Result};
 use aptos_indexer_grpc_utils::{
     build_protobuf_encoded_transaction_wrappers,
-    cache_operator::{CacheBatchGetStatus, CacheOperator},
+    cache_operator::{CacheBatchGetStatus, CacheClient, CacheConnection, CacheOperator},
     config::IndexerGrpcFileStoreConfig,
     constants::BLOB_STORAGE_SIZE,
     counters::{log_grpc_step, IndexerGrpcStep},
-    file_store_operator::{FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator},
+    file_store_operator::{
+        FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator, S3FileStoreOperator,
+    },
     types::RedisUrl,
     EncodedTransactionWithVersion,
 };
@@ -27,7 +29,7 @@ const SERVICE_TYPE: &str = "file_worker";
 
 /// Processor tails the data in cache and stores the data in file store.
 pub struct Processor {
-    cache_operator: CacheOperator<redis::aio::ConnectionManager>,
+    cache_operator: CacheOperator<CacheConnection>,
     file_store_operator: Box<dyn FileStoreOperator>,
     cache_chain_id: u64,
     enable_verbose_logging: bool,
@@ -40,21 +42,32 @@ impl Processor {
         enable_verbose_logging: bool,
     ) -> Result<Self> {
         // Connection to redis is a hard dependency for file store processor.
-        let conn = redis::Client::open(redis_main_instance_address.0.clone())
+        let redis_client = redis::Client::open(redis_main_instance_address.0.clone())
             .with_context(|| {
                 format!(
                     "Create redis client for {} failed",
                     redis_main_instance_address.0
                 )
-            })?
-            .get_tokio_connection_manager()
-            .await
-            .with_context(|| {
-                format!(
-                    "Create redis connection to {} failed.",
-                    redis_main_instance_address.0
-                )
             })?;
+        Self::new_with_cache_client(
+            CacheClient::Redis(redis_client),
+            file_store_config,
+            enable_verbose_logging,
+        )
+        .await
+    }
+
+    /// Creates a processor that tails the given cache, e.g., an embedded cache
+    /// that is filled by a cache worker running in the same process.
+    pub async fn new_with_cache_client(
+        cache_client: CacheClient,
+        file_store_config: IndexerGrpcFileStoreConfig,
+        enable_verbose_logging: bool,
+    ) -> Result<Self> {
+        let conn = cache_client
+            .get_connection()
+            .await
+            .context("Create cache connection failed.")?;
 
         let mut cache_operator = CacheOperator::new(conn);
         let cache_chain_id = cache_operator
@@ -74,6 +87,13 @@ impl Processor {
             IndexerGrpcFileStoreConfig::LocalFileStore(local_file_store) => Box::new(
                 LocalFileStoreOperator::new(local_file_store.local_file_store_path.clone()),
             ),
+            IndexerGrpcFileStoreConfig::S3FileStore(s3_file_store) => {
+                Box::new(S3FileStoreOperator::new(
+                    s3_file_store.s3_file_store_bucket_name.clone(),
+                    s3_file_store.s3_file_store_region.clone(),
+                    s3_file_store.s3_file_store_endpoint.clone(),
+                ))
+            },
         };
         file_store_operator.verify_storage_bucket_existence().await;
 
@@ -589,6 +589,10 @@ fn convert_signature(signature: &Signature) -> transaction::AnySignature {
             r#type: transaction::any_signature::Type::Secp256k1Ecdsa as i32,
             signature: s.0.clone(),
         },
+        Signature::WebAuthn(s) => transaction::AnySignature {
+            r#type: transaction::any_signature::Type::Webauthn as i32,
+            signature: s.0.clone(),
+        },
     }
 }
 
@@ -602,6 +606,10 @@ fn convert_public_key(public_key: &PublicKey) -> transaction::AnyPublicKey {
             r#type: transaction::any_public_key::Type::Secp256k1Ecdsa as i32,
             public_key: p.0.clone(),
         },
+        PublicKey::Secp256r1Ecdsa(p) => transaction::AnyPublicKey {
+            r#type: transaction::any_public_key::Type::Secp256r1Ecdsa as i32,
+            public_key: p.0.clone(),
+        },
     }
 }
 
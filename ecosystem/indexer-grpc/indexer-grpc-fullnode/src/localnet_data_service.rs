@@ -40,6 +40,11 @@ impl RawData for LocalnetDataService {
         // Some node metadata
         let context = self.service_context.context.clone();
         let r = req.into_inner();
+        if r.transactions_filter.is_some() {
+            return Err(Status::unimplemented(
+                "Transactions filter is not supported by the localnet data service",
+            ));
+        }
         let starting_version = r.starting_version.expect("Starting version must be set");
         let processor_batch_size = self.service_context.processor_batch_size;
         let output_batch_size = self.service_context.output_batch_size;
@@ -99,6 +104,7 @@ impl RawData for LocalnetDataService {
                         },
                         _ => panic!("Unexpected response type."),
                     },
+                    processed_range: None,
                 });
                 match external_service_tx.send(response).await {
                     Ok(_) => {},
@@ -6,7 +6,8 @@ use aptos_indexer_grpc_utils::{
     config::IndexerGrpcFileStoreConfig,
     constants::BLOB_STORAGE_SIZE,
     file_store_operator::{
-        FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator, TransactionsFile,
+        FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator, S3FileStoreOperator,
+        TransactionsFile,
     },
 };
 use aptos_protos::transaction::v1::Transaction;
@@ -38,6 +39,13 @@ impl FileStorageVerifier {
             IndexerGrpcFileStoreConfig::LocalFileStore(local_file_store) => Box::new(
                 LocalFileStoreOperator::new(local_file_store.local_file_store_path.clone()),
             ),
+            IndexerGrpcFileStoreConfig::S3FileStore(s3_file_store) => {
+                Box::new(S3FileStoreOperator::new(
+                    s3_file_store.s3_file_store_bucket_name.clone(),
+                    s3_file_store.s3_file_store_region.clone(),
+                    s3_file_store.s3_file_store_endpoint.clone(),
+                ))
+            },
         };
         // Verify the existence of the storage bucket.
         file_store_operator.verify_storage_bucket_existence().await;
@@ -1,9 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::constants::BLOB_STORAGE_SIZE;
+use crate::{constants::BLOB_STORAGE_SIZE, embedded_cache::EmbeddedCache};
 use anyhow::Context;
-use redis::{AsyncCommands, RedisError, RedisResult};
+use redis::{aio::ConnectionLike, AsyncCommands, RedisError, RedisResult};
+use std::ops::Range;
 
 // Configurations for cache.
 // Cache entries that are present.
@@ -14,7 +15,7 @@ const CACHE_SIZE_ESTIMATION: u64 = 250_000_u64;
 // lower than the latest version - CACHE_SIZE_EVICTION_LOWER_BOUND.
 // The gap between CACHE_SIZE_ESTIMATION and this is to give buffer since
 // reading latest version and actual data not atomic(two operations).
-const CACHE_SIZE_EVICTION_LOWER_BOUND: u64 = 300_000_u64;
+pub(crate) const CACHE_SIZE_EVICTION_LOWER_BOUND: u64 = 300_000_u64;
 
 // Keys for cache.
 const CACHE_KEY_LATEST_VERSION: &str = "latest_version";
@@ -105,104 +106,94 @@ pub fn get_ttl_in_seconds(timestamp_in_seconds: u64) -> u64 {
     BASE_EXPIRATION_EPOCH_TIME_IN_SECONDS - (current_time - timestamp_in_seconds)
 }
 
-// Cache operator directly interacts with redis conn.
-pub struct CacheOperator<T: redis::aio::ConnectionLike + Send> {
-    conn: T,
+/// The storage behind the cache operator, i.e., Redis or an in-process ring buffer.
+///
+/// Transactions are keyed by version; the cache also tracks the chain id and the latest
+/// version, i.e., the version right after the last transaction that is in the cache.
+#[async_trait::async_trait]
+pub trait Cache: Send {
+    /// Initializes the latest version if missing. Returns true if it is initialized.
+    async fn setup_if_needed(&mut self) -> anyhow::Result<bool>;
+    /// Sets the chain id if missing. Returns false if the cached chain id is different.
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<bool>;
+    async fn get_chain_id(&mut self) -> anyhow::Result<u64>;
+    async fn get_latest_version(&mut self) -> anyhow::Result<u64>;
+    /// Moves the latest version after `num_of_versions` transactions ending at `version` are
+    /// cached, unless this would leave a gap.
+    async fn update_latest_version(
+        &mut self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<CacheUpdateStatus>;
+    /// Caches the (version, encoded transaction, timestamp in seconds) entries and evicts the
+    /// entries that are too old.
+    async fn update_cache_transactions(
+        &mut self,
+        transactions: Vec<(u64, String, u64)>,
+    ) -> anyhow::Result<()>;
+    /// Gets the encoded transactions of the versions, which must all be cached.
+    async fn batch_get(&mut self, versions: Range<u64>) -> anyhow::Result<Vec<String>>;
 }
 
-impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
-    pub fn new(conn: T) -> Self {
-        Self { conn }
-    }
-
-    // Set up the cache if needed.
-    pub async fn cache_setup_if_needed(&mut self) -> anyhow::Result<bool> {
-        let version_inserted: bool = redis::cmd("SET")
+#[async_trait::async_trait]
+impl<T: ConnectionLike + Send> Cache for T {
+    async fn setup_if_needed(&mut self) -> anyhow::Result<bool> {
+        redis::cmd("SET")
             .arg(CACHE_KEY_LATEST_VERSION)
             .arg(CACHE_DEFAULT_LATEST_VERSION_NUMBER)
             .arg("NX")
-            .query_async(&mut self.conn)
+            .query_async(self)
             .await
-            .context("Redis latest_version check failed.")?;
-        if version_inserted {
-            tracing::info!(
-                initialized_latest_version = CACHE_DEFAULT_LATEST_VERSION_NUMBER,
-                "Cache latest version is initialized."
-            );
-        }
-        Ok(version_inserted)
+            .context("Redis latest_version check failed.")
     }
 
-    // Update the chain id in cache if missing; otherwise, verify the chain id.
-    // It's a fatal error if the chain id is not correct.
-    pub async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<()> {
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<bool> {
         let script = redis::Script::new(CACHE_SCRIPT_UPDATE_OR_VERIFY_CHAIN_ID);
         let result: u8 = script
             .key(CACHE_KEY_CHAIN_ID)
             .arg(chain_id)
-            .invoke_async(&mut self.conn)
+            .invoke_async(self)
             .await
             .context("Redis chain id update/verification failed.")?;
-        if result != 1 {
-            anyhow::bail!("Chain id is not correct.");
-        }
-        Ok(())
+        Ok(result == 1)
     }
 
-    // Downstream system can infer the chain id from cache.
-    pub async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
-        let chain_id: u64 = match self.conn.get::<&str, String>(CACHE_KEY_CHAIN_ID).await {
-            Ok(v) => v
-                .parse::<u64>()
-                .with_context(|| format!("Redis key {} is not a number.", CACHE_KEY_CHAIN_ID))?,
-            Err(err) => return Err(err.into()),
-        };
-        Ok(chain_id)
+    async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
+        self.get::<&str, String>(CACHE_KEY_CHAIN_ID)
+            .await?
+            .parse::<u64>()
+            .with_context(|| format!("Redis key {} is not a number.", CACHE_KEY_CHAIN_ID))
     }
 
-    pub async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
-        let chain_id: u64 = match self
-            .conn
-            .get::<&str, String>(CACHE_KEY_LATEST_VERSION)
-            .await
-        {
-            Ok(v) => v.parse::<u64>().with_context(|| {
-                format!("Redis key {} is not a number.", CACHE_KEY_LATEST_VERSION)
-            })?,
-            Err(err) => return Err(err.into()),
-        };
-        Ok(chain_id)
+    async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
+        self.get::<&str, String>(CACHE_KEY_LATEST_VERSION)
+            .await?
+            .parse::<u64>()
+            .with_context(|| format!("Redis key {} is not a number.", CACHE_KEY_LATEST_VERSION))
     }
 
-    // Internal function to get the latest version from cache.
-    pub(crate) async fn check_cache_coverage_status(
+    async fn update_latest_version(
         &mut self,
-        requested_version: u64,
-    ) -> anyhow::Result<CacheCoverageStatus> {
-        let latest_version: u64 = match self
-            .conn
-            .get::<&str, String>(CACHE_KEY_LATEST_VERSION)
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<CacheUpdateStatus> {
+        let script = redis::Script::new(CACHE_SCRIPT_UPDATE_LATEST_VERSION);
+        let result: u8 = script
+            .key(CACHE_KEY_LATEST_VERSION)
+            .arg(num_of_versions)
+            .arg(version)
+            .invoke_async(self)
             .await
-        {
-            Ok(v) => v
-                .parse::<u64>()
-                .expect("Redis latest_version is not a number."),
-            Err(err) => return Err(err.into()),
-        };
-
-        if requested_version >= latest_version {
-            Ok(CacheCoverageStatus::DataNotReady)
-        } else if requested_version + CACHE_SIZE_ESTIMATION < latest_version {
-            Ok(CacheCoverageStatus::CacheEvicted)
-        } else {
-            Ok(CacheCoverageStatus::CacheHit(std::cmp::min(
-                latest_version - requested_version,
-                BLOB_STORAGE_SIZE as u64,
-            )))
+            .context("Redis latest version update failed.")?;
+        match result {
+            0 => Ok(CacheUpdateStatus::Ok),
+            1 => Ok(CacheUpdateStatus::VerifiedWithoutUpdate),
+            2 => Ok(CacheUpdateStatus::AheadOfLatestVersion),
+            _ => anyhow::bail!("Unexpected latest version update result {}.", result),
         }
     }
 
-    pub async fn update_cache_transactions(
+    async fn update_cache_transactions(
         &mut self,
         transactions: Vec<(u64, String, u64)>,
     ) -> anyhow::Result<()> {
@@ -226,8 +217,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
             }
         }
 
-        let redis_result: RedisResult<()> =
-            redis_pipeline.query_async::<_, _>(&mut self.conn).await;
+        let redis_result: RedisResult<()> = redis_pipeline.query_async::<_, _>(self).await;
 
         match redis_result {
             Ok(_) => Ok(()),
@@ -235,27 +225,173 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
         }
     }
 
+    async fn batch_get(&mut self, versions: Range<u64>) -> anyhow::Result<Vec<String>> {
+        let versions = versions.map(|e| e.to_string()).collect::<Vec<String>>();
+        let encoded_transactions: Result<Vec<String>, RedisError> = self.mget(versions).await;
+        match encoded_transactions {
+            Ok(v) => Ok(v),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// The client of the cache that the cache worker writes to and the data service reads from.
+#[derive(Clone, Debug)]
+pub enum CacheClient {
+    Redis(redis::Client),
+    Embedded(EmbeddedCache),
+}
+
+impl CacheClient {
+    pub async fn get_connection(&self) -> RedisResult<CacheConnection> {
+        match self {
+            CacheClient::Redis(client) => Ok(CacheConnection::Redis(
+                client.get_tokio_connection_manager().await?,
+            )),
+            CacheClient::Embedded(cache) => Ok(CacheConnection::Embedded(cache.clone())),
+        }
+    }
+}
+
+/// A connection to either a Redis instance or an embedded cache.
+#[derive(Clone)]
+pub enum CacheConnection {
+    Redis(redis::aio::ConnectionManager),
+    Embedded(EmbeddedCache),
+}
+
+impl CacheConnection {
+    fn cache(&mut self) -> &mut dyn Cache {
+        match self {
+            CacheConnection::Redis(conn) => conn,
+            CacheConnection::Embedded(cache) => cache,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Cache for CacheConnection {
+    async fn setup_if_needed(&mut self) -> anyhow::Result<bool> {
+        self.cache().setup_if_needed().await
+    }
+
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<bool> {
+        self.cache().update_or_verify_chain_id(chain_id).await
+    }
+
+    async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
+        self.cache().get_chain_id().await
+    }
+
+    async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
+        self.cache().get_latest_version().await
+    }
+
+    async fn update_latest_version(
+        &mut self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<CacheUpdateStatus> {
+        self.cache()
+            .update_latest_version(num_of_versions, version)
+            .await
+    }
+
+    async fn update_cache_transactions(
+        &mut self,
+        transactions: Vec<(u64, String, u64)>,
+    ) -> anyhow::Result<()> {
+        self.cache().update_cache_transactions(transactions).await
+    }
+
+    async fn batch_get(&mut self, versions: Range<u64>) -> anyhow::Result<Vec<String>> {
+        self.cache().batch_get(versions).await
+    }
+}
+
+// Cache operator directly interacts with the cache.
+pub struct CacheOperator<T: Cache> {
+    cache: T,
+}
+
+impl<T: Cache> CacheOperator<T> {
+    pub fn new(cache: T) -> Self {
+        Self { cache }
+    }
+
+    // Set up the cache if needed.
+    pub async fn cache_setup_if_needed(&mut self) -> anyhow::Result<bool> {
+        let version_inserted = self.cache.setup_if_needed().await?;
+        if version_inserted {
+            tracing::info!(
+                initialized_latest_version = CACHE_DEFAULT_LATEST_VERSION_NUMBER,
+                "Cache latest version is initialized."
+            );
+        }
+        Ok(version_inserted)
+    }
+
+    // Update the chain id in cache if missing; otherwise, verify the chain id.
+    // It's a fatal error if the chain id is not correct.
+    pub async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<()> {
+        if !self.cache.update_or_verify_chain_id(chain_id).await? {
+            anyhow::bail!("Chain id is not correct.");
+        }
+        Ok(())
+    }
+
+    // Downstream system can infer the chain id from cache.
+    pub async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
+        self.cache.get_chain_id().await
+    }
+
+    pub async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
+        self.cache.get_latest_version().await
+    }
+
+    // Internal function to get the latest version from cache.
+    pub(crate) async fn check_cache_coverage_status(
+        &mut self,
+        requested_version: u64,
+    ) -> anyhow::Result<CacheCoverageStatus> {
+        let latest_version = self.cache.get_latest_version().await?;
+
+        if requested_version >= latest_version {
+            Ok(CacheCoverageStatus::DataNotReady)
+        } else if requested_version + CACHE_SIZE_ESTIMATION < latest_version {
+            Ok(CacheCoverageStatus::CacheEvicted)
+        } else {
+            Ok(CacheCoverageStatus::CacheHit(std::cmp::min(
+                latest_version - requested_version,
+                BLOB_STORAGE_SIZE as u64,
+            )))
+        }
+    }
+
+    pub async fn update_cache_transactions(
+        &mut self,
+        transactions: Vec<(u64, String, u64)>,
+    ) -> anyhow::Result<()> {
+        self.cache.update_cache_transactions(transactions).await
+    }
+
     // Update the latest version in cache.
     pub async fn update_cache_latest_version(
         &mut self,
         num_of_versions: u64,
         version: u64,
     ) -> anyhow::Result<()> {
-        let script = redis::Script::new(CACHE_SCRIPT_UPDATE_LATEST_VERSION);
         tracing::debug!(
             num_of_versions = num_of_versions,
             version = version,
             "Updating latest version in cache."
         );
-        match script
-            .key(CACHE_KEY_LATEST_VERSION)
-            .arg(num_of_versions)
-            .arg(version)
-            .invoke_async(&mut self.conn)
-            .await
-            .context("Redis latest version update failed.")?
+        match self
+            .cache
+            .update_latest_version(num_of_versions, version)
+            .await?
         {
-            2 => {
+            CacheUpdateStatus::AheadOfLatestVersion => {
                 tracing::error!(version=version, "Redis latest version update failed. The version is beyond the next expected version.");
                 Err(anyhow::anyhow!("Version is not right."))
             },
@@ -269,17 +405,11 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
     ) -> anyhow::Result<CacheBatchGetStatus> {
         let cache_coverage_status = self.check_cache_coverage_status(start_version).await;
         match cache_coverage_status {
-            Ok(CacheCoverageStatus::CacheHit(v)) => {
-                let versions = (start_version..start_version + v)
-                    .map(|e| e.to_string())
-                    .collect::<Vec<String>>();
-                let encoded_transactions: Result<Vec<String>, RedisError> =
-                    self.conn.mget(versions).await;
-                match encoded_transactions {
-                    Ok(v) => Ok(CacheBatchGetStatus::Ok(v)),
-                    Err(err) => Err(err.into()),
-                }
-            },
+            Ok(CacheCoverageStatus::CacheHit(v)) => self
+                .cache
+                .batch_get(start_version..start_version + v)
+                .await
+                .map(CacheBatchGetStatus::Ok),
             Ok(CacheCoverageStatus::CacheEvicted) => Ok(CacheBatchGetStatus::EvictedFromCache),
             Ok(CacheCoverageStatus::DataNotReady) => Ok(CacheBatchGetStatus::NotReady),
             Err(err) => Err(err),
@@ -492,6 +622,29 @@ mod tests {
 
     // TODO:Cache update tests.
 
+    // Cache latest version update tests.
+    #[tokio::test]
+    async fn cache_update_latest_version_with_gap_fails() {
+        let script = redis::Script::new(CACHE_SCRIPT_UPDATE_LATEST_VERSION);
+        let cmds = vec![MockCmd::new(
+            redis::cmd("EVALSHA")
+                .arg(script.get_hash())
+                .arg(1)
+                .arg(CACHE_KEY_LATEST_VERSION)
+                .arg(10)
+                .arg(30),
+            Ok(2),
+        )];
+        let mock_connection = MockRedisConnection::new(cmds);
+        let mut cache_operator: CacheOperator<MockRedisConnection> =
+            CacheOperator::new(mock_connection);
+
+        assert!(cache_operator
+            .update_cache_latest_version(10, 30)
+            .await
+            .is_err());
+    }
+
     // Cache chain id tests.
     #[tokio::test]
     async fn cache_chain_id_ok() {
@@ -17,11 +17,23 @@ pub struct LocalFileStore {
     pub local_file_store_path: PathBuf,
 }
 
+/// Amazon S3 or any S3-compatible object store, e.g., MinIO.
+/// The credentials are read from `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct S3FileStore {
+    pub s3_file_store_bucket_name: String,
+    pub s3_file_store_region: String,
+    // Custom endpoint for S3-compatible stores, e.g., `http://127.0.0.1:9000` for MinIO.
+    #[serde(default)]
+    pub s3_file_store_endpoint: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "file_store_type")]
 pub enum IndexerGrpcFileStoreConfig {
     GcsFileStore(GcsFileStore),
     LocalFileStore(LocalFileStore),
+    S3FileStore(S3FileStore),
 }
 
 impl Default for IndexerGrpcFileStoreConfig {
@@ -0,0 +1,242 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cache_operator::{Cache, CacheUpdateStatus, CACHE_SIZE_EVICTION_LOWER_BOUND};
+use anyhow::{bail, format_err, Result};
+use std::{cmp::Ordering, ops::Range, sync::Arc};
+use tokio::sync::RwLock;
+
+/// An in-process cache that can be used in place of Redis by the cache operator, so the cache
+/// worker, the file store processor and the data service can share one cache inside a single
+/// process. Clones share the same underlying storage.
+///
+/// Transactions are kept in a ring buffer indexed by version, so a transaction is evicted once
+/// the transaction `CACHE_SIZE_EVICTION_LOWER_BOUND` versions after it is cached, like the
+/// active eviction done on Redis. TTLs are ignored.
+#[derive(Clone, Debug)]
+pub struct EmbeddedCache {
+    inner: Arc<RwLock<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    chain_id: Option<u64>,
+    latest_version: Option<u64>,
+    /// The transaction of version `v` lives in slot `v % capacity`, if it is still cached.
+    transactions: Vec<Option<(u64, String)>>,
+}
+
+impl EmbeddedCache {
+    pub fn new() -> Self {
+        Self::with_capacity(CACHE_SIZE_EVICTION_LOWER_BOUND as usize)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Inner {
+                chain_id: None,
+                latest_version: None,
+                transactions: vec![None; capacity],
+            })),
+        }
+    }
+
+    /// Returns the number of transactions in the cache.
+    pub async fn len(&self) -> usize {
+        self.inner
+            .read()
+            .await
+            .transactions
+            .iter()
+            .filter(|slot| slot.is_some())
+            .count()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+}
+
+impl Default for EmbeddedCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Cache for EmbeddedCache {
+    async fn setup_if_needed(&mut self) -> Result<bool> {
+        let mut inner = self.inner.write().await;
+        if inner.latest_version.is_some() {
+            return Ok(false);
+        }
+        inner.latest_version = Some(0);
+        Ok(true)
+    }
+
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> Result<bool> {
+        let mut inner = self.inner.write().await;
+        Ok(*inner.chain_id.get_or_insert(chain_id) == chain_id)
+    }
+
+    async fn get_chain_id(&mut self) -> Result<u64> {
+        self.inner
+            .read()
+            .await
+            .chain_id
+            .ok_or_else(|| format_err!("Chain id is not in the cache."))
+    }
+
+    async fn get_latest_version(&mut self) -> Result<u64> {
+        self.inner
+            .read()
+            .await
+            .latest_version
+            .ok_or_else(|| format_err!("Latest version is not in the cache."))
+    }
+
+    async fn update_latest_version(
+        &mut self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> Result<CacheUpdateStatus> {
+        let mut inner = self.inner.write().await;
+        let latest_version = match inner.latest_version {
+            Some(latest_version) => latest_version,
+            None => {
+                inner.latest_version = Some(num_of_versions);
+                return Ok(CacheUpdateStatus::Ok);
+            },
+        };
+        match (latest_version + num_of_versions).cmp(&version) {
+            Ordering::Less => Ok(CacheUpdateStatus::AheadOfLatestVersion),
+            Ordering::Equal => {
+                inner.latest_version = Some(version);
+                Ok(CacheUpdateStatus::Ok)
+            },
+            Ordering::Greater => {
+                inner.latest_version = Some(std::cmp::max(version, latest_version));
+                Ok(CacheUpdateStatus::VerifiedWithoutUpdate)
+            },
+        }
+    }
+
+    async fn update_cache_transactions(
+        &mut self,
+        transactions: Vec<(u64, String, u64)>,
+    ) -> Result<()> {
+        let mut inner = self.inner.write().await;
+        let capacity = inner.transactions.len() as u64;
+        for (version, encoded_proto_data, _timestamp_in_seconds) in transactions {
+            inner.transactions[(version % capacity) as usize] = Some((version, encoded_proto_data));
+        }
+        Ok(())
+    }
+
+    async fn batch_get(&mut self, versions: Range<u64>) -> Result<Vec<String>> {
+        let inner = self.inner.read().await;
+        let capacity = inner.transactions.len() as u64;
+        versions
+            .map(
+                |version| match &inner.transactions[(version % capacity) as usize] {
+                    Some((cached_version, encoded_proto_data)) if *cached_version == version => {
+                        Ok(encoded_proto_data.clone())
+                    },
+                    _ => bail!("Transaction {} is not in the cache.", version),
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache_operator::{CacheBatchGetStatus, CacheOperator};
+
+    #[tokio::test]
+    async fn embedded_cache_is_setup_once() {
+        let mut cache_operator = CacheOperator::new(EmbeddedCache::new());
+        assert!(cache_operator.get_latest_version().await.is_err());
+        assert!(cache_operator.cache_setup_if_needed().await.unwrap());
+        assert!(!cache_operator.cache_setup_if_needed().await.unwrap());
+        assert_eq!(cache_operator.get_latest_version().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn embedded_cache_verifies_chain_id() {
+        let mut cache_operator = CacheOperator::new(EmbeddedCache::new());
+        assert!(cache_operator.get_chain_id().await.is_err());
+        cache_operator.update_or_verify_chain_id(2).await.unwrap();
+        cache_operator.update_or_verify_chain_id(2).await.unwrap();
+        assert!(cache_operator.update_or_verify_chain_id(1).await.is_err());
+        assert_eq!(cache_operator.get_chain_id().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn embedded_cache_is_shared_between_clones() {
+        let cache = EmbeddedCache::new();
+        let mut writer = CacheOperator::new(cache.clone());
+        let mut reader = CacheOperator::new(cache);
+        writer.cache_setup_if_needed().await.unwrap();
+        assert_eq!(
+            reader.batch_get_encoded_proto_data(0).await.unwrap(),
+            CacheBatchGetStatus::NotReady
+        );
+
+        let transactions = (0..3)
+            .map(|version| (version, format!("txn_{}", version), 0))
+            .collect();
+        writer
+            .update_cache_transactions(transactions)
+            .await
+            .unwrap();
+        writer.update_cache_latest_version(3, 3).await.unwrap();
+
+        assert_eq!(reader.get_latest_version().await.unwrap(), 3);
+        assert_eq!(
+            reader.batch_get_encoded_proto_data(1).await.unwrap(),
+            CacheBatchGetStatus::Ok(vec!["txn_1".to_string(), "txn_2".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn embedded_cache_detects_version_gap() {
+        let mut cache_operator = CacheOperator::new(EmbeddedCache::new());
+        cache_operator.cache_setup_if_needed().await.unwrap();
+        cache_operator
+            .update_cache_latest_version(10, 10)
+            .await
+            .unwrap();
+        // An overlapping update doesn't move the latest version backwards.
+        cache_operator
+            .update_cache_latest_version(5, 8)
+            .await
+            .unwrap();
+        assert_eq!(cache_operator.get_latest_version().await.unwrap(), 10);
+        assert!(cache_operator
+            .update_cache_latest_version(10, 30)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn embedded_cache_evicts_old_versions() {
+        let mut cache = EmbeddedCache::with_capacity(10);
+        let transactions = (0..25)
+            .map(|version| (version, format!("txn_{}", version), 0))
+            .collect();
+        cache.update_cache_transactions(transactions).await.unwrap();
+
+        // Only the last `capacity` versions are kept.
+        assert_eq!(cache.len().await, 10);
+        assert!(cache.batch_get(14..16).await.is_err());
+        assert_eq!(cache.batch_get(15..17).await.unwrap(), vec![
+            "txn_15".to_string(),
+            "txn_16".to_string()
+        ]);
+        assert_eq!(cache.batch_get(24..25).await.unwrap(), vec![
+            "txn_24".to_string()
+        ]);
+    }
+}
@@ -9,6 +9,8 @@ pub mod gcs;
 pub use gcs::*;
 pub mod local;
 pub use local::*;
+pub mod s3;
+pub use s3::*;
 
 pub const FILE_FOLDER_NAME: &str = "files";
 const METADATA_FILE_NAME: &str = "metadata.json";
@@ -0,0 +1,393 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{constants::BLOB_STORAGE_SIZE, file_store_operator::*, EncodedTransactionWithVersion};
+use aws_config::environment::EnvironmentVariableCredentialsProvider;
+use aws_sdk_s3::{config::Region, error::SdkError, primitives::ByteStream, Client};
+use itertools::{any, Itertools};
+
+const JSON_FILE_TYPE: &str = "application/json";
+
+/// File store operator for Amazon S3 and S3-compatible object stores (e.g., MinIO).
+/// The credentials are read from the standard AWS environment variables, i.e.,
+/// `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY` (and optionally `AWS_SESSION_TOKEN`).
+pub struct S3FileStoreOperator {
+    bucket_name: String,
+    client: Client,
+    /// The timestamp of the latest metadata update; this is to avoid too frequent metadata update.
+    latest_metadata_update_timestamp: Option<std::time::Instant>,
+
+    /// The timestamp of the latest verification metadata update; this is to avoid too frequent metadata update.
+    latest_verification_metadata_update_timestamp: Option<std::time::Instant>,
+}
+
+impl S3FileStoreOperator {
+    pub fn new(bucket_name: String, region: String, endpoint: Option<String>) -> Self {
+        let mut config_builder = aws_sdk_s3::Config::builder()
+            .region(Region::new(region))
+            .credentials_provider(EnvironmentVariableCredentialsProvider::new());
+        if let Some(endpoint) = endpoint {
+            // S3-compatible stores usually only support path-style requests, i.e., ${endpoint}/${bucket}/${key}.
+            config_builder = config_builder.endpoint_url(endpoint).force_path_style(true);
+        }
+        Self {
+            bucket_name,
+            client: Client::from_conf(config_builder.build()),
+            latest_metadata_update_timestamp: None,
+            latest_verification_metadata_update_timestamp: None,
+        }
+    }
+
+    /// Downloads the object with the given key. Returns None if the object doesn't exist.
+    async fn download_object(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => {
+                let bytes = output.body.collect().await?.into_bytes();
+                Ok(Some(bytes.to_vec()))
+            },
+            Err(SdkError::ServiceError(service_error)) if service_error.err().is_no_such_key() => {
+                Ok(None)
+            },
+            Err(err) => Err(anyhow::Error::from(err)),
+        }
+    }
+
+    /// Uploads the object with the given key, replacing the existing one (if any).
+    async fn upload_object(&self, key: &str, bytes: Vec<u8>) -> anyhow::Result<()> {
+        upload_object(&self.client, &self.bucket_name, key, bytes).await
+    }
+}
+
+async fn upload_object(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    bytes: Vec<u8>,
+) -> anyhow::Result<()> {
+    client
+        .put_object()
+        .bucket(bucket_name)
+        .key(key)
+        .content_type(JSON_FILE_TYPE)
+        .body(ByteStream::from(bytes))
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(anyhow::Error::from)
+}
+
+#[async_trait::async_trait]
+impl FileStoreOperator for S3FileStoreOperator {
+    /// Bootstraps the file store operator. This is required before any other operations.
+    async fn verify_storage_bucket_existence(&self) {
+        tracing::info!(
+            bucket_name = self.bucket_name,
+            "Before file store operator starts, verify the bucket exists."
+        );
+        // Verifies the bucket exists.
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket_name)
+            .send()
+            .await
+            .expect("Failed to read bucket.");
+    }
+
+    /// Gets the transactions files from the file store. version has to be a multiple of BLOB_STORAGE_SIZE.
+    async fn get_transactions(&self, version: u64) -> anyhow::Result<Vec<String>> {
+        let batch_start_version = version / BLOB_STORAGE_SIZE as u64 * BLOB_STORAGE_SIZE as u64;
+        let current_file_name = generate_blob_name(batch_start_version);
+        match self.download_object(current_file_name.as_str()).await {
+            Ok(Some(file)) => {
+                let file: TransactionsFile =
+                    serde_json::from_slice(&file).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+                Ok(file
+                    .transactions
+                    .into_iter()
+                    .skip((version % BLOB_STORAGE_SIZE as u64) as usize)
+                    .collect())
+            },
+            Ok(None) => {
+                anyhow::bail!("[Indexer File] Transactions file not found. Gap might happen between cache and file store. {}", current_file_name)
+            },
+            Err(err) => {
+                anyhow::bail!(
+                    "[Indexer File] Error happens when transaction file. {}",
+                    err
+                );
+            },
+        }
+    }
+
+    /// Gets the raw transactions file from the file store. Mainly for verification purpose.
+    async fn get_raw_transactions(&self, version: u64) -> anyhow::Result<TransactionsFile> {
+        let batch_start_version = version / BLOB_STORAGE_SIZE as u64 * BLOB_STORAGE_SIZE as u64;
+        let current_file_name = generate_blob_name(batch_start_version);
+        let bytes = self
+            .download_object(current_file_name.as_str())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transactions file not found: {}", current_file_name))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to deserialize transactions file: {}", e))
+    }
+
+    /// Gets the metadata from the file store. Operator will panic if error happens when accessing the metadata file(except not found).
+    async fn get_file_store_metadata(&self) -> Option<FileStoreMetadata> {
+        match self.download_object(METADATA_FILE_NAME).await {
+            Ok(Some(metadata)) => {
+                let metadata: FileStoreMetadata =
+                    serde_json::from_slice(&metadata).expect("Expected metadata to be valid JSON.");
+                Some(metadata)
+            },
+            // Metadata is not found.
+            Ok(None) => None,
+            Err(e) => {
+                panic!(
+                    "[Indexer File] Error happens when accessing metadata file. {}",
+                    e
+                );
+            },
+        }
+    }
+
+    /// If the file store is empty, the metadata will be created; otherwise, return the existing metadata.
+    async fn create_default_file_store_metadata_if_absent(
+        &mut self,
+        expected_chain_id: u64,
+    ) -> anyhow::Result<FileStoreMetadata> {
+        match self.download_object(METADATA_FILE_NAME).await? {
+            Some(metadata) => {
+                let metadata: FileStoreMetadata =
+                    serde_json::from_slice(&metadata).expect("Expected metadata to be valid JSON.");
+                anyhow::ensure!(metadata.chain_id == expected_chain_id, "Chain ID mismatch.");
+                Ok(metadata)
+            },
+            None => {
+                // If the metadata is not found, it means the file store is empty.
+                self.update_file_store_metadata(expected_chain_id, 0)
+                    .await
+                    .expect("[Indexer File] Update metadata failed.");
+                Ok(FileStoreMetadata::new(expected_chain_id, 0))
+            },
+        }
+    }
+
+    /// Updates the file store metadata. This is only performed by the operator when new file transactions are uploaded.
+    async fn update_file_store_metadata(
+        &mut self,
+        chain_id: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let metadata = FileStoreMetadata::new(chain_id, version);
+        // If the metadata is not updated, the indexer will be restarted.
+        self.upload_object(METADATA_FILE_NAME, serde_json::to_vec(&metadata).unwrap())
+            .await?;
+        self.latest_metadata_update_timestamp = Some(std::time::Instant::now());
+        Ok(())
+    }
+
+    /// Updates the verification metadata file.
+    async fn update_verification_metadata(
+        &mut self,
+        chain_id: u64,
+        next_version_to_verify: u64,
+    ) -> Result<()> {
+        let verification_metadata = VerificationMetadata {
+            chain_id,
+            next_version_to_verify,
+        };
+        let time_now = std::time::Instant::now();
+        if let Some(last_update_time) = self.latest_verification_metadata_update_timestamp {
+            if time_now.duration_since(last_update_time) < std::time::Duration::from_secs(20) {
+                return Ok(());
+            }
+        }
+        // If the metadata is not updated, the indexer will be restarted.
+        self.upload_object(
+            VERIFICATION_FILE_NAME,
+            serde_json::to_vec(&verification_metadata).unwrap(),
+        )
+        .await?;
+        self.latest_verification_metadata_update_timestamp = Some(std::time::Instant::now());
+        Ok(())
+    }
+
+    /// Uploads the transactions to the file store. The transactions are grouped into batches of BLOB_STORAGE_SIZE.
+    /// Updates the file store metadata after the upload.
+    async fn upload_transactions(
+        &mut self,
+        chain_id: u64,
+        transactions: Vec<EncodedTransactionWithVersion>,
+    ) -> anyhow::Result<()> {
+        let start_version = transactions.first().unwrap().1;
+        let batch_size = transactions.len();
+        anyhow::ensure!(
+            start_version % BLOB_STORAGE_SIZE as u64 == 0,
+            "Starting version has to be a multiple of BLOB_STORAGE_SIZE."
+        );
+        anyhow::ensure!(
+            batch_size % BLOB_STORAGE_SIZE == 0,
+            "The number of transactions to upload has to be multiplier of BLOB_STORAGE_SIZE."
+        );
+        let mut tasks = vec![];
+
+        // Split the transactions into batches of BLOB_STORAGE_SIZE.
+        for i in transactions.chunks(BLOB_STORAGE_SIZE) {
+            let client = self.client.clone();
+            let bucket_name = self.bucket_name.clone();
+            let current_batch = i.iter().cloned().collect_vec();
+            let transactions_file = build_transactions_file(current_batch).unwrap();
+            let task = tokio::spawn(async move {
+                upload_object(
+                    &client,
+                    bucket_name.as_str(),
+                    generate_blob_name(transactions_file.starting_version).as_str(),
+                    serde_json::to_vec(&transactions_file).unwrap(),
+                )
+                .await
+            });
+            tasks.push(task);
+        }
+        let results = match futures::future::try_join_all(tasks).await {
+            Ok(res) => res,
+            Err(err) => panic!("Error processing transaction batches: {:?}", err),
+        };
+        // If any uploading fails, retry.
+        for result in &results {
+            if result.is_err() {
+                tracing::error!("Error happens when uploading transactions. {:?}", result);
+            }
+        }
+        if any(results, |x| x.is_err()) {
+            anyhow::bail!("Uploading transactions failed.");
+        }
+
+        if let Some(ts) = self.latest_metadata_update_timestamp {
+            // a periodic metadata update
+            if ts.elapsed().as_secs() > FILE_STORE_UPDATE_FREQUENCY_SECS {
+                self.update_file_store_metadata(chain_id, start_version + batch_size as u64)
+                    .await?;
+            }
+        } else {
+            // the first metadata update
+            self.update_file_store_metadata(chain_id, start_version + batch_size as u64)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_or_create_verification_metadata(
+        &self,
+        chain_id: u64,
+    ) -> Result<VerificationMetadata> {
+        let file_metadata = self
+            .get_file_store_metadata()
+            .await
+            .ok_or(anyhow::anyhow!("No file store metadata found"))?;
+        anyhow::ensure!(file_metadata.chain_id == chain_id, "Chain ID mismatch");
+
+        match self.download_object(VERIFICATION_FILE_NAME).await? {
+            Some(verification_metadata) => {
+                let metadata: VerificationMetadata = serde_json::from_slice(&verification_metadata)
+                    .expect("Expected metadata to be valid JSON.");
+                anyhow::ensure!(metadata.chain_id == chain_id, "Chain ID mismatch.");
+                Ok(metadata)
+            },
+            None => {
+                // Metadata is not found.
+                let metadata = VerificationMetadata {
+                    chain_id,
+                    next_version_to_verify: 0,
+                };
+                self.upload_object(
+                    VERIFICATION_FILE_NAME,
+                    serde_json::to_vec(&metadata).unwrap(),
+                )
+                .await?;
+                Ok(metadata)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The MinIO endpoint and bucket used by the test, e.g., started with:
+    // docker run -p 9000:9000 -e MINIO_ROOT_USER=minioadmin -e MINIO_ROOT_PASSWORD=minioadmin minio/minio server /data
+    // and a bucket created with: mc mb local/indexer-grpc-file-store-test
+    const TEST_ENDPOINT: &str = "http://127.0.0.1:9000";
+    const TEST_BUCKET_NAME: &str = "indexer-grpc-file-store-test";
+
+    #[tokio::test]
+    #[ignore] // Requires a running MinIO server (see above) and AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY.
+    async fn test_s3_file_store_operator_with_minio() {
+        let mut operator = S3FileStoreOperator::new(
+            TEST_BUCKET_NAME.to_string(),
+            "us-east-1".to_string(),
+            Some(TEST_ENDPOINT.to_string()),
+        );
+        operator.verify_storage_bucket_existence().await;
+
+        // Upload a batch of transactions and read them back.
+        let transactions = (0..BLOB_STORAGE_SIZE as u64)
+            .map(|version| (format!("transaction_{}", version), version))
+            .collect::<Vec<_>>();
+        operator
+            .upload_transactions(1, transactions.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            operator.get_transactions(10).await.unwrap(),
+            transactions
+                .iter()
+                .skip(10)
+                .map(|(transaction, _)| transaction.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            operator
+                .get_raw_transactions(0)
+                .await
+                .unwrap()
+                .starting_version,
+            0
+        );
+
+        // The metadata is updated on the first upload.
+        let metadata = operator.get_file_store_metadata().await.unwrap();
+        assert_eq!(metadata.chain_id, 1);
+        assert_eq!(metadata.version, BLOB_STORAGE_SIZE as u64);
+
+        // Missing transaction files are reported as gaps.
+        let error = operator
+            .get_transactions(u64::MAX / 2)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Transactions file not found"));
+
+        // The verification metadata is created (and then reused).
+        let verification_metadata = operator
+            .get_or_create_verification_metadata(1)
+            .await
+            .unwrap();
+        operator
+            .update_verification_metadata(1, verification_metadata.next_version_to_verify)
+            .await
+            .unwrap();
+        assert!(operator
+            .get_or_create_verification_metadata(2)
+            .await
+            .is_err());
+    }
+}
@@ -5,6 +5,7 @@ pub mod cache_operator;
 pub mod config;
 pub mod constants;
 pub mod counters;
+pub mod embedded_cache;
 pub mod file_store_operator;
 pub mod types;
 
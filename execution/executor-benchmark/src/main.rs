@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_block_executor::contention::{ContentionReportFileDumper, CONTENTION_REPORT_LIMIT};
 use aptos_block_partitioner::{
     pre_partition::{
         connected_component::config::ConnectedComponentPartitionerConfig,
@@ -22,13 +23,16 @@ use aptos_experimental_runtimes::thread_manager::{ThreadConfigStrategy, ThreadMa
 use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_profiler::{ProfilerConfig, ProfilerHandler};
 use aptos_push_metrics::MetricsPusher;
-use aptos_transaction_generator_lib::args::TransactionTypeArg;
+use aptos_transaction_generator_lib::{
+    args::TransactionTypeArg, trace::TraceProfile, TransactionType,
+};
 use aptos_vm::AptosVM;
 use clap::{ArgGroup, Parser, Subcommand};
 use once_cell::sync::Lazy;
 use std::{
     net::SocketAddr,
     path::PathBuf,
+    sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -256,6 +260,16 @@ struct Opt {
 
     #[clap(flatten)]
     profiler_opt: ProfilerOpt,
+
+    /// Append a Block-STM contention report (top conflicting keys, incarnations and
+    /// dependency chains) for every block executed in parallel to this file.
+    #[clap(long, value_parser)]
+    contention_report_path: Option<PathBuf>,
+
+    /// Use read/write hints derived from transaction payloads to defer the execution of
+    /// transactions predicted to conflict with lower transactions in Block-STM.
+    #[clap(long)]
+    hint_based_scheduling: bool,
 }
 
 impl Opt {
@@ -310,6 +324,12 @@ enum Command {
         #[clap(long, default_value_t = 1)]
         module_working_set_size: usize,
 
+        /// Replay a workload similar to the historical transactions summarized in this trace
+        /// profile (see `aptos-db-tool trace-profile`), in addition to the transaction types
+        /// above. Transactions per sender are then taken from the profile as well.
+        #[clap(long, value_parser)]
+        trace_profile: Option<PathBuf>,
+
         #[clap(long, value_parser)]
         data_dir: PathBuf,
 
@@ -359,10 +379,11 @@ where
             transaction_type,
             transaction_weights,
             module_working_set_size,
+            trace_profile,
             data_dir,
             checkpoint_dir,
         } => {
-            let transaction_mix = if transaction_type.is_empty() {
+            let mut transaction_mix = if transaction_type.is_empty() {
                 None
             } else {
                 let mix_per_phase = TransactionTypeArg::args_to_transaction_mix_per_phase(
@@ -376,6 +397,21 @@ where
                 Some(mix_per_phase[0].clone())
             };
 
+            let mut transactions_per_sender = opt.transactions_per_sender;
+            if let Some(trace_profile) = trace_profile {
+                let profile = TraceProfile::load(&trace_profile).unwrap();
+                transactions_per_sender =
+                    (profile.mean_transactions_per_sender_per_block().round() as usize).max(1);
+                transaction_mix.get_or_insert_with(Vec::new).push((
+                    TransactionType::Trace {
+                        profile: Arc::new(profile),
+                        num_modules: module_working_set_size,
+                        use_account_pool: false,
+                    },
+                    1,
+                ));
+            }
+
             if let Some(hotspot_probability) = opt.hotspot_probability {
                 if !(0.5..1.0).contains(&hotspot_probability) {
                     panic!("Parameter hotspot-probability has to a decimal number in [0.5, 1.0).");
@@ -386,7 +422,7 @@ where
                 opt.block_size,
                 blocks,
                 transaction_mix,
-                opt.transactions_per_sender,
+                transactions_per_sender,
                 opt.connected_tx_grps,
                 opt.shuffle_connected_txns,
                 opt.hotspot_probability,
@@ -483,6 +519,13 @@ fn main() {
     AptosVM::set_concurrency_level_once(execution_threads_per_shard);
     NativeExecutor::set_concurrency_level_once(execution_threads_per_shard);
     AptosVM::set_processed_transactions_detailed_counters();
+    if let Some(path) = &opt.contention_report_path {
+        AptosVM::set_contention_report_hook_once(Arc::new(
+            ContentionReportFileDumper::new(path, CONTENTION_REPORT_LIMIT)
+                .expect("Failed to open contention report file"),
+        ));
+    }
+    AptosVM::set_hint_based_scheduling_once(opt.hint_based_scheduling);
 
     let config = ProfilerConfig::new_with_defaults();
     let handler = ProfilerHandler::new(config);
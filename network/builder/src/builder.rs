@@ -30,6 +30,7 @@ use aptos_network::{
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
     constants::MAX_MESSAGE_SIZE,
     logging::NetworkSchema,
+    peer::outbound_queue::OutboundPriorities,
     peer_manager::{
         builder::{AuthenticationMode, PeerManagerBuilder},
         ConnectionRequestSender,
@@ -85,6 +86,7 @@ impl NetworkBuilder {
         authentication_mode: AuthenticationMode,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_priorities: OutboundPriorities,
         enable_proxy_protocol: bool,
         network_channel_size: usize,
         max_concurrent_network_reqs: usize,
@@ -104,6 +106,7 @@ impl NetworkBuilder {
             max_concurrent_network_reqs,
             max_frame_size,
             max_message_size,
+            outbound_priorities,
             enable_proxy_protocol,
             inbound_connection_limit,
             tcp_buffer_cfg,
@@ -142,6 +145,7 @@ impl NetworkBuilder {
             authentication_mode,
             MAX_FRAME_SIZE,
             MAX_MESSAGE_SIZE,
+            OutboundPriorities::default(),
             false, /* Disable proxy protocol */
             NETWORK_CHANNEL_SIZE,
             MAX_CONCURRENT_NETWORK_REQS,
@@ -193,6 +197,8 @@ impl NetworkBuilder {
             authentication_mode,
             config.max_frame_size,
             config.max_message_size,
+            OutboundPriorities::from_config(&config.outbound_protocol_priorities)
+                .expect("The outbound protocol priorities are checked by the config sanitizer"),
             config.enable_proxy_protocol,
             config.network_channel_size,
             config.max_concurrent_network_reqs,
@@ -403,6 +409,14 @@ impl NetworkBuilder {
                 Duration::from_secs(rest_discovery.interval_secs),
                 self.time_service.clone(),
             ),
+            DiscoveryMethod::Dns(dns_discovery) => DiscoveryChangeListener::dns(
+                self.network_context,
+                conn_mgr_reqs_tx,
+                dns_discovery.name.clone(),
+                dns_discovery.nameserver,
+                Duration::from_secs(dns_discovery.interval_secs),
+                self.time_service.clone(),
+            ),
             DiscoveryMethod::None => return,
         };
 
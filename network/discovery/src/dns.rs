@@ -0,0 +1,857 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DiscoveryError;
+use aptos_config::{
+    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_logger::{info, warn};
+use aptos_time_service::{Interval, TimeService, TimeServiceTrait};
+use aptos_types::{
+    account_address::{from_identity_public_key, AccountAddress},
+    network_address::{DnsName, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{Future, Stream};
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    ops::Range,
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+
+const RESOLV_CONF_PATH: &str = "/etc/resolv.conf";
+const DNS_PORT: u16 = 53;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Responses to queries without EDNS are limited to 512 bytes, larger ones are truncated
+const MAX_RESPONSE_SIZE: usize = 512;
+/// Upper bound on compression pointers followed while reading a single name
+const MAX_NAME_POINTERS: usize = 16;
+
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+const RCODE_MASK: u16 = 0x000F;
+const RCODE_NAME_ERROR: u16 = 3;
+
+/// A discovery stream that resolves the seed peers from the SRV and TXT records of a DNS
+/// name. Results are cached for the (minimum) TTL of the records they were built from.
+///
+/// The lookups are blocking, so they run on the blocking thread pool and the pending
+/// resolution is polled like any other future, without stalling the stream's executor.
+pub struct DnsStream {
+    network_context: NetworkContext,
+    name: String,
+    nameserver: Option<SocketAddr>,
+    time_service: TimeService,
+    interval: Pin<Box<Interval>>,
+    cached_peers: Option<(PeerSet, Instant)>,
+    pending_resolution: Option<JoinHandle<Result<(PeerSet, Duration), DiscoveryError>>>,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        network_context: NetworkContext,
+        name: String,
+        nameserver: Option<SocketAddr>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        DnsStream {
+            network_context,
+            name,
+            nameserver,
+            interval: Box::pin(time_service.interval(interval_duration)),
+            time_service,
+            cached_peers: None,
+            pending_resolution: None,
+        }
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.pending_resolution.is_none() {
+            // Wait for delay, or add the delay for next call
+            futures::ready!(self.interval.as_mut().poll_next(cx));
+
+            // Only query again once the records have expired
+            if let Some((peers, expiration)) = &self.cached_peers {
+                if self.time_service.now() < *expiration {
+                    return Poll::Ready(Some(Ok(peers.clone())));
+                }
+            }
+
+            let network_context = self.network_context;
+            let name = self.name.clone();
+            let nameserver = self.nameserver;
+            self.pending_resolution = Some(tokio::task::spawn_blocking(move || {
+                resolve_peers(network_context, &name, nameserver)
+            }));
+        }
+
+        let resolution = self
+            .pending_resolution
+            .as_mut()
+            .expect("A resolution must be pending");
+        let result = futures::ready!(Pin::new(resolution).poll(cx));
+        self.pending_resolution = None;
+
+        let result = result
+            .map_err(|err| DiscoveryError::Dns(format!("DNS resolution task failed: {}", err)))
+            .and_then(|result| result);
+        Poll::Ready(match result {
+            Ok((peers, ttl)) => {
+                let expiration = self.time_service.now() + ttl;
+                self.cached_peers = Some((peers.clone(), expiration));
+                Some(Ok(peers))
+            },
+            Err(err) => {
+                info!(
+                    "{} Failed to resolve peers of {} by DNS discovery {:?}",
+                    self.network_context, self.name, err
+                );
+                Some(Err(err))
+            },
+        })
+    }
+}
+
+/// Resolves the peers, returning them with the minimum TTL of all the records used.
+///
+/// Only a failure to look up the SRV records fails the resolution. Targets whose TXT records
+/// can't be looked up or don't describe a valid peer are logged and skipped, so that a single
+/// misconfigured seed doesn't hide all the others.
+fn resolve_peers(
+    network_context: NetworkContext,
+    name: &str,
+    nameserver: Option<SocketAddr>,
+) -> Result<(PeerSet, Duration), DiscoveryError> {
+    let nameserver = match nameserver {
+        Some(nameserver) => nameserver,
+        None => system_nameserver(Path::new(RESOLV_CONF_PATH))?,
+    };
+    let resolver = DnsResolver::new(nameserver, QUERY_TIMEOUT);
+
+    let (mut srv_records, mut ttl) = resolver.lookup_srv(name)?;
+    // Addresses are dialed in order, so keep the preferred targets first
+    srv_records.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| b.weight.cmp(&a.weight))
+    });
+
+    let mut peers = PeerSet::new();
+    for srv_record in srv_records {
+        // A target of "." means the service is explicitly not available
+        if srv_record.target.is_empty() {
+            continue;
+        }
+        let (peer_id, peer) =
+            match resolver
+                .lookup_txt(&srv_record.target)
+                .and_then(|(txt_records, txt_ttl)| {
+                    ttl = ttl.min(txt_ttl);
+                    parse_peer(&srv_record, &txt_records)
+                }) {
+                Ok(peer) => peer,
+                Err(err) => {
+                    warn!(
+                        "{} Skipping seed {} of {} in DNS discovery: {:?}",
+                        network_context, srv_record.target, name, err
+                    );
+                    continue;
+                },
+            };
+
+        if let Some(existing_peer) = peers.get_mut(&peer_id) {
+            existing_peer.addresses.extend(peer.addresses);
+            existing_peer.keys.extend(peer.keys);
+        } else {
+            peers.insert(peer_id, peer);
+        }
+    }
+    Ok((peers, Duration::from_secs(ttl as u64)))
+}
+
+/// Builds the peer behind a SRV record from the TXT records of its target.
+///
+/// Every TXT string is a whitespace separated list of `key=value` pairs, where `noise` is the
+/// hex encoded x25519 public key of the peer (repeated during key rotations), and `peer_id` is
+/// its hex encoded peer id. If the peer id is missing, it's derived from the first key, as for
+/// mutually authenticated networks. Unknown keys are ignored.
+fn parse_peer(
+    srv_record: &SrvRecord,
+    txt_records: &[String],
+) -> Result<(PeerId, Peer), DiscoveryError> {
+    let mut peer_id = None;
+    let mut keys = Vec::new();
+    for (key, value) in txt_records
+        .iter()
+        .flat_map(|txt| txt.split_whitespace())
+        .filter_map(|pair| pair.split_once('='))
+    {
+        match key {
+            "noise" => {
+                let pubkey = x25519::PublicKey::from_encoded_string(value).map_err(|err| {
+                    DiscoveryError::Parsing(format!(
+                        "Invalid noise key {} of {}: {}",
+                        value, srv_record.target, err
+                    ))
+                })?;
+                if !keys.contains(&pubkey) {
+                    keys.push(pubkey);
+                }
+            },
+            "peer_id" => {
+                peer_id = Some(AccountAddress::from_str(value).map_err(|err| {
+                    DiscoveryError::Parsing(format!(
+                        "Invalid peer id {} of {}: {}",
+                        value, srv_record.target, err
+                    ))
+                })?);
+            },
+            _ => (),
+        }
+    }
+
+    let pubkey = *keys.first().ok_or_else(|| {
+        DiscoveryError::Parsing(format!("No noise key found for {}", srv_record.target))
+    })?;
+    let peer_id = peer_id.unwrap_or_else(|| from_identity_public_key(pubkey));
+
+    let dns_name = DnsName::from_str(&srv_record.target)
+        .map_err(|err| DiscoveryError::Parsing(err.to_string()))?;
+    let address = NetworkAddress::from_protocols(vec![
+        Protocol::Dns(dns_name),
+        Protocol::Tcp(srv_record.port),
+    ])
+    .map_err(|err| DiscoveryError::Parsing(err.to_string()))?
+    .append_prod_protos(pubkey, HANDSHAKE_VERSION);
+
+    let keys: HashSet<_> = keys.into_iter().collect();
+    Ok((peer_id, Peer::new(vec![address], keys, PeerRole::Upstream)))
+}
+
+/// Returns the first nameserver of a resolv.conf file
+fn system_nameserver(resolv_conf_path: &Path) -> Result<SocketAddr, DiscoveryError> {
+    let contents = std::fs::read_to_string(resolv_conf_path).map_err(DiscoveryError::IO)?;
+    contents
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|address| IpAddr::from_str(address.trim()).ok())
+        .map(|ip| SocketAddr::new(ip, DNS_PORT))
+        .next()
+        .ok_or_else(|| {
+            DiscoveryError::Dns(format!(
+                "No nameserver found in {}",
+                resolv_conf_path.display()
+            ))
+        })
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SrvRecord {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: String,
+}
+
+/// A minimal stub resolver, sending recursive queries over UDP to a single nameserver
+struct DnsResolver {
+    nameserver: SocketAddr,
+    timeout: Duration,
+}
+
+impl DnsResolver {
+    fn new(nameserver: SocketAddr, timeout: Duration) -> Self {
+        DnsResolver {
+            nameserver,
+            timeout,
+        }
+    }
+
+    fn lookup_srv(&self, name: &str) -> Result<(Vec<SrvRecord>, u32), DiscoveryError> {
+        let response = self.query(name, TYPE_SRV)?;
+        let records = response
+            .answers
+            .iter()
+            .map(|answer| {
+                let rdata = &response.message[answer.rdata.clone()];
+                let target_offset = answer.rdata.start + 6;
+                if rdata.len() < 7 {
+                    return Err(malformed("SRV record too short"));
+                }
+                Ok(SrvRecord {
+                    priority: read_u16(rdata, 0)?,
+                    weight: read_u16(rdata, 2)?,
+                    port: read_u16(rdata, 4)?,
+                    target: read_name(&response.message, target_offset)?.0,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((records, response.ttl()))
+    }
+
+    fn lookup_txt(&self, name: &str) -> Result<(Vec<String>, u32), DiscoveryError> {
+        let response = self.query(name, TYPE_TXT)?;
+        let mut records = Vec::new();
+        for answer in &response.answers {
+            // A TXT record is a sequence of length prefixed strings, that together form its value
+            let rdata = &response.message[answer.rdata.clone()];
+            let mut value = String::new();
+            let mut offset = 0;
+            while offset < rdata.len() {
+                let len = rdata[offset] as usize;
+                let string = rdata
+                    .get(offset + 1..offset + 1 + len)
+                    .ok_or_else(|| malformed("TXT string out of bounds"))?;
+                value.push_str(&String::from_utf8_lossy(string));
+                offset += 1 + len;
+            }
+            records.push(value);
+        }
+        Ok((records, response.ttl()))
+    }
+
+    fn query(&self, name: &str, record_type: u16) -> Result<DnsResponse, DiscoveryError> {
+        let id: u16 = rand::random();
+        let query = encode_query(id, name, record_type)?;
+
+        let bind_address = match self.nameserver {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind((bind_address, 0)).map_err(DiscoveryError::IO)?;
+        socket
+            .set_read_timeout(Some(self.timeout))
+            .map_err(DiscoveryError::IO)?;
+        socket
+            .connect(self.nameserver)
+            .map_err(DiscoveryError::IO)?;
+        socket.send(&query).map_err(DiscoveryError::IO)?;
+
+        // Ignore stray responses (e.g. late responses to previous queries), until the timeout
+        let mut buf = [0u8; MAX_RESPONSE_SIZE];
+        loop {
+            let len = socket.recv(&mut buf).map_err(DiscoveryError::IO)?;
+            if len >= 2 && read_u16(&buf, 0)? == id {
+                return decode_response(buf[..len].to_vec(), name, record_type);
+            }
+        }
+    }
+}
+
+struct DnsAnswer {
+    ttl: u32,
+    rdata: Range<usize>,
+}
+
+struct DnsResponse {
+    message: Vec<u8>,
+    answers: Vec<DnsAnswer>,
+}
+
+impl DnsResponse {
+    /// The minimum TTL of the answers, or 0 if there are none (i.e. don't cache)
+    fn ttl(&self) -> u32 {
+        self.answers
+            .iter()
+            .map(|answer| answer.ttl)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+fn malformed(reason: &str) -> DiscoveryError {
+    DiscoveryError::Dns(format!("Malformed DNS response: {}", reason))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, DiscoveryError> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| malformed("unexpected end of message"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, DiscoveryError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| malformed("unexpected end of message"))
+}
+
+/// Encodes a name as a sequence of length prefixed labels
+fn encode_name(name: &str, buf: &mut Vec<u8>) -> Result<(), DiscoveryError> {
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(DiscoveryError::Dns(format!("Invalid DNS name: {}", name)));
+        }
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    Ok(())
+}
+
+/// Reads a (possibly compressed) name, returning it along with the offset right after it
+fn read_name(message: &[u8], mut offset: usize) -> Result<(String, usize), DiscoveryError> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    let mut pointers = 0;
+    loop {
+        let len = *message
+            .get(offset)
+            .ok_or_else(|| malformed("name out of bounds"))? as usize;
+        if len & 0xC0 == 0xC0 {
+            pointers += 1;
+            if pointers > MAX_NAME_POINTERS {
+                return Err(malformed("too many name pointers"));
+            }
+            let pointer = read_u16(message, offset)? & 0x3FFF;
+            end.get_or_insert(offset + 2);
+            offset = pointer as usize;
+        } else if len == 0 {
+            return Ok((labels.join("."), end.unwrap_or(offset + 1)));
+        } else {
+            let label = message
+                .get(offset + 1..offset + 1 + len)
+                .ok_or_else(|| malformed("label out of bounds"))?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            offset += 1 + len;
+        }
+    }
+}
+
+fn encode_query(id: u16, name: &str, record_type: u16) -> Result<Vec<u8>, DiscoveryError> {
+    let mut query = Vec::with_capacity(MAX_RESPONSE_SIZE);
+    for field in [id, FLAG_RECURSION_DESIRED, 1, 0, 0, 0] {
+        query.extend_from_slice(&field.to_be_bytes());
+    }
+    encode_name(name, &mut query)?;
+    query.extend_from_slice(&record_type.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(query)
+}
+
+fn decode_response(
+    message: Vec<u8>,
+    name: &str,
+    record_type: u16,
+) -> Result<DnsResponse, DiscoveryError> {
+    let flags = read_u16(&message, 2)?;
+    if flags & FLAG_RESPONSE == 0 {
+        return Err(malformed("not a response"));
+    }
+    if flags & FLAG_TRUNCATED != 0 {
+        return Err(DiscoveryError::Dns(format!(
+            "DNS response for {} is truncated",
+            name
+        )));
+    }
+    match flags & RCODE_MASK {
+        0 => (),
+        RCODE_NAME_ERROR => {
+            return Ok(DnsResponse {
+                message,
+                answers: vec![],
+            })
+        },
+        rcode => {
+            return Err(DiscoveryError::Dns(format!(
+                "DNS query for {} failed with rcode {}",
+                name, rcode
+            )))
+        },
+    }
+
+    let num_questions = read_u16(&message, 4)?;
+    let num_answers = read_u16(&message, 6)?;
+    let mut offset = 12;
+    for _ in 0..num_questions {
+        offset = read_name(&message, offset)?.1 + 4;
+    }
+
+    let mut answers = Vec::new();
+    for _ in 0..num_answers {
+        offset = read_name(&message, offset)?.1;
+        let answer_type = read_u16(&message, offset)?;
+        let answer_class = read_u16(&message, offset + 2)?;
+        let ttl = read_u32(&message, offset + 4)?;
+        let rdata_len = read_u16(&message, offset + 8)? as usize;
+        let rdata = offset + 10..offset + 10 + rdata_len;
+        if rdata.end > message.len() {
+            return Err(malformed("record data out of bounds"));
+        }
+        offset = rdata.end;
+
+        // Skip anything else, e.g. CNAME records of the chain leading to the answers
+        if answer_type == record_type && answer_class == CLASS_IN {
+            answers.push(DnsAnswer { ttl, rdata });
+        }
+    }
+    Ok(DnsResponse { message, answers })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::Uniform;
+    use aptos_temppath::TempPath;
+    use futures::StreamExt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+    use tokio::time::timeout;
+
+    /// Records served by the stub resolver, keyed by name and type. Names that aren't in the
+    /// zone don't exist, and names without any records fail with a server failure.
+    type Zone = HashMap<(String, u16), Vec<Vec<u8>>>;
+
+    const RCODE_SERVER_FAILURE: u16 = 2;
+
+    /// Builds the response of the stub resolver to a query, answering from `zone` with the
+    /// given TTL
+    fn stub_response(zone: &Zone, ttl: u32, query: &[u8]) -> Vec<u8> {
+        let (name, end) = read_name(query, 12).unwrap();
+        let record_type = read_u16(query, end).unwrap();
+        let (records, rcode) = match zone.get(&(name, record_type)) {
+            None => (vec![], RCODE_NAME_ERROR),
+            Some(records) if records.is_empty() => (vec![], RCODE_SERVER_FAILURE),
+            Some(records) => (records.clone(), 0),
+        };
+
+        let mut response = query[..2].to_vec();
+        let flags = FLAG_RESPONSE | FLAG_RECURSION_DESIRED | rcode;
+        for field in [flags, 1, records.len() as u16, 0, 0] {
+            response.extend_from_slice(&field.to_be_bytes());
+        }
+        response.extend_from_slice(&query[12..end + 4]);
+        for rdata in records {
+            // Compressed name, pointing to the question
+            response.extend_from_slice(&0xC00Cu16.to_be_bytes());
+            response.extend_from_slice(&record_type.to_be_bytes());
+            response.extend_from_slice(&CLASS_IN.to_be_bytes());
+            response.extend_from_slice(&ttl.to_be_bytes());
+            response.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            response.extend_from_slice(&rdata);
+        }
+        response
+    }
+
+    /// Runs a stub nameserver on localhost, answering from `zone` with the given TTL and
+    /// counting the received queries.
+    fn start_stub_resolver(zone: Zone, ttl: u32) -> (SocketAddr, Arc<AtomicUsize>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        let num_queries = Arc::new(AtomicUsize::new(0));
+        let counter = num_queries.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; MAX_RESPONSE_SIZE];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf) {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = stub_response(&zone, ttl, &buf[..len]);
+                socket.send_to(&response, peer).unwrap();
+            }
+        });
+        (address, num_queries)
+    }
+
+    fn srv_rdata(priority: u16, weight: u16, port: u16, target: &str) -> Vec<u8> {
+        let mut rdata = Vec::new();
+        for field in [priority, weight, port] {
+            rdata.extend_from_slice(&field.to_be_bytes());
+        }
+        encode_name(target, &mut rdata).unwrap();
+        rdata
+    }
+
+    fn txt_rdata(strings: &[&str]) -> Vec<u8> {
+        let mut rdata = Vec::new();
+        for string in strings {
+            rdata.push(string.len() as u8);
+            rdata.extend_from_slice(string.as_bytes());
+        }
+        rdata
+    }
+
+    fn random_pubkey(rng: &mut StdRng) -> x25519::PublicKey {
+        x25519::PrivateKey::generate(rng).public_key()
+    }
+
+    fn encoded(pubkey: &x25519::PublicKey) -> String {
+        pubkey.to_encoded_string().unwrap()
+    }
+
+    fn dns_address(host: &str, port: u16, pubkey: x25519::PublicKey) -> NetworkAddress {
+        NetworkAddress::from_str(&format!("/dns/{}/tcp/{}", host, port))
+            .unwrap()
+            .append_prod_protos(pubkey, HANDSHAKE_VERSION)
+    }
+
+    fn dns_stream(name: &str, nameserver: SocketAddr) -> DnsStream {
+        DnsStream::new(
+            NetworkContext::mock(),
+            name.to_string(),
+            Some(nameserver),
+            Duration::from_millis(5),
+            TimeService::real(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let (key_1, key_2, rotated_key_2) = (
+            random_pubkey(&mut rng),
+            random_pubkey(&mut rng),
+            random_pubkey(&mut rng),
+        );
+        let peer_id_2 = PeerId::random();
+
+        let name = "_aptos._tcp.seeds.example.com";
+        let mut zone = Zone::new();
+        zone.insert((name.to_string(), TYPE_SRV), vec![
+            srv_rdata(20, 0, 6182, "seed-2.example.com"),
+            srv_rdata(10, 0, 6182, "seed-1.example.com"),
+            srv_rdata(10, 0, 6183, "seed-2-backup.example.com"),
+        ]);
+        zone.insert(("seed-1.example.com".to_string(), TYPE_TXT), vec![
+            txt_rdata(&[&format!("noise={}", encoded(&key_1))]),
+        ]);
+        let seed_2_txt = txt_rdata(&[
+            &format!("noise={} ", encoded(&key_2)),
+            &format!("noise={} peer_id={}", encoded(&rotated_key_2), peer_id_2),
+        ]);
+        zone.insert(("seed-2.example.com".to_string(), TYPE_TXT), vec![
+            seed_2_txt.clone(),
+        ]);
+        zone.insert(("seed-2-backup.example.com".to_string(), TYPE_TXT), vec![
+            seed_2_txt,
+            txt_rdata(&["unknown=value"]),
+        ]);
+        let (nameserver, num_queries) = start_stub_resolver(zone, 3600);
+
+        let mut expected_peers = PeerSet::new();
+        expected_peers.insert(
+            from_identity_public_key(key_1),
+            Peer::new(
+                vec![dns_address("seed-1.example.com", 6182, key_1)],
+                [key_1].into_iter().collect(),
+                PeerRole::Upstream,
+            ),
+        );
+        expected_peers.insert(
+            peer_id_2,
+            Peer::new(
+                vec![
+                    dns_address("seed-2-backup.example.com", 6183, key_2),
+                    dns_address("seed-2.example.com", 6182, key_2),
+                ],
+                [key_2, rotated_key_2].into_iter().collect(),
+                PeerRole::Upstream,
+            ),
+        );
+
+        let mut stream = dns_stream(name, nameserver);
+        assert_eq!(stream.next().await.unwrap().unwrap(), expected_peers);
+        assert_eq!(num_queries.load(Ordering::SeqCst), 4);
+
+        // The records haven't expired, so they are not queried again
+        assert_eq!(stream.next().await.unwrap().unwrap(), expected_peers);
+        assert_eq!(num_queries.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_expired_ttl() {
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let key = random_pubkey(&mut rng);
+
+        let name = "_aptos._tcp.seeds.example.com";
+        let mut zone = Zone::new();
+        zone.insert((name.to_string(), TYPE_SRV), vec![srv_rdata(
+            0,
+            0,
+            6182,
+            "seed.example.com",
+        )]);
+        zone.insert(("seed.example.com".to_string(), TYPE_TXT), vec![txt_rdata(
+            &[&format!("noise={}", encoded(&key))],
+        )]);
+        let (nameserver, num_queries) = start_stub_resolver(zone, 0);
+
+        let mut stream = dns_stream(name, nameserver);
+        for i in 1..=3 {
+            let peers = stream.next().await.unwrap().unwrap();
+            assert_eq!(peers.len(), 1);
+            assert!(peers.contains_key(&from_identity_public_key(key)));
+            assert_eq!(num_queries.load(Ordering::SeqCst), 2 * i);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_partial_failures() {
+        let mut rng = StdRng::from_seed([2u8; 32]);
+        let key = random_pubkey(&mut rng);
+
+        let name = "_aptos._tcp.seeds.example.com";
+        let mut zone = Zone::new();
+        zone.insert((name.to_string(), TYPE_SRV), vec![
+            srv_rdata(0, 0, 6182, "invalid-key.example.com"),
+            srv_rdata(0, 0, 6182, "invalid-peer-id.example.com"),
+            srv_rdata(0, 0, 6182, "no-key.example.com"),
+            srv_rdata(0, 0, 6182, "unknown.example.com"),
+            srv_rdata(0, 0, 6182, "failing.example.com"),
+            srv_rdata(10, 0, 6182, "seed.example.com"),
+        ]);
+        zone.insert(("invalid-key.example.com".to_string(), TYPE_TXT), vec![
+            txt_rdata(&["noise=not-a-key"]),
+        ]);
+        zone.insert(("invalid-peer-id.example.com".to_string(), TYPE_TXT), vec![
+            txt_rdata(&[&format!("noise={} peer_id=not-a-peer-id", encoded(&key))]),
+        ]);
+        zone.insert(("no-key.example.com".to_string(), TYPE_TXT), vec![
+            txt_rdata(&["unknown=value"]),
+        ]);
+        zone.insert(("failing.example.com".to_string(), TYPE_TXT), vec![]);
+        zone.insert(("seed.example.com".to_string(), TYPE_TXT), vec![txt_rdata(
+            &[&format!("noise={}", encoded(&key))],
+        )]);
+        let (nameserver, num_queries) = start_stub_resolver(zone, 3600);
+
+        // The broken seeds are skipped, without dropping the valid one
+        let mut expected_peers = PeerSet::new();
+        expected_peers.insert(
+            from_identity_public_key(key),
+            Peer::new(
+                vec![dns_address("seed.example.com", 6182, key)],
+                [key].into_iter().collect(),
+                PeerRole::Upstream,
+            ),
+        );
+        let mut stream = dns_stream(name, nameserver);
+        assert_eq!(stream.next().await.unwrap().unwrap(), expected_peers);
+        assert_eq!(num_queries.load(Ordering::SeqCst), 7);
+
+        // A name that doesn't exist has no peers
+        let mut stream = dns_stream("_aptos._tcp.unknown.example.com", nameserver);
+        assert_eq!(stream.next().await.unwrap().unwrap(), PeerSet::new());
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_srv_failure() {
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let key = random_pubkey(&mut rng);
+
+        let name = "_aptos._tcp.seeds.example.com";
+        let mut zone = Zone::new();
+        zone.insert((name.to_string(), TYPE_SRV), vec![]);
+        let (nameserver, num_queries) = start_stub_resolver(zone, 3600);
+
+        // Failures are reported and not cached, so the next poll queries again
+        let mut stream = dns_stream(name, nameserver);
+        for i in 1..=2 {
+            assert!(matches!(
+                stream.next().await.unwrap(),
+                Err(DiscoveryError::Dns(_))
+            ));
+            assert_eq!(num_queries.load(Ordering::SeqCst), i);
+        }
+
+        // The stream keeps working once the records can be resolved
+        let mut zone = Zone::new();
+        zone.insert((name.to_string(), TYPE_SRV), vec![srv_rdata(
+            0,
+            0,
+            6182,
+            "seed.example.com",
+        )]);
+        zone.insert(("seed.example.com".to_string(), TYPE_TXT), vec![txt_rdata(
+            &[&format!("noise={}", encoded(&key))],
+        )]);
+        let (nameserver, _) = start_stub_resolver(zone, 3600);
+        stream.nameserver = Some(nameserver);
+        let peers = stream.next().await.unwrap().unwrap();
+        assert!(peers.contains_key(&from_identity_public_key(key)));
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_does_not_block() {
+        let mut rng = StdRng::from_seed([4u8; 32]);
+        let key = random_pubkey(&mut rng);
+
+        let name = "_aptos._tcp.seeds.example.com";
+        let mut zone = Zone::new();
+        zone.insert((name.to_string(), TYPE_SRV), vec![srv_rdata(
+            0,
+            0,
+            6182,
+            "seed.example.com",
+        )]);
+        zone.insert(("seed.example.com".to_string(), TYPE_TXT), vec![txt_rdata(
+            &[&format!("noise={}", encoded(&key))],
+        )]);
+
+        // A nameserver that only answers when told to
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut stream = dns_stream(name, socket.local_addr().unwrap());
+
+        // While the queries are outstanding, the stream is pending instead of blocking
+        let mut buf = [0u8; MAX_RESPONSE_SIZE];
+        socket
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        assert!(timeout(Duration::from_millis(100), stream.next())
+            .await
+            .is_err());
+        for _ in 0..2 {
+            let (len, peer) = socket.recv_from(&mut buf).unwrap();
+            socket
+                .send_to(&stub_response(&zone, 3600, &buf[..len]), peer)
+                .unwrap();
+        }
+        let peers = stream.next().await.unwrap().unwrap();
+        assert!(peers.contains_key(&from_identity_public_key(key)));
+    }
+
+    #[test]
+    fn test_system_nameserver() {
+        let path = TempPath::new();
+        path.create_as_file().unwrap();
+        std::fs::write(
+            path.path(),
+            "# comment\nsearch example.com\nnameserver fe80::1%eth0\nnameserver 10.0.0.2\n",
+        )
+        .unwrap();
+        assert_eq!(
+            system_nameserver(path.path()).unwrap(),
+            SocketAddr::from_str("10.0.0.2:53").unwrap()
+        );
+
+        std::fs::write(path.path(), "search example.com\n").unwrap();
+        assert!(matches!(
+            system_nameserver(path.path()),
+            Err(DiscoveryError::Dns(_))
+        ));
+    }
+}
@@ -284,6 +284,27 @@ pub fn inbound_rpc_handler_latency(
     ])
 }
 
+pub static APTOS_NETWORK_OUTBOUND_QUEUEING_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_network_outbound_queueing_delay_seconds",
+        "Time outbound messages wait in the peer's outbound queue before being written, in seconds",
+        &["role_type", "network_id", "peer_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_queueing_delay(
+    network_context: &NetworkContext,
+    protocol_id: Option<ProtocolId>,
+) -> Histogram {
+    APTOS_NETWORK_OUTBOUND_QUEUEING_DELAY.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.map_or("error", |protocol_id| protocol_id.as_str()),
+    ])
+}
+
 pub static APTOS_NETWORK_DIRECT_SEND_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_network_direct_send_messages",
@@ -417,15 +438,6 @@ pub static PENDING_WIRE_MESSAGES: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
-/// Counter of messages pending in queue to be sent out on the multiplex channel
-pub static PENDING_MULTIPLEX_MESSAGE: Lazy<IntGauge> = Lazy::new(|| {
-    register_int_gauge!(
-        "aptos_network_pending_multiplex_messages",
-        "Number of pending multiplex messages"
-    )
-    .unwrap()
-});
-
 /// Counter of stream messages pending in queue to be sent out on the multiplex channel
 pub static PENDING_MULTIPLEX_STREAM: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -4,7 +4,7 @@
 
 use crate::{
     constants,
-    peer::Peer,
+    peer::{outbound_queue::OutboundPriorities, Peer},
     protocols::wire::{
         handshake::v1::{MessagingProtocolVersion, ProtocolIdSet},
         messaging::v1::{MultiplexMessage, MultiplexMessageSink},
@@ -108,6 +108,7 @@ pub fn fuzz(data: &[u8]) {
         constants::MAX_CONCURRENT_OUTBOUND_RPCS,
         constants::MAX_FRAME_SIZE,
         constants::MAX_MESSAGE_SIZE,
+        OutboundPriorities::default(),
     );
     executor.spawn(peer.start());
 
@@ -21,11 +21,12 @@ use crate::{
         FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
+    peer::outbound_queue::{OutboundMessage, OutboundPriorities, OutboundQueueSender},
     peer_manager::{PeerManagerError, TransportNotification},
     protocols::{
         direct_send::Message,
         rpc::{InboundRpcRequest, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
-        stream::{InboundStreamBuffer, OutboundStream, StreamMessage},
+        stream::{InboundStreamBuffer, StreamMessage},
         wire::messaging::v1::{
             DirectSendMsg, ErrorCode, MultiplexMessage, MultiplexMessageSink,
             MultiplexMessageStream, NetworkMessage, Priority, ReadError, WriteError,
@@ -48,7 +49,6 @@ use futures::{
     stream::StreamExt,
     SinkExt,
 };
-use futures_util::stream::select;
 use serde::Serialize;
 use std::{fmt, panic, time::Duration};
 use tokio::runtime::Handle;
@@ -56,6 +56,7 @@ use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+pub mod outbound_queue;
 #[cfg(test)]
 mod test;
 
@@ -138,6 +139,8 @@ pub struct Peer<TSocket> {
     max_message_size: usize,
     /// Inbound stream buffer
     inbound_stream: InboundStreamBuffer,
+    /// The priorities used to schedule outbound messages
+    outbound_priorities: OutboundPriorities,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -158,6 +161,7 @@ where
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_priorities: OutboundPriorities,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -191,6 +195,7 @@ where
             max_frame_size,
             max_message_size,
             inbound_stream: InboundStreamBuffer::new(max_fragments),
+            outbound_priorities,
         }
     }
 
@@ -228,6 +233,7 @@ where
             writer,
             self.max_frame_size,
             self.max_message_size,
+            &self.outbound_priorities,
         );
 
         // Start main Peer event loop.
@@ -294,10 +300,12 @@ where
     // Start a new task on the given executor which is responsible for writing outbound messages on
     // the wire. The function returns two channels which can be used to send instructions to the
     // task:
-    // 1. The first channel is used to send outbound NetworkMessages to the task
+    // 1. The first channel is used to queue outbound NetworkMessages for the task. Messages are
+    //    queued per protocol, and written in the order of their protocols' priorities.
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
+    #[allow(clippy::too_many_arguments)]
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
@@ -306,30 +314,37 @@ where
         mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
         max_frame_size: usize,
         max_message_size: usize,
-    ) -> (aptos_channels::Sender<NetworkMessage>, oneshot::Sender<()>) {
+        outbound_priorities: &OutboundPriorities,
+    ) -> (OutboundQueueSender, oneshot::Sender<()>) {
         let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (aptos_channels::Sender<NetworkMessage>, _) =
-            aptos_channels::new(1024, &counters::PENDING_WIRE_MESSAGES);
+        let (write_reqs_tx, mut write_reqs_rx) = outbound_queue::new(
+            outbound_priorities,
+            network_context,
+            max_frame_size,
+            max_message_size,
+            time_service.clone(),
+        );
         let (close_tx, mut close_rx) = oneshot::channel();
 
-        let (mut msg_tx, msg_rx) = aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_MESSAGE);
-        let (stream_msg_tx, stream_msg_rx) =
-            aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_STREAM);
-
-        // this task ends when the multiplex task ends (by dropping the senders)
         let writer_task = async move {
-            let mut stream = select(msg_rx, stream_msg_rx);
             let log_context =
                 NetworkSchema::new(&network_context).connection_metadata(&connection_metadata);
-            while let Some(message) = stream.next().await {
-                if let Err(err) = writer.send(&message).await {
-                    warn!(
-                        log_context,
-                        error = %err,
-                        "{} Error in sending message to peer: {}",
-                        network_context,
-                        remote_peer_id.short_str(),
-                    );
+            loop {
+                futures::select! {
+                    message = write_reqs_rx.select_next_some() => {
+                        if let Err(err) = writer.send(&message).await {
+                            warn!(
+                                log_context,
+                                error = %err,
+                                "{} Error in sending message to peer: {}",
+                                network_context,
+                                remote_peer_id.short_str(),
+                            );
+                        }
+                    },
+                    _ = close_rx => {
+                        break;
+                    }
                 }
             }
             info!(
@@ -375,35 +390,7 @@ where
                 },
             }
         };
-        let multiplex_task = async move {
-            let mut outbound_stream =
-                OutboundStream::new(max_frame_size, max_message_size, stream_msg_tx);
-            loop {
-                futures::select! {
-                    message = write_reqs_rx.select_next_some() => {
-                        // either channel full would block the other one
-                        let result = if outbound_stream.should_stream(&message) {
-                            outbound_stream.stream_message(message).await
-                        } else {
-                            msg_tx.send(MultiplexMessage::Message(message)).await.map_err(|_| anyhow::anyhow!("Writer task ended"))
-                        };
-                        if let Err(err) = result {
-                            warn!(
-                                error = %err,
-                                "{} Error in sending message to peer: {}",
-                                network_context,
-                                remote_peer_id.short_str(),
-                            );
-                        }
-                    },
-                    _ = close_rx => {
-                        break;
-                    }
-                }
-            }
-        };
         executor.spawn(writer_task);
-        executor.spawn(multiplex_task);
         (write_reqs_tx, close_tx)
     }
 
@@ -466,7 +453,7 @@ where
     async fn handle_inbound_message(
         &mut self,
         message: Result<MultiplexMessage, ReadError>,
-        write_reqs_tx: &mut aptos_channels::Sender<NetworkMessage>,
+        write_reqs_tx: &mut OutboundQueueSender,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -488,7 +475,7 @@ where
                     let error_code = ErrorCode::parsing_error(*message_type, *protocol_id);
                     let message = NetworkMessage::Error(error_code);
 
-                    write_reqs_tx.send(message).await?;
+                    write_reqs_tx.push(OutboundMessage::error(message))?;
                     return Err(err.into());
                 },
                 ReadError::IoError(_) => {
@@ -554,7 +541,7 @@ where
     async fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut aptos_channels::Sender<NetworkMessage>,
+        write_reqs_tx: &mut OutboundQueueSender,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -574,7 +561,7 @@ where
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
 
-                match write_reqs_tx.send(message).await {
+                match write_reqs_tx.push(OutboundMessage::new(protocol_id, message)) {
                     Ok(_) => {
                         self.update_outbound_direct_send_metrics(protocol_id, message_len as u64);
                     },
@@ -0,0 +1,710 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! All protocols share a single connection to a remote peer, so a large backlog of messages
+//! for one protocol (e.g., state sync responses) can delay the messages of other protocols
+//! (e.g., consensus votes). To avoid this, outbound messages are queued per protocol as soon as
+//! they are sent, and the writer takes them according to the priorities of their protocols:
+//! - Higher priority levels are served first, and the protocols of a level are served round
+//!   robin.
+//! - A level that has been passed over for [`MAX_CONSECUTIVE_SKIPS`] messages in a row is
+//!   served next, so lower priorities can't be starved.
+//! - Queueing never waits. If the queue of a protocol is full, new messages of that protocol
+//!   are rejected, without affecting the other protocols.
+//! - Messages larger than a frame are streamed, and their fragments are scheduled like any
+//!   other message of their protocol. The remote peer only reassembles one stream at a time, so
+//!   a message that has to be streamed while another stream is in progress lends its turns to
+//!   the fragments of that stream until it completes.
+
+use crate::{
+    counters,
+    protocols::{
+        stream::{OutboundStream, StreamMessage},
+        wire::{
+            handshake::v1::ProtocolId,
+            messaging::v1::{MultiplexMessage, NetworkMessage},
+        },
+    },
+};
+use anyhow::{anyhow, ensure};
+use aptos_config::network_id::NetworkContext;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use futures::stream::{FusedStream, Stream};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+/// The maximum number of messages queued per protocol
+pub const MAX_QUEUED_MESSAGES_PER_PROTOCOL: usize = 1024;
+/// The number of messages of other levels after which a waiting level is served
+pub const MAX_CONSECUTIVE_SKIPS: usize = 16;
+
+/// A message to write to the remote peer, along with the protocol it belongs to, if any
+/// (error messages don't belong to a protocol).
+#[derive(Debug)]
+pub struct OutboundMessage {
+    pub protocol_id: Option<ProtocolId>,
+    pub message: NetworkMessage,
+}
+
+impl OutboundMessage {
+    pub fn new(protocol_id: ProtocolId, message: NetworkMessage) -> Self {
+        Self {
+            protocol_id: Some(protocol_id),
+            message,
+        }
+    }
+
+    pub fn error(message: NetworkMessage) -> Self {
+        Self {
+            protocol_id: None,
+            message,
+        }
+    }
+}
+
+/// The priority of the outbound messages of each protocol, where higher priorities are written
+/// first. Protocols without a priority (and error messages) have the default priority, 0.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OutboundPriorities(HashMap<ProtocolId, u8>);
+
+impl OutboundPriorities {
+    pub fn new(priorities: HashMap<ProtocolId, u8>) -> Self {
+        Self(priorities)
+    }
+
+    /// Parses the priorities from the config, where protocols are identified by name (see
+    /// [`ProtocolId::as_str`]).
+    pub fn from_config(priorities: &HashMap<String, u8>) -> anyhow::Result<Self> {
+        priorities
+            .iter()
+            .map(|(name, priority)| {
+                ProtocolId::all()
+                    .iter()
+                    .find(|protocol_id| protocol_id.as_str() == name)
+                    .map(|protocol_id| (*protocol_id, *priority))
+                    .ok_or_else(|| anyhow!("Unknown protocol in outbound priorities: {}", name))
+            })
+            .collect::<anyhow::Result<_>>()
+            .map(Self)
+    }
+
+    pub fn priority(&self, protocol_id: Option<ProtocolId>) -> u8 {
+        protocol_id
+            .and_then(|protocol_id| self.0.get(&protocol_id).copied())
+            .unwrap_or_default()
+    }
+
+    /// All the distinct priorities, from highest to lowest
+    fn levels(&self) -> Vec<u8> {
+        let mut levels: BTreeSet<u8> = self.0.values().copied().collect();
+        levels.insert(u8::default());
+        levels.into_iter().rev().collect()
+    }
+}
+
+/// A message waiting to be written, with the time it was queued at.
+struct QueuedMessage {
+    queued_at: Instant,
+    message: NetworkMessage,
+}
+
+/// The messages waiting to be written for a protocol (or the error messages)
+struct ProtocolQueue {
+    protocol_id: Option<ProtocolId>,
+    /// The remaining fragments of the message being streamed, sent before any other message
+    fragments: VecDeque<StreamMessage>,
+    messages: VecDeque<QueuedMessage>,
+}
+
+impl ProtocolQueue {
+    fn is_empty(&self) -> bool {
+        self.fragments.is_empty() && self.messages.is_empty()
+    }
+}
+
+/// The queues of all the protocols with the same priority
+struct PriorityLevel {
+    priority: u8,
+    queues: Vec<ProtocolQueue>,
+    /// The index of the queue to serve next, for round robin
+    next_queue: usize,
+    /// The number of messages taken from other levels since this level was last served
+    num_skips: usize,
+}
+
+impl PriorityLevel {
+    fn is_waiting(&self) -> bool {
+        self.queues.iter().any(|queue| !queue.is_empty())
+    }
+}
+
+struct SharedState {
+    /// The priority levels, from highest to lowest priority
+    levels: Vec<PriorityLevel>,
+    /// The level and queue index of the message being streamed, if any
+    active_stream: Option<(usize, usize)>,
+    outbound_stream: OutboundStream,
+    network_context: NetworkContext,
+    time_service: TimeService,
+    waker: Option<Waker>,
+    sender_dropped: bool,
+    receiver_dropped: bool,
+}
+
+impl SharedState {
+    fn queue_mut(&mut self, priority: u8, protocol_id: Option<ProtocolId>) -> &mut ProtocolQueue {
+        let level = self
+            .levels
+            .iter_mut()
+            .find(|level| level.priority == priority)
+            .expect("Every priority must have a level");
+        let index = match level
+            .queues
+            .iter()
+            .position(|queue| queue.protocol_id == protocol_id)
+        {
+            Some(index) => index,
+            None => {
+                level.queues.push(ProtocolQueue {
+                    protocol_id,
+                    fragments: VecDeque::new(),
+                    messages: VecDeque::new(),
+                });
+                level.queues.len() - 1
+            },
+        };
+        &mut level.queues[index]
+    }
+
+    /// Returns the level to serve next: the highest priority level that has been passed over
+    /// too many times, otherwise the highest priority waiting level.
+    fn next_level(&self) -> Option<usize> {
+        let mut waiting_levels = self
+            .levels
+            .iter()
+            .enumerate()
+            .filter(|(_, level)| level.is_waiting());
+        let (highest_level, _) = waiting_levels.clone().next()?;
+        Some(
+            waiting_levels
+                .find(|(_, level)| level.num_skips >= MAX_CONSECUTIVE_SKIPS)
+                .map_or(highest_level, |(index, _)| index),
+        )
+    }
+
+    fn next_message(&mut self) -> Option<MultiplexMessage> {
+        let level_index = self.next_level()?;
+        for (index, level) in self.levels.iter_mut().enumerate() {
+            if index == level_index {
+                level.num_skips = 0;
+            } else if level.is_waiting() {
+                level.num_skips += 1;
+            }
+        }
+
+        // Serve the queues of the level round robin
+        let level = &mut self.levels[level_index];
+        let num_queues = level.queues.len();
+        let queue_index = (0..num_queues)
+            .map(|offset| (level.next_queue + offset) % num_queues)
+            .find(|index| !level.queues[*index].is_empty())
+            .expect("A waiting level must have a non-empty queue");
+        level.next_queue = (queue_index + 1) % num_queues;
+        self.pop_message(level_index, queue_index)
+    }
+
+    fn pop_message(&mut self, level_index: usize, queue_index: usize) -> Option<MultiplexMessage> {
+        let queue = &mut self.levels[level_index].queues[queue_index];
+        if let Some(fragment) = queue.fragments.pop_front() {
+            counters::PENDING_MULTIPLEX_STREAM.dec();
+            if queue.fragments.is_empty() {
+                self.active_stream = None;
+            }
+            return Some(MultiplexMessage::Stream(fragment));
+        }
+
+        let should_stream = self
+            .outbound_stream
+            .should_stream(&queue.messages.front()?.message);
+        if should_stream {
+            if let Some((stream_level_index, stream_queue_index)) = self.active_stream {
+                // Only one stream can be in progress, so let it use this turn
+                return self.pop_message(stream_level_index, stream_queue_index);
+            }
+        }
+
+        let queue = &mut self.levels[level_index].queues[queue_index];
+        let QueuedMessage { queued_at, message } = queue.messages.pop_front()?;
+        counters::PENDING_WIRE_MESSAGES.dec();
+        counters::outbound_queueing_delay(&self.network_context, queue.protocol_id).observe(
+            self.time_service
+                .now()
+                .saturating_duration_since(queued_at)
+                .as_secs_f64(),
+        );
+        if !should_stream {
+            return Some(MultiplexMessage::Message(message));
+        }
+
+        match self.outbound_stream.stream_message(message) {
+            Ok(stream_messages) => {
+                let mut stream_messages = stream_messages.into_iter();
+                let header = stream_messages.next();
+                queue.fragments.extend(stream_messages);
+                counters::PENDING_MULTIPLEX_STREAM.add(queue.fragments.len() as i64);
+                if !queue.fragments.is_empty() {
+                    self.active_stream = Some((level_index, queue_index));
+                }
+                header.map(MultiplexMessage::Stream)
+            },
+            Err(err) => {
+                warn!(
+                    error = %err,
+                    "{} Failed to stream outbound message: {}",
+                    self.network_context,
+                    err
+                );
+                self.next_message()
+            },
+        }
+    }
+
+    /// Drops all the queued messages
+    fn clear(&mut self) {
+        for queue in self
+            .levels
+            .iter_mut()
+            .flat_map(|level| level.queues.iter_mut())
+        {
+            counters::PENDING_WIRE_MESSAGES.sub(queue.messages.len() as i64);
+            counters::PENDING_MULTIPLEX_STREAM.sub(queue.fragments.len() as i64);
+            queue.messages.clear();
+            queue.fragments.clear();
+        }
+        self.active_stream = None;
+    }
+}
+
+/// Creates the outbound queue of a connection, whose receiver returns the messages to write
+/// in the order they should be written (splitting messages larger than a frame into streams).
+pub(crate) fn new(
+    priorities: &OutboundPriorities,
+    network_context: NetworkContext,
+    max_frame_size: usize,
+    max_message_size: usize,
+    time_service: TimeService,
+) -> (OutboundQueueSender, OutboundQueueReceiver) {
+    let levels = priorities
+        .levels()
+        .into_iter()
+        .map(|priority| PriorityLevel {
+            priority,
+            queues: vec![],
+            next_queue: 0,
+            num_skips: 0,
+        })
+        .collect();
+    let shared_state = Arc::new(Mutex::new(SharedState {
+        levels,
+        active_stream: None,
+        outbound_stream: OutboundStream::new(max_frame_size, max_message_size),
+        network_context,
+        time_service,
+        waker: None,
+        sender_dropped: false,
+        receiver_dropped: false,
+    }));
+    (
+        OutboundQueueSender {
+            priorities: priorities.clone(),
+            max_message_size,
+            shared_state: shared_state.clone(),
+        },
+        OutboundQueueReceiver {
+            shared_state,
+            terminated: false,
+        },
+    )
+}
+
+pub struct OutboundQueueSender {
+    priorities: OutboundPriorities,
+    max_message_size: usize,
+    shared_state: Arc<Mutex<SharedState>>,
+}
+
+impl OutboundQueueSender {
+    /// Queues the message to be written, without waiting. Fails if the message is too large,
+    /// the queue of its protocol is full, or the connection is closing.
+    pub fn push(&self, outbound_message: OutboundMessage) -> anyhow::Result<()> {
+        let OutboundMessage {
+            protocol_id,
+            message,
+        } = outbound_message;
+        ensure!(
+            message.data_len() <= self.max_message_size,
+            "Message length {} exceeds size limit {}",
+            message.data_len(),
+            self.max_message_size
+        );
+
+        let priority = self.priorities.priority(protocol_id);
+        let mut shared_state = self.shared_state.lock();
+        ensure!(!shared_state.receiver_dropped, "Writer task ended");
+        let queued_at = shared_state.time_service.now();
+        let queue = shared_state.queue_mut(priority, protocol_id);
+        ensure!(
+            queue.messages.len() < MAX_QUEUED_MESSAGES_PER_PROTOCOL,
+            "Outbound queue of {} is full",
+            protocol_id.map_or("error messages", |protocol_id| protocol_id.as_str())
+        );
+        queue
+            .messages
+            .push_back(QueuedMessage { queued_at, message });
+        counters::PENDING_WIRE_MESSAGES.inc();
+
+        if let Some(waker) = shared_state.waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OutboundQueueSender {
+    fn drop(&mut self) {
+        let mut shared_state = self.shared_state.lock();
+        shared_state.sender_dropped = true;
+        if let Some(waker) = shared_state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Returns the messages to write, until the sender is dropped and all queued messages were
+/// returned. Messages still queued when the receiver is dropped are discarded.
+pub struct OutboundQueueReceiver {
+    shared_state: Arc<Mutex<SharedState>>,
+    terminated: bool,
+}
+
+impl Stream for OutboundQueueReceiver {
+    type Item = MultiplexMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared_state = self.shared_state.lock();
+        if let Some(message) = shared_state.next_message() {
+            Poll::Ready(Some(message))
+        } else if shared_state.sender_dropped {
+            drop(shared_state);
+            self.terminated = true;
+            Poll::Ready(None)
+        } else {
+            shared_state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl FusedStream for OutboundQueueReceiver {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+impl Drop for OutboundQueueReceiver {
+    fn drop(&mut self) {
+        let mut shared_state = self.shared_state.lock();
+        shared_state.receiver_dropped = true;
+        shared_state.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::DirectSendMsg;
+    use aptos_config::config::OUTBOUND_PRIORITY_PROTOCOLS;
+    use futures::{executor::block_on, FutureExt, StreamExt};
+
+    const MAX_FRAME_SIZE: usize = 128;
+    const MAX_MESSAGE_SIZE: usize = 64 * 255;
+
+    fn direct_send(protocol_id: ProtocolId, data: u8) -> NetworkMessage {
+        direct_send_with_len(protocol_id, data, 1)
+    }
+
+    fn direct_send_with_len(protocol_id: ProtocolId, data: u8, len: usize) -> NetworkMessage {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg: vec![data; len],
+        })
+    }
+
+    fn new_queue(priorities: &[(ProtocolId, u8)]) -> (OutboundQueueSender, OutboundQueueReceiver) {
+        new(
+            &OutboundPriorities::new(priorities.iter().copied().collect()),
+            NetworkContext::mock(),
+            MAX_FRAME_SIZE,
+            MAX_MESSAGE_SIZE,
+            TimeService::mock(),
+        )
+    }
+
+    fn push(sender: &OutboundQueueSender, protocol_id: ProtocolId, message: NetworkMessage) {
+        sender
+            .push(OutboundMessage::new(protocol_id, message))
+            .unwrap();
+    }
+
+    /// Returns the next message, which must already be queued
+    fn next_message(receiver: &mut OutboundQueueReceiver) -> MultiplexMessage {
+        receiver.next().now_or_never().unwrap().unwrap()
+    }
+
+    /// Returns the data of the next message, which must be a direct send message
+    fn next_data(receiver: &mut OutboundQueueReceiver) -> u8 {
+        match next_message(receiver) {
+            MultiplexMessage::Message(NetworkMessage::DirectSendMsg(message)) => message.raw_msg[0],
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+
+    /// Returns the request and fragment IDs of the next message, which must be a stream message
+    /// (where headers have the fragment ID 0)
+    fn next_stream_message(receiver: &mut OutboundQueueReceiver) -> (u32, u8) {
+        match next_message(receiver) {
+            MultiplexMessage::Stream(StreamMessage::Header(header)) => (header.request_id, 0),
+            MultiplexMessage::Stream(StreamMessage::Fragment(fragment)) => {
+                (fragment.request_id, fragment.fragment_id)
+            },
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = [
+            ("ConsensusRpcBcs".to_string(), 10),
+            ("StorageServiceRpc".to_string(), 0),
+        ]
+        .into_iter()
+        .collect();
+        let priorities = OutboundPriorities::from_config(&config).unwrap();
+        assert_eq!(priorities.priority(Some(ProtocolId::ConsensusRpcBcs)), 10);
+        assert_eq!(priorities.priority(Some(ProtocolId::StorageServiceRpc)), 0);
+        assert_eq!(priorities.priority(Some(ProtocolId::MempoolDirectSend)), 0);
+        assert_eq!(priorities.priority(None), 0);
+        assert_eq!(priorities.levels(), vec![10, 0]);
+
+        let config = [("Consensus".to_string(), 10)].into_iter().collect();
+        OutboundPriorities::from_config(&config).unwrap_err();
+    }
+
+    #[test]
+    fn test_config_protocol_names() {
+        // The config sanitizer accepts exactly the protocols known to the network
+        let names: Vec<_> = ProtocolId::all()
+            .iter()
+            .map(|protocol_id| protocol_id.as_str())
+            .collect();
+        assert_eq!(names, OUTBOUND_PRIORITY_PROTOCOLS);
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let (sender, receiver) = new_queue(&[
+            (ProtocolId::ConsensusDirectSendBcs, 2),
+            (ProtocolId::MempoolDirectSend, 1),
+        ]);
+        let messages = [
+            (ProtocolId::StorageServiceRpc, 0),
+            (ProtocolId::MempoolDirectSend, 1),
+            (ProtocolId::StorageServiceRpc, 2),
+            (ProtocolId::ConsensusDirectSendBcs, 3),
+            (ProtocolId::MempoolDirectSend, 4),
+            (ProtocolId::ConsensusDirectSendBcs, 5),
+        ];
+        for (protocol_id, data) in messages {
+            push(&sender, protocol_id, direct_send(protocol_id, data));
+        }
+        drop(sender);
+
+        // Higher priority protocols first, FIFO within the same protocol
+        let received: Vec<_> = block_on(receiver.collect());
+        let expected: Vec<_> = [3, 5, 1, 4, 0, 2]
+            .into_iter()
+            .map(|i| MultiplexMessage::Message(direct_send(messages[i].0, messages[i].1)))
+            .collect();
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn test_round_robin_within_level() {
+        let (sender, mut receiver) = new_queue(&[]);
+        for data in 0..3 {
+            push(
+                &sender,
+                ProtocolId::StorageServiceRpc,
+                direct_send(ProtocolId::StorageServiceRpc, data),
+            );
+        }
+        for data in 10..12 {
+            push(
+                &sender,
+                ProtocolId::MempoolDirectSend,
+                direct_send(ProtocolId::MempoolDirectSend, data),
+            );
+        }
+        let received: Vec<_> = (0..5).map(|_| next_data(&mut receiver)).collect();
+        assert_eq!(received, vec![0, 10, 1, 11, 2]);
+    }
+
+    #[test]
+    fn test_full_queue_does_not_delay_consensus() {
+        for consensus_priority in [0, 1] {
+            let (sender, mut receiver) =
+                new_queue(&[(ProtocolId::ConsensusDirectSendBcs, consensus_priority)]);
+
+            // Fill the state sync queue, after which its messages are rejected without waiting
+            for _ in 0..MAX_QUEUED_MESSAGES_PER_PROTOCOL {
+                push(
+                    &sender,
+                    ProtocolId::StorageServiceRpc,
+                    direct_send(ProtocolId::StorageServiceRpc, 0),
+                );
+            }
+            sender
+                .push(OutboundMessage::new(
+                    ProtocolId::StorageServiceRpc,
+                    direct_send(ProtocolId::StorageServiceRpc, 0),
+                ))
+                .unwrap_err();
+
+            // Consensus messages are still queued, and sent next (or right after the message of
+            // the full queue that is first in line, if consensus has the same priority)
+            push(
+                &sender,
+                ProtocolId::ConsensusDirectSendBcs,
+                direct_send(ProtocolId::ConsensusDirectSendBcs, 1),
+            );
+            let num_before_consensus = (0..2).position(|_| next_data(&mut receiver) == 1).unwrap();
+            assert_eq!(num_before_consensus, (1 - consensus_priority) as usize);
+        }
+    }
+
+    #[test]
+    fn test_aging() {
+        let (sender, mut receiver) = new_queue(&[(ProtocolId::ConsensusDirectSendBcs, 1)]);
+        for _ in 0..2 * MAX_CONSECUTIVE_SKIPS + 10 {
+            push(
+                &sender,
+                ProtocolId::ConsensusDirectSendBcs,
+                direct_send(ProtocolId::ConsensusDirectSendBcs, 1),
+            );
+        }
+        for _ in 0..2 {
+            push(
+                &sender,
+                ProtocolId::StorageServiceRpc,
+                direct_send(ProtocolId::StorageServiceRpc, 0),
+            );
+        }
+
+        // The lower priority level is served after being passed over the maximum number of times
+        let received: Vec<_> = (0..2 * MAX_CONSECUTIVE_SKIPS + 12)
+            .map(|_| next_data(&mut receiver))
+            .collect();
+        let low_priority_positions: Vec<_> = received
+            .iter()
+            .enumerate()
+            .filter(|(_, data)| **data == 0)
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(low_priority_positions, vec![
+            MAX_CONSECUTIVE_SKIPS,
+            2 * MAX_CONSECUTIVE_SKIPS + 1
+        ]);
+    }
+
+    #[test]
+    fn test_streams_are_scheduled() {
+        let (sender, mut receiver) = new_queue(&[(ProtocolId::ConsensusDirectSendBcs, 1)]);
+
+        // A large state sync message is streamed as a header and 2 fragments
+        push(
+            &sender,
+            ProtocolId::StorageServiceRpc,
+            direct_send_with_len(ProtocolId::StorageServiceRpc, 0, 3 * 64),
+        );
+        let (state_sync_request_id, fragment_id) = next_stream_message(&mut receiver);
+        assert_eq!(fragment_id, 0);
+
+        // Small consensus messages are sent between the fragments
+        push(
+            &sender,
+            ProtocolId::ConsensusDirectSendBcs,
+            direct_send(ProtocolId::ConsensusDirectSendBcs, 1),
+        );
+        assert_eq!(next_data(&mut receiver), 1);
+
+        // A large consensus message waits for the stream in progress, which it lets use its
+        // turns. The state sync message queued after the stream waits for it as well.
+        push(
+            &sender,
+            ProtocolId::StorageServiceRpc,
+            direct_send(ProtocolId::StorageServiceRpc, 2),
+        );
+        push(
+            &sender,
+            ProtocolId::ConsensusDirectSendBcs,
+            direct_send_with_len(ProtocolId::ConsensusDirectSendBcs, 3, 2 * 64),
+        );
+        assert_eq!(
+            next_stream_message(&mut receiver),
+            (state_sync_request_id, 1)
+        );
+        assert_eq!(
+            next_stream_message(&mut receiver),
+            (state_sync_request_id, 2)
+        );
+        let (consensus_request_id, fragment_id) = next_stream_message(&mut receiver);
+        assert_ne!(consensus_request_id, state_sync_request_id);
+        assert_eq!(fragment_id, 0);
+        assert_eq!(
+            next_stream_message(&mut receiver),
+            (consensus_request_id, 1)
+        );
+        assert_eq!(next_data(&mut receiver), 2);
+        assert!(receiver.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn test_rejected_messages() {
+        let (sender, receiver) = new_queue(&[]);
+
+        // Messages over the size limit are rejected
+        sender
+            .push(OutboundMessage::new(
+                ProtocolId::StorageServiceRpc,
+                direct_send_with_len(ProtocolId::StorageServiceRpc, 0, MAX_MESSAGE_SIZE + 1),
+            ))
+            .unwrap_err();
+
+        // Messages are rejected once the writer is gone
+        drop(receiver);
+        sender
+            .push(OutboundMessage::new(
+                ProtocolId::StorageServiceRpc,
+                direct_send(ProtocolId::StorageServiceRpc, 0),
+            ))
+            .unwrap_err();
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     application::storage::PeersAndMetadata,
     counters,
     noise::{stream::NoiseStream, HandshakeAuthMode},
+    peer::outbound_queue::OutboundPriorities,
     peer_manager::{
         conn_notifs_channel, ConnectionRequest, ConnectionRequestSender, PeerManager,
         PeerManagerNotification, PeerManagerRequest, PeerManagerRequestSender,
@@ -77,6 +78,7 @@ struct PeerManagerContext {
     channel_size: usize,
     max_frame_size: usize,
     max_message_size: usize,
+    outbound_priorities: OutboundPriorities,
     inbound_connection_limit: usize,
     tcp_buffer_cfg: TCPBufferCfg,
 }
@@ -100,6 +102,7 @@ impl PeerManagerContext {
         channel_size: usize,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_priorities: OutboundPriorities,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
     ) -> Self {
@@ -117,6 +120,7 @@ impl PeerManagerContext {
             channel_size,
             max_frame_size,
             max_message_size,
+            outbound_priorities,
             inbound_connection_limit,
             tcp_buffer_cfg,
         }
@@ -174,6 +178,7 @@ impl PeerManagerBuilder {
         max_concurrent_network_reqs: usize,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_priorities: OutboundPriorities,
         enable_proxy_protocol: bool,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
@@ -210,6 +215,7 @@ impl PeerManagerBuilder {
                 channel_size,
                 max_frame_size,
                 max_message_size,
+                outbound_priorities,
                 inbound_connection_limit,
                 tcp_buffer_cfg,
             )),
@@ -345,6 +351,7 @@ impl PeerManagerBuilder {
             pm_context.max_concurrent_network_reqs,
             pm_context.max_frame_size,
             pm_context.max_message_size,
+            pm_context.outbound_priorities,
             pm_context.inbound_connection_limit,
         );
 
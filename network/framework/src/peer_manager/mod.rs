@@ -15,7 +15,7 @@ use crate::{
     constants,
     counters::{self},
     logging::*,
-    peer::{Peer, PeerNotification, PeerRequest},
+    peer::{outbound_queue::OutboundPriorities, Peer, PeerNotification, PeerRequest},
     transport::{
         Connection, ConnectionId, ConnectionMetadata, TSocket as TransportTSocket,
         TRANSPORT_TIMEOUT,
@@ -117,6 +117,8 @@ where
     max_frame_size: usize,
     /// Max network message size
     max_message_size: usize,
+    /// The priorities used to schedule outbound messages to each peer
+    outbound_priorities: OutboundPriorities,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
 }
@@ -146,6 +148,7 @@ where
         max_concurrent_network_reqs: usize,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_priorities: OutboundPriorities,
         inbound_connection_limit: usize,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = aptos_channels::new(
@@ -188,6 +191,7 @@ where
             channel_size,
             max_frame_size,
             max_message_size,
+            outbound_priorities,
             inbound_connection_limit,
         }
     }
@@ -672,6 +676,7 @@ where
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
             self.max_message_size,
+            self.outbound_priorities.clone(),
         );
         self.executor.spawn(peer.start());
 
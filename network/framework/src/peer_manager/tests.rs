@@ -5,7 +5,7 @@
 use crate::{
     application::storage::PeersAndMetadata,
     constants,
-    peer::DisconnectReason,
+    peer::{outbound_queue::OutboundPriorities, DisconnectReason},
     peer_manager::{
         conn_notifs_channel, error::PeerManagerError, ConnectionNotification, ConnectionRequest,
         PeerManager, PeerManagerNotification, PeerManagerRequest, TransportNotification,
@@ -117,6 +117,7 @@ fn build_test_peer_manager(
         constants::MAX_CONCURRENT_NETWORK_REQS,
         constants::MAX_FRAME_SIZE,
         constants::MAX_MESSAGE_SIZE,
+        OutboundPriorities::default(),
         MAX_INBOUND_CONNECTIONS,
     );
 
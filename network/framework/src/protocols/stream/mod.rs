@@ -1,11 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::protocols::wire::messaging::v1::{MultiplexMessage, NetworkMessage};
+use crate::protocols::wire::messaging::v1::NetworkMessage;
 use anyhow::{bail, ensure};
-use aptos_channels::Sender;
 use aptos_id_generator::{IdGenerator, U32IdGenerator};
-use futures_util::SinkExt;
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
@@ -147,19 +145,15 @@ impl InboundStream {
     }
 }
 
+/// Splits messages larger than a frame into a stream header and fragments
 pub struct OutboundStream {
     request_id_gen: U32IdGenerator,
     max_frame_size: usize,
     max_message_size: usize,
-    stream_tx: Sender<MultiplexMessage>,
 }
 
 impl OutboundStream {
-    pub fn new(
-        max_frame_size: usize,
-        max_message_size: usize,
-        stream_tx: Sender<MultiplexMessage>,
-    ) -> Self {
+    pub fn new(max_frame_size: usize, max_message_size: usize) -> Self {
         // some buffer for headers
         let max_frame_size = max_frame_size - 64;
         assert!(
@@ -172,7 +166,6 @@ impl OutboundStream {
             request_id_gen: U32IdGenerator::new(),
             max_frame_size,
             max_message_size,
-            stream_tx,
         }
     }
 
@@ -180,7 +173,12 @@ impl OutboundStream {
         message.data_len() > self.max_frame_size
     }
 
-    pub async fn stream_message(&mut self, mut message: NetworkMessage) -> anyhow::Result<()> {
+    /// Returns the stream messages to send for the given message, i.e., its header followed by
+    /// its fragments
+    pub fn stream_message(
+        &mut self,
+        mut message: NetworkMessage,
+    ) -> anyhow::Result<Vec<StreamMessage>> {
         ensure!(
             message.data_len() <= self.max_message_size,
             "Message length {} exceed size limit {}",
@@ -213,24 +211,19 @@ impl OutboundStream {
             chunks.len() <= u8::MAX as usize,
             "Number of fragments overflowed"
         );
-        let header = StreamMessage::Header(StreamHeader {
+        let mut stream_messages = Vec::with_capacity(chunks.len() + 1);
+        stream_messages.push(StreamMessage::Header(StreamHeader {
             request_id,
             num_fragments: chunks.len() as u8,
             message,
-        });
-        self.stream_tx
-            .send(MultiplexMessage::Stream(header))
-            .await?;
+        }));
         for (index, chunk) in chunks.enumerate() {
-            let message = StreamMessage::Fragment(StreamFragment {
+            stream_messages.push(StreamMessage::Fragment(StreamFragment {
                 request_id,
                 fragment_id: index as u8 + 1,
                 raw_data: Vec::from(chunk),
-            });
-            self.stream_tx
-                .send(MultiplexMessage::Stream(message))
-                .await?;
+            }));
         }
-        Ok(())
+        Ok(stream_messages)
     }
 }
@@ -183,3 +183,28 @@ fn ignore_unknown_protocols() {
         ProtocolIdSet::empty(),
     );
 }
+
+// Shared mempool broadcasts are large and highly compressible, so they are always compressed
+// on the wire (and decompressed on receipt).
+#[test]
+fn mempool_direct_send_is_compressed() {
+    let message: Vec<Vec<u8>> = (0..100).map(|_| vec![7u8; 1000]).collect();
+    let bcs_bytes = bcs::to_bytes(&message).unwrap();
+
+    let protocol = ProtocolId::MempoolDirectSend;
+    let wire_bytes = protocol.to_bytes(&message).unwrap();
+    assert!(wire_bytes.len() < bcs_bytes.len() / 10);
+    assert_eq!(
+        aptos_compression::decompress(
+            &wire_bytes,
+            CompressionClient::Mempool,
+            MAX_APPLICATION_MESSAGE_SIZE
+        )
+        .unwrap(),
+        bcs_bytes
+    );
+    assert_eq!(
+        protocol.from_bytes::<Vec<Vec<u8>>>(&wire_bytes).unwrap(),
+        message
+    );
+}
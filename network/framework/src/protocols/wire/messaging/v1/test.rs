@@ -235,16 +235,18 @@ proptest! {
 
         let mut message_tx = MultiplexMessageSink::new(socket_tx, 128);
         let message_rx = MultiplexMessageStream::new(socket_rx, 128);
-        let (stream_tx, stream_rx) = aptos_channels::new_test(1024);
+        let (mut stream_tx, stream_rx) = aptos_channels::new_test(1024);
         let (mut msg_tx, msg_rx) = aptos_channels::new_test(1024);
-        let mut outbound_stream = OutboundStream::new(128, 64 * 255, stream_tx);
+        let mut outbound_stream = OutboundStream::new(128, 64 * 255);
         let mut inbound_stream = InboundStreamBuffer::new(255);
 
         let messages_clone = messages.clone();
         let f_stream_all = async move {
             for message in messages_clone {
                 if outbound_stream.should_stream(&message) {
-                    outbound_stream.stream_message(message).await.unwrap();
+                    for stream_message in outbound_stream.stream_message(message).unwrap() {
+                        stream_tx.send(MultiplexMessage::Stream(stream_message)).await.unwrap();
+                    }
                 } else {
                     msg_tx.send(MultiplexMessage::Message(message)).await.unwrap();
                 }
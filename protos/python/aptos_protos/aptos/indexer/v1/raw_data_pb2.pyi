@@ -11,33 +11,81 @@ from google.protobuf.internal import containers as _containers
 
 DESCRIPTOR: _descriptor.FileDescriptor
 
+class TransactionsFilter(_message.Message):
+    __slots__ = [
+        "senders",
+        "entry_functions",
+        "event_types",
+        "success",
+        "write_set_resource_types",
+    ]
+    SENDERS_FIELD_NUMBER: _ClassVar[int]
+    ENTRY_FUNCTIONS_FIELD_NUMBER: _ClassVar[int]
+    EVENT_TYPES_FIELD_NUMBER: _ClassVar[int]
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    WRITE_SET_RESOURCE_TYPES_FIELD_NUMBER: _ClassVar[int]
+    senders: _containers.RepeatedScalarFieldContainer[str]
+    entry_functions: _containers.RepeatedScalarFieldContainer[str]
+    event_types: _containers.RepeatedScalarFieldContainer[str]
+    success: bool
+    write_set_resource_types: _containers.RepeatedScalarFieldContainer[str]
+    def __init__(
+        self,
+        senders: _Optional[_Iterable[str]] = ...,
+        entry_functions: _Optional[_Iterable[str]] = ...,
+        event_types: _Optional[_Iterable[str]] = ...,
+        success: _Optional[bool] = ...,
+        write_set_resource_types: _Optional[_Iterable[str]] = ...,
+    ) -> None: ...
+
 class GetTransactionsRequest(_message.Message):
-    __slots__ = ["starting_version", "transactions_count", "batch_size"]
+    __slots__ = [
+        "starting_version",
+        "transactions_count",
+        "batch_size",
+        "transactions_filter",
+    ]
     STARTING_VERSION_FIELD_NUMBER: _ClassVar[int]
     TRANSACTIONS_COUNT_FIELD_NUMBER: _ClassVar[int]
     BATCH_SIZE_FIELD_NUMBER: _ClassVar[int]
+    TRANSACTIONS_FILTER_FIELD_NUMBER: _ClassVar[int]
     starting_version: int
     transactions_count: int
     batch_size: int
+    transactions_filter: TransactionsFilter
     def __init__(
         self,
         starting_version: _Optional[int] = ...,
         transactions_count: _Optional[int] = ...,
         batch_size: _Optional[int] = ...,
+        transactions_filter: _Optional[_Union[TransactionsFilter, _Mapping]] = ...,
+    ) -> None: ...
+
+class ProcessedRange(_message.Message):
+    __slots__ = ["first_version", "last_version"]
+    FIRST_VERSION_FIELD_NUMBER: _ClassVar[int]
+    LAST_VERSION_FIELD_NUMBER: _ClassVar[int]
+    first_version: int
+    last_version: int
+    def __init__(
+        self, first_version: _Optional[int] = ..., last_version: _Optional[int] = ...
     ) -> None: ...
 
 class TransactionsResponse(_message.Message):
-    __slots__ = ["transactions", "chain_id"]
+    __slots__ = ["transactions", "chain_id", "processed_range"]
     TRANSACTIONS_FIELD_NUMBER: _ClassVar[int]
     CHAIN_ID_FIELD_NUMBER: _ClassVar[int]
+    PROCESSED_RANGE_FIELD_NUMBER: _ClassVar[int]
     transactions: _containers.RepeatedCompositeFieldContainer[
         _transaction_pb2.Transaction
     ]
     chain_id: int
+    processed_range: ProcessedRange
     def __init__(
         self,
         transactions: _Optional[
             _Iterable[_Union[_transaction_pb2.Transaction, _Mapping]]
         ] = ...,
         chain_id: _Optional[int] = ...,
+        processed_range: _Optional[_Union[ProcessedRange, _Mapping]] = ...,
     ) -> None: ...
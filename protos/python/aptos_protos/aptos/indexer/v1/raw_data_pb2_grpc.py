@@ -24,7 +24,9 @@ class RawDataServicer(object):
     """Missing associated documentation comment in .proto file."""
 
     def GetTransactions(self, request, context):
-        """Get transactions batch without any filtering from starting version and end if transaction count is present."""
+        """Get transactions batch from starting version and end if transaction count is present.
+        If a transactions filter is present, only the matching transactions are returned.
+        """
         context.set_code(grpc.StatusCode.UNIMPLEMENTED)
         context.set_details("Method not implemented!")
         raise NotImplementedError("Method not implemented!")
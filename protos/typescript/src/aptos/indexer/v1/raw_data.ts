@@ -12,6 +12,41 @@ import Long from "long";
 import _m0 from "protobufjs/minimal";
 import { Transaction } from "../../transaction/v1/transaction";
 
+/**
+ * TransactionsFilter selects the transactions to stream. A transaction matches the filter
+ * if it matches all of the criteria that are set, and a criterion matches if any of its
+ * values match. Type and function names are matched on their canonical string form.
+ */
+export interface TransactionsFilter {
+  /** Optional; match user transactions sent by any of these accounts, e.g., "0x1". */
+  senders?:
+    | string[]
+    | undefined;
+  /**
+   * Optional; match user transactions calling any of these entry functions, either by
+   * function (e.g., "0x1::coin::transfer") or by module (e.g., "0x1::coin").
+   */
+  entryFunctions?:
+    | string[]
+    | undefined;
+  /**
+   * Optional; match transactions emitting an event of any of these types. Generic types
+   * without type arguments (e.g., "0x1::coin::DepositEvent") match all instantiations.
+   */
+  eventTypes?:
+    | string[]
+    | undefined;
+  /** Optional; match transactions that succeeded (true) or failed (false). */
+  success?:
+    | boolean
+    | undefined;
+  /**
+   * Optional; match transactions writing or deleting a resource of any of these types.
+   * Generic types without type arguments match all instantiations.
+   */
+  writeSetResourceTypes?: string[] | undefined;
+}
+
 export interface GetTransactionsRequest {
   /** Required; start version of current stream. */
   startingVersion?:
@@ -28,7 +63,20 @@ export interface GetTransactionsRequest {
    * Optional; number of transactions in each `TransactionsResponse` for current stream.
    * If not present, default to 1000. If larger than 1000, request will be rejected.
    */
-  batchSize?: bigint | undefined;
+  batchSize?:
+    | bigint
+    | undefined;
+  /**
+   * Optional; only stream the transactions matching this filter. If not present, stream
+   * all transactions. Note: `transactionsCount` counts the versions processed.
+   */
+  transactionsFilter?: TransactionsFilter | undefined;
+}
+
+/** ProcessedRange is an inclusive range of transaction versions. */
+export interface ProcessedRange {
+  firstVersion?: bigint | undefined;
+  lastVersion?: bigint | undefined;
 }
 
 /** TransactionsResponse is a batch of transactions. */
@@ -38,11 +86,191 @@ export interface TransactionsResponse {
     | Transaction[]
     | undefined;
   /** Required; chain id. */
-  chainId?: bigint | undefined;
+  chainId?:
+    | bigint
+    | undefined;
+  /**
+   * Optional; the versions covered by this response. Only set when the request has a
+   * filter: versions in this range that are missing from `transactions` were filtered
+   * out, so a response may have no transactions at all.
+   */
+  processedRange?: ProcessedRange | undefined;
+}
+
+function createBaseTransactionsFilter(): TransactionsFilter {
+  return { senders: [], entryFunctions: [], eventTypes: [], success: undefined, writeSetResourceTypes: [] };
 }
 
+export const TransactionsFilter = {
+  encode(message: TransactionsFilter, writer: _m0.Writer = _m0.Writer.create()): _m0.Writer {
+    if (message.senders !== undefined && message.senders.length !== 0) {
+      for (const v of message.senders) {
+        writer.uint32(10).string(v!);
+      }
+    }
+    if (message.entryFunctions !== undefined && message.entryFunctions.length !== 0) {
+      for (const v of message.entryFunctions) {
+        writer.uint32(18).string(v!);
+      }
+    }
+    if (message.eventTypes !== undefined && message.eventTypes.length !== 0) {
+      for (const v of message.eventTypes) {
+        writer.uint32(26).string(v!);
+      }
+    }
+    if (message.success !== undefined) {
+      writer.uint32(32).bool(message.success);
+    }
+    if (message.writeSetResourceTypes !== undefined && message.writeSetResourceTypes.length !== 0) {
+      for (const v of message.writeSetResourceTypes) {
+        writer.uint32(42).string(v!);
+      }
+    }
+    return writer;
+  },
+
+  decode(input: _m0.Reader | Uint8Array, length?: number): TransactionsFilter {
+    const reader = input instanceof _m0.Reader ? input : _m0.Reader.create(input);
+    let end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseTransactionsFilter();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1:
+          if (tag !== 10) {
+            break;
+          }
+
+          message.senders!.push(reader.string());
+          continue;
+        case 2:
+          if (tag !== 18) {
+            break;
+          }
+
+          message.entryFunctions!.push(reader.string());
+          continue;
+        case 3:
+          if (tag !== 26) {
+            break;
+          }
+
+          message.eventTypes!.push(reader.string());
+          continue;
+        case 4:
+          if (tag !== 32) {
+            break;
+          }
+
+          message.success = reader.bool();
+          continue;
+        case 5:
+          if (tag !== 42) {
+            break;
+          }
+
+          message.writeSetResourceTypes!.push(reader.string());
+          continue;
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skipType(tag & 7);
+    }
+    return message;
+  },
+
+  // encodeTransform encodes a source of message objects.
+  // Transform<TransactionsFilter, Uint8Array>
+  async *encodeTransform(
+    source:
+      | AsyncIterable<TransactionsFilter | TransactionsFilter[]>
+      | Iterable<TransactionsFilter | TransactionsFilter[]>,
+  ): AsyncIterable<Uint8Array> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [TransactionsFilter.encode(p).finish()];
+        }
+      } else {
+        yield* [TransactionsFilter.encode(pkt as any).finish()];
+      }
+    }
+  },
+
+  // decodeTransform decodes a source of encoded messages.
+  // Transform<Uint8Array, TransactionsFilter>
+  async *decodeTransform(
+    source: AsyncIterable<Uint8Array | Uint8Array[]> | Iterable<Uint8Array | Uint8Array[]>,
+  ): AsyncIterable<TransactionsFilter> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [TransactionsFilter.decode(p)];
+        }
+      } else {
+        yield* [TransactionsFilter.decode(pkt as any)];
+      }
+    }
+  },
+
+  fromJSON(object: any): TransactionsFilter {
+    return {
+      senders: globalThis.Array.isArray(object?.senders) ? object.senders.map((e: any) => globalThis.String(e)) : [],
+      entryFunctions: globalThis.Array.isArray(object?.entryFunctions)
+        ? object.entryFunctions.map((e: any) => globalThis.String(e))
+        : [],
+      eventTypes: globalThis.Array.isArray(object?.eventTypes)
+        ? object.eventTypes.map((e: any) => globalThis.String(e))
+        : [],
+      success: isSet(object.success) ? globalThis.Boolean(object.success) : undefined,
+      writeSetResourceTypes: globalThis.Array.isArray(object?.writeSetResourceTypes)
+        ? object.writeSetResourceTypes.map((e: any) => globalThis.String(e))
+        : [],
+    };
+  },
+
+  toJSON(message: TransactionsFilter): unknown {
+    const obj: any = {};
+    if (message.senders?.length) {
+      obj.senders = message.senders;
+    }
+    if (message.entryFunctions?.length) {
+      obj.entryFunctions = message.entryFunctions;
+    }
+    if (message.eventTypes?.length) {
+      obj.eventTypes = message.eventTypes;
+    }
+    if (message.success !== undefined) {
+      obj.success = message.success;
+    }
+    if (message.writeSetResourceTypes?.length) {
+      obj.writeSetResourceTypes = message.writeSetResourceTypes;
+    }
+    return obj;
+  },
+
+  create(base?: DeepPartial<TransactionsFilter>): TransactionsFilter {
+    return TransactionsFilter.fromPartial(base ?? {});
+  },
+  fromPartial(object: DeepPartial<TransactionsFilter>): TransactionsFilter {
+    const message = createBaseTransactionsFilter();
+    message.senders = object.senders?.map((e) => e) || [];
+    message.entryFunctions = object.entryFunctions?.map((e) => e) || [];
+    message.eventTypes = object.eventTypes?.map((e) => e) || [];
+    message.success = object.success ?? undefined;
+    message.writeSetResourceTypes = object.writeSetResourceTypes?.map((e) => e) || [];
+    return message;
+  },
+};
+
 function createBaseGetTransactionsRequest(): GetTransactionsRequest {
-  return { startingVersion: undefined, transactionsCount: undefined, batchSize: undefined };
+  return {
+    startingVersion: undefined,
+    transactionsCount: undefined,
+    batchSize: undefined,
+    transactionsFilter: undefined,
+  };
 }
 
 export const GetTransactionsRequest = {
@@ -65,6 +293,9 @@ export const GetTransactionsRequest = {
       }
       writer.uint32(24).uint64(message.batchSize.toString());
     }
+    if (message.transactionsFilter !== undefined) {
+      TransactionsFilter.encode(message.transactionsFilter, writer.uint32(34).fork()).ldelim();
+    }
     return writer;
   },
 
@@ -96,6 +327,13 @@ export const GetTransactionsRequest = {
 
           message.batchSize = longToBigint(reader.uint64() as Long);
           continue;
+        case 4:
+          if (tag !== 34) {
+            break;
+          }
+
+          message.transactionsFilter = TransactionsFilter.decode(reader, reader.uint32());
+          continue;
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -144,6 +382,9 @@ export const GetTransactionsRequest = {
       startingVersion: isSet(object.startingVersion) ? BigInt(object.startingVersion) : undefined,
       transactionsCount: isSet(object.transactionsCount) ? BigInt(object.transactionsCount) : undefined,
       batchSize: isSet(object.batchSize) ? BigInt(object.batchSize) : undefined,
+      transactionsFilter: isSet(object.transactionsFilter)
+        ? TransactionsFilter.fromJSON(object.transactionsFilter)
+        : undefined,
     };
   },
 
@@ -158,6 +399,9 @@ export const GetTransactionsRequest = {
     if (message.batchSize !== undefined) {
       obj.batchSize = message.batchSize.toString();
     }
+    if (message.transactionsFilter !== undefined) {
+      obj.transactionsFilter = TransactionsFilter.toJSON(message.transactionsFilter);
+    }
     return obj;
   },
 
@@ -169,12 +413,129 @@ export const GetTransactionsRequest = {
     message.startingVersion = object.startingVersion ?? undefined;
     message.transactionsCount = object.transactionsCount ?? undefined;
     message.batchSize = object.batchSize ?? undefined;
+    message.transactionsFilter = (object.transactionsFilter !== undefined && object.transactionsFilter !== null)
+      ? TransactionsFilter.fromPartial(object.transactionsFilter)
+      : undefined;
+    return message;
+  },
+};
+
+function createBaseProcessedRange(): ProcessedRange {
+  return { firstVersion: BigInt("0"), lastVersion: BigInt("0") };
+}
+
+export const ProcessedRange = {
+  encode(message: ProcessedRange, writer: _m0.Writer = _m0.Writer.create()): _m0.Writer {
+    if (message.firstVersion !== undefined && message.firstVersion !== BigInt("0")) {
+      if (BigInt.asUintN(64, message.firstVersion) !== message.firstVersion) {
+        throw new Error("value provided for field message.firstVersion of type uint64 too large");
+      }
+      writer.uint32(8).uint64(message.firstVersion.toString());
+    }
+    if (message.lastVersion !== undefined && message.lastVersion !== BigInt("0")) {
+      if (BigInt.asUintN(64, message.lastVersion) !== message.lastVersion) {
+        throw new Error("value provided for field message.lastVersion of type uint64 too large");
+      }
+      writer.uint32(16).uint64(message.lastVersion.toString());
+    }
+    return writer;
+  },
+
+  decode(input: _m0.Reader | Uint8Array, length?: number): ProcessedRange {
+    const reader = input instanceof _m0.Reader ? input : _m0.Reader.create(input);
+    let end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseProcessedRange();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1:
+          if (tag !== 8) {
+            break;
+          }
+
+          message.firstVersion = longToBigint(reader.uint64() as Long);
+          continue;
+        case 2:
+          if (tag !== 16) {
+            break;
+          }
+
+          message.lastVersion = longToBigint(reader.uint64() as Long);
+          continue;
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skipType(tag & 7);
+    }
+    return message;
+  },
+
+  // encodeTransform encodes a source of message objects.
+  // Transform<ProcessedRange, Uint8Array>
+  async *encodeTransform(
+    source:
+      | AsyncIterable<ProcessedRange | ProcessedRange[]>
+      | Iterable<ProcessedRange | ProcessedRange[]>,
+  ): AsyncIterable<Uint8Array> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [ProcessedRange.encode(p).finish()];
+        }
+      } else {
+        yield* [ProcessedRange.encode(pkt as any).finish()];
+      }
+    }
+  },
+
+  // decodeTransform decodes a source of encoded messages.
+  // Transform<Uint8Array, ProcessedRange>
+  async *decodeTransform(
+    source: AsyncIterable<Uint8Array | Uint8Array[]> | Iterable<Uint8Array | Uint8Array[]>,
+  ): AsyncIterable<ProcessedRange> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [ProcessedRange.decode(p)];
+        }
+      } else {
+        yield* [ProcessedRange.decode(pkt as any)];
+      }
+    }
+  },
+
+  fromJSON(object: any): ProcessedRange {
+    return {
+      firstVersion: isSet(object.firstVersion) ? BigInt(object.firstVersion) : BigInt("0"),
+      lastVersion: isSet(object.lastVersion) ? BigInt(object.lastVersion) : BigInt("0"),
+    };
+  },
+
+  toJSON(message: ProcessedRange): unknown {
+    const obj: any = {};
+    if (message.firstVersion !== undefined && message.firstVersion !== BigInt("0")) {
+      obj.firstVersion = message.firstVersion.toString();
+    }
+    if (message.lastVersion !== undefined && message.lastVersion !== BigInt("0")) {
+      obj.lastVersion = message.lastVersion.toString();
+    }
+    return obj;
+  },
+
+  create(base?: DeepPartial<ProcessedRange>): ProcessedRange {
+    return ProcessedRange.fromPartial(base ?? {});
+  },
+  fromPartial(object: DeepPartial<ProcessedRange>): ProcessedRange {
+    const message = createBaseProcessedRange();
+    message.firstVersion = object.firstVersion ?? BigInt("0");
+    message.lastVersion = object.lastVersion ?? BigInt("0");
     return message;
   },
 };
 
 function createBaseTransactionsResponse(): TransactionsResponse {
-  return { transactions: [], chainId: undefined };
+  return { transactions: [], chainId: undefined, processedRange: undefined };
 }
 
 export const TransactionsResponse = {
@@ -190,6 +551,9 @@ export const TransactionsResponse = {
       }
       writer.uint32(16).uint64(message.chainId.toString());
     }
+    if (message.processedRange !== undefined) {
+      ProcessedRange.encode(message.processedRange, writer.uint32(26).fork()).ldelim();
+    }
     return writer;
   },
 
@@ -214,6 +578,13 @@ export const TransactionsResponse = {
 
           message.chainId = longToBigint(reader.uint64() as Long);
           continue;
+        case 3:
+          if (tag !== 26) {
+            break;
+          }
+
+          message.processedRange = ProcessedRange.decode(reader, reader.uint32());
+          continue;
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -263,6 +634,7 @@ export const TransactionsResponse = {
         ? object.transactions.map((e: any) => Transaction.fromJSON(e))
         : [],
       chainId: isSet(object.chainId) ? BigInt(object.chainId) : undefined,
+      processedRange: isSet(object.processedRange) ? ProcessedRange.fromJSON(object.processedRange) : undefined,
     };
   },
 
@@ -274,6 +646,9 @@ export const TransactionsResponse = {
     if (message.chainId !== undefined) {
       obj.chainId = message.chainId.toString();
     }
+    if (message.processedRange !== undefined) {
+      obj.processedRange = ProcessedRange.toJSON(message.processedRange);
+    }
     return obj;
   },
 
@@ -284,13 +659,19 @@ export const TransactionsResponse = {
     const message = createBaseTransactionsResponse();
     message.transactions = object.transactions?.map((e) => Transaction.fromPartial(e)) || [];
     message.chainId = object.chainId ?? undefined;
+    message.processedRange = (object.processedRange !== undefined && object.processedRange !== null)
+      ? ProcessedRange.fromPartial(object.processedRange)
+      : undefined;
     return message;
   },
 };
 
 export type RawDataService = typeof RawDataService;
 export const RawDataService = {
-  /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
+  /**
+   * Get transactions batch from starting version and end if transaction count is present.
+   * If a transactions filter is present, only the matching transactions are returned.
+   */
   getTransactions: {
     path: "/aptos.indexer.v1.RawData/GetTransactions",
     requestStream: false,
@@ -303,12 +684,18 @@ export const RawDataService = {
 } as const;
 
 export interface RawDataServer extends UntypedServiceImplementation {
-  /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
+  /**
+   * Get transactions batch from starting version and end if transaction count is present.
+   * If a transactions filter is present, only the matching transactions are returned.
+   */
   getTransactions: handleServerStreamingCall<GetTransactionsRequest, TransactionsResponse>;
 }
 
 export interface RawDataClient extends Client {
-  /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
+  /**
+   * Get transactions batch from starting version and end if transaction count is present.
+   * If a transactions filter is present, only the matching transactions are returned.
+   */
   getTransactions(
     request: GetTransactionsRequest,
     options?: Partial<CallOptions>,
@@ -872,6 +872,7 @@ export enum AnyPublicKey_Type {
   TYPE_UNSPECIFIED = 0,
   TYPE_ED25519 = 1,
   TYPE_SECP256K1_ECDSA = 2,
+  TYPE_SECP256R1_ECDSA = 3,
   UNRECOGNIZED = -1,
 }
 
@@ -886,6 +887,9 @@ export function anyPublicKey_TypeFromJSON(object: any): AnyPublicKey_Type {
     case 2:
     case "TYPE_SECP256K1_ECDSA":
       return AnyPublicKey_Type.TYPE_SECP256K1_ECDSA;
+    case 3:
+    case "TYPE_SECP256R1_ECDSA":
+      return AnyPublicKey_Type.TYPE_SECP256R1_ECDSA;
     case -1:
     case "UNRECOGNIZED":
     default:
@@ -901,6 +905,8 @@ export function anyPublicKey_TypeToJSON(object: AnyPublicKey_Type): string {
       return "TYPE_ED25519";
     case AnyPublicKey_Type.TYPE_SECP256K1_ECDSA:
       return "TYPE_SECP256K1_ECDSA";
+    case AnyPublicKey_Type.TYPE_SECP256R1_ECDSA:
+      return "TYPE_SECP256R1_ECDSA";
     case AnyPublicKey_Type.UNRECOGNIZED:
     default:
       return "UNRECOGNIZED";
@@ -916,6 +922,7 @@ export enum AnySignature_Type {
   TYPE_UNSPECIFIED = 0,
   TYPE_ED25519 = 1,
   TYPE_SECP256K1_ECDSA = 2,
+  TYPE_WEBAUTHN = 3,
   UNRECOGNIZED = -1,
 }
 
@@ -930,6 +937,9 @@ export function anySignature_TypeFromJSON(object: any): AnySignature_Type {
     case 2:
     case "TYPE_SECP256K1_ECDSA":
       return AnySignature_Type.TYPE_SECP256K1_ECDSA;
+    case 3:
+    case "TYPE_WEBAUTHN":
+      return AnySignature_Type.TYPE_WEBAUTHN;
     case -1:
     case "UNRECOGNIZED":
     default:
@@ -945,6 +955,8 @@ export function anySignature_TypeToJSON(object: AnySignature_Type): string {
       return "TYPE_ED25519";
     case AnySignature_Type.TYPE_SECP256K1_ECDSA:
       return "TYPE_SECP256K1_ECDSA";
+    case AnySignature_Type.TYPE_WEBAUTHN:
+      return "TYPE_WEBAUTHN";
     case AnySignature_Type.UNRECOGNIZED:
     default:
       return "UNRECOGNIZED";
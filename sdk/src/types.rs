@@ -11,11 +11,15 @@ use crate::{
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+        transaction::{
+            authenticator::{AnyPublicKey, AuthenticationKey},
+            webauthn::{self, AssertionSignature, PartialAuthenticatorAssertionResponse},
+            RawTransaction, SignedTransaction,
+        },
     },
 };
 use anyhow::Result;
-use aptos_crypto::ed25519::Ed25519Signature;
+use aptos_crypto::{ed25519::Ed25519Signature, p256_ecdsa};
 use aptos_ledger::AptosLedgerError;
 use aptos_types::event::EventKey;
 pub use aptos_types::*;
@@ -334,6 +338,76 @@ impl HardwareWalletAccount {
     }
 }
 
+/// An account whose secp256r1 key is held by a WebAuthn authenticator (i.e., a passkey), so
+/// transactions are signed out of process, e.g., by `navigator.credentials.get()` in a browser.
+#[derive(Debug)]
+pub struct PasskeyAccount {
+    address: AccountAddress,
+    public_key: p256_ecdsa::PublicKey,
+    /// Same as LocalAccount's sequence_number.
+    sequence_number: u64,
+}
+
+impl PasskeyAccount {
+    pub fn new(public_key: p256_ecdsa::PublicKey, sequence_number: u64) -> Self {
+        let address = AuthenticationKey::any_key(AnyPublicKey::secp256r1_ecdsa(public_key.clone()))
+            .account_address();
+        Self {
+            address,
+            public_key,
+            sequence_number,
+        }
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn public_key(&self) -> &p256_ecdsa::PublicKey {
+        &self.public_key
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn sequence_number_mut(&mut self) -> &mut u64 {
+        &mut self.sequence_number
+    }
+
+    /// Returns the WebAuthn challenge that the authenticator must sign for `txn`.
+    pub fn challenge(&self, txn: &RawTransaction) -> Result<Vec<u8>> {
+        webauthn::challenge(txn)
+    }
+
+    /// Builds the signed transaction from the `AuthenticatorAssertionResponse` returned by the
+    /// authenticator for the challenge of `txn`. The signature is expected to be DER encoded, as
+    /// returned by WebAuthn.
+    pub fn sign_transaction(
+        &self,
+        txn: RawTransaction,
+        authenticator_data: Vec<u8>,
+        client_data_json: Vec<u8>,
+        der_signature: &[u8],
+    ) -> Result<SignedTransaction> {
+        let signature = p256_ecdsa::Signature::from_der(der_signature)?;
+        let assertion = PartialAuthenticatorAssertionResponse::new(
+            AssertionSignature::Secp256r1Ecdsa { signature },
+            authenticator_data,
+            client_data_json,
+        );
+        assertion.verify(
+            &txn,
+            &AnyPublicKey::secp256r1_ecdsa(self.public_key.clone()),
+        )?;
+        Ok(SignedTransaction::new_webauthn(
+            txn,
+            self.public_key.clone(),
+            assertion,
+        ))
+    }
+}
+
 #[derive(Debug)]
 pub struct AccountKey {
     private_key: Ed25519PrivateKey,
@@ -13,6 +13,7 @@ use crate::{
     metrics::{
         increment_request_counter, set_gauge, start_request_timer, PRIORITIZED_PEER, REGULAR_PEER,
     },
+    peer_reputation::{PeerReputationStorage, PEER_REPUTATIONS_FILE_NAME},
     peer_states::{ErrorType, PeerStates},
     poller::DataSummaryPoller,
     priority, utils,
@@ -110,13 +111,29 @@ impl AptosDataClient {
         let base_config = Arc::new(base_config);
         let data_client_config = Arc::new(data_client_config);
 
+        // Create the peer states (persisting peer reputations in the data directory)
+        let reputation_storage = if data_client_config
+            .peer_reputation_config
+            .enable_peer_reputation_persistence
+        {
+            let reputation_file_path = base_config.data_dir.join(PEER_REPUTATIONS_FILE_NAME);
+            Some(PeerReputationStorage::new(reputation_file_path))
+        } else {
+            None
+        };
+        let peer_states = PeerStates::new(
+            data_client_config.clone(),
+            reputation_storage,
+            time_service.clone(),
+        );
+
         // Create the data client
         let data_client = Self {
             base_config,
             data_client_config: data_client_config.clone(),
             storage_service_client: storage_service_client.clone(),
             active_subscription_state: Arc::new(Mutex::new(None)),
-            peer_states: Arc::new(PeerStates::new(data_client_config.clone())),
+            peer_states: Arc::new(peer_states),
             global_summary_cache: Arc::new(ArcSwap::from(Arc::new(GlobalDataSummary::empty()))),
             response_id_generator: Arc::new(U64IdGenerator::new()),
             time_service: time_service.clone(),
@@ -150,6 +167,11 @@ impl AptosDataClient {
         self.peer_states.update_peer_request_logs_and_metrics();
     }
 
+    /// Persists the peer reputations (if the persistence interval has elapsed)
+    pub fn persist_peer_reputations_if_due(&self) {
+        self.peer_states.persist_peer_reputations_if_due();
+    }
+
     /// Update a peer's storage summary
     pub fn update_peer_storage_summary(&self, peer: PeerNetworkId, summary: StorageServerSummary) {
         self.peer_states.update_summary(peer, summary)
@@ -435,7 +457,9 @@ impl AptosDataClient {
         // and return the error. Otherwise, stop the timer and update the metrics.
         let storage_response = match response {
             Ok(storage_response) => {
-                timer.stop_and_record(); // Update the latency metrics
+                let latency_secs = timer.stop_and_record(); // Update the latency metrics
+                self.peer_states
+                    .record_response_latency(peer, Duration::from_secs_f64(latency_secs));
                 storage_response
             },
             Err(error) => {
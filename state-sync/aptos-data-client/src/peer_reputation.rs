@@ -0,0 +1,98 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::Error;
+use aptos_config::network_id::PeerNetworkId;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+/// The file (in the node's data directory) that holds the peer reputations
+pub const PEER_REPUTATIONS_FILE_NAME: &str = "peer_reputations.json";
+
+/// The reputation of a peer, as remembered across disconnections and restarts
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PeerReputation {
+    /// The score of the peer when the reputation was last updated
+    pub score: f64,
+    /// The reason the peer is ignored (if the score is below the ignore threshold)
+    pub ignore_reason: Option<String>,
+    /// The most recent response latencies (in milliseconds) observed for the peer
+    pub recent_response_latencies_ms: Vec<u64>,
+    /// The unix time (in seconds) at which the reputation was last updated
+    pub last_updated_unix_secs: u64,
+}
+
+impl PeerReputation {
+    /// Returns the number of seconds since the reputation was last updated
+    pub fn get_age_secs(&self, now_unix_secs: u64) -> u64 {
+        now_unix_secs.saturating_sub(self.last_updated_unix_secs)
+    }
+}
+
+/// A simple file-backed store for peer reputations. The reputations
+/// are written to a temporary file first, and then moved into place
+/// (to avoid corrupting the existing reputations on a crash).
+#[derive(Clone, Debug)]
+pub struct PeerReputationStorage {
+    file_path: PathBuf,
+}
+
+impl PeerReputationStorage {
+    pub fn new(file_path: PathBuf) -> Self {
+        Self { file_path }
+    }
+
+    /// Loads the persisted peer reputations. If no reputations
+    /// have been persisted yet, an empty list is returned.
+    pub fn load_reputations(&self) -> Result<Vec<(PeerNetworkId, PeerReputation)>, Error> {
+        let contents = match fs::read(&self.file_path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => {
+                return Err(Error::UnexpectedErrorEncountered(format!(
+                    "Failed to read the peer reputations from {:?}: {:?}",
+                    self.file_path, error
+                )))
+            },
+        };
+        serde_json::from_slice(&contents).map_err(|error| {
+            Error::UnexpectedErrorEncountered(format!(
+                "Failed to deserialize the peer reputations from {:?}: {:?}",
+                self.file_path, error
+            ))
+        })
+    }
+
+    /// Persists the given peer reputations (replacing any existing ones)
+    pub fn save_reputations(
+        &self,
+        reputations: &[(PeerNetworkId, PeerReputation)],
+    ) -> Result<(), Error> {
+        let contents = serde_json::to_vec_pretty(reputations).map_err(|error| {
+            Error::UnexpectedErrorEncountered(format!(
+                "Failed to serialize the peer reputations: {:?}",
+                error
+            ))
+        })?;
+
+        let temp_file_path = self.file_path.with_extension("json.tmp");
+        fs::write(&temp_file_path, contents)
+            .and_then(|_| fs::rename(&temp_file_path, &self.file_path))
+            .map_err(|error| {
+                Error::UnexpectedErrorEncountered(format!(
+                    "Failed to write the peer reputations to {:?}: {:?}",
+                    self.file_path, error
+                ))
+            })
+    }
+}
+
+/// Decays the given score towards the starting score, halving the
+/// difference between them every `half_life_secs` seconds.
+pub fn decay_score(score: f64, starting_score: f64, elapsed_secs: u64, half_life_secs: u64) -> f64 {
+    if half_life_secs == 0 {
+        return starting_score;
+    }
+    let num_half_lives = elapsed_secs as f64 / half_life_secs as f64;
+    starting_score + (score - starting_score) * 0.5_f64.powf(num_half_lives)
+}
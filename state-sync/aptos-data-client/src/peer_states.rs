@@ -5,20 +5,22 @@ use crate::{
     global_summary::{AdvertisedData, GlobalDataSummary, OptimalChunkSizes},
     interface::ResponseError,
     logging::{LogEntry, LogEvent, LogSchema},
-    metrics,
+    metrics, peer_reputation,
+    peer_reputation::{PeerReputation, PeerReputationStorage},
 };
 use aptos_config::{config::AptosDataClientConfig, network_id::PeerNetworkId};
+use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_storage_service_types::{
     requests::StorageServiceRequest, responses::StorageServerSummary,
 };
-use aptos_time_service::TimeService;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use dashmap::DashMap;
 use std::{
     cmp::min,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashSet, VecDeque},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 // Useful constants
@@ -59,6 +61,16 @@ impl From<ResponseError> for ErrorType {
     }
 }
 
+impl ErrorType {
+    /// Returns a summary label for the error type
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            ErrorType::NotUseful => "not_useful",
+            ErrorType::Malicious => "malicious",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PeerState {
     /// The number of responses received from this peer (by data request label)
@@ -70,6 +82,10 @@ pub struct PeerState {
     storage_summary: Option<StorageServerSummary>,
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
+    /// The reason the peer is ignored, or `None` if the peer is not ignored.
+    ignore_reason: Option<String>,
+    /// The most recent response latencies (in milliseconds) observed for this peer
+    recent_response_latencies_ms: VecDeque<u64>,
 }
 
 impl Default for PeerState {
@@ -79,11 +95,41 @@ impl Default for PeerState {
             sent_requests_by_type: Arc::new(DashMap::new()),
             storage_summary: None,
             score: STARTING_SCORE,
+            ignore_reason: None,
+            recent_response_latencies_ms: VecDeque::new(),
         }
     }
 }
 
 impl PeerState {
+    /// Creates a new peer state from the given (previously seen) reputation.
+    /// The score is expected to have already been decayed.
+    fn new_with_reputation(score: f64, reputation: PeerReputation) -> Self {
+        // Only keep the ignore reason if the peer is still ignored
+        let ignore_reason = if score <= IGNORE_PEER_THRESHOLD {
+            reputation.ignore_reason
+        } else {
+            None
+        };
+
+        Self {
+            score,
+            ignore_reason,
+            recent_response_latencies_ms: reputation.recent_response_latencies_ms.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the reputation of the peer (as of the given unix time)
+    fn get_reputation(&self, now_unix_secs: u64) -> PeerReputation {
+        PeerReputation {
+            score: self.score,
+            ignore_reason: self.ignore_reason.clone(),
+            recent_response_latencies_ms: self.get_recent_response_latencies_ms(),
+            last_updated_unix_secs: now_unix_secs,
+        }
+    }
+
     /// Increments the received response counter for the given label
     fn increment_received_response_counter(&mut self, response_label: String) {
         self.received_responses_by_type
@@ -110,6 +156,16 @@ impl PeerState {
         self.storage_summary.clone()
     }
 
+    /// Returns the reason the peer is ignored (if it is ignored)
+    pub fn get_ignore_reason(&self) -> Option<String> {
+        self.ignore_reason.clone()
+    }
+
+    /// Returns the most recent response latencies (in milliseconds), oldest first
+    pub fn get_recent_response_latencies_ms(&self) -> Vec<u64> {
+        self.recent_response_latencies_ms.iter().copied().collect()
+    }
+
     /// Returns a sorted copy of the sent requests by type map
     pub fn get_sent_requests_by_type(&self) -> BTreeMap<String, u64> {
         let mut sorted_requests_by_type = BTreeMap::new();
@@ -156,6 +212,15 @@ impl PeerState {
     fn update_storage_summary(&mut self, storage_summary: StorageServerSummary) {
         self.storage_summary = Some(storage_summary);
     }
+
+    /// Records a response latency for the peer (dropping the oldest
+    /// latency if the max number of samples has been reached).
+    fn record_response_latency(&mut self, latency_ms: u64, max_num_latency_samples: u64) {
+        self.recent_response_latencies_ms.push_back(latency_ms);
+        while self.recent_response_latencies_ms.len() as u64 > max_num_latency_samples {
+            self.recent_response_latencies_ms.pop_front();
+        }
+    }
 }
 
 /// Contains all of the unbanned peers' most recent [`StorageServerSummary`] data
@@ -164,13 +229,61 @@ impl PeerState {
 pub struct PeerStates {
     data_client_config: Arc<AptosDataClientConfig>,
     peer_to_state: Arc<DashMap<PeerNetworkId, PeerState>>,
+    /// The reputations of peers that we don't currently hold a state for
+    /// (e.g., peers that disconnected, or were seen before a restart).
+    peer_to_reputation: Arc<DashMap<PeerNetworkId, PeerReputation>>,
+    /// The storage to which peer reputations are persisted (if any)
+    reputation_storage: Option<PeerReputationStorage>,
+    /// The time at which the peer reputations were last persisted
+    last_reputation_persist_time: Arc<Mutex<Option<Instant>>>,
+    time_service: TimeService,
 }
 
 impl PeerStates {
-    pub fn new(data_client_config: Arc<AptosDataClientConfig>) -> Self {
+    pub fn new(
+        data_client_config: Arc<AptosDataClientConfig>,
+        reputation_storage: Option<PeerReputationStorage>,
+        time_service: TimeService,
+    ) -> Self {
+        // Load any previously persisted peer reputations
+        let peer_to_reputation = Arc::new(DashMap::new());
+        if let Some(reputation_storage) = &reputation_storage {
+            match reputation_storage.load_reputations() {
+                Ok(reputations) => {
+                    let max_reputation_age_secs = data_client_config
+                        .peer_reputation_config
+                        .max_reputation_age_secs;
+                    let now_unix_secs = time_service.now_secs();
+                    for (peer, reputation) in reputations {
+                        if reputation.get_age_secs(now_unix_secs) <= max_reputation_age_secs {
+                            peer_to_reputation.insert(peer, reputation);
+                        }
+                    }
+                    info!(
+                        (LogSchema::new(LogEntry::PeerStates).message(&format!(
+                            "Loaded {} persisted peer reputations",
+                            peer_to_reputation.len()
+                        )))
+                    );
+                },
+                Err(error) => {
+                    warn!(
+                        (LogSchema::new(LogEntry::PeerStates)
+                            .event(LogEvent::PeerReputationStorageError)
+                            .message("Failed to load the persisted peer reputations!")
+                            .error(&error))
+                    );
+                },
+            }
+        }
+
         Self {
             data_client_config,
             peer_to_state: Arc::new(DashMap::new()),
+            peer_to_reputation,
+            reputation_storage,
+            last_reputation_persist_time: Arc::new(Mutex::new(None)),
+            time_service,
         }
     }
 
@@ -262,6 +375,7 @@ impl PeerStates {
             // Log if the peer is no longer ignored
             let new_score = entry.score;
             if old_score <= IGNORE_PEER_THRESHOLD && new_score > IGNORE_PEER_THRESHOLD {
+                entry.ignore_reason = None;
                 info!(
                     (LogSchema::new(LogEntry::PeerStates)
                         .event(LogEvent::PeerNoLongerIgnored)
@@ -279,11 +393,19 @@ impl PeerStates {
             let old_score = entry.score;
 
             // Update the peer's score with an error
+            let error_label = error.get_label();
             entry.update_score_error(error);
 
-            // Log if the peer is now ignored
+            // Log (and record the reason) if the peer is now ignored
             let new_score = entry.score;
             if old_score > IGNORE_PEER_THRESHOLD && new_score <= IGNORE_PEER_THRESHOLD {
+                entry.ignore_reason = Some(format!(
+                    "Score dropped to {:.2} (ignore threshold: {}) after a {} response at unix time {}",
+                    new_score,
+                    IGNORE_PEER_THRESHOLD,
+                    error_label,
+                    self.time_service.now_secs()
+                ));
                 info!(
                     (LogSchema::new(LogEntry::PeerStates)
                         .event(LogEvent::PeerIgnored)
@@ -294,18 +416,132 @@ impl PeerStates {
         }
     }
 
+    /// Records a response latency for the given peer
+    pub fn record_response_latency(&self, peer: PeerNetworkId, latency: Duration) {
+        if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
+            let max_num_latency_samples = self
+                .data_client_config
+                .peer_reputation_config
+                .max_num_latency_samples_per_peer;
+            entry.record_response_latency(latency.as_millis() as u64, max_num_latency_samples);
+        }
+    }
+
     /// Updates the storage summary for the given peer
     pub fn update_summary(&self, peer: PeerNetworkId, storage_summary: StorageServerSummary) {
         self.peer_to_state
             .entry(peer)
-            .or_default()
+            .or_insert_with(|| self.create_peer_state(&peer))
             .update_storage_summary(storage_summary);
     }
 
-    /// Garbage collects the peer states to remove data for disconnected peers
+    /// Creates a new state for the given peer, using the
+    /// peer's previous reputation (if one exists).
+    fn create_peer_state(&self, peer: &PeerNetworkId) -> PeerState {
+        match self.peer_to_reputation.remove(peer) {
+            Some((_, reputation)) => {
+                let score = self.get_decayed_score(&reputation);
+                PeerState::new_with_reputation(score, reputation)
+            },
+            None => PeerState::default(),
+        }
+    }
+
+    /// Returns the score of the given reputation, decayed towards
+    /// the starting score for the time since it was last updated.
+    fn get_decayed_score(&self, reputation: &PeerReputation) -> f64 {
+        let elapsed_secs = reputation.get_age_secs(self.time_service.now_secs());
+        let score_decay_half_life_secs = self
+            .data_client_config
+            .peer_reputation_config
+            .score_decay_half_life_secs;
+        peer_reputation::decay_score(
+            reputation.score,
+            STARTING_SCORE,
+            elapsed_secs,
+            score_decay_half_life_secs,
+        )
+    }
+
+    /// Garbage collects the peer states to remove data for disconnected peers.
+    /// The reputations of the disconnected peers are remembered.
     pub fn garbage_collect_peer_states(&self, connected_peers: HashSet<PeerNetworkId>) {
-        self.peer_to_state
-            .retain(|peer_network_id, _| connected_peers.contains(peer_network_id));
+        let now_unix_secs = self.time_service.now_secs();
+        self.peer_to_state.retain(|peer_network_id, peer_state| {
+            if connected_peers.contains(peer_network_id) {
+                true
+            } else {
+                self.peer_to_reputation
+                    .insert(*peer_network_id, peer_state.get_reputation(now_unix_secs));
+                false
+            }
+        });
+    }
+
+    /// Returns the reputations of all peers that we don't currently hold a
+    /// state for. The scores are decayed to reflect the current time.
+    pub fn get_disconnected_peer_reputations(&self) -> BTreeMap<PeerNetworkId, PeerReputation> {
+        self.peer_to_reputation
+            .iter()
+            .map(|entry| {
+                let mut reputation = entry.value().clone();
+                reputation.score = self.get_decayed_score(&reputation);
+                (*entry.key(), reputation)
+            })
+            .collect()
+    }
+
+    /// Persists the reputations of all known peers, if persistence is
+    /// enabled and the persistence interval has elapsed.
+    pub fn persist_peer_reputations_if_due(&self) {
+        // Verify that persistence is enabled
+        let reputation_storage = match &self.reputation_storage {
+            Some(reputation_storage) => reputation_storage,
+            None => return,
+        };
+
+        // Verify that the persistence interval has elapsed
+        let peer_reputation_config = self.data_client_config.peer_reputation_config;
+        let now = self.time_service.now();
+        {
+            let mut last_reputation_persist_time = self.last_reputation_persist_time.lock();
+            if let Some(last_persist_time) = *last_reputation_persist_time {
+                let persistence_interval =
+                    Duration::from_secs(peer_reputation_config.persistence_interval_secs);
+                if now.duration_since(last_persist_time) < persistence_interval {
+                    return;
+                }
+            }
+            *last_reputation_persist_time = Some(now);
+        }
+
+        // Forget the reputations of peers we haven't seen for too long
+        let now_unix_secs = self.time_service.now_secs();
+        self.peer_to_reputation.retain(|_, reputation| {
+            reputation.get_age_secs(now_unix_secs) <= peer_reputation_config.max_reputation_age_secs
+        });
+
+        // Gather the reputations of all connected and disconnected peers
+        let mut reputations: Vec<(PeerNetworkId, PeerReputation)> = self
+            .peer_to_state
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().get_reputation(now_unix_secs)))
+            .collect();
+        reputations.extend(
+            self.peer_to_reputation
+                .iter()
+                .map(|entry| (*entry.key(), entry.value().clone())),
+        );
+
+        // Persist the reputations
+        if let Err(error) = reputation_storage.save_reputations(&reputations) {
+            warn!(
+                (LogSchema::new(LogEntry::PeerStates)
+                    .event(LogEvent::PeerReputationStorageError)
+                    .message("Failed to persist the peer reputations!")
+                    .error(&error))
+            );
+        }
     }
 
     /// Calculates a global data summary using all known storage summaries
@@ -5,16 +5,21 @@ use crate::{
     client::AptosDataClient,
     error::Error,
     interface::AptosDataClientInterface,
+    peer_states::ErrorType,
     poller,
     poller::{poll_peer, DataSummaryPoller},
     tests::{mock::MockNetwork, utils},
 };
-use aptos_config::{config::AptosDataClientConfig, network_id::PeerNetworkId};
+use aptos_config::{
+    config::{AptosDataClientConfig, BaseConfig},
+    network_id::PeerNetworkId,
+};
 use aptos_storage_service_types::{
     requests::DataRequest,
     responses::{CompleteDataRange, DataResponse, StorageServerSummary, StorageServiceResponse},
     StorageServiceError,
 };
+use aptos_temppath::TempPath;
 use aptos_types::transaction::TransactionListWithProof;
 use claims::{assert_err, assert_matches};
 use maplit::hashset;
@@ -350,6 +355,97 @@ async fn disconnected_peers_garbage_collection() {
     }
 }
 
+#[tokio::test]
+async fn peer_reputation_persisted_across_restarts() {
+    // Create a base config with a temporary data directory
+    let data_dir = TempPath::new();
+    data_dir.create_as_dir().unwrap();
+    let base_config = BaseConfig {
+        data_dir: data_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    // Create the mock network and client, and add a peer
+    let (mut mock_network, _, client, _) = MockNetwork::new(Some(base_config.clone()), None, None);
+    let peer = mock_network.add_peer(true);
+    client.update_peer_storage_summary(peer, utils::create_storage_summary(100));
+
+    // Report malicious responses until the peer is ignored
+    for _ in 0..10 {
+        client
+            .get_peer_states()
+            .update_score_error(peer, ErrorType::Malicious);
+    }
+    let (ignored_score, ignore_reason) = get_peer_score_and_ignore_reason(&client, peer);
+    assert!(ignore_reason.unwrap().contains("malicious"));
+
+    // Persist the peer reputations
+    client.persist_peer_reputations_if_due();
+
+    // Restart the client and update the peer's storage summary
+    let (_, _, client, _) = MockNetwork::new(Some(base_config), None, None);
+    client.update_peer_storage_summary(peer, utils::create_storage_summary(100));
+
+    // Verify the peer is still ignored
+    let (score, ignore_reason) = get_peer_score_and_ignore_reason(&client, peer);
+    assert!((score - ignored_score).abs() < 1e-9);
+    assert!(ignore_reason.is_some());
+}
+
+#[tokio::test]
+async fn peer_reputation_decays_over_time() {
+    // Create the mock network and client, and add a peer
+    let data_client_config = AptosDataClientConfig::default();
+    let (mut mock_network, mock_time, client, _) =
+        MockNetwork::new(None, Some(data_client_config), None);
+    let peer = mock_network.add_peer(true);
+    client.update_peer_storage_summary(peer, utils::create_storage_summary(100));
+
+    // Report malicious responses until the peer is ignored
+    for _ in 0..10 {
+        client
+            .get_peer_states()
+            .update_score_error(peer, ErrorType::Malicious);
+    }
+    let (ignored_score, _) = get_peer_score_and_ignore_reason(&client, peer);
+
+    // Disconnect the peer (but keep another peer connected) and garbage collect the peer states
+    let _ = mock_network.add_peer(true);
+    mock_network.disconnect_peer(peer);
+    client.update_global_summary_cache().unwrap();
+
+    // Verify the reputation of the peer is remembered
+    let disconnected_peer_reputations =
+        client.get_peer_states().get_disconnected_peer_reputations();
+    assert_eq!(
+        disconnected_peer_reputations.get(&peer).unwrap().score,
+        ignored_score
+    );
+
+    // Elapse several score half-lives and reconnect the peer
+    let score_decay_half_life_secs = data_client_config
+        .peer_reputation_config
+        .score_decay_half_life_secs;
+    mock_time.advance_secs(10 * score_decay_half_life_secs);
+    mock_network.reconnect_peer(peer);
+    client.update_peer_storage_summary(peer, utils::create_storage_summary(100));
+
+    // Verify the peer's score has decayed back towards the starting score
+    let (score, ignore_reason) = get_peer_score_and_ignore_reason(&client, peer);
+    assert!(score > 49.9);
+    assert!(ignore_reason.is_none());
+}
+
+/// Returns the score and ignore reason of the specified peer
+fn get_peer_score_and_ignore_reason(
+    client: &AptosDataClient,
+    peer: PeerNetworkId,
+) -> (f64, Option<String>) {
+    let peer_to_states = client.get_peer_states().get_peer_to_states();
+    let peer_state = peer_to_states.get(&peer).unwrap();
+    (peer_state.get_score(), peer_state.get_ignore_reason())
+}
+
 /// A simple helper function that polls all the specified peers
 /// and returns storage server summaries for each.
 async fn poll_peers(
@@ -0,0 +1,16 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::transaction::Version;
+use async_trait::async_trait;
+
+/// The interface through which the bootstrapper restores storage from backups
+/// (e.g., those written by the backup CLI), so that only the data committed
+/// since the latest backup needs to be fetched from peers.
+#[async_trait]
+pub trait BackupRestorer: Send + Sync {
+    /// Restores the epoch ending ledger infos, the latest state snapshot and
+    /// the transactions after it from the backups (resuming any restore that
+    /// was interrupted). Returns the latest version restored.
+    async fn restore(&self) -> anyhow::Result<Version>;
+}
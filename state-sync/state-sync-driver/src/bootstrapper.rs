@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    backup_restorer::BackupRestorer,
     driver::DriverConfiguration,
     error::Error,
     logging::{LogEntry, LogSchema},
@@ -32,6 +33,7 @@ use aptos_types::{
 };
 use futures::channel::oneshot;
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
 
 // Useful bootstrapper constants
 const BOOTSTRAPPER_LOG_INTERVAL_SECS: u64 = 3;
@@ -290,6 +292,15 @@ pub struct Bootstrapper<MetadataStorage, StorageSyncer, StreamingClient> {
     // The currently active data stream (provided by the data streaming service)
     active_data_stream: Option<DataStreamListener>,
 
+    // The restorer used to restore storage from backups (if restoring from backups)
+    backup_restorer: Option<Arc<dyn BackupRestorer>>,
+
+    // The task restoring storage from backups (if a restore is in progress)
+    backup_restore_task: Option<JoinHandle<anyhow::Result<Version>>>,
+
+    // If the node has finished restoring from backups (or doesn't need to)
+    backup_restore_complete: bool,
+
     // The channel used to notify a listener of successful bootstrapping
     bootstrap_notifier_channel: Option<oneshot::Sender<Result<(), Error>>>,
 
@@ -320,6 +331,9 @@ pub struct Bootstrapper<MetadataStorage, StorageSyncer, StreamingClient> {
     // The storage synchronizer used to update local storage
     storage_synchronizer: StorageSyncer,
 
+    // The version restored from backups (if the driver is yet to be notified)
+    unnotified_restored_version: Option<Version>,
+
     // The epoch states verified by this node (held in memory)
     verified_epoch_states: VerifiedEpochStates,
 }
@@ -337,6 +351,7 @@ impl<
         streaming_client: StreamingClient,
         storage: Arc<dyn DbReader>,
         storage_synchronizer: StorageSyncer,
+        backup_restorer: Option<Arc<dyn BackupRestorer>>,
     ) -> Self {
         // Load the latest epoch state from storage
         let latest_epoch_state = utils::fetch_latest_epoch_state(storage.clone())
@@ -346,6 +361,9 @@ impl<
         Self {
             state_value_syncer: StateValueSyncer::new(),
             active_data_stream: None,
+            backup_restorer,
+            backup_restore_task: None,
+            backup_restore_complete: false,
             bootstrap_notifier_channel: None,
             bootstrapped: false,
             driver_configuration,
@@ -355,6 +373,7 @@ impl<
             streaming_client,
             storage,
             storage_synchronizer,
+            unnotified_restored_version: None,
             verified_epoch_states,
         }
     }
@@ -430,6 +449,13 @@ impl<
                 SampleRate::Duration(Duration::from_secs(PENDING_DATA_LOG_FREQ_SECS)),
                 info!("Waiting for the storage synchronizer to handle pending data!")
             );
+        } else if !self.drive_backup_restore().await? {
+            // Wait for the restore from backups to complete
+            sample!(
+                SampleRate::Duration(Duration::from_secs(BOOTSTRAPPER_LOG_INTERVAL_SECS)),
+                info!(LogSchema::new(LogEntry::Bootstrapper)
+                    .message("Waiting for the restore from backups to complete!"))
+            );
         } else {
             // Fetch a new data stream to start streaming data
             self.initialize_active_data_stream(global_data_summary)
@@ -440,6 +466,113 @@ impl<
         self.notify_listeners_if_bootstrapped().await
     }
 
+    /// Drives the restore of storage from backups (if the node is restoring from
+    /// backups). Returns true iff the restore is complete (or isn't required),
+    /// i.e., the node can sync the remaining data from its peers.
+    async fn drive_backup_restore(&mut self) -> Result<bool, Error> {
+        if self.backup_restore_complete {
+            return Ok(true);
+        }
+
+        // Only restore if the node is configured to do so
+        let backup_restorer = match &self.backup_restorer {
+            Some(backup_restorer) if self.get_bootstrapping_mode().is_restore_from_backup() => {
+                backup_restorer.clone()
+            },
+            _ => {
+                self.backup_restore_complete = true;
+                return Ok(true);
+            },
+        };
+
+        // Check the progress of any active restore
+        if let Some(backup_restore_task) = &self.backup_restore_task {
+            if !backup_restore_task.is_finished() {
+                return Ok(false);
+            }
+            let restore_result = self
+                .backup_restore_task
+                .take()
+                .expect("The backup restore task must exist!")
+                .await
+                .map_err(|error| {
+                    Error::UnexpectedError(format!(
+                        "The backup restore task failed to complete: {:?}",
+                        error
+                    ))
+                })?;
+            let restored_version = restore_result.map_err(|error| {
+                Error::StorageError(format!("Failed to restore from backups: {:?}", error))
+            })?;
+            self.metadata_storage
+                .update_backup_restore_progress(Some(restored_version))?;
+            return self
+                .backup_restore_completed(restored_version)
+                .map(|_| true);
+        }
+
+        // Check if a restore is required
+        match self.metadata_storage.previous_backup_restore()? {
+            Some(restore_progress) => {
+                if let Some(restored_version) = restore_progress.restored_version {
+                    info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                        "The restore from backups has already completed! Restored version: {}",
+                        restored_version
+                    )));
+                    self.backup_restore_complete = true;
+                    return Ok(true);
+                }
+                info!(LogSchema::new(LogEntry::Bootstrapper)
+                    .message("Resuming the previously interrupted restore from backups!"));
+            },
+            None => {
+                // Backups can only be restored into an empty database
+                let highest_synced_version =
+                    utils::fetch_latest_synced_version(self.storage.clone())?;
+                if highest_synced_version != GENESIS_TRANSACTION_VERSION {
+                    info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                        "Skipping the restore from backups, the node has already synced to version {}!",
+                        highest_synced_version
+                    )));
+                    self.backup_restore_complete = true;
+                    return Ok(true);
+                }
+                info!(LogSchema::new(LogEntry::Bootstrapper)
+                    .message("Starting to restore from backups!"));
+                self.metadata_storage.update_backup_restore_progress(None)?;
+            },
+        }
+
+        // Start the restore in the background, so the driver remains responsive
+        self.backup_restore_task =
+            Some(tokio::spawn(async move { backup_restorer.restore().await }));
+        Ok(false)
+    }
+
+    /// Updates the bootstrapper once storage has been restored from backups
+    fn backup_restore_completed(&mut self, restored_version: Version) -> Result<(), Error> {
+        info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+            "The restore from backups is complete! Restored version: {}",
+            restored_version
+        )));
+
+        // The restored epoch history was verified against the waypoint (if it
+        // was restored beyond it), so trust the latest epoch state in storage.
+        let latest_epoch_state = utils::fetch_latest_epoch_state(self.storage.clone())?;
+        self.verified_epoch_states = VerifiedEpochStates::new(latest_epoch_state);
+
+        self.backup_restore_complete = true;
+        self.unnotified_restored_version = Some(restored_version);
+        Ok(())
+    }
+
+    /// Returns the version restored from backups (if the restore has completed
+    /// since the last call). This allows the driver to notify subscribers of
+    /// the restored on-chain configs.
+    pub fn take_restored_version(&mut self) -> Option<Version> {
+        self.unnotified_restored_version.take()
+    }
+
     /// Returns true iff the bootstrapper should continue to fetch epoch ending
     /// ledger infos (in order to make progress).
     fn should_fetch_epoch_ending_ledger_infos(&self) -> bool {
@@ -757,7 +890,7 @@ impl<
                     )
                     .await?
             },
-            BootstrappingMode::ExecuteOrApplyFromGenesis => {
+            BootstrappingMode::ExecuteOrApplyFromGenesis | BootstrappingMode::RestoreFromBackup => {
                 if self.output_fallback_handler.in_fallback_mode() {
                     metrics::set_gauge(
                         &metrics::DRIVER_FALLBACK_MODE,
@@ -1203,7 +1336,7 @@ impl<
                     ));
                 }
             },
-            BootstrappingMode::ExecuteOrApplyFromGenesis => {
+            BootstrappingMode::ExecuteOrApplyFromGenesis | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_list_with_proof) = transaction_list_with_proof {
                     utils::execute_transactions(
                         self.storage_synchronizer.clone(),
@@ -1390,7 +1523,7 @@ impl<
                     ));
                 }
             },
-            BootstrappingMode::ExecuteOrApplyFromGenesis => {
+            BootstrappingMode::ExecuteOrApplyFromGenesis | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_list_with_proof) = transaction_list_with_proof {
                     transaction_list_with_proof.transactions.len()
                 } else if let Some(output_list_with_proof) = transaction_outputs_with_proof {
@@ -1503,7 +1636,9 @@ impl<
             .await?;
 
         // Fallback to output syncing if we need to
-        if let BootstrappingMode::ExecuteOrApplyFromGenesis = self.get_bootstrapping_mode() {
+        if let BootstrappingMode::ExecuteOrApplyFromGenesis | BootstrappingMode::RestoreFromBackup =
+            self.get_bootstrapping_mode()
+        {
             self.output_fallback_handler.fallback_to_outputs();
             metrics::set_gauge(
                 &metrics::DRIVER_FALLBACK_MODE,
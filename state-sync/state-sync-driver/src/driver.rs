@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    backup_restorer::BackupRestorer,
     bootstrapper::Bootstrapper,
     continuous_syncer::ContinuousSyncer,
     driver_client::{ClientNotificationListener, DriverNotification},
@@ -28,7 +29,7 @@ use aptos_data_client::interface::AptosDataClientInterface;
 use aptos_data_streaming_service::streaming_client::{
     DataStreamingClient, NotificationAndFeedback, NotificationFeedback,
 };
-use aptos_event_notifications::EventSubscriptionService;
+use aptos_event_notifications::{EventNotificationSender, EventSubscriptionService};
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_mempool_notifications::MempoolNotificationSender;
@@ -159,6 +160,7 @@ impl<
         streaming_client: StreamingClient,
         storage: Arc<dyn DbReader>,
         time_service: TimeService,
+        backup_restorer: Option<Arc<dyn BackupRestorer>>,
     ) -> Self {
         let output_fallback_handler =
             OutputFallbackHandler::new(driver_configuration.clone(), time_service.clone());
@@ -169,6 +171,7 @@ impl<
             streaming_client.clone(),
             storage.clone(),
             storage_synchronizer.clone(),
+            backup_restorer,
         );
         let continuous_syncer = ContinuousSyncer::new(
             driver_configuration.clone(),
@@ -644,6 +647,20 @@ impl<
                 );
                 metrics::increment_counter(&metrics::BOOTSTRAPPER_ERRORS, error.get_label());
             }
+
+            // Notify the subscribers of the on-chain configs restored from backups
+            if let Some(restored_version) = self.bootstrapper.take_restored_version() {
+                if let Err(error) = self
+                    .event_subscription_service
+                    .lock()
+                    .notify_initial_configs(restored_version)
+                {
+                    error!(LogSchema::new(LogEntry::Driver).message(&format!(
+                        "Failed to notify subscribers of the restored on-chain configs! Error: {:?}",
+                        error
+                    )));
+                }
+            }
         };
     }
 }
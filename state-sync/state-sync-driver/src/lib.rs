@@ -4,6 +4,7 @@
 
 #![forbid(unsafe_code)]
 
+pub mod backup_restorer;
 mod bootstrapper;
 mod continuous_syncer;
 mod driver;
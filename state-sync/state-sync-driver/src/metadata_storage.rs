@@ -12,7 +12,7 @@ use aptos_schemadb::{
     schema::{KeyCodec, ValueCodec},
     ColumnFamilyName, Options, SchemaBatch, DB,
 };
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use serde::{Deserialize, Serialize};
 use std::{path::Path, sync::Arc, time::Instant};
 
@@ -47,6 +47,17 @@ pub trait MetadataStorageInterface {
         last_persisted_state_value_index: u64,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error>;
+
+    /// Returns the progress of any restore from backups that has previously
+    /// started. If no restore started, None is returned.
+    fn previous_backup_restore(&self) -> Result<Option<BackupRestoreProgress>, Error>;
+
+    /// Updates the progress of the restore from backups. The restored version
+    /// should only be specified once the restore has completed.
+    fn update_backup_restore_progress(
+        &self,
+        restored_version: Option<Version>,
+    ) -> Result<(), Error>;
 }
 
 /// The name of the state sync db file
@@ -106,10 +117,35 @@ impl PersistentMetadataStorage {
                     ))
                 })?;
         match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the existing backup restore progress. Returns None if no progress is found.
+    fn get_backup_restore_progress(&self) -> Result<Option<BackupRestoreProgress>, Error> {
+        let metadata_key = MetadataKey::BackupRestore;
+        let maybe_metadata_value =
+            self.database
+                .get::<MetadataSchema>(&metadata_key)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to read metadata value for key: {:?}. Error: {:?}",
+                        metadata_key, error
+                    ))
+                })?;
+        match maybe_metadata_value {
+            Some(MetadataValue::BackupRestore(restore_progress)) => Ok(Some(restore_progress)),
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
@@ -224,6 +260,20 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         // Insert the new key/value pair
         self.commit_key_value(metadata_key, metadata_value)
     }
+
+    fn previous_backup_restore(&self) -> Result<Option<BackupRestoreProgress>, Error> {
+        self.get_backup_restore_progress()
+    }
+
+    fn update_backup_restore_progress(
+        &self,
+        restored_version: Option<Version>,
+    ) -> Result<(), Error> {
+        let metadata_key = MetadataKey::BackupRestore;
+        let metadata_value =
+            MetadataValue::BackupRestore(BackupRestoreProgress { restored_version });
+        self.commit_key_value(metadata_key, metadata_value)
+    }
 }
 
 /// A simple struct for recording the progress of a state snapshot sync
@@ -234,6 +284,12 @@ pub struct StateSnapshotProgress {
     pub snapshot_sync_completed: bool,
 }
 
+/// A simple struct for recording the progress of a restore from backups
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BackupRestoreProgress {
+    pub restored_version: Option<Version>, // The restored version (once the restore completes)
+}
+
 /// The raw schema format used by the database
 pub mod database_schema {
     use super::*;
@@ -252,6 +308,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataKey {
         StateSnapshotSync, // A state snapshot sync that was started
+        BackupRestore,     // A restore from backups that was started
     }
 
     /// A metadata value that can be inserted into the database
@@ -259,6 +316,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        BackupRestore(BackupRestoreProgress),     // A backup restore progress marker
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
@@ -5,10 +5,11 @@ use crate::{
     bootstrapper::{Bootstrapper, GENESIS_TRANSACTION_VERSION},
     driver::DriverConfiguration,
     error::Error,
+    metadata_storage::BackupRestoreProgress,
     tests::{
         mocks::{
             create_mock_db_reader, create_mock_streaming_client, create_ready_storage_synchronizer,
-            MockMetadataStorage, MockStorageSynchronizer, MockStreamingClient,
+            MockBackupRestorer, MockMetadataStorage, MockStorageSynchronizer, MockStreamingClient,
         },
         utils::{
             create_data_stream_listener, create_empty_epoch_state, create_epoch_ending_ledger_info,
@@ -789,6 +790,98 @@ async fn test_fetch_epoch_ending_ledger_infos() {
     assert_matches!(error, Error::DataStreamNotificationTimeout(_));
 }
 
+#[tokio::test]
+async fn test_restore_from_backup() {
+    // Create a driver configuration with a genesis waypoint and restoring from backups
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::RestoreFromBackup;
+
+    // Create the mock metadata storage and expect the restore progress to be recorded
+    let restored_version = 1000;
+    let mut mock_metadata_storage = MockMetadataStorage::new();
+    mock_metadata_storage
+        .expect_previous_backup_restore()
+        .times(1)
+        .returning(|| Ok(None));
+    let mut restore_sequence = Sequence::new();
+    mock_metadata_storage
+        .expect_update_backup_restore_progress()
+        .with(eq(None))
+        .times(1)
+        .in_sequence(&mut restore_sequence)
+        .returning(|_| Ok(()));
+    mock_metadata_storage
+        .expect_update_backup_restore_progress()
+        .with(eq(Some(restored_version)))
+        .times(1)
+        .in_sequence(&mut restore_sequence)
+        .returning(|_| Ok(()));
+
+    // Create the mock backup restorer and expect a single restore
+    let mut mock_backup_restorer = MockBackupRestorer::new();
+    mock_backup_restorer
+        .expect_restore()
+        .times(1)
+        .returning(move || Ok(restored_version));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_backup_restorer(
+        driver_configuration,
+        mock_metadata_storage,
+        mock_backup_restorer,
+    );
+
+    // Drive progress until the restore completes and verify the node bootstraps
+    let global_data_summary = create_global_summary(0);
+    while !bootstrapper.is_bootstrapped() {
+        drive_progress(&mut bootstrapper, &global_data_summary, false)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Verify the restored version is only reported once
+    assert_eq!(bootstrapper.take_restored_version(), Some(restored_version));
+    assert_none!(bootstrapper.take_restored_version());
+}
+
+#[tokio::test]
+async fn test_restore_from_backup_already_complete() {
+    // Create a driver configuration with a genesis waypoint and restoring from backups
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::RestoreFromBackup;
+
+    // Create the mock metadata storage with a completed restore
+    let mut mock_metadata_storage = MockMetadataStorage::new();
+    mock_metadata_storage
+        .expect_previous_backup_restore()
+        .times(1)
+        .returning(|| {
+            Ok(Some(BackupRestoreProgress {
+                restored_version: Some(1000),
+            }))
+        });
+
+    // Create the mock backup restorer and expect no restores
+    let mut mock_backup_restorer = MockBackupRestorer::new();
+    mock_backup_restorer.expect_restore().times(0);
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_backup_restorer(
+        driver_configuration,
+        mock_metadata_storage,
+        mock_backup_restorer,
+    );
+
+    // Drive progress and verify the node bootstraps without restoring
+    let global_data_summary = create_global_summary(0);
+    drive_progress(&mut bootstrapper, &global_data_summary, true)
+        .await
+        .unwrap();
+    assert!(bootstrapper.is_bootstrapped());
+    assert_none!(bootstrapper.take_restored_version());
+}
+
 #[tokio::test]
 async fn test_snapshot_sync_epoch_change() {
     // Create test data
@@ -1464,6 +1557,7 @@ fn create_bootstrapper(
         mock_streaming_client,
         Arc::new(mock_database_reader),
         mock_storage_synchronizer,
+        None,
     );
 
     (bootstrapper, output_fallback_handler)
@@ -1519,6 +1613,47 @@ fn create_bootstrapper_with_storage(
         mock_streaming_client,
         Arc::new(mock_database_reader),
         mock_storage_synchronizer,
+        None,
+    )
+}
+
+/// Creates a bootstrapper for testing with a mock backup restorer and
+/// a mock metadata storage (with only genesis loaded in storage).
+fn create_bootstrapper_with_backup_restorer(
+    driver_configuration: DriverConfiguration,
+    mock_metadata_storage: MockMetadataStorage,
+    mock_backup_restorer: MockBackupRestorer,
+) -> Bootstrapper<MockMetadataStorage, MockStorageSynchronizer, MockStreamingClient> {
+    // Initialize the logger for tests
+    aptos_logger::Logger::init_for_testing();
+
+    // Create the mock storage synchronizer
+    let mock_storage_synchronizer = create_ready_storage_synchronizer(true);
+
+    // Create the mock db reader with only genesis loaded
+    let mut mock_database_reader = create_mock_db_reader();
+    mock_database_reader
+        .expect_get_latest_epoch_state()
+        .returning(|| Ok(create_empty_epoch_state()));
+    mock_database_reader
+        .expect_get_latest_ledger_info()
+        .returning(|| Ok(create_epoch_ending_ledger_info()));
+    mock_database_reader
+        .expect_get_latest_version()
+        .returning(|| Ok(0));
+
+    // Create the output fallback handler
+    let output_fallback_handler =
+        OutputFallbackHandler::new(driver_configuration.clone(), TimeService::mock());
+
+    Bootstrapper::new(
+        driver_configuration,
+        mock_metadata_storage,
+        output_fallback_handler,
+        create_mock_streaming_client(),
+        Arc::new(mock_database_reader),
+        mock_storage_synchronizer,
+        Some(Arc::new(mock_backup_restorer)),
     )
 }
 
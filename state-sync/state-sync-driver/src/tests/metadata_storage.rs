@@ -4,7 +4,8 @@
 use crate::{
     metadata_storage::{
         database_schema::{MetadataKey, MetadataSchema, MetadataValue},
-        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress,
+        BackupRestoreProgress, MetadataStorageInterface, PersistentMetadataStorage,
+        StateSnapshotProgress,
     },
     tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
@@ -99,6 +100,12 @@ fn test_metadata_schema_encode_decode() {
             snapshot_sync_completed: false,
         }),
     );
+    assert_encode_decode::<MetadataSchema>(
+        &MetadataKey::BackupRestore,
+        &MetadataValue::BackupRestore(BackupRestoreProgress {
+            restored_version: Some(1234),
+        }),
+    );
 }
 
 #[test]
@@ -167,3 +174,45 @@ fn test_writes_to_different_targets() {
         .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
         .unwrap_err();
 }
+
+#[test]
+fn test_backup_restore_progress() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify the storage is empty
+    assert_none!(metadata_storage.previous_backup_restore().unwrap());
+
+    // Start a restore and verify it's in progress
+    metadata_storage
+        .update_backup_restore_progress(None)
+        .unwrap();
+    assert_eq!(
+        Some(BackupRestoreProgress {
+            restored_version: None
+        }),
+        metadata_storage.previous_backup_restore().unwrap()
+    );
+
+    // Complete the restore
+    let restored_version = 98765;
+    metadata_storage
+        .update_backup_restore_progress(Some(restored_version))
+        .unwrap();
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+
+    // Create another storage (it should reopen the existing file) and verify the state
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    assert_eq!(
+        Some(BackupRestoreProgress {
+            restored_version: Some(restored_version)
+        }),
+        metadata_storage.previous_backup_restore().unwrap()
+    );
+
+    // Verify the restore progress doesn't affect the snapshot sync progress
+    assert_none!(metadata_storage.previous_snapshot_sync_target().unwrap());
+}
@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    backup_restorer::BackupRestorer,
     error::Error,
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{BackupRestoreProgress, MetadataStorageInterface},
     storage_synchronizer::StorageSynchronizerInterface,
     tests::utils::{create_empty_epoch_state, create_epoch_ending_ledger_info},
 };
@@ -331,6 +332,15 @@ mock! {
     }
 }
 
+// This automatically creates a MockBackupRestorer.
+mock! {
+    pub BackupRestorer {}
+    #[async_trait]
+    impl BackupRestorer for BackupRestorer {
+        async fn restore(&self) -> Result<Version>;
+    }
+}
+
 // This automatically creates a MockMetadataStorage.
 mock! {
     pub MetadataStorage {}
@@ -353,6 +363,13 @@ mock! {
             last_persisted_state_value_index: u64,
             snapshot_sync_completed: bool,
         ) -> Result<(), Error>;
+
+        fn previous_backup_restore(&self) -> Result<Option<BackupRestoreProgress>, Error>;
+
+        fn update_backup_restore_progress(
+            &self,
+            restored_version: Option<Version>,
+        ) -> Result<(), Error>;
     }
 
     impl Clone for MetadataStorage {
@@ -7,6 +7,7 @@ mod examine;
 pub mod ledger;
 pub mod state_tree;
 pub mod truncate;
+pub mod verify_all;
 
 use anyhow::Result;
 use clap::Parser;
@@ -31,6 +32,8 @@ pub enum Cmd {
 
     #[clap(subcommand)]
     Examine(examine::Cmd),
+
+    VerifyAll(verify_all::Cmd),
 }
 
 impl Cmd {
@@ -41,6 +44,7 @@ impl Cmd {
             Cmd::Ledger(cmd) => cmd.run(),
             Cmd::Truncate(cmd) => cmd.run(),
             Cmd::Examine(cmd) => cmd.run(),
+            Cmd::VerifyAll(cmd) => cmd.run(),
         }
     }
 }
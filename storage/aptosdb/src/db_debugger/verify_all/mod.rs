@@ -0,0 +1,501 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod report;
+mod state_root;
+#[cfg(test)]
+mod test;
+
+use crate::{
+    db::AptosDB,
+    db_debugger::{
+        common::DbDir,
+        verify_all::{
+            report::{CheckResult, Report, ReportWriter},
+            state_root::StateRootHasher,
+        },
+    },
+    pruner::PrunerManager,
+};
+use anyhow::{ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::{
+    epoch_change::Verifier, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    proof::accumulator::InMemoryEventAccumulator, transaction::Version,
+    write_set::TransactionWrite,
+};
+use clap::Parser;
+use rayon::prelude::*;
+use std::{collections::BTreeSet, path::PathBuf, sync::Mutex};
+
+#[derive(Parser)]
+#[clap(
+    about = "Verify the whole DB: the transaction accumulator against the ledger infos, events and \
+    write sets against the transaction infos, write sets against the state KV entries, and the \
+    root hash of each retained state snapshot."
+)]
+pub struct Cmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+
+    /// JSON file to write the report to. If it exists, the run is resumed, skipping the checks
+    /// already recorded in it.
+    #[clap(long, value_parser)]
+    report: PathBuf,
+
+    #[clap(long, default_value_t = 10000)]
+    batch_size: u64,
+
+    /// Number of threads to check with, defaults to the number of CPUs.
+    #[clap(long)]
+    concurrency: Option<usize>,
+
+    #[clap(long)]
+    skip_state_snapshots: bool,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        ensure!(
+            self.batch_size > 0 && self.batch_size <= MAX_REQUEST_LIMIT,
+            "Batch size must be in [1, {}].",
+            MAX_REQUEST_LIMIT,
+        );
+        let db = self.db_dir.open_aptos_db_readonly()?;
+        let verifier = DbVerifier::new(&db)?;
+        let report = Report::load_or(
+            &self.report,
+            Report::new(
+                verifier.ledger_version(),
+                verifier.ledger_accumulator_root_hash(),
+                verifier.first_version,
+                self.batch_size,
+            ),
+        )?;
+        if report.finished {
+            println!("Report at {:?} is already finished.", self.report);
+        }
+        let writer = Mutex::new(ReportWriter::new(self.report.clone(), report));
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.concurrency.unwrap_or_else(num_cpus::get))
+            .thread_name(|index| format!("db-verify-{}", index))
+            .build()?;
+        pool.install(|| verifier.run(&writer, self.batch_size, self.skip_state_snapshots))?;
+
+        let mut writer = writer.into_inner().expect("Lock poisoned.");
+        writer.update(|report| report.finished = true)?;
+        writer.save()?;
+        let report = writer.into_report();
+
+        println!(
+            "Verified ledger infos, {} transactions, {} state snapshots.",
+            report
+                .transaction_batches
+                .values()
+                .map(|result| result.num_checked)
+                .sum::<u64>(),
+            report.state_snapshots.len(),
+        );
+        for note in report
+            .ledger_infos
+            .iter()
+            .chain(report.transaction_batches.values())
+            .chain(report.state_snapshots.values())
+            .flat_map(|result| &result.notes)
+        {
+            println!("Note: {}", note);
+        }
+        let num_errors = report.num_errors();
+        ensure!(
+            num_errors == 0,
+            "Found {} errors, see {:?} for details.",
+            num_errors,
+            self.report,
+        );
+        println!("No errors found. Report written to {:?}.", self.report);
+        Ok(())
+    }
+}
+
+fn record(writer: &Mutex<ReportWriter>, f: impl FnOnce(&mut Report)) -> Result<()> {
+    writer.lock().expect("Lock poisoned.").update(f)
+}
+
+struct DbVerifier<'a> {
+    db: &'a AptosDB,
+    latest_ledger_info: LedgerInfoWithSignatures,
+    /// First version not pruned from the ledger.
+    first_version: Version,
+    /// First version not pruned from the state KV DB.
+    first_state_kv_version: Version,
+}
+
+impl<'a> DbVerifier<'a> {
+    fn new(db: &'a AptosDB) -> Result<Self> {
+        Ok(Self {
+            db,
+            latest_ledger_info: db.get_latest_ledger_info()?,
+            first_version: db.get_first_txn_version()?.unwrap_or(0),
+            first_state_kv_version: db
+                .state_store
+                .state_db
+                .state_kv_pruner
+                .get_min_readable_version(),
+        })
+    }
+
+    fn ledger_version(&self) -> Version {
+        self.latest_ledger_info.ledger_info().version()
+    }
+
+    fn ledger_accumulator_root_hash(&self) -> HashValue {
+        self.latest_ledger_info
+            .ledger_info()
+            .transaction_accumulator_hash()
+    }
+
+    fn run(
+        &self,
+        writer: &Mutex<ReportWriter>,
+        batch_size: u64,
+        skip_state_snapshots: bool,
+    ) -> Result<()> {
+        let (ledger_infos_checked, checked_batches, checked_snapshots) = {
+            let writer = writer.lock().expect("Lock poisoned.");
+            let report = writer.report();
+            (
+                report.ledger_infos.is_some(),
+                report
+                    .transaction_batches
+                    .keys()
+                    .cloned()
+                    .collect::<BTreeSet<_>>(),
+                report
+                    .state_snapshots
+                    .keys()
+                    .cloned()
+                    .collect::<BTreeSet<_>>(),
+            )
+        };
+        let check_ledger_infos = || -> Result<()> {
+            if !ledger_infos_checked {
+                let result = self.check_ledger_infos();
+                record(writer, |report| report.ledger_infos = Some(result))?;
+            }
+            Ok(())
+        };
+        let check_transactions = || -> Result<()> {
+            (self.first_version..=self.ledger_version())
+                .step_by(batch_size as usize)
+                .filter(|first| !checked_batches.contains(first))
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .try_for_each(|first| {
+                    let limit = std::cmp::min(batch_size, self.ledger_version() + 1 - first);
+                    let result = self.check_transactions(first, limit);
+                    record(writer, |report| {
+                        report.transaction_batches.insert(first, result);
+                    })
+                })
+        };
+        let (ledger_infos_res, transactions_res) =
+            rayon::join(check_ledger_infos, check_transactions);
+        ledger_infos_res?;
+        transactions_res?;
+
+        if skip_state_snapshots {
+            return Ok(());
+        }
+        self.retained_state_snapshots()?
+            .into_par_iter()
+            .filter(|(version, _)| !checked_snapshots.contains(version))
+            .try_for_each(|(version, root_hash)| {
+                let result = self.check_state_snapshot(version, root_hash);
+                record(writer, |report| {
+                    report.state_snapshots.insert(version, result);
+                })
+            })
+    }
+
+    /// Checks the chain of epoch ending ledger infos and the latest ledger info, each signed by
+    /// the validators of its epoch, set in the previous one, and their transaction accumulator
+    /// root hashes against the accumulator in the DB.
+    fn check_ledger_infos(&self) -> CheckResult {
+        let mut result = CheckResult::default();
+        if let Err(err) = self.check_ledger_infos_impl(&mut result) {
+            result.error(format!("Failed to check ledger infos: {:?}", err));
+        }
+        result
+    }
+
+    fn check_ledger_infos_impl(&self, result: &mut CheckResult) -> Result<()> {
+        let next_epoch = self.latest_ledger_info.ledger_info().next_block_epoch();
+        let mut epoch = 0;
+        // The genesis is trusted by its waypoint instead of signatures.
+        let mut epoch_state: Option<EpochState> = None;
+        while epoch < next_epoch {
+            let ledger_infos = self
+                .db
+                .get_epoch_ending_ledger_infos(epoch, next_epoch)?
+                .ledger_info_with_sigs;
+            ensure!(
+                !ledger_infos.is_empty(),
+                "No epoch ending ledger info for epoch {}.",
+                epoch,
+            );
+            for li in ledger_infos {
+                ensure!(
+                    li.ledger_info().epoch() == epoch,
+                    "Expected epoch ending ledger info for epoch {}, got {}.",
+                    epoch,
+                    li.ledger_info().epoch(),
+                );
+                self.check_ledger_info(&li, epoch_state.as_ref(), result)?;
+                epoch_state = li.ledger_info().next_epoch_state().cloned();
+                ensure!(
+                    epoch_state.is_some(),
+                    "Epoch ending ledger info for epoch {} has no next epoch state.",
+                    epoch,
+                );
+                epoch += 1;
+            }
+        }
+        if !self.latest_ledger_info.ledger_info().ends_epoch() {
+            self.check_ledger_info(&self.latest_ledger_info, epoch_state.as_ref(), result)?;
+        }
+        Ok(())
+    }
+
+    fn check_ledger_info(
+        &self,
+        li: &LedgerInfoWithSignatures,
+        epoch_state: Option<&EpochState>,
+        result: &mut CheckResult,
+    ) -> Result<()> {
+        let version = li.ledger_info().version();
+        if let Some(epoch_state) = epoch_state {
+            if let Err(err) = epoch_state.verify(li) {
+                result.error(format!(
+                    "Ledger info at version {} not properly signed: {}",
+                    version, err
+                ));
+            }
+        }
+        if version >= self.first_version {
+            let root_hash = self.db.get_accumulator_root_hash(version)?;
+            if root_hash != li.ledger_info().transaction_accumulator_hash() {
+                result.error(format!(
+                    "Transaction accumulator root hash at version {} is {}, but {} in the \
+                     ledger info.",
+                    version,
+                    root_hash,
+                    li.ledger_info().transaction_accumulator_hash(),
+                ));
+            }
+        } else {
+            result.note(format!(
+                "Transaction accumulator pruned at version {}, not checked against the ledger \
+                 info.",
+                version
+            ));
+        }
+        result.num_checked += 1;
+        Ok(())
+    }
+
+    /// Checks the transaction infos in [`first`, `first + limit`) against the latest ledger
+    /// info, and the transactions, events and write sets against the transaction infos, and the
+    /// write sets against the state KV entries.
+    fn check_transactions(&self, first: Version, limit: u64) -> CheckResult {
+        let mut result = CheckResult::default();
+        if let Err(err) = self.check_transactions_impl(first, limit, &mut result) {
+            result.error(format!(
+                "Failed to check transactions [{}, {}): {:?}",
+                first,
+                first + limit,
+                err
+            ));
+        }
+        result
+    }
+
+    fn check_transactions_impl(
+        &self,
+        first: Version,
+        limit: u64,
+        result: &mut CheckResult,
+    ) -> Result<()> {
+        let txn_infos = self
+            .db
+            .get_transaction_info_iterator(first, limit)?
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            txn_infos.len() as u64 == limit,
+            "Expected {} transaction infos, got {}.",
+            limit,
+            txn_infos.len(),
+        );
+        let txn_info_hashes: Vec<_> = txn_infos.iter().map(CryptoHash::hash).collect();
+        if let Err(err) = self
+            .db
+            .get_transaction_accumulator_range_proof(first, limit, self.ledger_version())?
+            .verify(
+                self.ledger_accumulator_root_hash(),
+                Some(first),
+                &txn_info_hashes,
+            )
+        {
+            result.error(format!(
+                "Transaction infos [{}, {}) don't match the accumulator root hash: {}",
+                first,
+                first + limit,
+                err
+            ));
+        }
+
+        let txns = self.db.get_transaction_iterator(first, limit)?;
+        let events = self.db.get_events_iterator(first, limit)?;
+        let write_sets = self.db.get_write_set_iterator(first, limit)?;
+        let mut num_checked = 0;
+        for (version, ((txn_info, txn), (events, write_set))) in
+            (first..).zip(txn_infos.iter().zip(txns).zip(events.zip(write_sets)))
+        {
+            let (txn, events, write_set) = (txn?, events?, write_set?);
+            if txn.hash() != txn_info.transaction_hash() {
+                result.error(format!("Transaction hash mismatch at version {}.", version));
+            }
+            let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+            if InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash()
+                != txn_info.event_root_hash()
+            {
+                result.error(format!("Event root hash mismatch at version {}.", version));
+            }
+            if CryptoHash::hash(&write_set) != txn_info.state_change_hash() {
+                result.error(format!("Write set hash mismatch at version {}.", version));
+            }
+            if version >= self.first_state_kv_version {
+                for (key, op) in write_set.iter() {
+                    if self.db.get_state_value_by_version(key, version)? != op.as_state_value() {
+                        result.error(format!(
+                            "State value of {:?} at version {} doesn't match the write set.",
+                            key, version
+                        ));
+                    }
+                }
+            }
+            num_checked += 1;
+        }
+        ensure!(
+            num_checked == limit,
+            "Expected {} transactions, events and write sets, got {}.",
+            limit,
+            num_checked,
+        );
+        result.num_checked = num_checked;
+        if first < self.first_state_kv_version {
+            result.note(format!(
+                "State KV pruned before version {}, write sets before it not checked against it.",
+                self.first_state_kv_version
+            ));
+        }
+        Ok(())
+    }
+
+    /// State snapshots not pruned, latest first: all at or after the min readable version of the
+    /// state merkle pruner, and epoch ending ones at or after that of the epoch snapshot pruner.
+    fn retained_state_snapshots(&self) -> Result<Vec<(Version, HashValue)>> {
+        let state_db = &self.db.state_store.state_db;
+        let first_snapshot_version = state_db.state_merkle_pruner.get_min_readable_version();
+        let first_epoch_snapshot_version =
+            state_db.epoch_snapshot_pruner.get_min_readable_version();
+
+        let mut snapshots = vec![];
+        let mut next_version = self.ledger_version() + 1;
+        while let Some((version, root_hash)) = self
+            .db
+            .state_store
+            .get_state_snapshot_before(next_version)?
+        {
+            if version < first_snapshot_version.min(first_epoch_snapshot_version) {
+                break;
+            }
+            if version >= first_snapshot_version
+                || self.db.ledger_store.ensure_epoch_ending(version).is_ok()
+            {
+                snapshots.push((version, root_hash));
+            }
+            next_version = version;
+        }
+        Ok(snapshots)
+    }
+
+    /// Checks the root hash of the snapshot against the transaction info, and recomputes it from
+    /// the leaves of the tree, i.e. the state KV entries.
+    fn check_state_snapshot(&self, version: Version, root_hash: HashValue) -> CheckResult {
+        let mut result = CheckResult::default();
+        if let Err(err) = self.check_state_snapshot_impl(version, root_hash, &mut result) {
+            result.error(format!(
+                "Failed to check state snapshot at version {}: {:?}",
+                version, err
+            ));
+        }
+        result
+    }
+
+    fn check_state_snapshot_impl(
+        &self,
+        version: Version,
+        root_hash: HashValue,
+        result: &mut CheckResult,
+    ) -> Result<()> {
+        if version >= self.first_version {
+            let txn_info = self
+                .db
+                .get_transaction_info_iterator(version, 1)?
+                .next()
+                .transpose()?;
+            let state_checkpoint_hash = txn_info.and_then(|info| info.state_checkpoint_hash());
+            if state_checkpoint_hash != Some(root_hash) {
+                result.error(format!(
+                    "State snapshot root hash at version {} is {}, but {:?} in the transaction \
+                     info.",
+                    version, root_hash, state_checkpoint_hash,
+                ));
+            }
+        } else {
+            result.note(format!(
+                "Transaction info pruned at version {}, state snapshot root hash not checked \
+                 against it.",
+                version
+            ));
+        }
+
+        if version < self.first_state_kv_version {
+            result.note(format!(
+                "State KV pruned at version {}, state snapshot leaves not checked.",
+                version
+            ));
+            return Ok(());
+        }
+        let mut hasher = StateRootHasher::new();
+        for item in self
+            .db
+            .state_store
+            .get_state_key_and_value_iter(version, HashValue::zero())?
+        {
+            let (key, value) = item?;
+            hasher.add_leaf(key.hash(), value.hash())?;
+            result.num_checked += 1;
+        }
+        let computed_root_hash = hasher.finish();
+        if computed_root_hash != root_hash {
+            result.error(format!(
+                "State snapshot at version {} has root hash {}, but its leaves add up to {}.",
+                version, root_hash, computed_root_hash,
+            ));
+        }
+        Ok(())
+    }
+}
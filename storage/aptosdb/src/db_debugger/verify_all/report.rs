@@ -0,0 +1,171 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Errors beyond this number are counted but not recorded in a single check.
+const MAX_ERRORS_PER_CHECK: usize = 100;
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CheckResult {
+    /// Number of items (ledger infos, transactions, or state leaves) checked.
+    pub num_checked: u64,
+    pub num_errors: u64,
+    /// The first `MAX_ERRORS_PER_CHECK` errors.
+    pub errors: Vec<String>,
+    /// Parts not checked, e.g. because the data is pruned.
+    pub notes: Vec<String>,
+}
+
+impl CheckResult {
+    pub fn error(&mut self, error: String) {
+        self.num_errors += 1;
+        if self.errors.len() < MAX_ERRORS_PER_CHECK {
+            self.errors.push(error);
+        }
+    }
+
+    pub fn note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+}
+
+/// Result of verifying a DB, persisted so that an interrupted run can be resumed, and so that
+/// operators can keep it as a certificate of the DB.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Report {
+    /// Latest version in the DB, to which the checks are done.
+    pub ledger_version: Version,
+    /// Transaction accumulator root hash in the latest ledger info.
+    pub ledger_accumulator_root_hash: HashValue,
+    /// First version not pruned from the ledger.
+    pub first_version: Version,
+    /// Number of transactions in a batch.
+    pub batch_size: u64,
+    /// Epoch ending ledger infos, and the latest one.
+    pub ledger_infos: Option<CheckResult>,
+    /// Batches of transactions, keyed by the first version in the batch.
+    pub transaction_batches: BTreeMap<Version, CheckResult>,
+    /// Retained state snapshots, keyed by version.
+    pub state_snapshots: BTreeMap<Version, CheckResult>,
+    /// Whether all checks are done.
+    pub finished: bool,
+}
+
+impl Report {
+    pub fn new(
+        ledger_version: Version,
+        ledger_accumulator_root_hash: HashValue,
+        first_version: Version,
+        batch_size: u64,
+    ) -> Self {
+        Self {
+            ledger_version,
+            ledger_accumulator_root_hash,
+            first_version,
+            batch_size,
+            ledger_infos: None,
+            transaction_batches: BTreeMap::new(),
+            state_snapshots: BTreeMap::new(),
+            finished: false,
+        }
+    }
+
+    /// Loads the report of a previous run on the same DB at the same version, if one exists at
+    /// `path`; otherwise returns `new`.
+    pub fn load_or(path: &Path, new: Self) -> Result<Self> {
+        if !path.exists() {
+            return Ok(new);
+        }
+        let report: Self = serde_json::from_slice(&fs::read(path)?)?;
+        ensure!(
+            report.ledger_version == new.ledger_version
+                && report.ledger_accumulator_root_hash == new.ledger_accumulator_root_hash,
+            "Report at {:?} is for ledger version {} (root hash {}), but the DB is at version {} \
+             (root hash {}). Remove it to start over.",
+            path,
+            report.ledger_version,
+            report.ledger_accumulator_root_hash,
+            new.ledger_version,
+            new.ledger_accumulator_root_hash,
+        );
+        ensure!(
+            report.first_version == new.first_version && report.batch_size == new.batch_size,
+            "Report at {:?} is for first version {} and batch size {}, expected {} and {}.",
+            path,
+            report.first_version,
+            report.batch_size,
+            new.first_version,
+            new.batch_size,
+        );
+        Ok(report)
+    }
+
+    pub fn num_errors(&self) -> u64 {
+        self.ledger_infos
+            .iter()
+            .chain(self.transaction_batches.values())
+            .chain(self.state_snapshots.values())
+            .map(|result| result.num_errors)
+            .sum()
+    }
+
+    /// Writes to a temporary file and renames it, so an existing report is never left corrupted.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Collects the results of the checks, saving the report periodically.
+pub struct ReportWriter {
+    path: PathBuf,
+    report: Report,
+    last_saved: Instant,
+}
+
+impl ReportWriter {
+    pub fn new(path: PathBuf, report: Report) -> Self {
+        Self {
+            path,
+            report,
+            last_saved: Instant::now(),
+        }
+    }
+
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    pub fn update(&mut self, f: impl FnOnce(&mut Report)) -> Result<()> {
+        f(&mut self.report);
+        if self.last_saved.elapsed() >= SAVE_INTERVAL {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        self.report.save(&self.path)?;
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+
+    pub fn into_report(self) -> Report {
+        self.report
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
+use aptos_types::proof::{SparseMerkleInternalNode, SparseMerkleLeafNode};
+
+/// Computes the root hash of a sparse Merkle tree from its leaves, fed in the order of their keys,
+/// keeping only the left siblings of the path to the latest leaf in memory.
+///
+/// A leaf sits right under the lowest node shared with either of its neighbours, so its position
+/// is known once the next leaf is seen.
+#[derive(Default)]
+pub struct StateRootHasher {
+    /// Subtrees waiting for their right siblings, as the depth of their parent and their hash,
+    /// from the root down.
+    left_subtrees: Vec<(usize, HashValue)>,
+    /// The latest leaf, as its key and hash, and the depth of the node it shares with the leaf
+    /// on its left.
+    pending_leaf: Option<(HashValue, HashValue, Option<usize>)>,
+}
+
+impl StateRootHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_leaf(&mut self, key: HashValue, value_hash: HashValue) -> Result<()> {
+        let left_split = match self.pending_leaf.take() {
+            Some((pending_key, pending_hash, pending_left_split)) => {
+                ensure!(
+                    pending_key < key,
+                    "Leaves not ordered by key: {:x} after {:x}.",
+                    key,
+                    pending_key,
+                );
+                let split = pending_key.common_prefix_bits_len(key);
+                self.settle_leaf(pending_key, pending_hash, pending_left_split, Some(split));
+                Some(split)
+            },
+            None => None,
+        };
+        let leaf_hash = SparseMerkleLeafNode::new(key, value_hash).hash();
+        self.pending_leaf = Some((key, leaf_hash, left_split));
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> HashValue {
+        match self.pending_leaf.take() {
+            Some((key, leaf_hash, left_split)) => self
+                .settle_leaf(key, leaf_hash, left_split, None)
+                .expect("Root returned for the last leaf."),
+            None => *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        }
+    }
+
+    /// Merges the leaf with the subtrees on its left that don't extend further to the right than
+    /// it, and leaves the result waiting for its right sibling at `right_split`. Returns the root
+    /// hash if it's the last leaf.
+    fn settle_leaf(
+        &mut self,
+        key: HashValue,
+        leaf_hash: HashValue,
+        left_split: Option<usize>,
+        right_split: Option<usize>,
+    ) -> Option<HashValue> {
+        let mut depth = left_split.max(right_split).map_or(0, |split| split + 1);
+        let mut hash = leaf_hash;
+        while let Some(&(split, left)) = self.left_subtrees.last() {
+            if right_split.map_or(false, |right_split| split < right_split) {
+                break;
+            }
+            self.left_subtrees.pop();
+            hash = SparseMerkleInternalNode::new(left, lift(hash, key, depth, split + 1)).hash();
+            depth = split;
+        }
+        match right_split {
+            Some(split) => {
+                self.left_subtrees
+                    .push((split, lift(hash, key, depth, split + 1)));
+                None
+            },
+            None => Some(lift(hash, key, depth, 0)),
+        }
+    }
+}
+
+/// Hash of the node at `to_depth` on the path to `key`, given that of the node at `from_depth`
+/// and empty siblings in between.
+fn lift(mut hash: HashValue, key: HashValue, from_depth: usize, to_depth: usize) -> HashValue {
+    for depth in (to_depth..from_depth).rev() {
+        hash = if key.bit(depth) {
+            SparseMerkleInternalNode::new(*SPARSE_MERKLE_PLACEHOLDER_HASH, hash).hash()
+        } else {
+            SparseMerkleInternalNode::new(hash, *SPARSE_MERKLE_PLACEHOLDER_HASH).hash()
+        };
+    }
+    hash
+}
@@ -0,0 +1,150 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    db::test_helper::{arb_blocks_to_commit_with_block_nums, update_in_memory_state},
+    schema::write_set::WriteSetSchema,
+};
+use aptos_crypto::hash::SPARSE_MERKLE_PLACEHOLDER_HASH;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    proof::{SparseMerkleInternalNode, SparseMerkleLeafNode},
+    write_set::WriteSet,
+};
+use proptest::{collection::btree_set, prelude::*};
+use std::{fs, path::Path};
+
+fn verify_all(db_dir: &Path, report: &Path, batch_size: u64) -> Result<()> {
+    Cmd::try_parse_from([
+        "verify-all",
+        "--db-dir",
+        db_dir.to_str().unwrap(),
+        "--report",
+        report.to_str().unwrap(),
+        "--batch-size",
+        &batch_size.to_string(),
+    ])?
+    .run()
+}
+
+fn load_report(path: &Path) -> Report {
+    serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+}
+
+/// Root hash of the sparse Merkle tree with the leaves, which share the first `depth` bits of
+/// their keys.
+fn naive_root_hash(leaves: &[(HashValue, HashValue)], depth: usize) -> HashValue {
+    match leaves {
+        [] => *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        [(key, value_hash)] => SparseMerkleLeafNode::new(*key, *value_hash).hash(),
+        _ => {
+            let split = leaves.partition_point(|(key, _)| !key.bit(depth));
+            SparseMerkleInternalNode::new(
+                naive_root_hash(&leaves[..split], depth + 1),
+                naive_root_hash(&leaves[split..], depth + 1),
+            )
+            .hash()
+        },
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1))]
+
+    #[test]
+    fn test_verify_all(input in arb_blocks_to_commit_with_block_nums(20, 30)) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let mut in_memory_state = db.state_store.buffered_state().lock().current_state().clone();
+        let mut version = 0;
+        let mut tampered_version = None;
+        for (txns_to_commit, ledger_info_with_sigs) in input.0.iter() {
+            update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+            db.save_transactions_for_test(
+                txns_to_commit,
+                version,
+                version.checked_sub(1),
+                Some(ledger_info_with_sigs),
+                true,
+                in_memory_state.clone(),
+            )
+            .unwrap();
+            for txn in txns_to_commit {
+                if txn.write_set() != &WriteSet::default() {
+                    tampered_version = Some(version);
+                }
+                version += 1;
+            }
+        }
+        drop(db);
+
+        let report_dir = TempPath::new();
+        report_dir.create_as_dir().unwrap();
+        let report_path = report_dir.path().join("report.json");
+        verify_all(tmp_dir.path(), &report_path, 7).unwrap();
+        let report = load_report(&report_path);
+        prop_assert!(report.finished);
+        prop_assert_eq!(report.ledger_version, version - 1);
+        prop_assert_eq!(report.num_errors(), 0);
+        prop_assert_eq!(
+            report.transaction_batches.values().map(|result| result.num_checked).sum::<u64>(),
+            version
+        );
+
+        // Resuming a finished run checks nothing again, but a different batch size is rejected.
+        verify_all(tmp_dir.path(), &report_path, 7).unwrap();
+        verify_all(tmp_dir.path(), &report_path, 8).unwrap_err();
+
+        let tampered_version = tampered_version.unwrap();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        db.ledger_db
+            .write_set_db()
+            .put::<WriteSetSchema>(&tampered_version, &WriteSet::default())
+            .unwrap();
+        drop(db);
+
+        let report_path = report_dir.path().join("tampered_report.json");
+        verify_all(tmp_dir.path(), &report_path, 7).unwrap_err();
+        let report = load_report(&report_path);
+        let first = report.first_version + (tampered_version - report.first_version) / 7 * 7;
+        prop_assert!(report.transaction_batches[&first].num_errors > 0);
+        prop_assert_eq!(
+            report.num_errors(),
+            report.transaction_batches[&first].num_errors
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_state_root_hasher(
+        // Short prefixes, so that the keys share long prefixes with each other.
+        prefixes in btree_set(any::<[u8; 2]>(), 0..100),
+    ) {
+        let leaves: Vec<_> = prefixes
+            .into_iter()
+            .map(|prefix| {
+                let mut key = [0u8; HashValue::LENGTH];
+                key[..prefix.len()].copy_from_slice(&prefix);
+                (HashValue::new(key), HashValue::random())
+            })
+            .collect();
+        let mut hasher = StateRootHasher::new();
+        for (key, value_hash) in &leaves {
+            hasher.add_leaf(*key, *value_hash).unwrap();
+        }
+        prop_assert_eq!(hasher.finish(), naive_root_hash(&leaves, 0));
+    }
+}
+
+#[test]
+fn test_state_root_hasher_rejects_unordered_leaves() {
+    let mut hasher = StateRootHasher::new();
+    hasher
+        .add_leaf(HashValue::new([2; HashValue::LENGTH]), HashValue::random())
+        .unwrap();
+    hasher
+        .add_leaf(HashValue::new([1; HashValue::LENGTH]), HashValue::random())
+        .unwrap_err();
+}
@@ -53,12 +53,10 @@ impl FastSyncStorageWrapper {
         .map_err(|err| anyhow!("fast sync DB failed to open {}", err))?;
 
         let mut db_dir = config.storage.dir();
-        // when the db is empty and configured to do fast sync, we will create a second DB
-        if config
-            .state_sync
-            .state_sync_driver
-            .bootstrapping_mode
-            .is_fast_sync()
+        // when the db is empty and configured to do fast sync (or to restore from backups),
+        // we will create a second DB
+        let bootstrapping_mode = config.state_sync.state_sync_driver.bootstrapping_mode;
+        if (bootstrapping_mode.is_fast_sync() || bootstrapping_mode.is_restore_from_backup())
             && (db_main.ledger_store.get_latest_version().map_or(0, |v| v) == 0)
         {
             db_dir.push(SECONDARY_DB_DIR);
@@ -95,6 +93,12 @@ impl FastSyncStorageWrapper {
         *self.fast_sync_status.read()
     }
 
+    /// Marks the bootstrapping as finished once the fast sync DB has been restored from
+    /// backups (which writes to it directly), so that all reads and writes go to it.
+    pub fn finish_restore_from_backup(&self) {
+        *self.fast_sync_status.write() = FastSyncStatus::FINISHED;
+    }
+
     /// Check if the fast sync finished already
     fn is_fast_sync_bootstrap_finished(&self) -> bool {
         let status = self.get_fast_sync_status();
@@ -0,0 +1,614 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        incremental_state_snapshot::{
+            manifest::{
+                BaseStateSnapshot, IncrementalStateSnapshotBackup, StateSnapshotChangesChunk,
+                StateSnapshotManifest,
+            },
+            restore::read_range_proofs,
+        },
+        state_snapshot::backup::get_version_for_epoch_ending,
+    },
+    metadata::Metadata,
+    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_logger::prelude::*;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::{TransactionWrite, WriteSet},
+};
+use clap::Parser;
+use once_cell::sync::Lazy;
+use std::{
+    cmp::{min, Reverse},
+    collections::{btree_map, btree_set, BTreeMap, BTreeSet, BinaryHeap},
+    convert::TryInto,
+    iter::Peekable,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
+};
+
+/// Latest value of each key changed since the base snapshot, `None` meaning deleted, by key hash.
+pub(crate) type StateChanges = BTreeMap<HashValue, (StateKey, Option<StateValue>)>;
+
+pub const DEFAULT_MAX_CHANGES_IN_MEMORY_BYTES: usize = 1024 * 1024 * 1024; // 1GB
+
+#[derive(Parser)]
+pub struct IncrementalStateSnapshotBackupOpt {
+    #[clap(
+        long = "state-snapshot-epoch",
+        help = "Epoch at the end of which a state snapshot is to be taken."
+    )]
+    pub epoch: u64,
+    #[clap(
+        long = "base-state-manifest",
+        help = "Manifest of the state snapshot (full or incremental) to take the incremental \
+        snapshot on top of. Only the state changed since this snapshot is backed up, and \
+        restoring the incremental snapshot requires it."
+    )]
+    pub base_manifest: FileHandle,
+    #[clap(
+        long,
+        hide(true),
+        default_value_t = DEFAULT_MAX_CHANGES_IN_MEMORY_BYTES,
+        help = "Approximate size of the state changes kept in memory, beyond which they are \
+        spilled to temporary files."
+    )]
+    pub max_changes_in_memory_bytes: usize,
+}
+
+pub struct IncrementalStateSnapshotBackupController {
+    epoch: u64,
+    base_manifest: FileHandle,
+    max_chunk_size: usize,
+    max_changes_in_memory_bytes: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl IncrementalStateSnapshotBackupController {
+    pub fn new(
+        opt: IncrementalStateSnapshotBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            epoch: opt.epoch,
+            base_manifest: opt.base_manifest,
+            max_chunk_size: global_opt.max_chunk_size,
+            max_changes_in_memory_bytes: opt.max_changes_in_memory_bytes,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        info!(
+            "Incremental state snapshot backup started, for epoch {}, on top of {}.",
+            self.epoch, self.base_manifest,
+        );
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("Incremental state snapshot backup failed: {}", e))?;
+        info!(
+            "Incremental state snapshot backup succeeded. Manifest: {}",
+            ret
+        );
+        Ok(ret)
+    }
+
+    async fn run_impl(self) -> Result<FileHandle> {
+        let version = get_version_for_epoch_ending(&self.client, self.epoch).await?;
+        let base: StateSnapshotManifest = self.storage.load_json_file(&self.base_manifest).await?;
+        let base_version = base.version();
+        ensure!(
+            base_version < version,
+            "Base state snapshot at version {} is not older than version {} of epoch {}.",
+            base_version,
+            version,
+            self.epoch,
+        );
+
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&format!(
+                "state_incremental_epoch_{}_ver_{}",
+                self.epoch, version
+            ))
+            .await?;
+
+        let changes = self.get_changes(base_version, version).await?;
+        info!(
+            base_version = base_version,
+            version = version,
+            num_spilled_runs = changes.num_spilled_runs(),
+            "Got state changes since base snapshot."
+        );
+        let chunks = self
+            .write_chunks(&backup_handle, changes.iter().await?)
+            .await?;
+
+        // Cut the state at the same keys as the base, plus where the changes are cut, so chunks
+        // stay roughly the size of those of the base.
+        let mut candidates = self.get_base_boundaries(base).await?;
+        candidates.extend(chunks.iter().map(|chunk| chunk.last_key));
+        let boundaries = select_boundaries(candidates, changes.iter().await?).await?;
+        let range_proofs = self
+            .write_range_proofs(&backup_handle, &boundaries, version)
+            .await?;
+
+        let base = BaseStateSnapshot {
+            version: base_version,
+            manifest: self.base_manifest.clone(),
+        };
+        self.write_manifest(&backup_handle, version, base, chunks, range_proofs)
+            .await
+    }
+}
+
+impl IncrementalStateSnapshotBackupController {
+    const TRANSACTIONS_PER_REQUEST: usize = 10_000;
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state.manifest").unwrap());
+        &NAME
+    }
+
+    fn proof_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state.proof").unwrap());
+        &NAME
+    }
+
+    fn range_proofs_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state.range_proofs").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(first_idx: usize) -> ShellSafeName {
+        format!("{}-.chunk", first_idx).try_into().unwrap()
+    }
+
+    /// Folds the write sets of transactions in (`base_version`, `version`] into the changes,
+    /// requesting the transactions in batches.
+    async fn get_changes(&self, base_version: Version, version: Version) -> Result<ChangeRuns> {
+        let mut changes = ChangeRuns::new(self.max_changes_in_memory_bytes);
+        let mut start_version = base_version + 1;
+        while start_version <= version {
+            let num_transactions = min(
+                version - start_version + 1,
+                Self::TRANSACTIONS_PER_REQUEST as u64,
+            ) as usize;
+            let mut transactions_file = self
+                .client
+                .get_transactions(start_version, num_transactions)
+                .await?;
+
+            let mut num_read = 0;
+            while let Some(record_bytes) = transactions_file.read_record_bytes().await? {
+                let (_, _, _, write_set): (
+                    Transaction,
+                    TransactionInfo,
+                    Vec<ContractEvent>,
+                    WriteSet,
+                ) = bcs::from_bytes(&record_bytes)?;
+                for (key, op) in write_set.iter() {
+                    changes.insert(key.clone(), op.as_state_value()).await?;
+                }
+                num_read += 1;
+            }
+            ensure!(
+                num_read == num_transactions,
+                "Server did not return all transactions requested. Expecting {}, got {}.",
+                num_transactions,
+                num_read,
+            );
+            start_version += num_transactions as u64;
+        }
+
+        Ok(changes)
+    }
+
+    async fn write_chunks(
+        &self,
+        backup_handle: &BackupHandleRef,
+        mut changes: MergedChanges<'_>,
+    ) -> Result<Vec<StateSnapshotChangesChunk>> {
+        let mut chunks = vec![];
+        let mut chunk_bytes = vec![];
+        let mut chunk_keys: Option<(HashValue, HashValue)> = None;
+        let mut chunk_first_idx = 0;
+
+        let mut idx = 0;
+        while let Some((key_hash, change)) = changes.next().await? {
+            let record_bytes = bcs::to_bytes(&change)?;
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let (first_key, last_key) = chunk_keys.take().expect("Chunk not empty.");
+                chunks.push(
+                    self.write_chunk(
+                        backup_handle,
+                        &chunk_bytes,
+                        chunk_first_idx,
+                        first_key,
+                        last_key,
+                    )
+                    .await?,
+                );
+                chunk_bytes = vec![];
+                chunk_first_idx = idx;
+            }
+
+            chunk_bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            chunk_keys = Some((
+                chunk_keys.map_or(key_hash, |(first_key, _)| first_key),
+                key_hash,
+            ));
+            idx += 1;
+        }
+
+        if let Some((first_key, last_key)) = chunk_keys {
+            chunks.push(
+                self.write_chunk(
+                    backup_handle,
+                    &chunk_bytes,
+                    chunk_first_idx,
+                    first_key,
+                    last_key,
+                )
+                .await?,
+            );
+        }
+
+        Ok(chunks)
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_idx: usize,
+        first_key: HashValue,
+        last_key: HashValue,
+    ) -> Result<StateSnapshotChangesChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+            .await?;
+        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        Ok(StateSnapshotChangesChunk {
+            first_key,
+            last_key,
+            changes: chunk_handle,
+        })
+    }
+
+    /// The keys the base snapshot is cut at.
+    async fn get_base_boundaries(
+        &self,
+        base: StateSnapshotManifest,
+    ) -> Result<BTreeSet<HashValue>> {
+        Ok(match base {
+            StateSnapshotManifest::Full(manifest) => {
+                manifest.chunks.iter().map(|chunk| chunk.last_key).collect()
+            },
+            StateSnapshotManifest::Incremental(manifest) => {
+                read_range_proofs(&self.storage, &manifest.range_proofs)
+                    .await?
+                    .into_iter()
+                    .map(|(key, _proof)| key)
+                    .collect()
+            },
+        })
+    }
+
+    async fn write_range_proofs(
+        &self,
+        backup_handle: &BackupHandleRef,
+        boundaries: &[HashValue],
+        version: Version,
+    ) -> Result<FileHandle> {
+        let (range_proofs_handle, mut range_proofs_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::range_proofs_name())
+            .await?;
+        for key in boundaries {
+            let mut proof_bytes = vec![];
+            self.client
+                .get_account_range_proof(*key, version)
+                .await?
+                .read_to_end(&mut proof_bytes)
+                .await?;
+            let proof: SparseMerkleRangeProof = bcs::from_bytes(&proof_bytes)?;
+            let record_bytes = bcs::to_bytes(&(key, proof))?;
+            range_proofs_file
+                .write_all(&(record_bytes.len() as u32).to_be_bytes())
+                .await?;
+            range_proofs_file.write_all(&record_bytes).await?;
+        }
+        range_proofs_file.shutdown().await?;
+
+        Ok(range_proofs_handle)
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        version: Version,
+        base: BaseStateSnapshot,
+        chunks: Vec<StateSnapshotChangesChunk>,
+        range_proofs: FileHandle,
+    ) -> Result<FileHandle> {
+        let proof_bytes = self.client.get_state_root_proof(version).await?;
+        let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+
+        let (proof_handle, mut proof_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::proof_name())
+            .await?;
+        proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
+
+        let base_version = base.version;
+        let manifest = IncrementalStateSnapshotBackup {
+            version,
+            epoch: self.epoch,
+            root_hash: txn_info.transaction_info().ensure_state_checkpoint_hash()?,
+            base,
+            chunks,
+            range_proofs,
+            proof: proof_handle,
+        };
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_incremental_state_snapshot_backup(
+            self.epoch,
+            version,
+            base_version,
+            manifest_handle.clone(),
+        );
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
+
+/// Selects the keys to cut the new state at, which must exist in the new state so their range
+/// proofs can be fetched: For each candidate, the candidate itself unless it's deleted, otherwise
+/// the largest key changed but not deleted since the previous candidate, if any.
+pub(crate) async fn select_boundaries(
+    candidates: BTreeSet<HashValue>,
+    mut changes: MergedChanges<'_>,
+) -> Result<Vec<HashValue>> {
+    let mut selector = BoundarySelector::new(candidates);
+    while let Some((key_hash, (_key, value))) = changes.next().await? {
+        selector.add_change(key_hash, value.is_none());
+    }
+    Ok(selector.finish())
+}
+
+/// Selects the boundaries out of the candidates, being fed the changes in key order.
+pub(crate) struct BoundarySelector {
+    candidates: Peekable<btree_set::IntoIter<HashValue>>,
+    /// The largest key changed but not deleted since the previous candidate.
+    last_live_key: Option<HashValue>,
+    boundaries: Vec<HashValue>,
+}
+
+impl BoundarySelector {
+    pub fn new(candidates: BTreeSet<HashValue>) -> Self {
+        Self {
+            candidates: candidates.into_iter().peekable(),
+            last_live_key: None,
+            boundaries: vec![],
+        }
+    }
+
+    pub fn add_change(&mut self, key_hash: HashValue, deleted: bool) {
+        // Candidates before the key are not changed.
+        while let Some(candidate) = self.candidates.next_if(|candidate| *candidate < key_hash) {
+            self.boundaries.push(candidate);
+            self.last_live_key = None;
+        }
+
+        if self.candidates.next_if_eq(&key_hash).is_some() {
+            if !deleted {
+                self.boundaries.push(key_hash);
+            } else if let Some(key) = self.last_live_key {
+                self.boundaries.push(key);
+            }
+            self.last_live_key = None;
+        } else if !deleted {
+            self.last_live_key = Some(key_hash);
+        }
+    }
+
+    pub fn finish(mut self) -> Vec<HashValue> {
+        // Candidates after the last change are not changed.
+        self.boundaries.extend(self.candidates);
+        self.boundaries
+    }
+}
+
+/// The changes since the base snapshot, folded in version order in memory, and spilled to disk
+/// in runs sorted by key once they take too much of it. Later runs override earlier ones.
+pub(crate) struct ChangeRuns {
+    dir: TempPath,
+    num_spilled_runs: usize,
+    in_memory: StateChanges,
+    in_memory_bytes: usize,
+    max_in_memory_bytes: usize,
+}
+
+impl ChangeRuns {
+    pub fn new(max_in_memory_bytes: usize) -> Self {
+        Self {
+            dir: TempPath::new(),
+            num_spilled_runs: 0,
+            in_memory: StateChanges::new(),
+            in_memory_bytes: 0,
+            max_in_memory_bytes,
+        }
+    }
+
+    pub fn num_spilled_runs(&self) -> usize {
+        self.num_spilled_runs
+    }
+
+    pub async fn insert(&mut self, key: StateKey, value: Option<StateValue>) -> Result<()> {
+        // Approximate, an overridden value is still counted.
+        self.in_memory_bytes +=
+            HashValue::LENGTH + key.size() + value.as_ref().map_or(0, StateValue::size);
+        self.in_memory.insert(key.hash(), (key, value));
+        if self.in_memory_bytes > self.max_in_memory_bytes {
+            self.spill().await?;
+        }
+        Ok(())
+    }
+
+    fn run_path(&self, run: usize) -> PathBuf {
+        self.dir.path().join(format!("{}.run", run))
+    }
+
+    async fn spill(&mut self) -> Result<()> {
+        if self.num_spilled_runs == 0 {
+            self.dir.create_as_dir()?;
+        }
+        let mut file = BufWriter::new(File::create(self.run_path(self.num_spilled_runs)).await?);
+        for change in std::mem::take(&mut self.in_memory) {
+            let record_bytes = bcs::to_bytes(&change)?;
+            file.write_all(&(record_bytes.len() as u32).to_be_bytes())
+                .await?;
+            file.write_all(&record_bytes).await?;
+        }
+        file.shutdown().await?;
+        self.num_spilled_runs += 1;
+        self.in_memory_bytes = 0;
+        Ok(())
+    }
+
+    /// Iterates the latest change of each key, in key order.
+    pub async fn iter(&self) -> Result<MergedChanges<'_>> {
+        let mut runs = vec![];
+        for run in 0..self.num_spilled_runs {
+            runs.push(Run::Spilled(BufReader::new(
+                File::open(self.run_path(run)).await?,
+            )));
+        }
+        runs.push(Run::InMemory(self.in_memory.iter()));
+        MergedChanges::new(runs).await
+    }
+}
+
+enum Run<'a> {
+    Spilled(BufReader<File>),
+    InMemory(btree_map::Iter<'a, HashValue, (StateKey, Option<StateValue>)>),
+}
+
+type Change = (HashValue, (StateKey, Option<StateValue>));
+
+impl Run<'_> {
+    async fn next(&mut self) -> Result<Option<Change>> {
+        Ok(match self {
+            Self::Spilled(file) => match file.read_record_bytes().await? {
+                Some(record_bytes) => Some(bcs::from_bytes(&record_bytes)?),
+                None => None,
+            },
+            Self::InMemory(iter) => iter
+                .next()
+                .map(|(key_hash, change)| (*key_hash, change.clone())),
+        })
+    }
+}
+
+/// Merges the sorted runs, taking the change of the latest run for keys changed in several.
+pub(crate) struct MergedChanges<'a> {
+    runs: Vec<Run<'a>>,
+    /// The next key of each run that's not exhausted, smallest first.
+    next_keys: BinaryHeap<Reverse<(HashValue, usize)>>,
+    /// The change at the next key of each run.
+    next_changes: Vec<Option<(StateKey, Option<StateValue>)>>,
+}
+
+impl<'a> MergedChanges<'a> {
+    async fn new(runs: Vec<Run<'a>>) -> Result<MergedChanges<'a>> {
+        let mut merged = Self {
+            next_changes: runs.iter().map(|_| None).collect(),
+            runs,
+            next_keys: BinaryHeap::new(),
+        };
+        for run in 0..merged.runs.len() {
+            merged.advance(run).await?;
+        }
+        Ok(merged)
+    }
+
+    async fn advance(&mut self, run: usize) -> Result<()> {
+        if let Some((key_hash, change)) = self.runs[run].next().await? {
+            self.next_keys.push(Reverse((key_hash, run)));
+            self.next_changes[run] = Some(change);
+        }
+        Ok(())
+    }
+
+    pub async fn next(&mut self) -> Result<Option<Change>> {
+        let Reverse((key_hash, first_run)) = match self.next_keys.pop() {
+            Some(next_key) => next_key,
+            None => return Ok(None),
+        };
+        let mut runs = vec![first_run];
+        while let Some(Reverse((_, run))) = self
+            .next_keys
+            .peek()
+            .filter(|Reverse((key, _))| *key == key_hash)
+        {
+            runs.push(*run);
+            self.next_keys.pop();
+        }
+
+        let latest_run = *runs.iter().max().expect("Not empty.");
+        let change = self.next_changes[latest_run]
+            .take()
+            .expect("Run has a next change.");
+        for run in runs {
+            self.advance(run).await?;
+        }
+        Ok(Some((key_hash, change)))
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{backup_types::state_snapshot::manifest::StateSnapshotBackup, storage::FileHandle};
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of the changes in an incremental state snapshot, representing the changes to keys in
+/// the key range [`first_key`, `last_key`] (right side inclusive).
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotChangesChunk {
+    /// key of the first change in this chunk.
+    pub first_key: HashValue,
+    /// key of the last change in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, Option<state_value>)`, `None` meaning the key is deleted.
+    pub changes: FileHandle,
+}
+
+/// The snapshot an incremental state snapshot is taken on top of.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BaseStateSnapshot {
+    /// Version of the base snapshot.
+    pub version: Version,
+    /// Manifest of the base snapshot, either a `StateSnapshotBackup` or an
+    /// `IncrementalStateSnapshotBackup`.
+    pub manifest: FileHandle,
+}
+
+/// Incremental state snapshot backup manifest, representing a complete state view at specified
+/// version, by the state keys changed since a base snapshot.
+#[derive(Deserialize, Serialize)]
+pub struct IncrementalStateSnapshotBackup {
+    /// Version at which this state snapshot is taken.
+    pub version: Version,
+    /// Epoch in which this state snapshot is taken.
+    pub epoch: u64,
+    /// Hash of the state tree root.
+    pub root_hash: HashValue,
+    /// The snapshot this one is taken on top of.
+    pub base: BaseStateSnapshot,
+    /// All keys changed between the base version (exclusive) and `version` (inclusive), in chunks
+    /// ordered by key.
+    pub chunks: Vec<StateSnapshotChangesChunk>,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, SparseMerkleRangeProof)`, ordered by key.
+    /// The keys exist at `version` and split the full state into chunks of about the size of
+    /// those of the base snapshot. The `SparseMerkleRangeProof` proves all keys up to and including
+    /// the key add up to the root hash, so the state can be restored chunk by chunk like a full
+    /// snapshot. Keys after the last one form the last chunk, which needs no proof.
+    pub range_proofs: FileHandle,
+    /// BCS serialized `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, same as
+    /// `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
+
+/// Manifest of a full or an incremental state snapshot.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum StateSnapshotManifest {
+    // Tried first, a full manifest lacks the `base` field.
+    Incremental(IncrementalStateSnapshotBackup),
+    Full(StateSnapshotBackup),
+}
+
+impl StateSnapshotManifest {
+    pub fn version(&self) -> Version {
+        match self {
+            Self::Incremental(m) => m.version,
+            Self::Full(m) => m.version,
+        }
+    }
+
+    pub fn root_hash(&self) -> HashValue {
+        match self {
+            Self::Incremental(m) => m.root_hash,
+            Self::Full(m) => m.root_hash,
+        }
+    }
+
+    pub fn proof(&self) -> &FileHandle {
+        match self {
+            Self::Incremental(m) => &m.proof,
+            Self::Full(m) => &m.proof,
+        }
+    }
+}
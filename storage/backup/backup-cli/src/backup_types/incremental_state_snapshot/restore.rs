@@ -0,0 +1,305 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        incremental_state_snapshot::manifest::{
+            BaseStateSnapshot, IncrementalStateSnapshotBackup, StateSnapshotChangesChunk,
+            StateSnapshotManifest,
+        },
+        state_snapshot::{manifest::StateSnapshotChunk, restore::StateChunk},
+    },
+    storage::{BackupStorage, FileHandle, FileHandleRef},
+    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX},
+};
+use anyhow::{ensure, Result};
+use aptos_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
+use aptos_types::{
+    proof::SparseMerkleRangeProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use futures::{
+    stream::{self, BoxStream, Peekable},
+    Stream, StreamExt, TryFutureExt, TryStreamExt,
+};
+use serde::de::DeserializeOwned;
+use std::{collections::VecDeque, pin::Pin, sync::Arc};
+
+type StateItem = (HashValue, StateKey, StateValue);
+type ChangeItem = (HashValue, StateKey, Option<StateValue>);
+
+/// Streams the state at the version of the incremental snapshot, in chunks that can be added to
+/// a state restore receiver: The state of the base snapshot (recursively, if that's incremental
+/// as well) with the changes applied, cut at the keys in the range proofs.
+pub(crate) fn read_state_chunks(
+    storage: Arc<dyn BackupStorage>,
+    manifest: IncrementalStateSnapshotBackup,
+    concurrent_downloads: usize,
+) -> BoxStream<'static, Result<StateChunk>> {
+    let range_proofs_handle = manifest.range_proofs.clone();
+    let state = read_state(
+        storage.clone(),
+        StateSnapshotManifest::Incremental(manifest),
+        concurrent_downloads,
+    );
+    async move { read_range_proofs(&storage, &range_proofs_handle).await }
+        .map_ok(move |range_proofs| cut_chunks(state, range_proofs.into()))
+        .try_flatten_stream()
+        .boxed()
+}
+
+/// Reads the keys and proofs in the `range_proofs` file of an incremental snapshot.
+pub(crate) async fn read_range_proofs(
+    storage: &Arc<dyn BackupStorage>,
+    file_handle: &FileHandleRef,
+) -> Result<Vec<(HashValue, SparseMerkleRangeProof)>> {
+    read_records(storage, file_handle).await
+}
+
+async fn read_records<T: DeserializeOwned>(
+    storage: &Arc<dyn BackupStorage>,
+    file_handle: &FileHandleRef,
+) -> Result<Vec<T>> {
+    let mut file = storage.open_for_read(file_handle).await?;
+    let mut records = vec![];
+    while let Some(record_bytes) = file.read_record_bytes().await? {
+        records.push(bcs::from_bytes(&record_bytes)?);
+    }
+    Ok(records)
+}
+
+/// Streams the full state of a snapshot, ordered by key.
+fn read_state(
+    storage: Arc<dyn BackupStorage>,
+    manifest: StateSnapshotManifest,
+    concurrent_downloads: usize,
+) -> BoxStream<'static, Result<StateItem>> {
+    match manifest {
+        StateSnapshotManifest::Full(manifest) => read_chunks(
+            storage,
+            manifest.chunks,
+            concurrent_downloads,
+            |chunk: &StateSnapshotChunk| chunk.blobs.clone(),
+        )
+        .map_ok(|(key, value): (StateKey, StateValue)| (key.hash(), key, value))
+        .boxed(),
+        StateSnapshotManifest::Incremental(manifest) => {
+            let base = read_base_state(storage.clone(), manifest.base, concurrent_downloads);
+            let changes = read_chunks(
+                storage,
+                manifest.chunks,
+                concurrent_downloads,
+                |chunk: &StateSnapshotChangesChunk| chunk.changes.clone(),
+            )
+            .map_ok(|(key, value): (StateKey, Option<StateValue>)| (key.hash(), key, value))
+            .boxed();
+            apply_changes(base, changes)
+        },
+    }
+}
+
+fn read_base_state(
+    storage: Arc<dyn BackupStorage>,
+    base: BaseStateSnapshot,
+    concurrent_downloads: usize,
+) -> BoxStream<'static, Result<StateItem>> {
+    let manifest_storage = storage.clone();
+    async move {
+        let manifest: StateSnapshotManifest =
+            manifest_storage.load_json_file(&base.manifest).await?;
+        ensure!(
+            manifest.version() == base.version,
+            "Base snapshot version mismatch. In manifest: {}, expected: {}",
+            manifest.version(),
+            base.version,
+        );
+        Ok(manifest)
+    }
+    .map_ok(move |manifest| read_state(storage, manifest, concurrent_downloads))
+    .try_flatten_stream()
+    .boxed()
+}
+
+/// Streams the records in the files of the chunks, in order, downloading ahead.
+fn read_chunks<C, T, F>(
+    storage: Arc<dyn BackupStorage>,
+    chunks: Vec<C>,
+    concurrent_downloads: usize,
+    file_handle: F,
+) -> impl Stream<Item = Result<T>>
+where
+    C: Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+    F: Fn(&C) -> FileHandle + Send + 'static,
+{
+    let futs_iter = chunks.into_iter().map(move |chunk| {
+        let storage = storage.clone();
+        let file_handle = file_handle(&chunk);
+        async move {
+            tokio::spawn(async move { read_records::<T>(&storage, &file_handle).await }).await?
+        }
+    });
+    stream::iter(futs_iter)
+        .buffered_x(concurrent_downloads * 2, concurrent_downloads)
+        .map_ok(|records| stream::iter(records.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+/// Merges the changes, ordered by key, into the state, ordered by key.
+fn apply_changes(
+    base: BoxStream<'static, Result<StateItem>>,
+    changes: BoxStream<'static, Result<ChangeItem>>,
+) -> BoxStream<'static, Result<StateItem>> {
+    stream::try_unfold(
+        (base.peekable(), changes.peekable()),
+        |(mut base, mut changes)| async move {
+            Ok(next_merged(&mut base, &mut changes)
+                .await?
+                .map(|item| (item, (base, changes))))
+        },
+    )
+    .boxed()
+}
+
+async fn next_merged(
+    base: &mut Peekable<BoxStream<'static, Result<StateItem>>>,
+    changes: &mut Peekable<BoxStream<'static, Result<ChangeItem>>>,
+) -> Result<Option<StateItem>> {
+    loop {
+        let base_key = peek_key(base).await?;
+        let change_key = peek_key(changes).await?;
+        let apply_change = match (base_key, change_key) {
+            (None, None) => return Ok(None),
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(base_key), Some(change_key)) => change_key <= base_key,
+        };
+        if !apply_change {
+            return base.try_next().await;
+        }
+
+        if base_key == change_key {
+            // Overwritten or deleted.
+            base.try_next().await?;
+        }
+        let (key_hash, key, value) = changes.try_next().await?.expect("Peeked.");
+        if let Some(value) = value {
+            return Ok(Some((key_hash, key, value)));
+        }
+        // Otherwise a key deleted, or created and deleted again, since the base snapshot.
+    }
+}
+
+async fn peek_key<V>(
+    stream: &mut Peekable<BoxStream<'static, Result<(HashValue, StateKey, V)>>>,
+) -> Result<Option<HashValue>> {
+    match Pin::new(&mut *stream).peek().await {
+        Some(Ok((key_hash, _, _))) => return Ok(Some(*key_hash)),
+        Some(Err(_)) => (),
+        None => return Ok(None),
+    }
+    match stream.next().await {
+        Some(Err(e)) => Err(e),
+        _ => unreachable!("Peeked an error."),
+    }
+}
+
+struct ChunkCutter {
+    state: BoxStream<'static, Result<StateItem>>,
+    range_proofs: VecDeque<(HashValue, SparseMerkleRangeProof)>,
+    next_idx: usize,
+    last_key: Option<HashValue>,
+    second_last_key: Option<HashValue>,
+    done: bool,
+}
+
+/// Cuts the state into chunks ending at the keys of the range proofs, plus the remaining keys as
+/// the last chunk.
+fn cut_chunks(
+    state: BoxStream<'static, Result<StateItem>>,
+    range_proofs: VecDeque<(HashValue, SparseMerkleRangeProof)>,
+) -> BoxStream<'static, Result<StateChunk>> {
+    let cutter = ChunkCutter {
+        state,
+        range_proofs,
+        next_idx: 0,
+        last_key: None,
+        second_last_key: None,
+        done: false,
+    };
+    stream::try_unfold(cutter, |mut c| async move {
+        if c.done {
+            return Ok(None);
+        }
+        let first_idx = c.next_idx;
+        let mut blobs = vec![];
+        while let Some((key_hash, key, value)) = c.state.try_next().await? {
+            ensure!(
+                c.last_key.map_or(true, |last_key| last_key < key_hash),
+                "State not ordered by key at {:x}.",
+                key_hash,
+            );
+            c.next_idx += 1;
+            c.second_last_key = c.last_key.replace(key_hash);
+            blobs.push((key, value));
+            if let Some((proof_key, _)) = c.range_proofs.front() {
+                ensure!(
+                    key_hash <= *proof_key,
+                    "Key {:x} of a range proof not found in the state.",
+                    proof_key,
+                );
+                if key_hash == *proof_key {
+                    let (_, proof) = c.range_proofs.pop_front().expect("Checked.");
+                    let chunk = StateChunk {
+                        first_idx,
+                        last_idx: c.next_idx - 1,
+                        last_key: key_hash,
+                        blobs,
+                        proof,
+                    };
+                    return Ok(Some((chunk, c)));
+                }
+            }
+        }
+        ensure!(
+            c.range_proofs.is_empty(),
+            "Key {:x} of a range proof not found in the state.",
+            c.range_proofs[0].0,
+        );
+        c.done = true;
+        if blobs.is_empty() {
+            return Ok(None);
+        }
+        let last_key = c.last_key.expect("Not empty.");
+        let chunk = StateChunk {
+            first_idx,
+            last_idx: c.next_idx - 1,
+            last_key,
+            blobs,
+            proof: rightmost_leaf_range_proof(c.second_last_key, last_key),
+        };
+        Ok(Some((chunk, c)))
+    })
+    .boxed()
+}
+
+/// The range proof of the rightmost leaf of a tree, i.e. no leaves exist on its right, given the
+/// key of the leaf on its left, if any.
+/// The leaf sits right under the lowest node shared with the leaf on its left, and all siblings on
+/// its right are empty. If there are in fact leaves on its right, the proof won't verify.
+pub(crate) fn rightmost_leaf_range_proof(
+    left_neighbor: Option<HashValue>,
+    key: HashValue,
+) -> SparseMerkleRangeProof {
+    let depth = left_neighbor.map_or(0, |left| left.common_prefix_bits_len(key) + 1);
+    SparseMerkleRangeProof::new(
+        key.iter_bits()
+            .take(depth)
+            .filter(|bit| !bit)
+            .map(|_| *SPARSE_MERKLE_PLACEHOLDER_HASH)
+            .collect(),
+    )
+}
@@ -0,0 +1,304 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        incremental_state_snapshot::{
+            backup::{
+                BoundarySelector, ChangeRuns, IncrementalStateSnapshotBackupController,
+                IncrementalStateSnapshotBackupOpt, StateChanges,
+            },
+            manifest::StateSnapshotManifest,
+            restore::rightmost_leaf_range_proof,
+        },
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            manifest::{StateSnapshotBackup, StateSnapshotChunk},
+            restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        },
+    },
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient,
+        test_utils::{start_local_backup_service, tmp_db_with_random_content},
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
+        RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
+use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    proof::{SparseMerkleInternalNode, SparseMerkleLeafNode},
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use std::{convert::TryInto, sync::Arc};
+use tokio::{runtime::Runtime, time::Duration};
+
+#[test]
+fn end_to_end() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let latest_epoch_ending_li = src_db
+        .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap();
+    let version = latest_epoch_ending_li.ledger_info().version();
+    let state_root_hash = src_db
+        .get_transactions(version, 1, version, false)
+        .unwrap()
+        .proof
+        .transaction_infos
+        .pop()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+
+    // A full snapshot at the first epoch, and a chain of incremental ones on top of it.
+    let mut manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { epoch: 0 },
+                global_backup_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    for epoch in 1..=epoch {
+        manifest_handle = rt
+            .block_on(
+                IncrementalStateSnapshotBackupController::new(
+                    IncrementalStateSnapshotBackupOpt {
+                        epoch,
+                        base_manifest: manifest_handle,
+                        // Small enough for the changes to be spilled to disk.
+                        max_changes_in_memory_bytes: 4096,
+                    },
+                    global_backup_opt.clone(),
+                    Arc::clone(&client),
+                    Arc::clone(&store),
+                )
+                .run(),
+            )
+            .unwrap();
+    }
+
+    rt.block_on(
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle,
+                version,
+                validate_modules: false,
+                restore_mode: StateSnapshotRestoreMode::Default,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                target_version: None, // max
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurrent_downloads: ConcurrentDownloadsOpt::default(),
+                replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            store,
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, state_root_hash)
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+fn key_with_prefix(prefix: &[u8]) -> HashValue {
+    let mut bytes = [0u8; HashValue::LENGTH];
+    bytes[..prefix.len()].copy_from_slice(prefix);
+    HashValue::new(bytes)
+}
+
+#[test]
+fn test_rightmost_leaf_range_proof() {
+    let value_hash = HashValue::random();
+
+    // A single leaf is the root itself.
+    let key = HashValue::random();
+    let leaf = SparseMerkleLeafNode::new(key, value_hash);
+    rightmost_leaf_range_proof(None, key)
+        .verify(leaf.hash(), leaf, vec![])
+        .unwrap();
+
+    // Two leaves sharing the first 9 bits of their keys.
+    let left_key = key_with_prefix(&[0b1010_0110, 0b0000_0000]);
+    let key = key_with_prefix(&[0b1010_0110, 0b0100_0000]);
+    let left_leaf = SparseMerkleLeafNode::new(left_key, value_hash);
+    let leaf = SparseMerkleLeafNode::new(key, value_hash);
+    // Left siblings ordered from the bottom up, as the right siblings in the proof.
+    let mut left_siblings = vec![left_leaf.hash()];
+    let mut parent = |bit: bool, hash: HashValue| {
+        if bit {
+            left_siblings.push(*SPARSE_MERKLE_PLACEHOLDER_HASH);
+            SparseMerkleInternalNode::new(*SPARSE_MERKLE_PLACEHOLDER_HASH, hash).hash()
+        } else {
+            SparseMerkleInternalNode::new(hash, *SPARSE_MERKLE_PLACEHOLDER_HASH).hash()
+        }
+    };
+    let bits: Vec<bool> = key.iter_bits().take(9).collect();
+    let mut subtree_hash = SparseMerkleInternalNode::new(left_leaf.hash(), leaf.hash()).hash();
+    for bit in bits[2..].iter().rev() {
+        subtree_hash = parent(*bit, subtree_hash);
+    }
+    // Both keys start with bits 1, 0.
+    let subtree_hash = parent(false, subtree_hash);
+    let root_hash = parent(true, subtree_hash);
+    let proof = rightmost_leaf_range_proof(Some(left_key), key);
+    assert_eq!(proof.right_siblings().len(), 10 - left_siblings.len());
+    proof
+        .verify(root_hash, leaf, left_siblings.clone())
+        .unwrap();
+
+    // Doesn't verify if there's in fact a leaf on the right, starting with bits 1, 1.
+    let right_leaf = SparseMerkleLeafNode::new(key_with_prefix(&[0xFF]), value_hash);
+    let root_with_right_leaf = SparseMerkleInternalNode::new(
+        *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        SparseMerkleInternalNode::new(subtree_hash, right_leaf.hash()).hash(),
+    )
+    .hash();
+    proof
+        .verify(root_with_right_leaf, leaf, left_siblings)
+        .unwrap_err();
+}
+
+#[test]
+fn test_select_boundaries() {
+    let keys: Vec<HashValue> = (1..=9u8).map(|i| key_with_prefix(&[i])).collect();
+    let change = |key: HashValue, deleted: bool| {
+        let state_key = StateKey::raw(key.to_vec());
+        let value = (!deleted).then(|| StateValue::from(key.to_vec()));
+        (key, (state_key, value))
+    };
+    let changes: StateChanges = vec![
+        change(keys[1], false),
+        change(keys[2], true),
+        change(keys[4], true),
+        change(keys[6], true),
+        change(keys[7], true),
+    ]
+    .into_iter()
+    .collect();
+
+    let candidates = [keys[0], keys[2], keys[3], keys[4], keys[7], keys[8]]
+        .into_iter()
+        .collect();
+    let mut selector = BoundarySelector::new(candidates);
+    for (key, (_, value)) in &changes {
+        selector.add_change(*key, value.is_none());
+    }
+    assert_eq!(selector.finish(), vec![
+        // not changed
+        keys[0],
+        // deleted, replaced by the largest key changed but not deleted since the previous one
+        keys[1], // not changed
+        keys[3],
+        // deleted, and no key changed but not deleted since the previous one, so dropped
+        // (keys[4])
+        // deleted, and the keys changed since the previous one are deleted as well, so dropped
+        // (keys[7])
+        // not changed
+        keys[8],
+    ]);
+}
+
+#[test]
+fn test_change_runs() {
+    let keys: Vec<StateKey> = (0..20u8).map(|i| StateKey::raw(vec![i])).collect();
+    // Folded like the write sets of consecutive transactions, each key changed a few times.
+    let mut expected = StateChanges::new();
+    let mut changes = ChangeRuns::new(200);
+    let rt = Runtime::new().unwrap();
+    for txn in 0..10usize {
+        for (i, key) in keys.iter().enumerate().skip(txn).step_by(txn % 3 + 1) {
+            let value = ((i + txn) % 4 != 0).then(|| StateValue::from(vec![i as u8, txn as u8]));
+            expected.insert(key.hash(), (key.clone(), value.clone()));
+            rt.block_on(changes.insert(key.clone(), value)).unwrap();
+        }
+    }
+    assert!(changes.num_spilled_runs() > 1);
+
+    let merged = rt.block_on(async {
+        let mut merged = vec![];
+        let mut iter = changes.iter().await.unwrap();
+        while let Some(change) = iter.next().await.unwrap() {
+            merged.push(change);
+        }
+        merged
+    });
+    assert_eq!(merged, expected.into_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_full_manifest_deserializes_as_full() {
+    let full = StateSnapshotBackup {
+        version: 100,
+        epoch: 2,
+        root_hash: HashValue::random(),
+        chunks: vec![StateSnapshotChunk {
+            first_idx: 0,
+            last_idx: 9,
+            first_key: HashValue::random(),
+            last_key: HashValue::random(),
+            blobs: "state_epoch_2_ver_100.1234/0-.chunk".to_string(),
+            proof: "state_epoch_2_ver_100.1234/0-.proof".to_string(),
+        }],
+        proof: "state_epoch_2_ver_100.1234/state.proof".to_string(),
+    };
+    // As written by the full state snapshot backup, which knows nothing of incremental ones.
+    let manifest_bytes = serde_json::to_vec(&full).unwrap();
+
+    match serde_json::from_slice(&manifest_bytes).unwrap() {
+        StateSnapshotManifest::Full(manifest) => {
+            assert_eq!(manifest.version, full.version);
+            assert_eq!(manifest.root_hash, full.root_hash);
+            assert_eq!(manifest.chunks.len(), 1);
+        },
+        StateSnapshotManifest::Incremental(_) => panic!("Full manifest read as incremental."),
+    }
+}
@@ -70,7 +70,7 @@ impl StateSnapshotBackupController {
     }
 
     async fn run_impl(mut self) -> Result<FileHandle> {
-        self.version = Some(self.get_version_for_epoch_ending(self.epoch).await?);
+        self.version = Some(get_version_for_epoch_ending(&self.client, self.epoch).await?);
         let backup_handle = self
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
@@ -174,21 +174,6 @@ impl StateSnapshotBackupController {
         Ok(key.hash())
     }
 
-    async fn get_version_for_epoch_ending(&self, epoch: u64) -> Result<u64> {
-        let ledger_info: LedgerInfoWithSignatures = bcs::from_bytes(
-            self.client
-                .get_epoch_ending_ledger_infos(epoch, epoch + 1)
-                .await?
-                .read_record_bytes()
-                .await?
-                .ok_or_else(|| {
-                    anyhow!("Failed to get epoch ending ledger info for epoch {}", epoch)
-                })?
-                .as_ref(),
-        )?;
-        Ok(ledger_info.ledger_info().version())
-    }
-
     async fn write_chunk(
         &self,
         backup_handle: &BackupHandleRef,
@@ -273,3 +258,19 @@ impl StateSnapshotBackupController {
         Ok(manifest_handle)
     }
 }
+
+pub(crate) async fn get_version_for_epoch_ending(
+    client: &BackupServiceClient,
+    epoch: u64,
+) -> Result<Version> {
+    let ledger_info: LedgerInfoWithSignatures = bcs::from_bytes(
+        client
+            .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+            .await?
+            .read_record_bytes()
+            .await?
+            .ok_or_else(|| anyhow!("Failed to get epoch ending ledger info for epoch {}", epoch))?
+            .as_ref(),
+    )?;
+    Ok(ledger_info.ledger_info().version())
+}
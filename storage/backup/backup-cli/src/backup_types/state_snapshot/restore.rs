@@ -4,7 +4,9 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotBackup,
+        epoch_ending::restore::EpochHistory,
+        incremental_state_snapshot::{manifest::StateSnapshotManifest, restore::read_state_chunks},
+        state_snapshot::manifest::StateSnapshotBackup,
     },
     metrics::{
         restore::{
@@ -23,6 +25,7 @@ use crate::{
     },
 };
 use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::HashValue;
 use aptos_db::state_restore::StateSnapshotRestoreMode;
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
@@ -31,7 +34,7 @@ use aptos_types::{
     access_path::Path,
     ledger_info::LedgerInfoWithSignatures,
     on_chain_config::{Features, TimedFeatureOverride, TimedFeaturesBuilder},
-    proof::TransactionInfoWithProof,
+    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
     state_store::{
         state_key::{StateKey, StateKeyInner},
         state_value::StateValue,
@@ -40,7 +43,11 @@ use aptos_types::{
 };
 use aptos_vm::move_vm_ext::verifier_config;
 use clap::Parser;
-use futures::{stream, TryStreamExt};
+use futures::{
+    future,
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
 use move_binary_format::CompiledModule;
 use move_bytecode_verifier::verify_module_with_config;
 use std::sync::Arc;
@@ -58,6 +65,19 @@ pub struct StateSnapshotRestoreOpt {
     pub restore_mode: StateSnapshotRestoreMode,
 }
 
+/// A chunk of state values, and the proof that they add up to the root hash with all values
+/// before them.
+pub(crate) struct StateChunk {
+    /// index of the first value in this chunk over all values.
+    pub first_idx: usize,
+    /// index of the last value in this chunk over all values.
+    pub last_idx: usize,
+    /// key of the last value in this chunk.
+    pub last_key: HashValue,
+    pub blobs: Vec<(StateKey, StateValue)>,
+    pub proof: SparseMerkleRangeProof,
+}
+
 pub struct StateSnapshotRestoreController {
     storage: Arc<dyn BackupStorage>,
     run_mode: Arc<RestoreRunMode>,
@@ -120,18 +140,18 @@ impl StateSnapshotRestoreController {
             return Ok(());
         }
 
-        let manifest: StateSnapshotBackup =
+        let manifest: StateSnapshotManifest =
             self.storage.load_json_file(&self.manifest_handle).await?;
         let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
-            self.storage.load_bcs_file(&manifest.proof).await?;
-        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+            self.storage.load_bcs_file(manifest.proof()).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version())?;
         let state_root_hash = txn_info_with_proof
             .transaction_info()
             .ensure_state_checkpoint_hash()?;
         ensure!(
-            state_root_hash == manifest.root_hash,
+            state_root_hash == manifest.root_hash(),
             "Root hash mismatch with that in proof. root hash: {}, expected: {}",
-            manifest.root_hash,
+            manifest.root_hash(),
             state_root_hash,
         );
         if let Some(epoch_history) = self.epoch_history.as_ref() {
@@ -140,7 +160,7 @@ impl StateSnapshotRestoreController {
 
         let receiver = Arc::new(Mutex::new(Some(self.run_mode.get_state_restore_receiver(
             self.version,
-            manifest.root_hash,
+            manifest.root_hash(),
             self.restore_mode,
         )?)));
 
@@ -159,47 +179,44 @@ impl StateSnapshotRestoreController {
         };
 
         ver_gauge.set(self.version as i64);
-        tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
-        let total_chunks = manifest.chunks.len();
-
         let resume_point_opt = receiver.lock().as_mut().unwrap().previous_key_hash()?;
-        let chunks = if let Some(resume_point) = resume_point_opt {
-            manifest
-                .chunks
-                .into_iter()
-                .skip_while(|chunk| chunk.last_key <= resume_point)
-                .collect()
-        } else {
-            manifest.chunks
+        let mut chunks_stream = match manifest {
+            StateSnapshotManifest::Full(manifest) => {
+                tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
+                self.read_chunks(manifest, resume_point_opt)
+            },
+            StateSnapshotManifest::Incremental(manifest) => {
+                // The number of values is unknown until all of them are read.
+                tgt_leaf_idx.set(0);
+                info!(
+                    base_version = manifest.base.version,
+                    "Restoring incremental state snapshot on top of its base."
+                );
+                // All values are read to compose the state, but only those not yet added are
+                // added.
+                read_state_chunks(self.storage.clone(), manifest, self.concurrent_downloads)
+                    .try_filter(move |chunk| {
+                        future::ready(resume_point_opt.map_or(true, |p| chunk.last_key > p))
+                    })
+                    .boxed()
+            },
         };
-        if chunks.len() < total_chunks {
-            info!(
-                chunks_to_add = chunks.len(),
-                total_chunks = total_chunks,
-                "Resumed state snapshot restore."
-            )
-        };
-        let chunks_to_add = chunks.len();
-
-        let start_idx = chunks.first().map_or(0, |chunk| chunk.first_idx);
 
-        let storage = self.storage.clone();
-        let futs_iter = chunks.into_iter().enumerate().map(|(chunk_idx, chunk)| {
-            let storage = storage.clone();
-            async move {
-                tokio::spawn(async move {
-                    let blobs = Self::read_state_value(&storage, chunk.blobs.clone()).await?;
-                    let proof = storage.load_bcs_file(&chunk.proof).await?;
-                    Result::<_>::Ok((chunk_idx, chunk, blobs, proof))
-                })
-                .await?
-            }
-        });
-        let con = self.concurrent_downloads;
-        let mut futs_stream = stream::iter(futs_iter).buffered_x(con * 2, con);
         let mut start = None;
-        while let Some((chunk_idx, chunk, mut blobs, proof)) = futs_stream.try_next().await? {
-            start = start.or_else(|| Some(Instant::now()));
+        let mut start_idx = 0;
+        let mut chunk_idx = 0;
+        while let Some(StateChunk {
+            first_idx,
+            last_idx,
+            last_key: _,
+            mut blobs,
+            proof,
+        }) = chunks_stream.try_next().await?
+        {
+            if start.is_none() {
+                start = Some(Instant::now());
+                start_idx = first_idx;
+            }
             let _timer = OTHER_TIMERS_SECONDS
                 .with_label_values(&["add_state_chunk"])
                 .start_timer();
@@ -215,16 +232,16 @@ impl StateSnapshotRestoreController {
                 receiver.lock().as_mut().unwrap().add_chunk(blobs, proof)
             })
             .await??;
-            leaf_idx.set(chunk.last_idx as i64);
+            leaf_idx.set(last_idx as i64);
             info!(
                 chunk = chunk_idx,
-                chunks_to_add = chunks_to_add,
-                last_idx = chunk.last_idx,
-                values_per_second = ((chunk.last_idx + 1 - start_idx) as f64
+                last_idx = last_idx,
+                values_per_second = ((last_idx + 1 - start_idx) as f64
                     / start.as_ref().unwrap().elapsed().as_secs_f64())
                     as u64,
                 "State chunk added.",
             );
+            chunk_idx += 1;
         }
 
         tokio::task::spawn_blocking(move || receiver.lock().take().unwrap().finish()).await??;
@@ -232,6 +249,51 @@ impl StateSnapshotRestoreController {
         Ok(())
     }
 
+    fn read_chunks(
+        &self,
+        manifest: StateSnapshotBackup,
+        resume_point_opt: Option<HashValue>,
+    ) -> BoxStream<'static, Result<StateChunk>> {
+        let total_chunks = manifest.chunks.len();
+        let chunks: Vec<_> = if let Some(resume_point) = resume_point_opt {
+            manifest
+                .chunks
+                .into_iter()
+                .skip_while(|chunk| chunk.last_key <= resume_point)
+                .collect()
+        } else {
+            manifest.chunks
+        };
+        if chunks.len() < total_chunks {
+            info!(
+                chunks_to_add = chunks.len(),
+                total_chunks = total_chunks,
+                "Resumed state snapshot restore."
+            )
+        };
+
+        let storage = self.storage.clone();
+        let futs_iter = chunks.into_iter().map(move |chunk| {
+            let storage = storage.clone();
+            async move {
+                tokio::spawn(async move {
+                    let blobs = Self::read_state_value(&storage, chunk.blobs.clone()).await?;
+                    let proof = storage.load_bcs_file(&chunk.proof).await?;
+                    Result::<_>::Ok(StateChunk {
+                        first_idx: chunk.first_idx,
+                        last_idx: chunk.last_idx,
+                        last_key: chunk.last_key,
+                        blobs,
+                        proof,
+                    })
+                })
+                .await?
+            }
+        });
+        let con = self.concurrent_downloads;
+        stream::iter(futs_iter).buffered_x(con * 2, con).boxed()
+    }
+
     fn validate_modules(blob: &[(StateKey, StateValue)]) {
         let config = verifier_config(
             &Features::default(),
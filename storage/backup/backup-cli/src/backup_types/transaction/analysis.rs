@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+use aptos_transaction_generator_lib::trace::TraceProfileBuilder;
 use aptos_types::{
     contract_event::ContractEvent,
     transaction::{Transaction, TransactionInfo, Version},
@@ -11,7 +12,7 @@ use aptos_types::{
 use serde::Serialize;
 use std::{
     fs::{File, OpenOptions},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 #[derive(Serialize)]
@@ -40,6 +41,8 @@ pub struct TransactionAnalysis {
     txn_writer: csv::Writer<File>,
     event_writer: csv::Writer<File>,
     write_op_writer: csv::Writer<File>,
+    trace_profile_builder: TraceProfileBuilder,
+    trace_profile_path: PathBuf,
 }
 
 impl TransactionAnalysis {
@@ -53,6 +56,8 @@ impl TransactionAnalysis {
             txn_writer,
             event_writer,
             write_op_writer,
+            trace_profile_builder: TraceProfileBuilder::new(),
+            trace_profile_path: output_dir.join("trace_profile.json"),
         })
     }
 
@@ -104,10 +109,22 @@ impl TransactionAnalysis {
             events_size,
             write_set_size,
         })?;
+        self.trace_profile_builder.add_transaction(version, txn);
 
         Ok(())
     }
 
+    /// Flushes the CSV files and writes the trace profile of the transactions analyzed, which
+    /// the transaction emitter and the executor benchmark can replay.
+    pub fn finish(mut self) -> Result<()> {
+        self.txn_writer.flush()?;
+        self.event_writer.flush()?;
+        self.write_op_writer.flush()?;
+        self.trace_profile_builder
+            .build()
+            .save(&self.trace_profile_path)
+    }
+
     fn txn_size(txn: &Transaction) -> usize {
         use Transaction::*;
 
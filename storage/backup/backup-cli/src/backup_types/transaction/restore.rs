@@ -667,7 +667,7 @@ impl TransactionRestoreBatchController {
             .map(|dir| TransactionAnalysis::new(dir))
             .transpose()?;
         let start = Instant::now();
-        loaded_chunk_stream
+        let analysis = loaded_chunk_stream
             .try_fold(analysis, |mut analysis, chunk| async move {
                 let mut version = chunk.manifest.first_version;
                 let last_version = chunk.manifest.last_version;
@@ -690,6 +690,9 @@ impl TransactionRestoreBatchController {
                 Ok(analysis)
             })
             .await?;
+        if let Some(analysis) = analysis {
+            analysis.finish()?;
+        }
         Ok(())
     }
 }
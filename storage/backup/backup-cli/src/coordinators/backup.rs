@@ -5,6 +5,10 @@
 use crate::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        incremental_state_snapshot::backup::{
+            IncrementalStateSnapshotBackupController, IncrementalStateSnapshotBackupOpt,
+            DEFAULT_MAX_CHANGES_IN_MEMORY_BYTES,
+        },
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
@@ -56,6 +60,16 @@ pub struct BackupCoordinatorOpt {
         is already at 19, then snapshot at 15 will be taken instead of at 10 (not at 18)."
     )]
     pub state_snapshot_interval_epochs: usize,
+    // Defaulting to 1 to always take full state snapshots.
+    #[clap(
+        long,
+        default_value_t = 1,
+        help = "Take a full state snapshot once every this many state snapshots, and incremental \
+        ones in between. An incremental snapshot only contains the state changed since the previous \
+        snapshot, so it's much cheaper to take and store, but restoring it requires reading all the \
+        snapshots back to the last full one."
+    )]
+    pub full_state_snapshot_every: usize,
     // Defaulting to 1M, which converts to a 20 minutes delay of a transaction showing up in a backup,
     // from a 1K TPS chain, and a few minutes replay time.
     #[clap(
@@ -76,7 +90,9 @@ pub struct BackupCoordinatorOpt {
 impl BackupCoordinatorOpt {
     fn validate(&self) -> Result<()> {
         ensure!(
-            self.state_snapshot_interval_epochs > 0 && self.transaction_batch_size > 0,
+            self.state_snapshot_interval_epochs > 0
+                && self.full_state_snapshot_every > 0
+                && self.transaction_batch_size > 0,
             "Backup interval and batch size must be greater than 0."
         );
         Ok(())
@@ -89,6 +105,7 @@ pub struct BackupCoordinator {
     global_opt: GlobalBackupOpt,
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval_epochs: usize,
+    full_state_snapshot_every: usize,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
 }
@@ -107,6 +124,7 @@ impl BackupCoordinator {
             global_opt,
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval_epochs: opt.state_snapshot_interval_epochs,
+            full_state_snapshot_every: opt.full_state_snapshot_every,
             transaction_batch_size: opt.transaction_batch_size,
             concurrent_downloads: opt.concurrent_downloads.get(),
         }
@@ -255,16 +273,57 @@ impl BackupCoordinator {
             return Ok(last_snapshot_epoch_in_backup);
         }
 
-        StateSnapshotBackupController::new(
-            StateSnapshotBackupOpt { epoch },
-            self.global_opt.clone(),
-            Arc::clone(&self.client),
+        match self.get_incremental_state_snapshot_base().await? {
+            Some(base_manifest) => {
+                IncrementalStateSnapshotBackupController::new(
+                    IncrementalStateSnapshotBackupOpt {
+                        epoch,
+                        base_manifest,
+                        max_changes_in_memory_bytes: DEFAULT_MAX_CHANGES_IN_MEMORY_BYTES,
+                    },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.client),
+                    Arc::clone(&self.storage),
+                )
+                .run()
+                .await?
+            },
+            None => {
+                StateSnapshotBackupController::new(
+                    StateSnapshotBackupOpt { epoch },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.client),
+                    Arc::clone(&self.storage),
+                )
+                .run()
+                .await?
+            },
+        };
+
+        Ok(Some(epoch))
+    }
+
+    /// Returns the manifest of the latest state snapshot in the backup storage to take an
+    /// incremental snapshot on top of, or None if it's time for a full snapshot.
+    async fn get_incremental_state_snapshot_base(&self) -> Result<Option<FileHandle>> {
+        if self.full_state_snapshot_every == 1 {
+            return Ok(None);
+        }
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
             Arc::clone(&self.storage),
+            self.concurrent_downloads,
         )
-        .run()
         .await?;
-
-        Ok(Some(epoch))
+        Ok(match metadata_view.select_state_snapshot(Version::MAX)? {
+            Some(base)
+                if metadata_view.state_snapshot_chain_len(base.version)? + 1
+                    < self.full_state_snapshot_every =>
+            {
+                Some(base.manifest)
+            },
+            _ => None,
+        })
     }
 
     async fn backup_transactions(
@@ -448,6 +507,17 @@ impl BackupCompactor {
                 .await?;
             new_files.insert(file_handle);
         }
+        for range in
+            metaview.compact_incremental_state_backups(self.state_snapshot_file_compact_factor)?
+        {
+            let (state_range, file_name) =
+                Metadata::compact_incremental_state_snapshot_backup_range(range.to_vec())?;
+            let file_handle = self
+                .storage
+                .save_metadata_lines(&file_name, state_range.as_slice())
+                .await?;
+            new_files.insert(file_handle);
+        }
 
         // Move expired files to the metadata backup folder
         let (to_move, compaction_meta) =
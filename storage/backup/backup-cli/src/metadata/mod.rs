@@ -17,6 +17,7 @@ use std::{cmp::Ordering, collections::HashMap, convert::TryInto};
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
+    IncrementalStateSnapshotBackup(IncrementalStateSnapshotBackupMeta),
     TransactionBackup(TransactionBackupMeta),
     Identity(IdentityMeta),
     CompactionTimestamps(CompactionTimestampsMeta),
@@ -47,6 +48,20 @@ impl Metadata {
         })
     }
 
+    pub fn new_incremental_state_snapshot_backup(
+        epoch: u64,
+        version: Version,
+        base_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::IncrementalStateSnapshotBackup(IncrementalStateSnapshotBackupMeta {
+            epoch,
+            version,
+            base_version,
+            manifest,
+        })
+    }
+
     pub fn new_transaction_backup(
         first_version: Version,
         last_version: Version,
@@ -112,6 +127,25 @@ impl Metadata {
         Ok((res, name.parse()?))
     }
 
+    pub fn compact_incremental_state_snapshot_backup_range(
+        backup_metas: Vec<IncrementalStateSnapshotBackupMeta>,
+    ) -> Result<(Vec<TextLine>, ShellSafeName)> {
+        ensure!(
+            !backup_metas.is_empty(),
+            "compacting an empty metadata vector"
+        );
+        let name = format!(
+            "incremental_state_snapshot_compacted_epoch_{}_{}.meta",
+            backup_metas[0].epoch,
+            backup_metas[backup_metas.len() - 1].epoch
+        );
+        let res: Vec<TextLine> = backup_metas
+            .into_iter()
+            .map(|e| Metadata::IncrementalStateSnapshotBackup(e).to_text_line())
+            .collect::<Result<_>>()?;
+        Ok((res, name.parse()?))
+    }
+
     pub fn compact_transaction_backup_range(
         backup_metas: Vec<TransactionBackupMeta>,
     ) -> Result<(Vec<TextLine>, ShellSafeName)> {
@@ -156,6 +190,9 @@ impl Metadata {
                 format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
             },
             Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::IncrementalStateSnapshotBackup(s) => {
+                format!("incremental_state_snapshot_ver_{}.meta", s.version)
+            },
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version)
             },
@@ -189,6 +226,15 @@ pub struct StateSnapshotBackupMeta {
     pub manifest: FileHandle,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct IncrementalStateSnapshotBackupMeta {
+    pub epoch: u64,
+    pub version: Version,
+    /// Version of the snapshot this one is taken on top of.
+    pub base_version: Version,
+    pub manifest: FileHandle,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TransactionBackupMeta {
     pub first_version: Version,
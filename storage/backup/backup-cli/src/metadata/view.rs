@@ -4,8 +4,9 @@
 
 use crate::{
     metadata::{
-        CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta, Metadata,
-        StateSnapshotBackupMeta, TransactionBackupMeta,
+        CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta,
+        IncrementalStateSnapshotBackupMeta, Metadata, StateSnapshotBackupMeta,
+        TransactionBackupMeta,
     },
     metrics::backup::COMPACTED_TXN_VERSION,
     storage::FileHandle,
@@ -20,6 +21,7 @@ use std::{fmt, str::FromStr};
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    incremental_state_snapshot_backups: Vec<IncrementalStateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
     _identity: Option<IdentityMeta>,
     // The compaction timestamps of the file handles producing this view
@@ -30,6 +32,7 @@ impl MetadataView {
     pub(crate) fn new(metadata_vec: Vec<Metadata>, file_handles: Vec<FileHandle>) -> Self {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
+        let mut incremental_state_snapshot_backups = Vec::new();
         let mut transaction_backups = Vec::new();
         let mut identity = None;
         let mut compaction_timestamps = Vec::new();
@@ -38,6 +41,9 @@ impl MetadataView {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
+                Metadata::IncrementalStateSnapshotBackup(s) => {
+                    incremental_state_snapshot_backups.push(s)
+                },
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
                 Metadata::Identity(i) => identity = Some(i),
                 Metadata::CompactionTimestamps(t) => compaction_timestamps.push(t),
@@ -47,6 +53,8 @@ impl MetadataView {
         epoch_ending_backups.dedup();
         state_snapshot_backups.sort_unstable();
         state_snapshot_backups.dedup();
+        incremental_state_snapshot_backups.sort_unstable();
+        incremental_state_snapshot_backups.dedup();
         transaction_backups.sort_unstable();
         transaction_backups.dedup();
 
@@ -72,6 +80,7 @@ impl MetadataView {
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
+            incremental_state_snapshot_backups,
             transaction_backups,
             _identity: identity,
             compaction_timestamps: compaction_meta_opt,
@@ -105,27 +114,75 @@ impl MetadataView {
         self.compaction_timestamps.clone()
     }
 
+    /// Selects the latest state snapshot no newer than `target_version`, full or incremental. An
+    /// incremental snapshot is restored on top of its base by the state snapshot restore, so it's
+    /// returned in the same form as a full one.
     pub fn select_state_snapshot(
         &self,
         target_version: Version,
     ) -> Result<Option<StateSnapshotBackupMeta>> {
-        Ok(self
+        let latest_version = self
             .state_snapshot_backups
             .iter()
-            .sorted()
-            .rev()
-            .find(|m| m.version <= target_version)
-            .map(Clone::clone))
+            .map(|m| m.version)
+            .chain(
+                self.incremental_state_snapshot_backups
+                    .iter()
+                    .map(|m| m.version),
+            )
+            .filter(|version| *version <= target_version)
+            .max();
+        latest_version
+            .map(|version| self.expect_state_snapshot(version))
+            .transpose()
     }
 
     pub fn expect_state_snapshot(&self, version: Version) -> Result<StateSnapshotBackupMeta> {
+        // A full snapshot is preferred, which is faster to restore.
         self.state_snapshot_backups
             .iter()
+            .sorted()
+            .rev()
             .find(|m| m.version == version)
             .map(Clone::clone)
+            .or_else(|| {
+                self.incremental_state_snapshot_backups
+                    .iter()
+                    .sorted()
+                    .rev()
+                    .find(|m| m.version == version)
+                    .map(|m| StateSnapshotBackupMeta {
+                        epoch: m.epoch,
+                        version: m.version,
+                        manifest: m.manifest.clone(),
+                    })
+            })
             .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
     }
 
+    /// Number of incremental snapshots to restore on top of one another (including itself) to
+    /// restore the snapshot at `version`, 0 if it's a full snapshot.
+    pub fn state_snapshot_chain_len(&self, version: Version) -> Result<usize> {
+        let mut chain_len = 0;
+        let mut version = version;
+        loop {
+            if self
+                .state_snapshot_backups
+                .iter()
+                .any(|m| m.version == version)
+            {
+                return Ok(chain_len);
+            }
+            version = self
+                .incremental_state_snapshot_backups
+                .iter()
+                .find(|m| m.version == version)
+                .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))?
+                .base_version;
+            chain_len += 1;
+        }
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
@@ -235,6 +292,13 @@ impl MetadataView {
         Self::compact_backups(&self.state_snapshot_backups, compaction_cnt)
     }
 
+    pub fn compact_incremental_state_backups(
+        &mut self,
+        compaction_cnt: usize,
+    ) -> Result<Vec<&[IncrementalStateSnapshotBackupMeta]>> {
+        Self::compact_backups(&self.incremental_state_snapshot_backups, compaction_cnt)
+    }
+
     pub fn get_file_handles(&self) -> Vec<FileHandle> {
         self.select_latest_compaction_timestamps()
             .as_ref()
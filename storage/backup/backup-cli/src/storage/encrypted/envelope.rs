@@ -0,0 +1,232 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The format of encrypted files.
+//!
+//! Every file is encrypted with its own random data key, which is stored in the file header,
+//! encrypted (wrapped) by the master key. So the master key only ever encrypts random keys, one
+//! per file, and a leaked data key only exposes a single file.
+//!
+//!   header: MAGIC | VERSION | wrapping nonce (12 bytes) | wrapped data key (32 bytes + 16 tag)
+//!   chunk:  flags and length (u32, big endian) | ciphertext (length bytes, including 16 tag)
+//!
+//! The content is split into chunks of at most `CHUNK_SIZE` bytes, which are sealed separately
+//! (AES-256-GCM) so files can be encrypted and decrypted while streaming. The nonce of a chunk is
+//! its index plus a flag marking the last chunk, so chunks can't be reordered, dropped or
+//! truncated without failing decryption.
+
+use anyhow::{ensure, format_err, Result};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{cmp::min, convert::TryInto};
+
+pub const MAGIC: &[u8; 8] = b"APTOSENC";
+const VERSION: u8 = 1;
+pub const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+pub const HEADER_LEN: usize = MAGIC.len() + 1 + NONCE_LEN + KEY_LEN + TAG_LEN;
+/// Size of the plaintext of all but the last chunk.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+const MAX_CHUNK_CIPHERTEXT_LEN: usize = CHUNK_SIZE + TAG_LEN;
+const LAST_CHUNK_FLAG: u32 = 1 << 31;
+
+/// The key that wraps the data key of every file.
+#[derive(Clone)]
+pub struct MasterKey([u8; KEY_LEN]);
+
+impl MasterKey {
+    pub fn from_hex(hex_key: &str) -> Result<Self> {
+        let bytes = hex::decode(hex_key.trim())
+            .map_err(|e| format_err!("Encryption key is not valid hex: {}", e))?;
+        Ok(Self(bytes.try_into().map_err(|bytes: Vec<u8>| {
+            format_err!(
+                "Encryption key must be {} bytes, got {}.",
+                KEY_LEN,
+                bytes.len()
+            )
+        })?))
+    }
+
+    #[cfg(test)]
+    pub fn generate() -> Self {
+        Self(random())
+    }
+}
+
+/// Returns the header of a new file, and the sealer of its chunks.
+pub fn new_file(master_key: &MasterKey) -> Result<(Vec<u8>, ChunkSealer)> {
+    let data_key: [u8; KEY_LEN] = random();
+    let wrapping_nonce: [u8; NONCE_LEN] = random();
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    header.extend_from_slice(&wrapping_nonce);
+    let mut wrapped_data_key = data_key.to_vec();
+    aead_key(&master_key.0)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(wrapping_nonce),
+            Aad::from(&header[..MAGIC.len() + 1]),
+            &mut wrapped_data_key,
+        )
+        .map_err(|_| format_err!("Failed wrapping data key."))?;
+    header.extend_from_slice(&wrapped_data_key);
+
+    Ok((header, ChunkSealer(ChunkCipher::new(&data_key))))
+}
+
+/// Parses the header of a file, returning the opener of its chunks.
+pub fn open_file(master_key: &MasterKey, header: &[u8; HEADER_LEN]) -> Result<ChunkOpener> {
+    let (magic, rest) = header.split_at(MAGIC.len());
+    ensure!(
+        magic == MAGIC,
+        "Not an encrypted backup file (or encrypted by an incompatible version)."
+    );
+    ensure!(
+        rest[0] == VERSION,
+        "Unsupported encryption version {}.",
+        rest[0]
+    );
+    let wrapping_nonce: [u8; NONCE_LEN] = rest[1..1 + NONCE_LEN].try_into()?;
+    let mut wrapped_data_key = rest[1 + NONCE_LEN..].to_vec();
+    let data_key = aead_key(&master_key.0)
+        .open_in_place(
+            Nonce::assume_unique_for_key(wrapping_nonce),
+            Aad::from(&header[..MAGIC.len() + 1]),
+            &mut wrapped_data_key,
+        )
+        .map_err(|_| format_err!("Failed unwrapping data key, wrong encryption key?"))?;
+
+    Ok(ChunkOpener(ChunkCipher::new(data_key)))
+}
+
+/// Splits the flags and length prefix of a chunk into (is_last, ciphertext length).
+pub fn parse_chunk_prefix(prefix: u32) -> Result<(bool, usize)> {
+    let len = (prefix & !LAST_CHUNK_FLAG) as usize;
+    ensure!(
+        len <= MAX_CHUNK_CIPHERTEXT_LEN,
+        "Corrupted encrypted file, chunk of {} bytes.",
+        len
+    );
+    Ok((prefix & LAST_CHUNK_FLAG != 0, len))
+}
+
+pub struct ChunkSealer(ChunkCipher);
+
+impl ChunkSealer {
+    /// Returns the chunk, including its prefix.
+    pub fn seal(&mut self, mut plaintext: Vec<u8>, is_last: bool) -> Result<Vec<u8>> {
+        assert!(plaintext.len() <= CHUNK_SIZE);
+        let nonce = self.0.next_nonce(is_last)?;
+        self.0
+            .key
+            .seal_in_place_append_tag(nonce, Aad::empty(), &mut plaintext)
+            .map_err(|_| format_err!("Failed encrypting chunk."))?;
+
+        let mut prefix = plaintext.len() as u32;
+        if is_last {
+            prefix |= LAST_CHUNK_FLAG;
+        }
+        let mut chunk = prefix.to_be_bytes().to_vec();
+        chunk.extend_from_slice(&plaintext);
+        Ok(chunk)
+    }
+}
+
+pub struct ChunkOpener(ChunkCipher);
+
+impl ChunkOpener {
+    /// Returns the plaintext of the chunk, given its ciphertext (without prefix).
+    pub fn open(&mut self, mut ciphertext: Vec<u8>, is_last: bool) -> Result<Vec<u8>> {
+        let nonce = self.0.next_nonce(is_last)?;
+        let len = self
+            .0
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .map_err(|_| format_err!("Failed decrypting chunk, file corrupted or tampered with."))?
+            .len();
+        ciphertext.truncate(len);
+        Ok(ciphertext)
+    }
+}
+
+struct ChunkCipher {
+    key: LessSafeKey,
+    next_chunk: u64,
+    finished: bool,
+}
+
+impl ChunkCipher {
+    fn new(data_key: &[u8]) -> Self {
+        Self {
+            key: aead_key(data_key),
+            next_chunk: 0,
+            finished: false,
+        }
+    }
+
+    fn next_nonce(&mut self, is_last: bool) -> Result<Nonce> {
+        ensure!(!self.finished, "Chunk after the last one.");
+        self.finished = is_last;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[0] = is_last as u8;
+        nonce[NONCE_LEN - 8..].copy_from_slice(&self.next_chunk.to_be_bytes());
+        self.next_chunk += 1;
+        Ok(Nonce::assume_unique_for_key(nonce))
+    }
+}
+
+fn aead_key(key: &[u8]) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("Key length is checked."))
+}
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("System random source failed.");
+    bytes
+}
+
+/// Encrypts a whole (small) content at once.
+pub fn seal_all(master_key: &MasterKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let (mut sealed, mut sealer) = new_file(master_key)?;
+    let mut offset = 0;
+    loop {
+        // Same as when streaming, a content of a multiple of CHUNK_SIZE ends with an empty chunk.
+        let end = min(offset + CHUNK_SIZE, plaintext.len());
+        let is_last = end - offset < CHUNK_SIZE;
+        sealed.extend(sealer.seal(plaintext[offset..end].to_vec(), is_last)?);
+        if is_last {
+            break;
+        }
+        offset = end;
+    }
+    Ok(sealed)
+}
+
+/// Decrypts a whole content encrypted by `seal_all()` (or streamed).
+pub fn open_all(master_key: &MasterKey, sealed: &[u8]) -> Result<Vec<u8>> {
+    ensure!(sealed.len() >= HEADER_LEN, "Encrypted content too short.");
+    let (header, mut rest) = sealed.split_at(HEADER_LEN);
+    let mut opener = open_file(master_key, header.try_into()?)?;
+
+    let mut plaintext = Vec::new();
+    loop {
+        ensure!(rest.len() >= 4, "Encrypted content truncated.");
+        let (prefix, after_prefix) = rest.split_at(4);
+        let (is_last, len) = parse_chunk_prefix(u32::from_be_bytes(prefix.try_into()?))?;
+        ensure!(after_prefix.len() >= len, "Encrypted content truncated.");
+        let (ciphertext, after_chunk) = after_prefix.split_at(len);
+        plaintext.extend(opener.open(ciphertext.to_vec(), is_last)?);
+        rest = after_chunk;
+        if is_last {
+            break;
+        }
+    }
+    ensure!(rest.is_empty(), "Trailing data after encrypted content.");
+    Ok(plaintext)
+}
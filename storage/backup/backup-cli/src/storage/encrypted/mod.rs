@@ -0,0 +1,247 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod envelope;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        encrypted::envelope::{ChunkSealer, MasterKey, CHUNK_SIZE, HEADER_LEN},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::{
+        error_notes::ErrorNotes,
+        task_pipe::{SinkReader, TaskAsDataSink, TaskAsDataSource},
+    },
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_config::config::SecureBackend;
+use aptos_secure_storage::{KVStorage, Storage};
+use async_trait::async_trait;
+use clap::Parser;
+use std::{path::PathBuf, sync::Arc};
+use tokio::io::{
+    AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream,
+};
+
+#[derive(Parser, Clone, Debug)]
+pub struct EncryptionOpt {
+    #[clap(
+        long,
+        help = "Encrypt backups written, and decrypt backups read, with the key in this file \
+        (32 bytes, hex encoded). Every file is encrypted with its own random data key, wrapped \
+        by this key."
+    )]
+    encryption_key_file: Option<PathBuf>,
+    #[clap(
+        long,
+        conflicts_with = "encryption_key_file",
+        requires = "encryption_key_name",
+        help = "Like --encryption-key-file, but the key is read from the secure storage \
+        configured in this file (a `SecureBackend` config, e.g. Vault)."
+    )]
+    encryption_secure_backend_config: Option<PathBuf>,
+    #[clap(
+        long,
+        requires = "encryption_secure_backend_config",
+        help = "Name of the key (32 bytes, hex encoded) in the secure storage."
+    )]
+    encryption_key_name: Option<String>,
+}
+
+impl EncryptionOpt {
+    /// Wraps the storage with encryption, if a key is configured.
+    pub async fn wrap_storage(
+        self,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Result<Arc<dyn BackupStorage>> {
+        Ok(match self.master_key().await? {
+            Some(master_key) => Arc::new(EncryptedStorage::new(storage, master_key)),
+            None => storage,
+        })
+    }
+
+    async fn master_key(&self) -> Result<Option<MasterKey>> {
+        if let Some(path) = &self.encryption_key_file {
+            let hex_key = tokio::fs::read_to_string(path).await.err_notes(path)?;
+            return MasterKey::from_hex(&hex_key).map(Some);
+        }
+        if let Some(path) = &self.encryption_secure_backend_config {
+            let config = tokio::fs::read(path).await.err_notes(path)?;
+            let backend: SecureBackend = serde_yaml::from_slice(&config)?;
+            let key_name = self
+                .encryption_key_name
+                .as_ref()
+                .ok_or_else(|| format_err!("--encryption-key-name is required."))?;
+            let hex_key = Storage::from(&backend)
+                .get::<String>(key_name)
+                .map_err(|e| format_err!("Failed reading key {}: {}", key_name, e))?
+                .value;
+            return MasterKey::from_hex(&hex_key).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+/// A BackupStorage that encrypts everything stored in the wrapped storage, with an AEAD
+/// (AES-256-GCM) and envelope encryption, see `envelope`.
+/// Data files are encrypted and decrypted while being streamed. Metadata lines must stay text
+/// lines in the wrapped storage, so the metadata of a file is encrypted as a whole and saved as a
+/// single hex encoded line.
+/// Unencrypted files are refused, rather than passed through, so a replaced file can't sneak in.
+pub struct EncryptedStorage {
+    inner: Arc<dyn BackupStorage>,
+    master_key: MasterKey,
+}
+
+impl EncryptedStorage {
+    /// Size of the in memory pipe between a stream and the task encrypting or decrypting it.
+    const PIPE_SIZE_BYTES: usize = 2 * CHUNK_SIZE;
+
+    pub fn new(inner: Arc<dyn BackupStorage>, master_key: MasterKey) -> Self {
+        Self { inner, master_key }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for EncryptedStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (file_handle, file) = self.inner.create_for_write(backup_handle, name).await?;
+        let (header, sealer) = envelope::new_file(&self.master_key)?;
+        let sink = TaskAsDataSink::spawn(Self::PIPE_SIZE_BYTES, |plaintext| {
+            encrypt(plaintext, header, sealer, file)
+        });
+        Ok((file_handle, Box::new(sink)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let mut file = BufReader::new(self.inner.open_for_read(file_handle).await?);
+        let is_metadata = file
+            .fill_buf()
+            .await?
+            .starts_with(METADATA_LINE_PREFIX.as_bytes());
+        if is_metadata {
+            // Metadata files are small, decrypt at once.
+            let mut plaintext = Vec::new();
+            let mut lines = file.lines();
+            while let Some(line) = lines.next_line().await? {
+                plaintext.extend(decrypt_metadata_line(&self.master_key, &line)?);
+            }
+            return Ok(Box::new(std::io::Cursor::new(plaintext)));
+        }
+
+        let master_key = self.master_key.clone();
+        let source = TaskAsDataSource::spawn(Self::PIPE_SIZE_BYTES, |plaintext| {
+            decrypt(file, master_key, plaintext)
+        });
+        Ok(Box::new(source))
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.inner.list_metadata_files().await
+    }
+
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.backup_metadata_file(file_handle).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let line = encrypt_metadata_line(&self.master_key, content.as_bytes())?;
+        self.inner.save_metadata_lines(name, &[line]).await
+    }
+}
+
+/// Distinguishes an encrypted metadata line from the (binary) header of an encrypted data file.
+const METADATA_LINE_PREFIX: &str = "aptosenc:";
+
+fn encrypt_metadata_line(master_key: &MasterKey, plaintext: &[u8]) -> Result<TextLine> {
+    TextLine::new(&format!(
+        "{}{}",
+        METADATA_LINE_PREFIX,
+        hex::encode(envelope::seal_all(master_key, plaintext)?)
+    ))
+}
+
+fn decrypt_metadata_line(master_key: &MasterKey, line: &str) -> Result<Vec<u8>> {
+    let sealed = line
+        .strip_prefix(METADATA_LINE_PREFIX)
+        .ok_or_else(|| format_err!("Metadata line is not encrypted: {}", line))?;
+    envelope::open_all(master_key, &hex::decode(sealed)?)
+}
+
+async fn encrypt(
+    mut plaintext: SinkReader,
+    header: Vec<u8>,
+    mut sealer: ChunkSealer,
+    mut file: Box<dyn AsyncWrite + Send + Unpin>,
+) -> Result<()> {
+    file.write_all(&header).await?;
+    loop {
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        (&mut plaintext)
+            .take(CHUNK_SIZE as u64)
+            .read_to_end(&mut chunk)
+            .await?;
+        // A content of a multiple of CHUNK_SIZE ends with an empty chunk.
+        let is_last = chunk.len() < CHUNK_SIZE;
+        file.write_all(&sealer.seal(chunk, is_last)?).await?;
+        if is_last {
+            break;
+        }
+    }
+    file.shutdown().await?;
+    Ok(())
+}
+
+async fn decrypt(
+    mut file: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
+    master_key: MasterKey,
+    mut plaintext: DuplexStream,
+) -> Result<()> {
+    let mut header = [0u8; HEADER_LEN];
+    file.read_exact(&mut header)
+        .await
+        .map_err(|e| format_err!("Failed reading encryption header: {}", e))?;
+    let mut opener = envelope::open_file(&master_key, &header)?;
+
+    loop {
+        let (is_last, len) = envelope::parse_chunk_prefix(file.read_u32().await?)?;
+        let mut ciphertext = vec![0u8; len];
+        file.read_exact(&mut ciphertext).await?;
+        plaintext
+            .write_all(&opener.open(ciphertext, is_last)?)
+            .await?;
+        if is_last {
+            break;
+        }
+    }
+    ensure!(
+        file.read(&mut [0u8; 1]).await? == 0,
+        "Trailing data after the last encrypted chunk."
+    );
+    plaintext.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,200 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    local_fs::LocalFs,
+    test_util::{
+        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl,
+    },
+};
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+fn new_store(dir: &Path, master_key: MasterKey) -> (Arc<LocalFs>, EncryptedStorage) {
+    let inner = Arc::new(LocalFs::new(dir.to_path_buf()));
+    let store = EncryptedStorage::new(inner.clone(), master_key);
+    (inner, store)
+}
+
+async fn write_file(store: &dyn BackupStorage, content: &[u8]) -> FileHandle {
+    let backup_handle = store
+        .create_backup(&"backup".parse().unwrap())
+        .await
+        .unwrap();
+    let (file_handle, mut file) = store
+        .create_for_write(&backup_handle, &"file".parse().unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    file_handle
+}
+
+async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut content)
+        .await?;
+    Ok(content)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let (_inner, store) = new_store(tmpdir.path(), MasterKey::generate());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let (_inner, store) = new_store(tmpdir.path(), MasterKey::generate());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[test]
+fn test_chunk_boundaries() {
+    Runtime::new().unwrap().block_on(async {
+        for len in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE,
+        ] {
+            let tmpdir = TempPath::new();
+            tmpdir.create_as_dir().unwrap();
+            let (_inner, store) = new_store(tmpdir.path(), MasterKey::generate());
+            let content: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let file_handle = write_file(&store, &content).await;
+            assert!(read_file(&store, &file_handle).await.unwrap() == content);
+        }
+    });
+}
+
+#[test]
+fn test_no_plaintext_in_inner_storage() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let (inner, store) = new_store(tmpdir.path(), MasterKey::generate());
+    let content = b"some very recognizable content".repeat(100);
+
+    Runtime::new().unwrap().block_on(async {
+        let file_handle = write_file(&store, &content).await;
+        let stored = read_file(inner.as_ref(), &file_handle).await.unwrap();
+        assert!(stored.starts_with(envelope::MAGIC));
+        assert!(!stored
+            .windows(content.len() / 100)
+            .any(|w| w == &content[..content.len() / 100]));
+
+        let line = TextLine::new("{\"some\":\"metadata\"}").unwrap();
+        let metadata_handle = store
+            .save_metadata_line(&"metadata".parse().unwrap(), &line)
+            .await
+            .unwrap();
+        let stored = read_file(inner.as_ref(), &metadata_handle).await.unwrap();
+        assert!(stored.starts_with(METADATA_LINE_PREFIX.as_bytes()));
+        assert!(!String::from_utf8(stored).unwrap().contains("metadata\""));
+        assert_eq!(
+            read_file(&store, &metadata_handle).await.unwrap(),
+            line.as_ref().as_bytes()
+        );
+    });
+}
+
+#[test]
+fn test_wrong_key() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let (inner, store) = new_store(tmpdir.path(), MasterKey::generate());
+    let other_store = EncryptedStorage::new(inner, MasterKey::generate());
+
+    Runtime::new().unwrap().block_on(async {
+        let file_handle = write_file(&store, b"content").await;
+        read_file(&other_store, &file_handle).await.unwrap_err();
+
+        let metadata_handle = store
+            .save_metadata_line(
+                &"metadata".parse().unwrap(),
+                &TextLine::new("metadata").unwrap(),
+            )
+            .await
+            .unwrap();
+        read_file(&other_store, &metadata_handle).await.unwrap_err();
+    });
+}
+
+#[test]
+fn test_tampered_files_are_rejected() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let (_inner, store) = new_store(tmpdir.path(), MasterKey::generate());
+    let content: Vec<u8> = (0..2 * CHUNK_SIZE + 100).map(|i| (i % 251) as u8).collect();
+
+    Runtime::new().unwrap().block_on(async {
+        let file_handle = write_file(&store, &content).await;
+        let path = tmpdir.path().join(&file_handle);
+        let original = tokio::fs::read(&path).await.unwrap();
+        let first_chunk_len = 4 + CHUNK_SIZE + 16;
+
+        let tampered: Vec<Vec<u8>> = vec![
+            // flipped bit in the ciphertext
+            {
+                let mut bytes = original.clone();
+                bytes[HEADER_LEN + 10] ^= 1;
+                bytes
+            },
+            // truncated after a full chunk, with the remaining chunks dropped
+            original[..HEADER_LEN + first_chunk_len].to_vec(),
+            // truncated in the middle of the last chunk
+            original[..original.len() - 1].to_vec(),
+            // trailing data
+            [original.clone(), vec![0]].concat(),
+            // the first chunk swapped with the second one
+            [
+                &original[..HEADER_LEN],
+                &original[HEADER_LEN + first_chunk_len..HEADER_LEN + 2 * first_chunk_len],
+                &original[HEADER_LEN..HEADER_LEN + first_chunk_len],
+                &original[HEADER_LEN + 2 * first_chunk_len..],
+            ]
+            .concat(),
+            // not encrypted at all
+            content.clone(),
+        ];
+        for bytes in tampered {
+            tokio::fs::write(&path, bytes).await.unwrap();
+            read_file(&store, &file_handle).await.unwrap_err();
+        }
+
+        tokio::fs::write(&path, original).await.unwrap();
+        assert!(read_file(&store, &file_handle).await.unwrap() == content);
+    });
+}
+
+#[test]
+fn test_master_key_from_hex() {
+    MasterKey::from_hex(&format!("{}\n", "ab".repeat(32))).unwrap();
+    assert!(MasterKey::from_hex(&"ab".repeat(31)).is_err());
+    assert!(MasterKey::from_hex(&"zz".repeat(32)).is_err());
+}
@@ -0,0 +1,216 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The subset of the S3 API used by the backup storage, on top of the AWS SDK, which takes care
+//! of signing, credentials and retries.
+
+use crate::storage::s3::config::S3Config;
+use anyhow::{format_err, Result};
+use aws_sdk_s3::{
+    error::DisplayErrorContext,
+    primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart},
+    Client,
+};
+use bytes::Bytes;
+use tokio::io::AsyncRead;
+
+pub(super) struct S3Client {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Client {
+    pub async fn new(config: &S3Config) -> Result<Self> {
+        Ok(Self {
+            client: Client::from_conf(config.sdk_config().await),
+            bucket: config.bucket.clone(),
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(s3_error("put", key))?;
+        Ok(())
+    }
+
+    pub async fn get_object(&self, key: &str) -> Result<impl AsyncRead + Send + Unpin> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(s3_error("get", key))?;
+        Ok(output.body.into_async_read())
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(s3_error("delete", key))?;
+        Ok(())
+    }
+
+    pub async fn copy_object(&self, src_key: &str, dst_key: &str) -> Result<()> {
+        self.client
+            .copy_object()
+            .bucket(&self.bucket)
+            .key(dst_key)
+            .copy_source(format!("{}/{}", self.bucket, uri_encode(src_key, false)))
+            .send()
+            .await
+            .map_err(s3_error("copy", src_key))?;
+        Ok(())
+    }
+
+    /// Lists the keys of all objects starting with the prefix.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token.take())
+                .send()
+                .await
+                .map_err(s3_error("list", prefix))?;
+            keys.extend(
+                output
+                    .contents()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|object| object.key().map(str::to_string)),
+            );
+            continuation_token = output.next_continuation_token().map(str::to_string);
+            if !output.is_truncated() || continuation_token.is_none() {
+                break;
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Starts a multipart upload and returns its upload id.
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        self.client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(s3_error("create multipart upload", key))?
+            .upload_id()
+            .map(str::to_string)
+            .ok_or_else(|| format_err!("No upload id for {}", key))
+    }
+
+    /// Uploads a part (part numbers start from 1) and returns its ETag.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        self.client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number as i32)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(s3_error("upload part", key))?
+            .e_tag()
+            .map(str::to_string)
+            .ok_or_else(|| format_err!("No ETag for part {} of {}", part_number, key))
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let parts = etags
+            .iter()
+            .enumerate()
+            .map(|(i, etag)| {
+                CompletedPart::builder()
+                    .part_number(i as i32 + 1)
+                    .e_tag(etag)
+                    .build()
+            })
+            .collect();
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(s3_error("complete multipart upload", key))?;
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.client
+            .abort_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+            .map_err(s3_error("abort multipart upload", key))?;
+        Ok(())
+    }
+}
+
+/// The errors of the SDK only describe their source when displayed with their context.
+fn s3_error<'a, E>(operation: &'a str, key: &'a str) -> impl FnOnce(E) -> anyhow::Error + 'a
+where
+    E: std::error::Error,
+{
+    move |e| {
+        format_err!(
+            "S3 {} of {} failed: {}",
+            operation,
+            key,
+            DisplayErrorContext(e)
+        )
+    }
+}
+
+/// Percent encodes everything but the unreserved characters (and '/' if `encode_slash` is false),
+/// as required for the source of a copy.
+pub(super) fn uri_encode(s: &str, encode_slash: bool) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            },
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
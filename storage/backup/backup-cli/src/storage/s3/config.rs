@@ -0,0 +1,113 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::error_notes::ErrorNotes;
+use anyhow::{ensure, Result};
+use aws_config::{meta::region::RegionProviderChain, retry::RetryConfig};
+use aws_sdk_s3::config::{Credentials, Region};
+use serde::Deserialize;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// Endpoint of an S3 compatible service, e.g. "http://localhost:9000" for a local MinIO.
+    /// The AWS endpoint of the region is used if not set.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Region of the bucket, resolved like the AWS CLI does (AWS_REGION, the profile, the
+    /// instance metadata) if not set, falling back to "us-east-1".
+    #[serde(default)]
+    pub region: Option<String>,
+    pub bucket: String,
+    /// Folder in the bucket under which all files are stored, e.g. "backup1/e1".
+    #[serde(default)]
+    pub prefix: String,
+    /// Address the bucket as "<endpoint>/<bucket>" instead of "<bucket>.<endpoint host>", which
+    /// is required by MinIO and most other S3 compatible services.
+    #[serde(default)]
+    pub path_style: bool,
+    /// Static credentials. If not set, they come from the default AWS credential chain:
+    /// environment variables, profile, web identity token (IRSA), ECS task or EC2 instance role.
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    /// Files larger than this are uploaded in parts of this size. S3 requires parts of at least
+    /// 5MiB and allows at most 10000 parts per file.
+    #[serde(default = "S3Config::default_part_size_bytes")]
+    pub part_size_bytes: usize,
+    /// Number of times a failed request is retried, with exponential backoff.
+    #[serde(default = "S3Config::default_max_retries")]
+    pub max_retries: u32,
+}
+
+impl S3Config {
+    const DEFAULT_REGION: &'static str = "us-east-1";
+    const MIN_PART_SIZE_BYTES: usize = 5 * 1024 * 1024;
+
+    fn default_part_size_bytes() -> usize {
+        16 * 1024 * 1024
+    }
+
+    fn default_max_retries() -> u32 {
+        5
+    }
+
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let path_str = path.to_str().unwrap_or_default();
+        let mut file = tokio::fs::File::open(path).await.err_notes(path_str)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).await.err_notes(path_str)?;
+
+        Self::validate(serde_yaml::from_slice(&content)?)
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Self> {
+        Self::validate(serde_yaml::from_str(content)?)
+    }
+
+    fn validate(self) -> Result<Self> {
+        ensure!(
+            self.part_size_bytes >= Self::MIN_PART_SIZE_BYTES,
+            "part_size_bytes must be at least {}, got {}",
+            Self::MIN_PART_SIZE_BYTES,
+            self.part_size_bytes,
+        );
+        ensure!(!self.bucket.is_empty(), "bucket must not be empty.");
+        ensure!(
+            self.access_key_id.is_some() == self.secret_access_key.is_some(),
+            "access_key_id and secret_access_key must be set together.",
+        );
+        Ok(self)
+    }
+
+    /// Config of the SDK client, with what's not configured here resolved from the environment.
+    pub(super) async fn sdk_config(&self) -> aws_sdk_s3::Config {
+        let region = RegionProviderChain::first_try(self.region.clone().map(Region::new))
+            .or_default_provider()
+            .or_else(Self::DEFAULT_REGION);
+        let mut loader = aws_config::from_env()
+            .region(region)
+            .retry_config(RetryConfig::standard().with_max_attempts(self.max_retries + 1));
+        if let (Some(access_key_id), Some(secret_access_key)) =
+            (&self.access_key_id, &self.secret_access_key)
+        {
+            loader = loader.credentials_provider(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                None,
+                None,
+                "backup-config",
+            ));
+        }
+
+        let mut builder = aws_sdk_s3::config::Builder::from(&loader.load().await)
+            .force_path_style(self.path_style);
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+        builder.build()
+    }
+}
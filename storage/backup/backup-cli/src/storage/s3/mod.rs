@@ -0,0 +1,222 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+pub mod config;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        s3::{client::S3Client, config::S3Config},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::task_pipe::{SinkReader, TaskAsDataSink},
+};
+use anyhow::{format_err, Result};
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+pub struct S3Opt {
+    #[clap(long = "config", help = "Config file for the S3 backup store.")]
+    config: PathBuf,
+}
+
+impl FromStr for S3Opt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(S3Opt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+/// A BackupStorage that talks to an S3 compatible object store directly.
+/// File handles are paths relative to the configured prefix in the bucket, like the ones of
+/// `LocalFs`, so backups can be copied between the two with ordinary tools.
+pub struct S3Storage {
+    client: Arc<S3Client>,
+    prefix: String,
+    part_size_bytes: usize,
+}
+
+impl S3Storage {
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+    /// Size of the in memory pipe between a writer and the uploading task.
+    const PIPE_SIZE_BYTES: usize = 1024 * 1024;
+
+    pub async fn new(config: S3Config) -> Result<Self> {
+        Ok(Self {
+            client: Arc::new(S3Client::new(&config).await?),
+            prefix: config.prefix.trim_matches('/').to_string(),
+            part_size_bytes: config.part_size_bytes,
+        })
+    }
+
+    pub async fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        Self::new(S3Config::load_from_file(&opt.config).await?).await
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+
+    fn file_handle<'a>(&self, key: &'a str) -> Option<&'a str> {
+        if self.prefix.is_empty() {
+            Some(key)
+        } else {
+            key.strip_prefix(&self.prefix)?.strip_prefix('/')
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3Storage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // Folders are implied by the object keys, nothing to create.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let client = self.client.clone();
+        let key = self.key(&file_handle);
+        let part_size_bytes = self.part_size_bytes;
+        // Bytes written are piped to a task that uploads them, in parts if they don't fit in one.
+        // Shutting the writer down waits for the upload to complete, so a file handle is never
+        // persisted before the file is fully stored. Dropping it without shutting it down fails
+        // the upload instead, which is then aborted, so a partial file is never stored.
+        let sink = TaskAsDataSink::spawn(Self::PIPE_SIZE_BYTES, |reader| async move {
+            let res = upload(&client, &key, reader, part_size_bytes).await;
+            if let Err(e) = &res {
+                error!(key = key, error = %e, "Failed uploading to S3.");
+            }
+            res
+        });
+        Ok((file_handle, Box::new(sink)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        Ok(Box::new(
+            self.client.get_object(&self.key(file_handle)).await?,
+        ))
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = format!("{}/", self.key(Self::METADATA_DIR));
+        Ok(self
+            .client
+            .list_objects(&prefix)
+            .await?
+            .iter()
+            .filter_map(|key| self.file_handle(key))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// file_handle are expected to be the return results from list_metadata_files
+    /// file_handle is a path with `metadata` in the path, Ex: metadata/epoch_ending_1.meta
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let name = Path::new(file_handle)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+        let src_key = self.key(file_handle);
+        let dst_key = self.key(&format!("{}/{}", Self::METADATA_BACKUP_DIR, name));
+
+        // S3 has no move, copy and then delete the original.
+        self.client.copy_object(&src_key, &dst_key).await?;
+        self.client.delete_object(&src_key).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        self.client
+            .put_object(&self.key(&file_handle), content.into())
+            .await?;
+        Ok(file_handle)
+    }
+}
+
+async fn upload(
+    client: &S3Client,
+    key: &str,
+    mut reader: SinkReader,
+    part_size_bytes: usize,
+) -> Result<()> {
+    let mut part = read_part(&mut reader, part_size_bytes).await?;
+    if part.len() < part_size_bytes {
+        // Small enough for a single request.
+        return client.put_object(key, part.into()).await;
+    }
+
+    let upload_id = client.create_multipart_upload(key).await?;
+    let res = async {
+        let mut etags = Vec::new();
+        while !part.is_empty() {
+            let part_number = etags.len() + 1;
+            etags.push(
+                client
+                    .upload_part(key, &upload_id, part_number, part.into())
+                    .await?,
+            );
+            part = read_part(&mut reader, part_size_bytes).await?;
+        }
+        client
+            .complete_multipart_upload(key, &upload_id, &etags)
+            .await
+    }
+    .await;
+
+    if res.is_err() {
+        // Otherwise the uploaded parts are kept (and charged for) until the bucket lifecycle
+        // policy, if any, cleans them up.
+        if let Err(e) = client.abort_multipart_upload(key, &upload_id).await {
+            warn!(key = key, error = %e, "Failed aborting multipart upload.");
+        }
+    }
+    res
+}
+
+async fn read_part(reader: &mut SinkReader, part_size_bytes: usize) -> Result<Vec<u8>> {
+    let mut part = Vec::with_capacity(part_size_bytes);
+    reader
+        .take(part_size_bytes as u64)
+        .read_to_end(&mut part)
+        .await?;
+    Ok(part)
+}
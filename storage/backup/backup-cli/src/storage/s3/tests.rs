@@ -0,0 +1,460 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    s3::client::uri_encode,
+    test_util::{
+        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl,
+    },
+};
+use proptest::prelude::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::Mutex,
+    time::Duration,
+};
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+use warp::hyper::{
+    body::to_bytes,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+
+const BUCKET: &str = "backup";
+/// The minimum allowed, for the multipart uploads of the tests to stay small.
+const PART_SIZE_BYTES: usize = 5 * 1024 * 1024;
+/// Small enough for the listings of the tests to span multiple pages.
+const LIST_PAGE_SIZE: usize = 2;
+
+/// The state of an in memory S3 service, serving the requests the backup storage sends.
+#[derive(Default)]
+struct FakeS3 {
+    objects: BTreeMap<String, Vec<u8>>,
+    /// Parts of the ongoing multipart uploads, by upload id.
+    uploads: HashMap<String, BTreeMap<i32, Vec<u8>>>,
+    num_uploads_started: usize,
+    num_uploads_aborted: usize,
+}
+
+impl FakeS3 {
+    fn handle(
+        &mut self,
+        method: &Method,
+        key: String,
+        query: &HashMap<String, String>,
+        copy_source: Option<String>,
+        body: Vec<u8>,
+    ) -> Response<Body> {
+        let upload_id = query.get("uploadId");
+        match (method, upload_id) {
+            (&Method::GET, _) if query.contains_key("list-type") => self.list(query),
+            (&Method::GET, _) => match self.objects.get(&key) {
+                Some(content) => Response::new(content.clone().into()),
+                None => xml_response(
+                    StatusCode::NOT_FOUND,
+                    "<Error><Code>NoSuchKey</Code><Message>Not found.</Message></Error>",
+                ),
+            },
+            (&Method::PUT, Some(upload_id)) => {
+                let part_number: i32 = query["partNumber"].parse().unwrap();
+                let parts = self.uploads.get_mut(upload_id).unwrap();
+                parts.insert(part_number, body);
+                etag_response(format!("\"part-{}\"", part_number))
+            },
+            (&Method::PUT, None) => {
+                if let Some(copy_source) = copy_source {
+                    let src_key = copy_source
+                        .trim_start_matches('/')
+                        .strip_prefix(&format!("{}/", BUCKET))
+                        .unwrap()
+                        .to_string();
+                    let content = self.objects[&src_key].clone();
+                    self.objects.insert(key, content);
+                    return xml_response(
+                        StatusCode::OK,
+                        "<CopyObjectResult><ETag>\"copy\"</ETag></CopyObjectResult>",
+                    );
+                }
+                self.objects.insert(key, body);
+                etag_response("\"object\"".to_string())
+            },
+            (&Method::POST, None) => {
+                assert!(query.contains_key("uploads"));
+                self.num_uploads_started += 1;
+                let upload_id = format!("upload-{}", self.num_uploads_started);
+                self.uploads.insert(upload_id.clone(), BTreeMap::new());
+                xml_response(
+                    StatusCode::OK,
+                    &format!(
+                        "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key>\
+                        <UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                        BUCKET, key, upload_id
+                    ),
+                )
+            },
+            (&Method::POST, Some(upload_id)) => {
+                let parts = self.uploads.remove(upload_id).unwrap();
+                let completed = String::from_utf8(body).unwrap();
+                for part_number in parts.keys() {
+                    assert!(
+                        completed.contains(&format!(
+                            "<Part><ETag>&quot;part-{}&quot;</ETag><PartNumber>{}</PartNumber></Part>",
+                            part_number, part_number
+                        )),
+                        "{}",
+                        completed
+                    );
+                }
+                self.objects
+                    .insert(key.clone(), parts.into_values().flatten().collect());
+                xml_response(
+                    StatusCode::OK,
+                    &format!(
+                        "<CompleteMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key>\
+                        <ETag>\"object\"</ETag></CompleteMultipartUploadResult>",
+                        BUCKET, key
+                    ),
+                )
+            },
+            (&Method::DELETE, Some(upload_id)) => {
+                self.uploads.remove(upload_id).unwrap();
+                self.num_uploads_aborted += 1;
+                empty_response()
+            },
+            (&Method::DELETE, None) => {
+                self.objects.remove(&key);
+                empty_response()
+            },
+            _ => panic!("Unexpected request: {} {}", method, key),
+        }
+    }
+
+    /// Lists a page of keys, continuing after the last key of the previous page.
+    fn list(&self, query: &HashMap<String, String>) -> Response<Body> {
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let start_after = query.get("continuation-token").cloned();
+        let keys: Vec<&String> = self
+            .objects
+            .keys()
+            .filter(|key| key.starts_with(&prefix))
+            .filter(|key| start_after.as_ref().map_or(true, |token| *key > token))
+            .collect();
+        let page = &keys[..keys.len().min(LIST_PAGE_SIZE)];
+        let contents: String = page
+            .iter()
+            .map(|key| format!("<Contents><Key>{}</Key></Contents>", key))
+            .collect();
+        let next = if page.len() < keys.len() {
+            format!(
+                "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+                page.last().unwrap()
+            )
+        } else {
+            "<IsTruncated>false</IsTruncated>".to_string()
+        };
+        xml_response(
+            StatusCode::OK,
+            &format!(
+                "<ListBucketResult><Name>{}</Name><Prefix>{}</Prefix>{}{}</ListBucketResult>",
+                BUCKET, prefix, contents, next
+            ),
+        )
+    }
+}
+
+fn xml_response(status: StatusCode, body: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/xml")
+        .body(body.to_string().into())
+        .unwrap()
+}
+
+fn etag_response(etag: String) -> Response<Body> {
+    Response::builder()
+        .header("ETag", etag)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn empty_response() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            decoded.push(u8::from_str_radix(&s[i + 1..i + 3], 16).unwrap());
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).unwrap()
+}
+
+async fn serve(state: Arc<Mutex<FakeS3>>, request: Request<Body>) -> Response<Body> {
+    let (parts, body) = request.into_parts();
+    // Path style addressing, "/<bucket>/<key>".
+    let key = percent_decode(
+        parts
+            .uri
+            .path()
+            .strip_prefix(&format!("/{}", BUCKET))
+            .unwrap()
+            .trim_start_matches('/'),
+    );
+    let query = parts
+        .uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+    let copy_source = parts
+        .headers
+        .get("x-amz-copy-source")
+        .map(|source| percent_decode(source.to_str().unwrap()));
+    let body = to_bytes(body).await.unwrap().to_vec();
+    state
+        .lock()
+        .unwrap()
+        .handle(&parts.method, key, &query, copy_source, body)
+}
+
+/// Starts a fake S3 service on a random local port and returns a store backed by it.
+async fn start_fake_s3(part_size_bytes: usize) -> (S3Storage, Arc<Mutex<FakeS3>>) {
+    let state = Arc::new(Mutex::new(FakeS3::default()));
+    let service_state = state.clone();
+    let make_service = make_service_fn(move |_| {
+        let state = service_state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(serve(state, request).await) }
+            }))
+        }
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let endpoint = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+
+    let config = S3Config::load_from_str(&format!(
+        r#"
+endpoint: "{}"
+region: "us-east-1"
+bucket: "{}"
+prefix: "backup1/e1"
+path_style: true
+access_key_id: "test"
+secret_access_key: "test"
+part_size_bytes: {}
+"#,
+        endpoint, BUCKET, part_size_bytes
+    ))
+    .unwrap();
+    (S3Storage::new(config).await.unwrap(), state)
+}
+
+/// Waits for the upload tasks to settle, which the fake service can't tell about.
+async fn wait_for(state: &Mutex<FakeS3>, condition: impl Fn(&FakeS3) -> bool) {
+    for _ in 0..500 {
+        if condition(&state.lock().unwrap()) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Timed out waiting for the fake S3 service.");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (store, _state) = start_fake_s3(PART_SIZE_BYTES).await;
+            test_write_and_read_impl(Box::new(store), backups).await
+        });
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (store, _state) = start_fake_s3(PART_SIZE_BYTES).await;
+            test_save_and_list_metadata_files_impl(Box::new(store), input).await
+        });
+    }
+}
+
+#[test]
+fn test_backup_metadata_file() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, state) = start_fake_s3(PART_SIZE_BYTES).await;
+        for name in ["epoch_ending_1.meta", "state_snapshot_2.meta"] {
+            store
+                .save_metadata_line(&name.parse().unwrap(), &TextLine::new("line").unwrap())
+                .await
+                .unwrap();
+        }
+        store
+            .backup_metadata_file("metadata/epoch_ending_1.meta")
+            .await
+            .unwrap();
+
+        assert_eq!(store.list_metadata_files().await.unwrap(), vec![
+            "metadata/state_snapshot_2.meta"
+        ]);
+        assert!(state
+            .lock()
+            .unwrap()
+            .objects
+            .contains_key("backup1/e1/metadata_backup/epoch_ending_1.meta"));
+    });
+}
+
+#[test]
+fn test_read_missing_file() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, _state) = start_fake_s3(PART_SIZE_BYTES).await;
+        let err = store.open_for_read("backup/missing").await.err().unwrap();
+        assert!(format!("{}", err).contains("NoSuchKey"), "{}", err);
+    });
+}
+
+#[test]
+fn test_multipart_write_and_read() {
+    // Spans three parts: two full ones and a smaller last one.
+    let content: Vec<u8> = (0..11 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    Runtime::new().unwrap().block_on(async {
+        let (store, state) = start_fake_s3(PART_SIZE_BYTES).await;
+        let backup_handle = store
+            .create_backup(&"multipart".parse().unwrap())
+            .await
+            .unwrap();
+        let (file_handle, mut file) = store
+            .create_for_write(&backup_handle, &"large_file".parse().unwrap())
+            .await
+            .unwrap();
+        file.write_all(&content).await.unwrap();
+        file.shutdown().await.unwrap();
+        assert_eq!(state.lock().unwrap().num_uploads_started, 1);
+
+        let mut read_back = Vec::new();
+        store
+            .open_for_read(&file_handle)
+            .await
+            .unwrap()
+            .read_to_end(&mut read_back)
+            .await
+            .unwrap();
+        assert!(read_back == content);
+    });
+}
+
+#[test]
+fn test_dropped_small_file_is_not_stored() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, state) = start_fake_s3(PART_SIZE_BYTES).await;
+        let (file_handle, mut file) = store
+            .create_for_write("backup", &"small_file".parse().unwrap())
+            .await
+            .unwrap();
+        file.write_all(b"incomplete").await.unwrap();
+        drop(file);
+
+        // Nothing to wait for if the upload is rightly never sent, so give it time to be sent.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(state.lock().unwrap().objects.is_empty());
+        store.open_for_read(&file_handle).await.err().unwrap();
+    });
+}
+
+#[test]
+fn test_dropped_multipart_upload_is_aborted() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, state) = start_fake_s3(PART_SIZE_BYTES).await;
+        let (_file_handle, mut file) = store
+            .create_for_write("backup", &"large_file".parse().unwrap())
+            .await
+            .unwrap();
+        // More than a part, so the upload is started before the file is dropped.
+        file.write_all(&vec![1u8; 6 * 1024 * 1024]).await.unwrap();
+        drop(file);
+
+        wait_for(&state, |s| s.num_uploads_aborted == 1).await;
+        let state = state.lock().unwrap();
+        assert_eq!(state.num_uploads_started, 1);
+        assert!(state.uploads.is_empty());
+        assert!(state.objects.is_empty());
+    });
+}
+
+#[test]
+fn test_uri_encode() {
+    assert_eq!(uri_encode("a/b c~d.e", false), "a/b%20c~d.e");
+    assert_eq!(uri_encode("a/b+c", true), "a%2Fb%2Bc");
+}
+
+#[test]
+fn test_config() {
+    let config = S3Config::load_from_str(
+        r#"
+endpoint: "http://localhost:9000"
+bucket: "backup"
+prefix: "testnet/e1"
+path_style: true
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.region, None);
+    assert_eq!(config.part_size_bytes, 16 * 1024 * 1024);
+
+    // Without an endpoint and credentials, the AWS defaults are used.
+    let config = S3Config::load_from_str(
+        r#"
+bucket: "backup"
+region: "us-west-2"
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.endpoint, None);
+    assert_eq!(config.access_key_id, None);
+
+    S3Config::load_from_str(
+        r#"
+endpoint: "http://localhost:9000"
+bucket: "backup"
+part_size_bytes: 1024
+"#,
+    )
+    .unwrap_err();
+
+    S3Config::load_from_str(
+        r#"
+bucket: "backup"
+access_key_id: "minioadmin"
+"#,
+    )
+    .unwrap_err();
+}
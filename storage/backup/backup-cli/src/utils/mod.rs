@@ -7,6 +7,7 @@ pub(crate) mod error_notes;
 pub mod read_record_bytes;
 pub mod storage_ext;
 pub(crate) mod stream;
+pub(crate) mod task_pipe;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod test_utils;
@@ -0,0 +1,176 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adapters that expose a spawned task consuming (or producing) a byte stream as an `AsyncWrite`
+//! (or `AsyncRead`), connected by an in memory pipe. Like the ones for child processes in the
+//! command adapter, they surface the failure of the task to the user of the stream: a data sink
+//! on shutdown, a data source on EOF, so a failed task never looks like a complete stream.
+//! Conversely, a data sink dropped without being shut down fails the task reading from it, so
+//! an abandoned write never looks like a complete one either.
+
+use anyhow::Result;
+use futures::{channel::oneshot, ready, Future};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf},
+    task::JoinHandle,
+};
+
+pub(crate) struct TaskAsDataSink {
+    pipe: DuplexStream,
+    /// Tells the reader that the EOF it is about to hit is the end of the data.
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<Result<()>>>,
+}
+
+impl TaskAsDataSink {
+    /// Spawns the task, which is given the reading end of the pipe.
+    pub fn spawn<F, Fut>(pipe_size: usize, task: F) -> Self
+    where
+        F: FnOnce(SinkReader) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let (pipe, reader) = tokio::io::duplex(pipe_size);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let reader = SinkReader {
+            pipe: reader,
+            shutdown_rx,
+            eof: None,
+        };
+        Self {
+            pipe,
+            shutdown_tx: Some(shutdown_tx),
+            task: Some(tokio::spawn(task(reader))),
+        }
+    }
+}
+
+impl AsyncWrite for TaskAsDataSink {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, tokio::io::Error>> {
+        Pin::new(&mut self.pipe).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        Pin::new(&mut self.pipe).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        ready!(Pin::new(&mut self.pipe).poll_shutdown(cx))?;
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            // The task might have failed and stopped reading already.
+            let _ = shutdown_tx.send(());
+        }
+        poll_join(&mut self.task, cx)
+    }
+}
+
+/// The reading end of a `TaskAsDataSink`. The EOF caused by dropping the sink without shutting
+/// it down is reported as an error, since the data is likely incomplete.
+pub(crate) struct SinkReader {
+    pipe: DuplexStream,
+    shutdown_rx: oneshot::Receiver<()>,
+    /// Whether the EOF is legit, known once it's hit.
+    eof: Option<bool>,
+}
+
+impl AsyncRead for SinkReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let filled_before_poll = buf.filled().len();
+        ready!(Pin::new(&mut self.pipe).poll_read(cx, buf))?;
+        if buf.filled().len() > filled_before_poll {
+            return Poll::Ready(Ok(()));
+        }
+        let eof = match self.eof {
+            Some(eof) => eof,
+            None => {
+                let eof = ready!(Pin::new(&mut self.shutdown_rx).poll(cx)).is_ok();
+                self.eof = Some(eof);
+                eof
+            },
+        };
+        if eof {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Data sink dropped without being shut down.",
+            )))
+        }
+    }
+}
+
+pub(crate) struct TaskAsDataSource {
+    pipe: DuplexStream,
+    task: Option<JoinHandle<Result<()>>>,
+}
+
+impl TaskAsDataSource {
+    /// Spawns the task, which is given the writing end of the pipe and is expected to shut it
+    /// down once all data is written.
+    pub fn spawn<F, Fut>(pipe_size: usize, task: F) -> Self
+    where
+        F: FnOnce(DuplexStream) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let (pipe, writer) = tokio::io::duplex(pipe_size);
+        Self {
+            pipe,
+            task: Some(tokio::spawn(task(writer))),
+        }
+    }
+}
+
+impl AsyncRead for TaskAsDataSource {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let filled_before_poll = buf.filled().len();
+        ready!(Pin::new(&mut self.pipe).poll_read(cx, buf))?;
+        if buf.filled().len() > filled_before_poll {
+            return Poll::Ready(Ok(()));
+        }
+        // hit EOF, which is only legit if the task succeeded
+        poll_join(&mut self.task, cx)
+    }
+}
+
+fn poll_join(
+    task: &mut Option<JoinHandle<Result<()>>>,
+    cx: &mut Context<'_>,
+) -> Poll<std::io::Result<()>> {
+    let handle = match task.as_mut() {
+        Some(handle) => handle,
+        None => return Poll::Ready(Ok(())),
+    };
+    let res = match ready!(Pin::new(handle).poll(cx)) {
+        Ok(res) => res,
+        Err(e) => Err(e.into()),
+    };
+    *task = None;
+    Poll::Ready(res.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)))
+}
@@ -0,0 +1,394 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_crypto::hash::CryptoHash;
+use aptos_db::AptosDB;
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::{
+    account_config::NewBlockEvent,
+    contract_event::ContractEvent,
+    proof::accumulator::InMemoryTransactionAccumulator,
+    transaction::{ExecutionStatus, Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use arrow::{
+    array::{ArrayRef, BooleanArray, Int64Array, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    ipc::writer::FileWriter as IpcFileWriter,
+    record_batch::RecordBatch,
+};
+use clap::{Parser, ValueEnum};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Export transactions from the DB to columnar files, with the schema of
+/// `aptos.bigquery_schema.transaction.v1.Transaction`.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(long = "db-dir", value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+    #[clap(long, value_parser)]
+    output_dir: PathBuf,
+    #[clap(long, value_enum, default_value_t = ExportFormat::Parquet)]
+    format: ExportFormat,
+    #[clap(long, help = "The first version to export. [Defaults to 0]")]
+    start_version: Option<Version>,
+    #[clap(
+        long,
+        help = "The last version to export. [Defaults to the latest version in the DB]"
+    )]
+    end_version: Option<Version>,
+    #[clap(
+        long,
+        default_value_t = 1_000_000,
+        help = "Versions are partitioned into files of ranges of this size, aligned to multiples \
+        of it. Files already existing are skipped, so that an export can be resumed."
+    )]
+    versions_per_file: u64,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    Parquet,
+    ArrowIpc,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::ArrowIpc => "arrow",
+        }
+    }
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        ensure!(
+            self.versions_per_file > 0,
+            "versions_per_file must be positive."
+        );
+        let db = AptosDB::open(
+            StorageDirPaths::from_path(&self.db_dir),
+            true,                        /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            self.rocksdb_opt.into(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+
+        let latest_version = db.get_latest_version()?;
+        let first_version = db.get_first_txn_version()?.unwrap_or(0);
+        let start_version = self.start_version.unwrap_or(0);
+        let end_version = self.end_version.unwrap_or(latest_version);
+        ensure!(
+            start_version <= end_version,
+            "Start version {} is after end version {}.",
+            start_version,
+            end_version,
+        );
+        ensure!(
+            start_version >= first_version && end_version <= latest_version,
+            "Versions [{}, {}] not in the DB, which has [{}, {}].",
+            start_version,
+            end_version,
+            first_version,
+            latest_version,
+        );
+
+        let dir = self.output_dir.join("transactions");
+        fs::create_dir_all(&dir)?;
+        let mut exporter = Exporter::new(&db)?;
+        let mut first = start_version;
+        while first <= end_version {
+            let last = std::cmp::min(
+                (first / self.versions_per_file + 1) * self.versions_per_file - 1,
+                end_version,
+            );
+            let path = dir.join(format!(
+                "{:020}-{:020}.{}",
+                first,
+                last,
+                self.format.extension()
+            ));
+            if path.exists() {
+                println!("Skipping {:?}, which already exists.", path);
+            } else {
+                exporter.export(first, last, self.format, &path)?;
+                println!("Exported versions [{}, {}] to {:?}.", first, last, path);
+            }
+            first = last + 1;
+        }
+        Ok(())
+    }
+}
+
+/// Schema matching `aptos.bigquery_schema.transaction.v1.Transaction`, with optional fields
+/// nullable.
+pub fn transaction_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("version", DataType::Int64, false),
+        Field::new("block_height", DataType::Int64, false),
+        Field::new("hash", DataType::Utf8, false),
+        Field::new("type", DataType::Utf8, false),
+        Field::new("payload", DataType::Utf8, true),
+        Field::new("state_change_hash", DataType::Utf8, false),
+        Field::new("event_root_hash", DataType::Utf8, false),
+        Field::new("state_checkpoint_hash", DataType::Utf8, true),
+        Field::new("gas_used", DataType::UInt64, false),
+        Field::new("success", DataType::Boolean, false),
+        Field::new("vm_status", DataType::Utf8, false),
+        Field::new("accumulator_root_hash", DataType::Utf8, false),
+        Field::new("num_events", DataType::Int64, false),
+        Field::new("num_write_set_changes", DataType::Int64, false),
+        Field::new("epoch", DataType::Int64, false),
+        Field::new("inserted_at", DataType::Int64, false),
+    ]))
+}
+
+struct Exporter<'a> {
+    db: &'a AptosDB,
+    schema: SchemaRef,
+    /// Microseconds since the Unix epoch when the export started.
+    inserted_at: i64,
+    /// First and last versions of the block of the latest transaction exported, and its
+    /// `NewBlockEvent`.
+    block: Option<(Version, Version, NewBlockEvent)>,
+}
+
+impl<'a> Exporter<'a> {
+    fn new(db: &'a AptosDB) -> Result<Self> {
+        Ok(Self {
+            db,
+            schema: transaction_schema(),
+            inserted_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros() as i64,
+            block: None,
+        })
+    }
+
+    /// Exports versions [`first`, `last`] to a file at `path`, which appears only when complete.
+    fn export(
+        &mut self,
+        first: Version,
+        last: Version,
+        format: ExportFormat,
+        path: &Path,
+    ) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = FileWriter::new(format, File::create(&tmp_path)?, &self.schema)?;
+        let mut accumulator = if first == 0 {
+            InMemoryTransactionAccumulator::new_empty()
+        } else {
+            self.db.get_accumulator_summary(first - 1)?.0
+        };
+        let mut batch_first = first;
+        while batch_first <= last {
+            let limit = std::cmp::min(MAX_REQUEST_LIMIT, last - batch_first + 1);
+            let txns = self.db.get_transaction_iterator(batch_first, limit)?;
+            let txn_infos = self.db.get_transaction_info_iterator(batch_first, limit)?;
+            let events = self.db.get_events_iterator(batch_first, limit)?;
+            let write_sets = self.db.get_write_set_iterator(batch_first, limit)?;
+
+            let mut rows = TransactionRows::default();
+            for (version, ((txn, txn_info), (events, write_set))) in
+                (batch_first..).zip(txns.zip(txn_infos).zip(events.zip(write_sets)))
+            {
+                let txn_info = txn_info?;
+                accumulator = accumulator.append(&[txn_info.hash()]);
+                let (block_height, epoch) = self.block_height_and_epoch(version)?;
+                rows.push(
+                    version,
+                    block_height,
+                    &txn?,
+                    &txn_info,
+                    &events?,
+                    &write_set?,
+                    &accumulator,
+                    epoch,
+                    self.inserted_at,
+                )?;
+            }
+            ensure!(
+                rows.len() as u64 == limit,
+                "Expected {} transactions from version {}, got {}.",
+                limit,
+                batch_first,
+                rows.len(),
+            );
+            writer.write(&rows.into_record_batch(self.schema.clone())?)?;
+            batch_first += limit;
+        }
+        writer.finish()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn block_height_and_epoch(&mut self, version: Version) -> Result<(u64, u64)> {
+        match &self.block {
+            Some((first, last, _)) if (*first..=*last).contains(&version) => (),
+            _ => self.block = Some(self.db.get_block_info_by_version(version)?),
+        }
+        let (_, _, new_block_event) = self.block.as_ref().expect("Set above.");
+        Ok((new_block_event.height(), new_block_event.epoch()))
+    }
+}
+
+enum FileWriter {
+    Parquet(ArrowWriter<File>),
+    ArrowIpc(IpcFileWriter<File>),
+}
+
+impl FileWriter {
+    fn new(format: ExportFormat, file: File, schema: &SchemaRef) -> Result<Self> {
+        Ok(match format {
+            ExportFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Self::Parquet(ArrowWriter::try_new(file, schema.clone(), Some(props))?)
+            },
+            ExportFormat::ArrowIpc => Self::ArrowIpc(IpcFileWriter::try_new(file, schema)?),
+        })
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => writer.write(batch)?,
+            Self::ArrowIpc(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => {
+                writer.close()?;
+            },
+            Self::ArrowIpc(mut writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// Columns of the transactions in a record batch.
+#[derive(Default)]
+struct TransactionRows {
+    version: Vec<i64>,
+    block_height: Vec<i64>,
+    hash: Vec<String>,
+    type_: Vec<&'static str>,
+    payload: Vec<Option<String>>,
+    state_change_hash: Vec<String>,
+    event_root_hash: Vec<String>,
+    state_checkpoint_hash: Vec<Option<String>>,
+    gas_used: Vec<u64>,
+    success: Vec<bool>,
+    vm_status: Vec<String>,
+    accumulator_root_hash: Vec<String>,
+    num_events: Vec<i64>,
+    num_write_set_changes: Vec<i64>,
+    epoch: Vec<i64>,
+    inserted_at: Vec<i64>,
+}
+
+impl TransactionRows {
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        version: Version,
+        block_height: u64,
+        txn: &Transaction,
+        txn_info: &TransactionInfo,
+        events: &[ContractEvent],
+        write_set: &WriteSet,
+        accumulator: &InMemoryTransactionAccumulator,
+        epoch: u64,
+        inserted_at: i64,
+    ) -> Result<()> {
+        let (type_, payload) = match txn {
+            Transaction::UserTransaction(signed_txn) => (
+                "user_transaction",
+                Some(serde_json::to_string(signed_txn.payload())?),
+            ),
+            Transaction::GenesisTransaction(_) => ("genesis_transaction", None),
+            Transaction::BlockMetadata(_) => ("block_metadata_transaction", None),
+            Transaction::StateCheckpoint(_) => ("state_checkpoint_transaction", None),
+            Transaction::ValidatorTransaction(_) => ("validator_transaction", None),
+        };
+        self.version.push(version as i64);
+        self.block_height.push(block_height as i64);
+        self.hash.push(txn.hash().to_hex_literal());
+        self.type_.push(type_);
+        self.payload.push(payload);
+        self.state_change_hash
+            .push(txn_info.state_change_hash().to_hex_literal());
+        self.event_root_hash
+            .push(txn_info.event_root_hash().to_hex_literal());
+        self.state_checkpoint_hash.push(
+            txn_info
+                .state_checkpoint_hash()
+                .map(|hash| hash.to_hex_literal()),
+        );
+        self.gas_used.push(txn_info.gas_used());
+        self.success.push(txn_info.status().is_success());
+        self.vm_status.push(match txn_info.status() {
+            // Same as in the API.
+            ExecutionStatus::Success => "Executed successfully".to_string(),
+            status => format!("{:?}", status),
+        });
+        self.accumulator_root_hash
+            .push(accumulator.root_hash().to_hex_literal());
+        self.num_events.push(events.len() as i64);
+        self.num_write_set_changes
+            .push(write_set.iter().count() as i64);
+        self.epoch.push(epoch as i64);
+        self.inserted_at.push(inserted_at);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.version.len()
+    }
+
+    fn into_record_batch(self, schema: SchemaRef) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(self.version)),
+            Arc::new(Int64Array::from(self.block_height)),
+            Arc::new(StringArray::from(self.hash)),
+            Arc::new(StringArray::from(self.type_)),
+            Arc::new(StringArray::from(self.payload)),
+            Arc::new(StringArray::from(self.state_change_hash)),
+            Arc::new(StringArray::from(self.event_root_hash)),
+            Arc::new(StringArray::from(self.state_checkpoint_hash)),
+            Arc::new(UInt64Array::from(self.gas_used)),
+            Arc::new(BooleanArray::from(self.success)),
+            Arc::new(StringArray::from(self.vm_status)),
+            Arc::new(StringArray::from(self.accumulator_root_hash)),
+            Arc::new(Int64Array::from(self.num_events)),
+            Arc::new(Int64Array::from(self.num_write_set_changes)),
+            Arc::new(Int64Array::from(self.epoch)),
+            Arc::new(Int64Array::from(self.inserted_at)),
+        ];
+        Ok(RecordBatch::try_new(schema, columns)?)
+    }
+}
+
+#[test]
+fn verify_tool() {
+    use clap::CommandFactory;
+    Command::command().debug_assert()
+}
@@ -6,10 +6,12 @@ extern crate core;
 mod backup;
 mod backup_maintenance;
 mod bootstrap;
+mod export;
 mod replay_verify;
 pub mod restore;
 #[cfg(test)]
 mod tests;
+mod trace_profile;
 mod utils;
 
 use anyhow::Result;
@@ -31,10 +33,14 @@ pub enum DBTool {
     #[clap(subcommand)]
     Debug(db_debugger::Cmd),
 
+    Export(export::Command),
+
     ReplayVerify(replay_verify::Opt),
 
     #[clap(subcommand)]
     Restore(restore::Command),
+
+    TraceProfile(trace_profile::Command),
 }
 
 impl DBTool {
@@ -44,12 +50,14 @@ impl DBTool {
             DBTool::BackupMaintenance(cmd) => cmd.run().await,
             DBTool::Bootstrap(cmd) => cmd.run(),
             DBTool::Debug(cmd) => cmd.run(),
+            DBTool::Export(cmd) => cmd.run(),
             DBTool::ReplayVerify(cmd) => {
                 let ret = cmd.run().await;
                 info!("Replay verify result: {:?}", ret);
                 ret
             },
             DBTool::Restore(cmd) => cmd.run().await,
+            DBTool::TraceProfile(cmd) => cmd.run(),
         }
     }
 }
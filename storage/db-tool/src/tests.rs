@@ -91,7 +91,12 @@ mod dbtool_tests {
         state_store::{state_key::StateKeyTag::AccessPath, state_key_prefix::StateKeyPrefix},
         transaction::Version,
     };
+    use arrow::{
+        array::{Int64Array, StringArray},
+        ipc::reader::FileReader,
+    };
     use clap::Parser;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
     use std::{
         default::Default,
         fs,
@@ -623,6 +628,87 @@ mod dbtool_tests {
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
+    #[test]
+    fn test_export() {
+        let db_dir = TempPath::new();
+        db_dir.create_as_dir().unwrap();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let latest_version = db.get_latest_version().unwrap();
+        let root_hash = db.get_accumulator_root_hash(latest_version).unwrap();
+        drop(db);
+
+        let export_dir = TempPath::new();
+        let rt = Runtime::new().unwrap();
+        for format in ["parquet", "arrow-ipc"] {
+            rt.block_on(
+                DBTool::try_parse_from([
+                    "aptos-db-tool",
+                    "export",
+                    "--db-dir",
+                    db_dir.path().to_str().unwrap(),
+                    "--output-dir",
+                    export_dir.path().to_str().unwrap(),
+                    "--format",
+                    format,
+                    "--versions-per-file",
+                    "10",
+                ])
+                .unwrap()
+                .run(),
+            )
+            .unwrap();
+        }
+
+        let mut paths: Vec<_> = fs::read_dir(export_dir.path().join("transactions"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        paths.sort();
+        for extension in ["parquet", "arrow"] {
+            let mut batches = vec![];
+            for path in paths
+                .iter()
+                .filter(|path| path.extension().unwrap() == extension)
+            {
+                let file = fs::File::open(path).unwrap();
+                if extension == "parquet" {
+                    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+                        .unwrap()
+                        .build()
+                        .unwrap();
+                    batches.extend(reader.map(Result::unwrap));
+                } else {
+                    batches.extend(FileReader::try_new(file, None).unwrap().map(Result::unwrap));
+                }
+            }
+            let versions: Vec<i64> = batches
+                .iter()
+                .flat_map(|batch| {
+                    batch
+                        .column_by_name("version")
+                        .unwrap()
+                        .as_any()
+                        .downcast_ref::<Int64Array>()
+                        .unwrap()
+                        .values()
+                        .to_vec()
+                })
+                .collect();
+            assert_eq!(versions, (0..=latest_version as i64).collect::<Vec<_>>());
+            let last_batch = batches.last().unwrap();
+            let root_hashes = last_batch
+                .column_by_name("accumulator_root_hash")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            assert_eq!(
+                root_hashes.value(root_hashes.len() - 1),
+                root_hash.to_hex_literal()
+            );
+        }
+    }
+
     fn dir_size<P: AsRef<Path>>(path: P) -> u64 {
         let mut size = 0;
 
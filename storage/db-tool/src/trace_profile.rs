@@ -0,0 +1,76 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_transaction_generator_lib::trace::TraceProfileBuilder;
+use aptos_types::transaction::Version;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Summarize a range of historical transactions in the DB into a trace profile, which the
+/// transaction emitter and the executor benchmark can replay with `--trace-profile`.
+///
+/// To profile transactions from a backup, pass `--output-transaction-analysis` to
+/// `backup verify`, which writes a `trace_profile.json` alongside the other analysis files.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(long = "db-dir", value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+    #[clap(long, value_parser)]
+    output: PathBuf,
+    #[clap(long)]
+    start_version: Version,
+    #[clap(long, default_value_t = 1_000_000)]
+    num_versions: u64,
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        ensure!(self.num_versions > 0, "num_versions must be positive.");
+        let db = AptosDB::open(
+            StorageDirPaths::from_path(&self.db_dir),
+            true,                        /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            self.rocksdb_opt.into(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+
+        let latest_version = db.get_latest_version()?;
+        let end_version = std::cmp::min(self.start_version + self.num_versions - 1, latest_version);
+        ensure!(
+            self.start_version <= end_version,
+            "Start version {} is after the latest version {}.",
+            self.start_version,
+            latest_version,
+        );
+
+        let mut builder = TraceProfileBuilder::new();
+        let mut version = self.start_version;
+        while version <= end_version {
+            let limit = std::cmp::min(MAX_REQUEST_LIMIT, end_version - version + 1);
+            for txn in db.get_transaction_iterator(version, limit)? {
+                builder.add_transaction(version, &txn?);
+                version += 1;
+            }
+        }
+
+        let profile = builder.build();
+        profile.save(&self.output)?;
+        println!(
+            "Profiled {} user transactions in versions [{}, {}] to {:?}.",
+            profile.num_user_transactions, self.start_version, end_version, self.output,
+        );
+        Ok(())
+    }
+}
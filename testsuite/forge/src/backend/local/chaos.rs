@@ -0,0 +1,479 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Network chaos for the local swarm, without root or `tc`.
+//!
+//! The validator network of every validator is fronted by a userspace TCP proxy, which listens on
+//! the address the validator advertises on-chain and forwards to the address the validator
+//! actually listens on. The dialer of a validator network connection always starts with its peer
+//! id (the plaintext Noise prologue), so the proxy knows both ends of every link and can delay,
+//! throttle, "lose" or cut the traffic on it.
+
+use crate::SwarmChaos;
+use anyhow::{bail, Result};
+use aptos_infallible::RwLock;
+use aptos_logger::{debug, info, warn};
+use aptos_sdk::types::PeerId;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::HashSet, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::Instant,
+};
+
+/// TCP retransmits lost segments, so instead of dropping bytes (which would corrupt the stream),
+/// a "lost" chunk is delivered after a typical retransmission timeout.
+const LOSS_RETRANSMISSION_DELAY: Duration = Duration::from_millis(200);
+
+/// How long to wait for the dialer to send its Noise prologue.
+const PROLOGUE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The combined effect of all active chaos on the link between two peers.
+#[derive(Clone, Copy, Debug, Default)]
+struct LinkConditions {
+    blocked: bool,
+    latency: Duration,
+    jitter: Duration,
+    delay_correlation: f64,
+    loss: f64,
+    loss_correlation: f64,
+    /// Bytes per second, if the link is throttled
+    rate: Option<u64>,
+}
+
+impl LinkConditions {
+    fn add_delay(&mut self, latency_ms: u64, jitter_ms: u64, correlation_percentage: u64) {
+        self.latency = self.latency.max(Duration::from_millis(latency_ms));
+        self.jitter = self.jitter.max(Duration::from_millis(jitter_ms));
+        self.delay_correlation = self
+            .delay_correlation
+            .max(percentage_to_probability(correlation_percentage));
+    }
+
+    fn add_loss(&mut self, loss_percentage: u64, correlation_percentage: u64) {
+        self.loss = self.loss.max(percentage_to_probability(loss_percentage));
+        self.loss_correlation = self
+            .loss_correlation
+            .max(percentage_to_probability(correlation_percentage));
+    }
+
+    fn add_rate(&mut self, megabytes_per_sec: u64) {
+        let rate = megabytes_per_sec.saturating_mul(1_000_000).max(1);
+        self.rate = Some(self.rate.map_or(rate, |current| current.min(rate)));
+    }
+}
+
+fn percentage_to_probability(percentage: u64) -> f64 {
+    (percentage.min(100) as f64) / 100.0
+}
+
+/// Returns true if the link between `a` and `b` goes from one of the `sources` to one of the
+/// `targets`, in either direction.
+fn is_between(sources: &[PeerId], targets: &[PeerId], a: PeerId, b: PeerId) -> bool {
+    (sources.contains(&a) && targets.contains(&b)) || (sources.contains(&b) && targets.contains(&a))
+}
+
+#[derive(Debug, Default)]
+struct ChaosState {
+    /// The validators behind a proxy, in index order
+    validators: Vec<PeerId>,
+    chaoses: HashSet<SwarmChaos>,
+}
+
+impl ChaosState {
+    /// Returns the validators cut off by a partition of `partition_percentage` percent. These are
+    /// the last validators by index, so that the first validator (which serves the swarm's REST
+    /// API) stays connected.
+    fn partitioned_validators(&self, partition_percentage: u64) -> &[PeerId] {
+        let num_partitioned =
+            self.validators.len() * (partition_percentage.min(100) as usize) / 100;
+        &self.validators[self.validators.len() - num_partitioned..]
+    }
+
+    fn link_conditions(&self, a: PeerId, b: PeerId) -> LinkConditions {
+        let mut conditions = LinkConditions::default();
+        for chaos in &self.chaoses {
+            match chaos {
+                SwarmChaos::Delay(delay) => {
+                    for group in &delay.group_network_delays {
+                        if is_between(&group.source_nodes, &group.target_nodes, a, b) {
+                            conditions.add_delay(
+                                group.latency_ms,
+                                group.jitter_ms,
+                                group.correlation_percentage,
+                            );
+                        }
+                    }
+                },
+                SwarmChaos::Partition(partition) => {
+                    let partitioned = self.partitioned_validators(partition.partition_percentage);
+                    if partitioned.contains(&a) || partitioned.contains(&b) {
+                        conditions.blocked = true;
+                    }
+                },
+                SwarmChaos::Bandwidth(bandwidth) => {
+                    for group in &bandwidth.group_network_bandwidths {
+                        conditions.add_rate(group.rate);
+                    }
+                },
+                SwarmChaos::Loss(loss) => {
+                    conditions.add_loss(loss.loss_percentage, loss.correlation_percentage);
+                },
+                SwarmChaos::NetEm(netem) => {
+                    for group in &netem.group_netems {
+                        if is_between(&group.source_nodes, &group.target_nodes, a, b) {
+                            conditions.add_delay(
+                                group.delay_latency_ms,
+                                group.delay_jitter_ms,
+                                group.delay_correlation_percentage,
+                            );
+                            conditions
+                                .add_loss(group.loss_percentage, group.loss_correlation_percentage);
+                            conditions.add_rate(group.rate_in_mbps);
+                        }
+                    }
+                },
+                SwarmChaos::CpuStress(_) => {},
+            }
+        }
+        conditions
+    }
+}
+
+/// Injects network chaos into the validator network of a local swarm, by proxying every
+/// connection between validators.
+///
+/// Unlike the tc-based chaos of the k8s backend, bandwidth limits apply to each direction of each
+/// connection (rather than to all egress traffic of a node), and loss delays traffic by a
+/// retransmission timeout instead of dropping packets.
+#[derive(Debug)]
+pub struct LocalNetworkChaos {
+    state: Arc<RwLock<ChaosState>>,
+    changes_tx: watch::Sender<()>,
+    changes_rx: watch::Receiver<()>,
+    proxies: Vec<JoinHandle<()>>,
+}
+
+impl LocalNetworkChaos {
+    pub fn new() -> Self {
+        let (changes_tx, changes_rx) = watch::channel(());
+        Self {
+            state: Arc::new(RwLock::new(ChaosState::default())),
+            changes_tx,
+            changes_rx,
+            proxies: vec![],
+        }
+    }
+
+    /// Starts proxying the validator network connections to `peer_id` that arrive on `listener`
+    /// to `upstream`, the address the validator listens on. Validators must be added in index
+    /// order.
+    pub fn add_validator_proxy(
+        &mut self,
+        peer_id: PeerId,
+        listener: std::net::TcpListener,
+        upstream: SocketAddr,
+    ) -> Result<()> {
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        self.state.write().validators.push(peer_id);
+
+        let state = self.state.clone();
+        let changes = self.changes_rx.clone();
+        self.proxies.push(tokio::spawn(run_proxy(
+            listener, peer_id, upstream, state, changes,
+        )));
+        Ok(())
+    }
+
+    pub fn inject(&mut self, chaos: SwarmChaos) -> Result<()> {
+        if let SwarmChaos::CpuStress(_) = chaos {
+            bail!("CPU stress chaos is not supported by the local swarm");
+        }
+        info!("Injecting chaos: {:?}", chaos);
+        self.state.write().chaoses.insert(chaos);
+        self.notify_changed();
+        Ok(())
+    }
+
+    pub fn remove(&mut self, chaos: &SwarmChaos) -> Result<()> {
+        if !self.state.write().chaoses.remove(chaos) {
+            bail!("Chaos {:?} not found", chaos);
+        }
+        info!("Removed chaos: {:?}", chaos);
+        self.notify_changed();
+        Ok(())
+    }
+
+    pub fn remove_all(&mut self) {
+        self.state.write().chaoses.clear();
+        self.notify_changed();
+    }
+
+    fn notify_changed(&self) {
+        // Cannot fail, as we hold a receiver ourselves
+        let _ = self.changes_tx.send(());
+    }
+}
+
+impl Default for LocalNetworkChaos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for LocalNetworkChaos {
+    fn drop(&mut self) {
+        for proxy in &self.proxies {
+            proxy.abort();
+        }
+    }
+}
+
+async fn run_proxy(
+    listener: TcpListener,
+    target: PeerId,
+    upstream: SocketAddr,
+    state: Arc<RwLock<ChaosState>>,
+    changes: watch::Receiver<()>,
+) {
+    loop {
+        let inbound = match listener.accept().await {
+            Ok((inbound, _)) => inbound,
+            Err(error) => {
+                warn!("Chaos proxy for {} failed to accept: {}", target, error);
+                continue;
+            },
+        };
+        let state = state.clone();
+        let changes = changes.clone();
+        tokio::spawn(async move {
+            if let Err(error) = proxy_connection(inbound, target, upstream, state, changes).await {
+                debug!("Chaos proxy connection to {} closed: {}", target, error);
+            }
+        });
+    }
+}
+
+async fn proxy_connection(
+    mut inbound: TcpStream,
+    target: PeerId,
+    upstream: SocketAddr,
+    state: Arc<RwLock<ChaosState>>,
+    changes: watch::Receiver<()>,
+) -> Result<()> {
+    let mut prologue = [0u8; PeerId::LENGTH];
+    tokio::time::timeout(PROLOGUE_TIMEOUT, inbound.read_exact(&mut prologue)).await??;
+    let link = Link {
+        source: PeerId::new(prologue),
+        target,
+        state,
+        changes,
+    };
+    link.ensure_not_blocked(&link.conditions())?;
+
+    let mut outbound = TcpStream::connect(upstream).await?;
+    outbound.write_all(&prologue).await?;
+    inbound.set_nodelay(true)?;
+    outbound.set_nodelay(true)?;
+
+    let (inbound_read, inbound_write) = inbound.into_split();
+    let (outbound_read, outbound_write) = outbound.into_split();
+    tokio::try_join!(
+        link.forward(inbound_read, outbound_write),
+        link.forward(outbound_read, inbound_write),
+    )?;
+    Ok(())
+}
+
+/// A proxied connection between two peers.
+struct Link {
+    source: PeerId,
+    target: PeerId,
+    state: Arc<RwLock<ChaosState>>,
+    changes: watch::Receiver<()>,
+}
+
+impl Link {
+    fn conditions(&self) -> LinkConditions {
+        self.state.read().link_conditions(self.source, self.target)
+    }
+
+    /// Forwards one direction of the link, until EOF or until the link is partitioned. Reading and
+    /// writing are decoupled, so that delayed chunks don't hold up the chunks behind them.
+    async fn forward(&self, reader: OwnedReadHalf, writer: OwnedWriteHalf) -> Result<()> {
+        let (chunk_tx, chunk_rx) = mpsc::unbounded_channel();
+        tokio::try_join!(
+            self.read_chunks(reader, chunk_tx),
+            self.write_chunks(writer, chunk_rx),
+        )?;
+        Ok(())
+    }
+
+    async fn read_chunks(
+        &self,
+        mut reader: OwnedReadHalf,
+        chunk_tx: mpsc::UnboundedSender<(Instant, Vec<u8>)>,
+    ) -> Result<()> {
+        let mut changes = self.changes.clone();
+        let mut sampler = Sampler::new();
+        let mut last_due = Instant::now();
+        let mut buffer = vec![0; READ_BUFFER_SIZE];
+        loop {
+            let num_bytes = tokio::select! {
+                result = reader.read(&mut buffer) => result?,
+                result = changes.changed() => {
+                    if result.is_err() {
+                        bail!("The local swarm has shut down");
+                    }
+                    self.ensure_not_blocked(&self.conditions())?;
+                    continue;
+                },
+            };
+            if num_bytes == 0 {
+                // Dropping the sender lets the writer flush and shut down
+                return Ok(());
+            }
+
+            let conditions = self.conditions();
+            self.ensure_not_blocked(&conditions)?;
+            // TCP delivers in order, so a chunk is never due before the previous one
+            let due = (Instant::now() + sampler.delay(&conditions)).max(last_due);
+            last_due = due;
+            if chunk_tx.send((due, buffer[..num_bytes].to_vec())).is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn write_chunks(
+        &self,
+        mut writer: OwnedWriteHalf,
+        mut chunk_rx: mpsc::UnboundedReceiver<(Instant, Vec<u8>)>,
+    ) -> Result<()> {
+        let mut next_send = Instant::now();
+        while let Some((due, chunk)) = chunk_rx.recv().await {
+            tokio::time::sleep_until(due.max(next_send)).await;
+            writer.write_all(&chunk).await?;
+            if let Some(rate) = self.conditions().rate {
+                next_send =
+                    Instant::now() + Duration::from_secs_f64(chunk.len() as f64 / rate as f64);
+            }
+        }
+        writer.shutdown().await?;
+        Ok(())
+    }
+
+    fn ensure_not_blocked(&self, conditions: &LinkConditions) -> Result<()> {
+        if conditions.blocked {
+            bail!(
+                "The link between {} and {} is partitioned",
+                self.source,
+                self.target
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Samples the delay of each chunk, with netem-style correlation between consecutive chunks.
+struct Sampler {
+    rng: StdRng,
+    last_jitter_secs: f64,
+    last_lost: bool,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+            last_jitter_secs: 0.0,
+            last_lost: false,
+        }
+    }
+
+    fn delay(&mut self, conditions: &LinkConditions) -> Duration {
+        let jitter_secs = conditions.jitter.as_secs_f64();
+        if jitter_secs > 0.0 {
+            let fresh = self.rng.gen_range(-jitter_secs, jitter_secs);
+            self.last_jitter_secs = conditions.delay_correlation * self.last_jitter_secs
+                + (1.0 - conditions.delay_correlation) * fresh;
+        } else {
+            self.last_jitter_secs = 0.0;
+        }
+
+        self.last_lost = if self.rng.gen_bool(conditions.loss_correlation) {
+            self.last_lost
+        } else {
+            self.rng.gen_bool(conditions.loss)
+        };
+
+        let mut delay = Duration::from_secs_f64(
+            (conditions.latency.as_secs_f64() + self.last_jitter_secs).max(0.0),
+        );
+        if self.last_lost {
+            delay += LOSS_RETRANSMISSION_DELAY;
+        }
+        delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupNetworkDelay, SwarmNetworkDelay, SwarmNetworkPartition};
+
+    fn state(num_validators: usize, chaoses: Vec<SwarmChaos>) -> ChaosState {
+        ChaosState {
+            validators: (0..num_validators).map(|_| PeerId::random()).collect(),
+            chaoses: chaoses.into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_partition_cuts_off_last_validators() {
+        let state = state(4, vec![SwarmChaos::Partition(SwarmNetworkPartition {
+            partition_percentage: 50,
+        })]);
+        let v = state.validators.clone();
+
+        assert!(!state.link_conditions(v[0], v[1]).blocked);
+        assert!(state.link_conditions(v[0], v[2]).blocked);
+        assert!(state.link_conditions(v[3], v[1]).blocked);
+        assert!(state.link_conditions(v[2], v[3]).blocked);
+    }
+
+    #[test]
+    fn test_delay_applies_between_groups_in_both_directions() {
+        let mut state = state(3, vec![]);
+        let v = state.validators.clone();
+        state.chaoses.insert(SwarmChaos::Delay(SwarmNetworkDelay {
+            group_network_delays: vec![GroupNetworkDelay {
+                name: "group".to_string(),
+                source_nodes: vec![v[0]],
+                target_nodes: vec![v[1]],
+                latency_ms: 100,
+                jitter_ms: 0,
+                correlation_percentage: 0,
+            }],
+        }));
+
+        assert_eq!(
+            state.link_conditions(v[0], v[1]).latency,
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            state.link_conditions(v[1], v[0]).latency,
+            Duration::from_millis(100)
+        );
+        assert_eq!(state.link_conditions(v[0], v[2]).latency, Duration::ZERO);
+    }
+}
@@ -18,10 +18,12 @@ use std::{
 };
 
 mod cargo;
+mod chaos;
 mod node;
 mod swarm;
 pub use self::swarm::ActiveNodesGuard;
 pub use cargo::cargo_build_common_args;
+pub use chaos::LocalNetworkChaos;
 pub use node::LocalNode;
 pub use swarm::{LocalSwarm, SwarmDirectory};
 
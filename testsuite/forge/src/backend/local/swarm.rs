@@ -3,14 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, Swarm, SwarmChaos,
-    SwarmExt, Validator, Version,
+    ChainInfo, FullNode, HealthCheckError, LocalNetworkChaos, LocalNode, LocalVersion, Node, Swarm,
+    SwarmChaos, SwarmExt, Validator, Version,
 };
 use anyhow::{anyhow, bail, Result};
 use aptos_config::{
     config::{NetworkConfig, NodeConfig, OverrideNodeConfig, PersistableConfig},
     keys::ConfigKey,
     network_id::NetworkId,
+    utils::get_available_port_in_multiaddr,
 };
 use aptos_framework::ReleaseBundle;
 use aptos_genesis::builder::{
@@ -21,8 +22,8 @@ use aptos_logger::{info, warn};
 use aptos_sdk::{
     crypto::{ed25519::Ed25519PrivateKey, encoding_type::EncodingType},
     types::{
-        chain_id::ChainId, transaction::Transaction, waypoint::Waypoint, AccountKey, LocalAccount,
-        PeerId,
+        chain_id::ChainId, network_address::NetworkAddress, transaction::Transaction,
+        waypoint::Waypoint, AccountKey, LocalAccount, PeerId,
     },
 };
 use prometheus_http_query::response::{PromqlResult, Sample};
@@ -32,6 +33,7 @@ use std::{
     fs::File,
     io::Write,
     mem,
+    net::{Ipv4Addr, SocketAddr, TcpListener},
     num::NonZeroUsize,
     ops,
     path::{Path, PathBuf},
@@ -99,6 +101,7 @@ pub struct LocalSwarm {
     root_account: LocalAccount,
     chain_id: ChainId,
     root_key: ConfigKey<Ed25519PrivateKey>,
+    network_chaos: LocalNetworkChaos,
 
     launched: bool,
     #[allow(dead_code)]
@@ -193,6 +196,7 @@ impl LocalSwarm {
             .collect::<Result<HashMap<_, _>>>()?;
 
         // After genesis, remove public network from validator and add to public_networks
+        let mut validator_network_addresses = vec![];
         let public_networks = validators
             .values_mut()
             .map(|validator| {
@@ -212,6 +216,24 @@ impl LocalSwarm {
                         .expect("Validator should have a public network");
                     validator_config.full_node_networks.remove(i)
                 };
+
+                // Move the validator network to a new port, so that a chaos proxy can listen on
+                // the address advertised on-chain
+                let validator_network = validator_config
+                    .validator_network
+                    .as_mut()
+                    .expect("Validator should have a validator network");
+                let advertised_address = mem::replace(
+                    &mut validator_network.listen_address,
+                    get_available_port_in_multiaddr(true),
+                );
+                validator_network_addresses.push((
+                    validator.index(),
+                    validator.peer_id(),
+                    advertised_address,
+                    validator_network.listen_address.clone(),
+                ));
+
                 validator_config.set_data_dir(validator.base_dir());
                 *validator.config_mut() = validator_config.clone();
                 // Since the validator's config has changed we need to save it
@@ -221,6 +243,13 @@ impl LocalSwarm {
             })
             .collect::<Result<HashMap<_, _>>>()?;
 
+        let mut network_chaos = LocalNetworkChaos::new();
+        validator_network_addresses.sort_by_key(|(index, ..)| *index);
+        for (_, peer_id, advertised_address, listen_address) in validator_network_addresses {
+            let listener = TcpListener::bind(socket_addr(&advertised_address)?)?;
+            network_chaos.add_validator_proxy(peer_id, listener, socket_addr(&listen_address)?)?;
+        }
+
         // We print out the root key to make it easy for users to deploy a local faucet
         let encoded_root_key = EncodingType::Hex.encode_key("root_key", &root_key)?;
         info!(
@@ -258,6 +287,7 @@ impl LocalSwarm {
             root_account,
             chain_id: ChainId::test(),
             root_key,
+            network_chaos,
             launched: false,
             guard,
         })
@@ -616,16 +646,17 @@ impl Swarm for LocalSwarm {
         self.dir.display().to_string()
     }
 
-    fn inject_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        self.network_chaos.inject(chaos)
     }
 
-    fn remove_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        self.network_chaos.remove(&chaos)
     }
 
     fn remove_all_chaos(&mut self) -> Result<()> {
-        todo!()
+        self.network_chaos.remove_all();
+        Ok(())
     }
 
     async fn ensure_no_validator_restart(&self) -> Result<()> {
@@ -681,6 +712,18 @@ impl Swarm for LocalSwarm {
     }
 }
 
+/// Returns the socket address of a `/ip4/<ip>/tcp/<port>` network address, where an unspecified
+/// IP is replaced by localhost.
+fn socket_addr(address: &NetworkAddress) -> Result<SocketAddr> {
+    match (address.find_ip_addr(), address.find_port()) {
+        (Some(ip), Some(port)) if ip.is_unspecified() => {
+            Ok(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
+        },
+        (Some(ip), Some(port)) => Ok(SocketAddr::new(ip, port)),
+        _ => bail!("Expected an IP address and a port in {}", address),
+    }
+}
+
 #[derive(Debug)]
 pub struct ActiveNodesGuard {
     counter: Arc<Mutex<usize>>,
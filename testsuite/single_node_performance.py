@@ -129,6 +129,8 @@ TESTS = [
 
     RunGroupConfig(expected_tps=50000, key=RunGroupKey("coin_transfer_connected_components", executor_type="sharded", sharding_traffic_flags="--connected-tx-grps 5000", transaction_type_override=""), included_in=Flow.REPRESENTATIVE),
     RunGroupConfig(expected_tps=50000, key=RunGroupKey("coin_transfer_hotspot", executor_type="sharded", sharding_traffic_flags="--hotspot-probability 0.8", transaction_type_override=""), included_in=Flow.REPRESENTATIVE),
+    # Block-STM with read/write hint based scheduling, on a contended workload.
+    RunGroupConfig(expected_tps=10000, key=RunGroupKey("coin_transfer_hotspot", executor_type="hinted", sharding_traffic_flags="--hotspot-probability 0.8", transaction_type_override=""), included_in=Flow.REPRESENTATIVE, waived=True),
 
     # setting separately for previewnet, as we run on a different number of cores.
     RunGroupConfig(expected_tps=29000 if NUM_ACCOUNTS < 5000000 else 20000, key=RunGroupKey("coin-transfer", smaller_working_set=True), included_in=Flow.PREVIEWNET | Flow.PREVIEWNET_LARGE_DB),
@@ -453,6 +455,8 @@ with tempfile.TemporaryDirectory() as tmpdirname:
 
         if test.key.executor_type == "VM":
             executor_type_str = "--transactions-per-sender 1"
+        elif test.key.executor_type == "hinted":
+            executor_type_str = f"--hint-based-scheduling {sharding_traffic_flags or '--transactions-per-sender 1'}"
         elif test.key.executor_type == "native":
             executor_type_str = "--use-native-executor --transactions-per-sender 1"
         elif test.key.executor_type == "sharded":
@@ -4,7 +4,7 @@
 
 use crate::{
     errors::{PartialVMError, PartialVMResult},
-    file_format::{AbilitySet, StructTypeParameter, Visibility},
+    file_format::{Ability, AbilitySet, StructTypeParameter, Visibility},
     file_format_common::VERSION_5,
     normalized::Module,
 };
@@ -28,6 +28,25 @@ pub struct Compatibility {
     check_friend_linking: bool,
 }
 
+/// The kind of compatibility a change between two versions of a module breaks.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum IncompatibilityKind {
+    /// Dependent modules that reference public functions or structs may fail to link.
+    StructAndPubFunctionLinking,
+    /// Struct values published by the old module can no longer be read.
+    StructLayout,
+    /// Friend modules that reference friend functions may fail to link.
+    FriendLinking,
+}
+
+/// A change between two versions of a module which breaks compatibility.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Incompatibility {
+    pub kind: IncompatibilityKind,
+    /// What changed, e.g. "public function `foo` was removed".
+    pub description: String,
+}
+
 impl Default for Compatibility {
     fn default() -> Self {
         Self {
@@ -71,13 +90,66 @@ impl Compatibility {
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
     pub fn check(&self, old_module: &Module, new_module: &Module) -> PartialVMResult<()> {
-        let mut struct_and_pub_function_linking = true;
-        let mut struct_layout = true;
-        let mut friend_linking = true;
+        let incompatibilities = self.incompatibilities(old_module, new_module);
+        let breaks = |kind: IncompatibilityKind| {
+            incompatibilities
+                .iter()
+                .any(|incompatibility| incompatibility.kind == kind)
+        };
+
+        if breaks(IncompatibilityKind::StructAndPubFunctionLinking) {
+            return Err(PartialVMError::new(
+                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+            ).with_message(format!("Module Update Failure: Public function/struct signature of new module differs from existing module in {:?}::{}", old_module.address, old_module.name)));
+        }
+        if breaks(IncompatibilityKind::StructLayout) {
+            return Err(PartialVMError::new(
+                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+            ).with_message(format!("Module Update Failure: Struct layout of new module differs from existing modul in {:?}::{}", old_module.address, old_module.name)));
+        }
+        if breaks(IncompatibilityKind::FriendLinking) {
+            return Err(PartialVMError::new(
+                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+            ).with_message(format!("Module Update Failure: Friend signature of new module differs from existing module in {:?}::{}", old_module.address, old_module.name)));
+        }
+
+        Ok(())
+    }
+
+    /// Lists every change of `new_module` relative to old module `old_module` which breaks one of
+    /// the kinds of compatibility checked, in the order they are found. `check` fails exactly
+    /// when this list is not empty.
+    pub fn incompatibilities(
+        &self,
+        old_module: &Module,
+        new_module: &Module,
+    ) -> Vec<Incompatibility> {
+        let mut incompatibilities = vec![];
+        let mut report = |kind: IncompatibilityKind, description: String| {
+            let checked = match kind {
+                IncompatibilityKind::StructAndPubFunctionLinking => {
+                    self.check_struct_and_pub_function_linking
+                },
+                IncompatibilityKind::StructLayout => self.check_struct_layout,
+                IncompatibilityKind::FriendLinking => self.check_friend_linking,
+            };
+            if checked {
+                incompatibilities.push(Incompatibility { kind, description });
+            }
+        };
 
         // module's name and address are unchanged
         if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_pub_function_linking = false;
+            report(
+                IncompatibilityKind::StructAndPubFunctionLinking,
+                format!(
+                    "module {}::{} was renamed to {}::{}",
+                    old_module.address.to_hex_literal(),
+                    old_module.name,
+                    new_module.address.to_hex_literal(),
+                    new_module.name
+                ),
+            );
         }
 
         // old module's structs are a subset of the new module's structs
@@ -88,19 +160,49 @@ impl Compatibility {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    struct_and_pub_function_linking = false;
-                    struct_layout = false;
-                    break;
+                    report(
+                        IncompatibilityKind::StructAndPubFunctionLinking,
+                        format!("struct `{}` was removed", name),
+                    );
+                    report(
+                        IncompatibilityKind::StructLayout,
+                        format!(
+                            "struct `{}` was removed, and could be re-added later with a \
+                             different layout",
+                            name
+                        ),
+                    );
+                    continue;
                 },
             };
 
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !struct_type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
-            {
-                struct_and_pub_function_linking = false;
+            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities) {
+                report(
+                    IncompatibilityKind::StructAndPubFunctionLinking,
+                    format!(
+                        "struct `{}` no longer has {}: abilities can be added, but not removed",
+                        name,
+                        ability_names(
+                            old_struct
+                                .abilities
+                                .into_iter()
+                                .filter(|ability| !new_struct.abilities.has_ability(*ability))
+                        )
+                    ),
+                );
+            }
+            if !struct_type_parameters_compatibile(
+                &old_struct.type_parameters,
+                &new_struct.type_parameters,
+            ) {
+                report(
+                    IncompatibilityKind::StructAndPubFunctionLinking,
+                    format!(
+                        "type parameters of struct `{}` changed: their number must stay the \
+                         same, constraints can only be removed, and `phantom` can only be added",
+                        name
+                    ),
+                );
             }
             if new_struct.fields != old_struct.fields {
                 // Fields changed. Code in this module will fail at runtime if it tries to
@@ -109,7 +211,14 @@ impl Compatibility {
                 // choose that changing the name (but not position or type) of a field is
                 // compatible. The VM does not care about the name of a field
                 // (it's purely informational), but clients presumably do.
-                struct_layout = false
+                report(
+                    IncompatibilityKind::StructLayout,
+                    format!(
+                        "fields of struct `{}` changed: fields cannot be added, removed, \
+                         renamed, reordered or retyped",
+                        name
+                    ),
+                );
             }
         }
 
@@ -128,14 +237,21 @@ impl Compatibility {
         // friend list. But for simplicity, we decided to go to the more restrictive form now and
         // we may revisit this in the future.
         for (name, old_func) in &old_module.exposed_functions {
+            let kind = if matches!(old_func.visibility, Visibility::Friend) {
+                IncompatibilityKind::FriendLinking
+            } else {
+                IncompatibilityKind::StructAndPubFunctionLinking
+            };
+            let func_desc = match old_func.visibility {
+                Visibility::Public => "public function",
+                Visibility::Friend => "friend function",
+                // Private functions are only exposed when they are entry functions.
+                Visibility::Private => "entry function",
+            };
             let new_func = match new_module.exposed_functions.get(name) {
                 Some(new_func) => new_func,
                 None => {
-                    if matches!(old_func.visibility, Visibility::Friend) {
-                        friend_linking = false;
-                    } else {
-                        struct_and_pub_function_linking = false;
-                    }
+                    report(kind, format!("{} `{}` was removed", func_desc, name));
                     continue;
                 },
             };
@@ -161,20 +277,55 @@ impl Compatibility {
                 // If it was not an entry function, it is allowed to become one.
                 !old_func.is_entry || new_func.is_entry
             };
-            if !is_vis_compatible
-                || !is_entry_compatible
-                || old_func.parameters != new_func.parameters
-                || old_func.return_ != new_func.return_
-                || !fun_type_parameters_compatibile(
-                    &old_func.type_parameters,
-                    &new_func.type_parameters,
-                )
-            {
-                if matches!(old_func.visibility, Visibility::Friend) {
-                    friend_linking = false;
-                } else {
-                    struct_and_pub_function_linking = false;
-                }
+            if !is_vis_compatible {
+                report(
+                    kind,
+                    format!(
+                        "visibility of {} `{}` was reduced to {}",
+                        func_desc,
+                        name,
+                        visibility_name(new_func.visibility)
+                    ),
+                );
+            }
+            if !is_entry_compatible {
+                report(
+                    kind,
+                    if old_func.is_entry {
+                        format!("{} `{}` is no longer an entry function", func_desc, name)
+                    } else {
+                        format!(
+                            "{} `{}` became an entry function, which is not allowed before \
+                             bytecode version 5",
+                            func_desc, name
+                        )
+                    },
+                );
+            }
+            if old_func.parameters != new_func.parameters {
+                report(
+                    kind,
+                    format!("parameters of {} `{}` changed", func_desc, name),
+                );
+            }
+            if old_func.return_ != new_func.return_ {
+                report(
+                    kind,
+                    format!("return type of {} `{}` changed", func_desc, name),
+                );
+            }
+            if !fun_type_parameters_compatibile(
+                &old_func.type_parameters,
+                &new_func.type_parameters,
+            ) {
+                report(
+                    kind,
+                    format!(
+                        "type parameters of {} `{}` changed: their number must stay the same, \
+                         and constraints can only be removed",
+                        func_desc, name
+                    ),
+                );
             }
         }
 
@@ -183,29 +334,20 @@ impl Compatibility {
         // - additions to the list are allowed
         // - removals are not allowed
         //
-        let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
         let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            friend_linking = false;
-        }
-
-        if self.check_struct_and_pub_function_linking && !struct_and_pub_function_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ).with_message(format!("Module Update Failure: Public function/struct signature of new module differs from existing module in {:?}::{}", old_module.address, old_module.name)));
-        }
-        if self.check_struct_layout && !struct_layout {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ).with_message(format!("Module Update Failure: Struct layout of new module differs from existing modul in {:?}::{}", old_module.address, old_module.name)));
-        }
-        if self.check_friend_linking && !friend_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ).with_message(format!("Module Update Failure: Friend signature of new module differs from existing module in {:?}::{}", old_module.address, old_module.name)));
+        for old_friend in &old_module.friends {
+            if !new_friend_module_ids.contains(old_friend) {
+                report(
+                    IncompatibilityKind::FriendLinking,
+                    format!(
+                        "friend declaration of module {} was removed",
+                        old_friend.short_str_lossless()
+                    ),
+                );
+            }
         }
 
-        Ok(())
+        incompatibilities
     }
 }
 
@@ -267,3 +409,23 @@ fn type_parameter_phantom_decl_compatibile(
     // old_type_paramter.is_phantom => new_type_parameter.is_phantom
     !old_type_parameter.is_phantom || new_type_parameter.is_phantom
 }
+
+fn ability_names(abilities: impl Iterator<Item = Ability>) -> String {
+    abilities
+        .map(|ability| match ability {
+            Ability::Copy => "`copy`",
+            Ability::Drop => "`drop`",
+            Ability::Store => "`store`",
+            Ability::Key => "`key`",
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn visibility_name(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "public",
+        Visibility::Friend => "friend",
+        Visibility::Private => "private",
+    }
+}
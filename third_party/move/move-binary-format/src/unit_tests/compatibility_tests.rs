@@ -1,7 +1,11 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*, normalized};
+use crate::{
+    compatibility::{Compatibility, Incompatibility, IncompatibilityKind},
+    file_format::*,
+    normalized,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use std::convert::TryFrom;
 
@@ -115,3 +119,35 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn incompatibilities_are_reported_by_kind() {
+    let public_module = mk_module(Visibility::Public as u8);
+    let friend_module = mk_module(Visibility::Friend as u8);
+    let private_module = mk_module(Visibility::Private as u8);
+
+    // public -> friend breaks linking of public functions
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&public_module, &friend_module),
+        vec![Incompatibility {
+            kind: IncompatibilityKind::StructAndPubFunctionLinking,
+            description: "visibility of public function `fn` was reduced to friend".to_string(),
+        }]
+    );
+    // friend -> private removes the friend function
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&friend_module, &private_module),
+        vec![Incompatibility {
+            kind: IncompatibilityKind::FriendLinking,
+            description: "friend function `fn` was removed".to_string(),
+        }]
+    );
+    // which is only reported when friend linking is checked
+    let no_friend_check = Compatibility::new(true, true, false);
+    assert!(no_friend_check
+        .incompatibilities(&friend_module, &private_module)
+        .is_empty());
+    assert!(no_friend_check
+        .check(&friend_module, &private_module)
+        .is_ok());
+}
@@ -0,0 +1,321 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Line and function coverage of Move source files, and their export in the formats coverage
+//! services and CI dashboards consume: LCOV tracefiles and Cobertura XML reports.
+
+use crate::coverage_map::ExecCoverageMap;
+use anyhow::{bail, Result};
+use codespan::Files;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::FileHash;
+use move_ir_types::location::Loc;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Coverage of a function: how many times it was called, and how many times each of its source
+/// lines was executed.
+#[derive(Clone, Debug)]
+pub struct FunctionLineCoverage {
+    pub name: String,
+    /// The (1-based) line the function is defined on.
+    pub line: u32,
+    pub hits: u64,
+    /// Execution counts by (1-based) line.
+    pub lines: BTreeMap<u32, u64>,
+}
+
+/// Line coverage of a module, against its source file.
+#[derive(Clone, Debug)]
+pub struct ModuleLineCoverage {
+    /// The module name, qualified with its address.
+    pub name: String,
+    pub source_path: PathBuf,
+    pub functions: Vec<FunctionLineCoverage>,
+}
+
+impl ModuleLineCoverage {
+    pub fn new(
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<Self> {
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents of {} out of sync with source map",
+                source_path.display()
+            );
+        }
+        let file_hash = FileHash::new(&file_contents);
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let line_of = |loc: &Loc| -> Result<Option<u32>> {
+            if loc.file_hash() != file_hash {
+                return Ok(None);
+            }
+            Ok(Some(files.location(file_id, loc.start())?.line.0 + 1))
+        };
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+
+        let mut functions = vec![];
+        for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+            let code_unit = match &function_def.code {
+                Some(code_unit) => code_unit,
+                // Native functions have no source to cover.
+                None => continue,
+            };
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+            let function_coverage =
+                module_map.and_then(|module_map| module_map.get_function_coverage(fn_name));
+            let count_at = |code_offset: u64| {
+                function_coverage
+                    .and_then(|function_coverage| function_coverage.get(&code_offset))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+            let function_source_map = source_map.get_function_source_map(function_def_idx)?;
+            let line = match line_of(&function_source_map.definition_location)? {
+                Some(line) => line,
+                None => continue,
+            };
+            let mut lines = BTreeMap::new();
+            for code_offset in 0..code_unit.code.len() {
+                let loc =
+                    source_map.get_code_location(function_def_idx, code_offset as CodeOffset)?;
+                if let Some(line) = line_of(&loc)? {
+                    let hits = lines.entry(line).or_insert(0);
+                    *hits = std::cmp::max(*hits, count_at(code_offset as u64));
+                }
+            }
+            functions.push(FunctionLineCoverage {
+                name: fn_name.to_string(),
+                line,
+                hits: count_at(0),
+                lines,
+            });
+        }
+
+        Ok(Self {
+            name: format!(
+                "{}::{}",
+                module_id.address().to_hex_literal(),
+                module_id.name()
+            ),
+            source_path: source_path.to_path_buf(),
+            functions,
+        })
+    }
+
+    /// Execution counts by (1-based) line, over all the functions of the module.
+    pub fn lines(&self) -> BTreeMap<u32, u64> {
+        let mut lines = BTreeMap::new();
+        for function in &self.functions {
+            for (line, hits) in &function.lines {
+                let entry = lines.entry(*line).or_insert(0);
+                *entry = std::cmp::max(*entry, *hits);
+            }
+        }
+        lines
+    }
+}
+
+/// Writes the coverage of the modules as an LCOV tracefile, with one record per source file.
+pub fn format_lcov<W: Write>(modules: &[ModuleLineCoverage], writer: &mut W) -> io::Result<()> {
+    let mut by_source: BTreeMap<&Path, Vec<&ModuleLineCoverage>> = BTreeMap::new();
+    for module in modules {
+        by_source
+            .entry(module.source_path.as_path())
+            .or_default()
+            .push(module);
+    }
+
+    writeln!(writer, "TN:")?;
+    for (source_path, modules) in by_source {
+        writeln!(writer, "SF:{}", source_path.display())?;
+        let functions: Vec<_> = modules
+            .iter()
+            .flat_map(|module| {
+                module
+                    .functions
+                    .iter()
+                    .map(move |function| (format!("{}::{}", module.name, function.name), function))
+            })
+            .collect();
+        for (name, function) in &functions {
+            writeln!(writer, "FN:{},{}", function.line, name)?;
+        }
+        for (name, function) in &functions {
+            writeln!(writer, "FNDA:{},{}", function.hits, name)?;
+        }
+        writeln!(writer, "FNF:{}", functions.len())?;
+        writeln!(
+            writer,
+            "FNH:{}",
+            functions
+                .iter()
+                .filter(|(_, function)| function.hits > 0)
+                .count()
+        )?;
+
+        let mut lines = BTreeMap::new();
+        for module in modules {
+            for (line, hits) in module.lines() {
+                let entry = lines.entry(line).or_insert(0);
+                *entry = std::cmp::max(*entry, hits);
+            }
+        }
+        for (line, hits) in &lines {
+            writeln!(writer, "DA:{},{}", line, hits)?;
+        }
+        writeln!(writer, "LF:{}", lines.len())?;
+        writeln!(
+            writer,
+            "LH:{}",
+            lines.values().filter(|hits| **hits > 0).count()
+        )?;
+        writeln!(writer, "end_of_record")?;
+    }
+    Ok(())
+}
+
+/// Writes the coverage of the modules of a package as a Cobertura XML report, with one class
+/// per module. Source file names are given relative to `source_root` when they are under it.
+pub fn format_cobertura<W: Write>(
+    package_name: &str,
+    source_root: &Path,
+    modules: &[ModuleLineCoverage],
+    writer: &mut W,
+) -> io::Result<()> {
+    let module_lines: Vec<_> = modules.iter().map(|module| module.lines()).collect();
+    let (lines_valid, lines_covered) =
+        module_lines.iter().fold((0, 0), |(valid, covered), lines| {
+            let (module_valid, module_covered) = count_lines(lines);
+            (valid + module_valid, covered + module_covered)
+        });
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    writeln!(writer, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        writer,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+        writer,
+        r#"<coverage line-rate="{}" branch-rate="0" lines-covered="{}" lines-valid="{}" branches-covered="0" branches-valid="0" complexity="0" version="" timestamp="{}">"#,
+        rate(lines_valid, lines_covered),
+        lines_covered,
+        lines_valid,
+        timestamp
+    )?;
+    writeln!(writer, "  <sources>")?;
+    writeln!(
+        writer,
+        "    <source>{}</source>",
+        escape_xml(&source_root.display().to_string())
+    )?;
+    writeln!(writer, "  </sources>")?;
+    writeln!(writer, "  <packages>")?;
+    writeln!(
+        writer,
+        r#"    <package name="{}" line-rate="{}" branch-rate="0" complexity="0">"#,
+        escape_xml(package_name),
+        rate(lines_valid, lines_covered)
+    )?;
+    writeln!(writer, "      <classes>")?;
+    for (module, lines) in modules.iter().zip(module_lines.iter()) {
+        let (valid, covered) = count_lines(lines);
+        let filename = module
+            .source_path
+            .strip_prefix(source_root)
+            .unwrap_or(&module.source_path);
+        writeln!(
+            writer,
+            r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="0" complexity="0">"#,
+            escape_xml(&module.name),
+            escape_xml(&filename.display().to_string()),
+            rate(valid, covered)
+        )?;
+        writeln!(writer, "          <methods>")?;
+        for function in &module.functions {
+            let (valid, covered) = count_lines(&function.lines);
+            writeln!(
+                writer,
+                r#"            <method name="{}" signature="" line-rate="{}" branch-rate="0" complexity="0">"#,
+                escape_xml(&function.name),
+                rate(valid, covered)
+            )?;
+            write_cobertura_lines(writer, &function.lines, "              ")?;
+            writeln!(writer, "            </method>")?;
+        }
+        writeln!(writer, "          </methods>")?;
+        write_cobertura_lines(writer, lines, "          ")?;
+        writeln!(writer, "        </class>")?;
+    }
+    writeln!(writer, "      </classes>")?;
+    writeln!(writer, "    </package>")?;
+    writeln!(writer, "  </packages>")?;
+    writeln!(writer, "</coverage>")
+}
+
+fn write_cobertura_lines<W: Write>(
+    writer: &mut W,
+    lines: &BTreeMap<u32, u64>,
+    indent: &str,
+) -> io::Result<()> {
+    writeln!(writer, "{}<lines>", indent)?;
+    for (line, hits) in lines {
+        writeln!(
+            writer,
+            r#"{}  <line number="{}" hits="{}" branch="false"/>"#,
+            indent, line, hits
+        )?;
+    }
+    writeln!(writer, "{}</lines>", indent)
+}
+
+/// The number of lines with code, and of those that were executed.
+fn count_lines(lines: &BTreeMap<u32, u64>) -> (usize, usize) {
+    (
+        lines.len(),
+        lines.values().filter(|hits| **hits > 0).count(),
+    )
+}
+
+fn rate(valid: usize, covered: usize) -> String {
+    if valid == 0 {
+        "1".to_string()
+    } else {
+        format!("{:.4}", covered as f64 / valid as f64)
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_cli::base::test::{run_move_unit_tests, UnitTestResult};
+use move_command_line_common::testing::{
+    add_update_baseline_fix, format_diff, read_env_update_baseline,
+};
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_coverage::{
+    coverage_map::CoverageMap,
+    line_coverage::{format_cobertura, format_lcov, ModuleLineCoverage},
+};
+use move_package::BuildConfig;
+use move_unit_test::UnitTestingConfig;
+use regex::Regex;
+use std::{fs, path::Path};
+
+const PACKAGE: &str = "tests/packages/basic";
+
+/// Copies the package to a temporary directory, since running the tests writes the trace, the
+/// coverage map and the build output next to the sources.
+fn copy_package(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            copy_package(&entry.path(), &to.join(entry.file_name()));
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+        }
+    }
+}
+
+/// Compares the output against the expected output in `exp_path`, or updates the latter if
+/// `UPDATE_BASELINE` is set.
+fn check_baseline(exp_path: &Path, output: &str) {
+    if read_env_update_baseline() {
+        fs::write(exp_path, output).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(exp_path).unwrap_or_default();
+    if expected != output {
+        panic!(
+            "{}",
+            add_update_baseline_fix(format!(
+                "Expected outputs differ for {:?}:\n{}",
+                exp_path,
+                format_diff(expected, output.to_string())
+            ))
+        );
+    }
+}
+
+#[test]
+fn test_line_coverage_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let package_path = dir.path().join("basic");
+    copy_package(Path::new(PACKAGE), &package_path);
+
+    let result = run_move_unit_tests(
+        &package_path,
+        BuildConfig::default(),
+        UnitTestingConfig::default_with_bound(Some(100_000)),
+        vec![],
+        None,
+        /* compute_coverage */ true,
+        &mut Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(result, UnitTestResult::Success);
+
+    // Like `aptos move coverage export`, cover the modules compiled without their tests.
+    let coverage_map = CoverageMap::from_binary_file(package_path.join(".coverage_map.mvcov"))
+        .unwrap()
+        .to_unified_exec_map();
+    let package = BuildConfig {
+        dev_mode: true,
+        ..Default::default()
+    }
+    .compile_package(&package_path, &mut Vec::new())
+    .unwrap();
+    let mut modules = package
+        .root_modules()
+        .filter_map(|unit| match &unit.unit {
+            CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) => Some(
+                ModuleLineCoverage::new(module, source_map, &unit.source_path, &coverage_map)
+                    .unwrap(),
+            ),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    modules.sort_by(|module1, module2| module1.name.cmp(&module2.name));
+
+    // The package is built in a temporary directory, which the reports refer to.
+    let package_dir = package_path.canonicalize().unwrap();
+    let normalize = |report: Vec<u8>| {
+        String::from_utf8(report)
+            .unwrap()
+            .replace(package_dir.to_str().unwrap(), "<package>")
+            .replace(package_path.to_str().unwrap(), "<package>")
+    };
+
+    let mut lcov = vec![];
+    format_lcov(&modules, &mut lcov).unwrap();
+    check_baseline(
+        &Path::new(PACKAGE).with_extension("lcov.exp"),
+        &normalize(lcov),
+    );
+
+    let mut cobertura = vec![];
+    format_cobertura("Basic", &package_path, &modules, &mut cobertura).unwrap();
+    let timestamp = Regex::new(r#"timestamp="\d+""#).unwrap();
+    let cobertura = normalize(cobertura);
+    check_baseline(
+        &Path::new(PACKAGE).with_extension("cobertura.exp"),
+        &timestamp.replace(&cobertura, r#"timestamp="""#),
+    );
+}
@@ -0,0 +1,26 @@
+module basic::counter {
+    use basic::math;
+
+    struct Counter has drop {
+        value: u64,
+    }
+
+    public fun new(): Counter {
+        Counter { value: 0 }
+    }
+
+    public fun add(counter: &mut Counter, n: u64) {
+        counter.value = counter.value + math::sum_to(n);
+    }
+
+    public fun value(counter: &Counter): u64 {
+        counter.value
+    }
+
+    #[test]
+    fun test_add() {
+        let counter = new();
+        add(&mut counter, 2);
+        assert!(value(&counter) == 3, 0);
+    }
+}
@@ -0,0 +1,33 @@
+module basic::math {
+    public fun max(a: u64, b: u64): u64 {
+        if (a > b) {
+            a
+        } else {
+            b
+        }
+    }
+
+    public fun sum_to(n: u64): u64 {
+        let sum = 0;
+        let i = 0;
+        while (i < n) {
+            i = i + 1;
+            sum = sum + i;
+        };
+        sum
+    }
+
+    public fun never_called(): u64 {
+        42
+    }
+
+    #[test]
+    fun test_max() {
+        assert!(max(1, 2) == 2, 0);
+    }
+
+    #[test]
+    fun test_sum_to() {
+        assert!(sum_to(3) == 6, 0);
+    }
+}
@@ -0,0 +1,1560 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reconstructs Move source from a compiled module: struct definitions, function signatures, and
+//! function bodies with structured control flow (`if`, `while`, `loop`, `break`, `continue`),
+//! named locals and nested expressions.
+//!
+//! The output is meant to be read, e.g. when auditing a module published without its sources, and
+//! is not guaranteed to compile: names of locals are lost, and values left on the operand stack
+//! across branches are carried in `s<n>` variables.
+
+use anyhow::{bail, format_err, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    control_flow_graph::{ControlFlowGraph, VMControlFlowGraph},
+    file_format::{
+        Ability, AbilitySet, Bytecode, CodeOffset, FieldHandleIndex, FieldInstantiationIndex,
+        FunctionDefinition, FunctionHandleIndex, FunctionInstantiationIndex, ModuleHandle,
+        ModuleHandleIndex, SignatureIndex, SignatureToken, StructDefInstantiationIndex,
+        StructDefinitionIndex, StructFieldInformation, StructHandleIndex, Visibility,
+    },
+    CompiledModule,
+};
+use move_core_types::{account_address::AccountAddress, value::MoveValue};
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+const INDENT: &str = "    ";
+
+/// Decompiles a module into Move source.
+pub struct Decompiler<'a> {
+    module: &'a CompiledModule,
+    /// How each module handle is referred to: by its name, or by its address and name when the
+    /// name is ambiguous.
+    module_names: Vec<String>,
+    /// The name `vector` operations are qualified with.
+    vector_module: String,
+    uses_vector: Cell<bool>,
+}
+
+impl<'a> Decompiler<'a> {
+    pub fn new(module: &'a CompiledModule) -> Self {
+        let self_idx = module.self_handle_idx();
+        let mut name_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for handle in module.module_handles() {
+            *name_counts
+                .entry(module.identifier_at(handle.name).as_str())
+                .or_default() += 1;
+        }
+        let module_names = module
+            .module_handles()
+            .iter()
+            .enumerate()
+            .map(|(idx, handle)| {
+                let name = module.identifier_at(handle.name).as_str();
+                if ModuleHandleIndex(idx as u16) != self_idx && name_counts[name] > 1 {
+                    format!(
+                        "{}::{}",
+                        module
+                            .address_identifier_at(handle.address)
+                            .to_hex_literal(),
+                        name
+                    )
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        // `vector` operations refer to `std::vector`, unless another `vector` module is used.
+        let vector_module = match module
+            .module_handles()
+            .iter()
+            .find(|handle| module.identifier_at(handle.name).as_str() == "vector")
+        {
+            Some(handle)
+                if *module.address_identifier_at(handle.address) != AccountAddress::ONE =>
+            {
+                "0x1::vector".to_string()
+            },
+            _ => "vector".to_string(),
+        };
+        Self {
+            module,
+            module_names,
+            vector_module,
+            uses_vector: Cell::new(false),
+        }
+    }
+
+    pub fn decompile(&self) -> Result<String> {
+        let module = self.module;
+        let mut definitions = vec![];
+        for struct_def_idx in 0..module.struct_defs().len() {
+            definitions
+                .push(self.decompile_struct_def(StructDefinitionIndex(struct_def_idx as u16))?);
+        }
+        for function_def in module.function_defs() {
+            definitions.push(self.decompile_function_def(function_def)?);
+        }
+
+        let self_idx = module.self_handle_idx();
+        let mut uses = BTreeSet::new();
+        for (idx, handle) in module.module_handles().iter().enumerate() {
+            if ModuleHandleIndex(idx as u16) != self_idx && !self.module_names[idx].contains("::") {
+                uses.insert(format!("use {};", self.module_path(handle)));
+            }
+        }
+        if self.uses_vector.get() && self.vector_module == "vector" {
+            uses.insert("use 0x1::vector;".to_string());
+        }
+        let friends: Vec<_> = module
+            .friend_decls()
+            .iter()
+            .map(|handle| format!("friend {};", self.module_path(handle)))
+            .collect();
+
+        let mut out = format!("module {} {{\n", self.module_path(module.self_handle()));
+        for section in [uses.into_iter().collect::<Vec<_>>(), friends] {
+            if !section.is_empty() {
+                for line in section {
+                    writeln!(out, "{}{}", INDENT, line)?;
+                }
+                out.push('\n');
+            }
+        }
+        for definition in definitions {
+            for line in definition.lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    writeln!(out, "{}{}", INDENT, line)?;
+                }
+            }
+            out.push('\n');
+        }
+        if out.ends_with("\n\n") {
+            out.pop();
+        }
+        out.push_str("}\n");
+        Ok(out)
+    }
+
+    fn decompile_struct_def(&self, struct_def_idx: StructDefinitionIndex) -> Result<String> {
+        let struct_def = self.module.struct_def_at(struct_def_idx);
+        let handle = self.module.struct_handle_at(struct_def.struct_handle);
+        let type_params = handle
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(idx, param)| {
+                format!(
+                    "{}T{}{}",
+                    if param.is_phantom { "phantom " } else { "" },
+                    idx,
+                    format_constraints(param.constraints)
+                )
+            })
+            .collect::<Vec<_>>();
+        let header = format!(
+            "struct {}{}{}",
+            self.module.identifier_at(handle.name),
+            format_type_list(&type_params),
+            format_abilities(handle.abilities)
+        );
+        match &struct_def.field_information {
+            StructFieldInformation::Native => Ok(format!("native {};", header)),
+            StructFieldInformation::Declared(fields) => {
+                let mut out = format!("{} {{\n", header);
+                for field in fields {
+                    writeln!(
+                        out,
+                        "{}{}: {},",
+                        INDENT,
+                        self.module.identifier_at(field.name),
+                        self.format_type(&field.signature.0)
+                    )?;
+                }
+                out.push('}');
+                Ok(out)
+            },
+        }
+    }
+
+    fn decompile_function_def(&self, function_def: &FunctionDefinition) -> Result<String> {
+        let handle = self.module.function_handle_at(function_def.function);
+        let parameters = &self.module.signature_at(handle.parameters).0;
+        let returns = &self.module.signature_at(handle.return_).0;
+
+        let mut header = String::new();
+        match function_def.visibility {
+            Visibility::Public => header.push_str("public "),
+            Visibility::Friend => header.push_str("public(friend) "),
+            Visibility::Private => (),
+        }
+        if function_def.is_entry {
+            header.push_str("entry ");
+        }
+        if function_def.code.is_none() {
+            header.push_str("native ");
+        }
+        let type_params = handle
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(idx, constraints)| format!("T{}{}", idx, format_constraints(*constraints)))
+            .collect::<Vec<_>>();
+        write!(
+            header,
+            "fun {}{}({})",
+            self.module.identifier_at(handle.name),
+            format_type_list(&type_params),
+            parameters
+                .iter()
+                .enumerate()
+                .map(|(idx, ty)| format!(
+                    "{}: {}",
+                    local_name(idx, parameters.len()),
+                    self.format_type(ty)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        match returns.len() {
+            0 => (),
+            1 => write!(header, ": {}", self.format_type(&returns[0]))?,
+            _ => write!(
+                header,
+                ": ({})",
+                returns
+                    .iter()
+                    .map(|ty| self.format_type(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?,
+        }
+        if !function_def.acquires_global_resources.is_empty() {
+            write!(
+                header,
+                " acquires {}",
+                function_def
+                    .acquires_global_resources
+                    .iter()
+                    .map(|idx| self.struct_def_name(*idx))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+
+        let code = match &function_def.code {
+            Some(code) => code,
+            None => return Ok(format!("{};", header)),
+        };
+        let locals = &self.module.signature_at(code.locals).0;
+        let mut body = vec![];
+        for (idx, ty) in locals.iter().enumerate() {
+            body.push(Stmt::Line(format!(
+                "let {}: {};",
+                local_name(parameters.len() + idx, parameters.len()),
+                self.format_type(ty)
+            )));
+        }
+        match FunctionDecompiler::new(self, &code.code, parameters.len(), returns.len()).decompile()
+        {
+            Ok(stmts) => body.extend(stmts),
+            Err(err) => body.push(Stmt::Line(format!(
+                "/* unable to decompile the function body: {} */",
+                err
+            ))),
+        }
+
+        let mut out = format!("{} {{\n", header);
+        format_stmts(&mut out, &body, 1);
+        out.push('}');
+        Ok(out)
+    }
+
+    fn module_path(&self, handle: &ModuleHandle) -> String {
+        format!(
+            "{}::{}",
+            self.module
+                .address_identifier_at(handle.address)
+                .to_hex_literal(),
+            self.module.identifier_at(handle.name)
+        )
+    }
+
+    /// The name of a struct or function declared in the module of `module_idx`, as referred to
+    /// from this module.
+    fn member_name(&self, module_idx: ModuleHandleIndex, name: &str) -> String {
+        if module_idx == self.module.self_handle_idx() {
+            name.to_string()
+        } else {
+            format!("{}::{}", self.module_names[module_idx.0 as usize], name)
+        }
+    }
+
+    fn struct_handle_name(&self, idx: StructHandleIndex) -> String {
+        let handle = self.module.struct_handle_at(idx);
+        self.member_name(
+            handle.module,
+            self.module.identifier_at(handle.name).as_str(),
+        )
+    }
+
+    fn struct_def_name(&self, idx: StructDefinitionIndex) -> String {
+        self.struct_handle_name(self.module.struct_def_at(idx).struct_handle)
+    }
+
+    fn struct_inst_name(&self, idx: StructDefInstantiationIndex) -> String {
+        let inst = self.module.struct_instantiation_at(idx);
+        format!(
+            "{}{}",
+            self.struct_def_name(inst.def),
+            self.format_type_args(inst.type_parameters)
+        )
+    }
+
+    fn function_name(&self, idx: FunctionHandleIndex) -> String {
+        let handle = self.module.function_handle_at(idx);
+        self.member_name(
+            handle.module,
+            self.module.identifier_at(handle.name).as_str(),
+        )
+    }
+
+    fn function_inst_name(&self, idx: FunctionInstantiationIndex) -> String {
+        let inst = self.module.function_instantiation_at(idx);
+        format!(
+            "{}{}",
+            self.function_name(inst.handle),
+            self.format_type_args(inst.type_parameters)
+        )
+    }
+
+    fn field_names(&self, idx: StructDefinitionIndex) -> Vec<String> {
+        match &self.module.struct_def_at(idx).field_information {
+            StructFieldInformation::Native => vec![],
+            StructFieldInformation::Declared(fields) => fields
+                .iter()
+                .map(|field| self.module.identifier_at(field.name).to_string())
+                .collect(),
+        }
+    }
+
+    fn field_name(&self, idx: FieldHandleIndex) -> Result<String> {
+        let handle = self.module.field_handle_at(idx);
+        self.field_names(handle.owner)
+            .get(handle.field as usize)
+            .cloned()
+            .ok_or_else(|| format_err!("Unknown field {} of field handle {}", handle.field, idx))
+    }
+
+    fn field_inst_name(&self, idx: FieldInstantiationIndex) -> Result<String> {
+        self.field_name(self.module.field_instantiation_at(idx).handle)
+    }
+
+    fn format_type_args(&self, idx: SignatureIndex) -> String {
+        let types = self
+            .module
+            .signature_at(idx)
+            .0
+            .iter()
+            .map(|ty| self.format_type(ty))
+            .collect::<Vec<_>>();
+        format_type_list(&types)
+    }
+
+    fn format_type(&self, ty: &SignatureToken) -> String {
+        match ty {
+            SignatureToken::Bool => "bool".to_string(),
+            SignatureToken::U8 => "u8".to_string(),
+            SignatureToken::U16 => "u16".to_string(),
+            SignatureToken::U32 => "u32".to_string(),
+            SignatureToken::U64 => "u64".to_string(),
+            SignatureToken::U128 => "u128".to_string(),
+            SignatureToken::U256 => "u256".to_string(),
+            SignatureToken::Address => "address".to_string(),
+            SignatureToken::Signer => "signer".to_string(),
+            SignatureToken::Vector(ty) => format!("vector<{}>", self.format_type(ty)),
+            SignatureToken::Struct(idx) => self.struct_handle_name(*idx),
+            SignatureToken::StructInstantiation(idx, tys) => format!(
+                "{}{}",
+                self.struct_handle_name(*idx),
+                format_type_list(
+                    &tys.iter()
+                        .map(|ty| self.format_type(ty))
+                        .collect::<Vec<_>>()
+                )
+            ),
+            SignatureToken::Reference(ty) => format!("&{}", self.format_type(ty)),
+            SignatureToken::MutableReference(ty) => format!("&mut {}", self.format_type(ty)),
+            SignatureToken::TypeParameter(idx) => format!("T{}", idx),
+        }
+    }
+
+    fn vector_function(&self, name: &str) -> String {
+        self.uses_vector.set(true);
+        format!("{}::{}", self.vector_module, name)
+    }
+}
+
+/// Parameters are named `a<n>` and other locals `l<n>`, by their index.
+fn local_name(idx: usize, num_params: usize) -> String {
+    if idx < num_params {
+        format!("a{}", idx)
+    } else {
+        format!("l{}", idx)
+    }
+}
+
+fn format_type_list(types: &[String]) -> String {
+    if types.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", types.join(", "))
+    }
+}
+
+fn ability_name(ability: Ability) -> &'static str {
+    match ability {
+        Ability::Copy => "copy",
+        Ability::Drop => "drop",
+        Ability::Store => "store",
+        Ability::Key => "key",
+    }
+}
+
+fn format_constraints(constraints: AbilitySet) -> String {
+    let abilities = constraints
+        .into_iter()
+        .map(ability_name)
+        .collect::<Vec<_>>();
+    if abilities.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", abilities.join(" + "))
+    }
+}
+
+fn format_abilities(abilities: AbilitySet) -> String {
+    let abilities = abilities.into_iter().map(ability_name).collect::<Vec<_>>();
+    if abilities.is_empty() {
+        String::new()
+    } else {
+        format!(" has {}", abilities.join(", "))
+    }
+}
+
+//**************************************************************************************************
+// Expressions and statements
+//**************************************************************************************************
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Exp {
+    /// A local, temporary or stack variable.
+    Var(String),
+    /// A literal value.
+    Value(String),
+    Call(String, Vec<Exp>),
+    Pack(String, Vec<(String, Exp)>),
+    Vector(String, Vec<Exp>),
+    /// `!`, `&`, `&mut ` or `*`.
+    Unary(&'static str, Box<Exp>),
+    Binary(&'static str, Box<Exp>, Box<Exp>),
+    Field(Box<Exp>, String),
+    Cast(Box<Exp>, &'static str),
+}
+
+const PREC_UNARY: u8 = 11;
+const PREC_ATOM: u8 = 12;
+
+fn binary_precedence(op: &str) -> u8 {
+    match op {
+        "||" => 1,
+        "&&" => 2,
+        "==" | "!=" | "<" | ">" | "<=" | ">=" => 3,
+        "|" => 4,
+        "^" => 5,
+        "&" => 6,
+        "<<" | ">>" => 7,
+        "+" | "-" => 8,
+        _ => 9,
+    }
+}
+
+impl Exp {
+    fn precedence(&self) -> u8 {
+        match self {
+            Exp::Binary(op, _, _) => binary_precedence(op),
+            Exp::Unary(_, _) => PREC_UNARY,
+            _ => PREC_ATOM,
+        }
+    }
+
+    /// Whether evaluating the expression later than where it occurs in the bytecode may change
+    /// its value or its effects.
+    fn is_pure(&self) -> bool {
+        match self {
+            Exp::Var(_) | Exp::Value(_) => true,
+            Exp::Call(_, _) | Exp::Unary("*", _) => false,
+            Exp::Pack(_, fields) => fields.iter().all(|(_, exp)| exp.is_pure()),
+            Exp::Vector(_, exps) => exps.iter().all(Exp::is_pure),
+            Exp::Unary(_, exp) | Exp::Field(exp, _) | Exp::Cast(exp, _) => exp.is_pure(),
+            Exp::Binary(_, lhs, rhs) => lhs.is_pure() && rhs.is_pure(),
+        }
+    }
+
+    fn reads(&self, var: &str) -> bool {
+        match self {
+            Exp::Var(name) => name == var,
+            Exp::Value(_) => false,
+            Exp::Call(_, exps) | Exp::Vector(_, exps) => exps.iter().any(|exp| exp.reads(var)),
+            Exp::Pack(_, fields) => fields.iter().any(|(_, exp)| exp.reads(var)),
+            Exp::Unary(_, exp) | Exp::Field(exp, _) | Exp::Cast(exp, _) => exp.reads(var),
+            Exp::Binary(_, lhs, rhs) => lhs.reads(var) || rhs.reads(var),
+        }
+    }
+
+    fn negate(self) -> Exp {
+        match self {
+            Exp::Unary("!", exp) => *exp,
+            Exp::Binary(op, lhs, rhs) if binary_precedence(op) == 3 => {
+                let op = match op {
+                    "==" => "!=",
+                    "!=" => "==",
+                    "<" => ">=",
+                    ">" => "<=",
+                    "<=" => ">",
+                    _ => "<",
+                };
+                Exp::Binary(op, lhs, rhs)
+            },
+            exp => Exp::Unary("!", Box::new(exp)),
+        }
+    }
+
+    /// The place a reference points to, e.g. `x.f` for `&mut x.f`, or `*r` for a reference `r`.
+    fn deref(self) -> Exp {
+        match self {
+            Exp::Unary("&", exp) | Exp::Unary("&mut ", exp) => *exp,
+            exp => Exp::Unary("*", Box::new(exp)),
+        }
+    }
+
+    /// The struct whose field a reference is borrowed from: fields are accessed through
+    /// references, so the reference itself is kept, unless it is a borrow.
+    fn field_base(self) -> Exp {
+        match self {
+            Exp::Unary("&", exp) | Exp::Unary("&mut ", exp) => *exp,
+            exp => exp,
+        }
+    }
+
+    fn render(&self, min_precedence: u8) -> String {
+        let text = match self {
+            Exp::Var(name) | Exp::Value(name) => name.clone(),
+            Exp::Call(name, args) => format!("{}({})", name, render_list(args)),
+            Exp::Pack(name, fields) => {
+                if fields.is_empty() {
+                    format!("{} {{}}", name)
+                } else {
+                    format!(
+                        "{} {{ {} }}",
+                        name,
+                        fields
+                            .iter()
+                            .map(|(field, exp)| match exp {
+                                Exp::Var(var) if var == field => field.clone(),
+                                _ => format!("{}: {}", field, exp.render(0)),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            },
+            Exp::Vector(ty, exps) => format!("vector<{}>[{}]", ty, render_list(exps)),
+            Exp::Unary(op, exp) => format!("{}{}", op, exp.render(PREC_UNARY)),
+            Exp::Binary(op, lhs, rhs) => {
+                let precedence = binary_precedence(op);
+                // Comparisons do not associate.
+                let lhs_precedence = if precedence == 3 {
+                    precedence + 1
+                } else {
+                    precedence
+                };
+                format!(
+                    "{} {} {}",
+                    lhs.render(lhs_precedence),
+                    op,
+                    rhs.render(precedence + 1)
+                )
+            },
+            Exp::Field(exp, field) => format!("{}.{}", exp.render(PREC_ATOM), field),
+            Exp::Cast(exp, ty) => format!("({} as {})", exp.render(0), ty),
+        };
+        if self.precedence() < min_precedence {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+}
+
+fn render_list(exps: &[Exp]) -> String {
+    exps.iter()
+        .map(|exp| exp.render(0))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Line(String),
+    /// The value of the function body.
+    Value(String),
+    Return(Vec<Exp>),
+    If(Exp, Vec<Stmt>, Vec<Stmt>),
+    Loop(Vec<Stmt>),
+    While(Exp, Vec<Stmt>),
+    Break,
+    Continue,
+}
+
+fn format_stmts(out: &mut String, stmts: &[Stmt], depth: usize) {
+    let indent = INDENT.repeat(depth);
+    for stmt in stmts {
+        match stmt {
+            Stmt::Line(line) | Stmt::Value(line) => {
+                out.push_str(&indent);
+                out.push_str(line);
+            },
+            Stmt::Return(exps) => {
+                out.push_str(&indent);
+                match exps.len() {
+                    0 => out.push_str("return;"),
+                    1 => out.push_str(&format!("return {};", exps[0].render(0))),
+                    _ => out.push_str(&format!("return ({});", render_list(exps))),
+                }
+            },
+            Stmt::If(cond, then_stmts, else_stmts) => {
+                out.push_str(&format!("{}if ({}) {{\n", indent, cond.render(0)));
+                format_stmts(out, then_stmts, depth + 1);
+                if !else_stmts.is_empty() {
+                    out.push_str(&format!("{}}} else {{\n", indent));
+                    format_stmts(out, else_stmts, depth + 1);
+                }
+                out.push_str(&format!("{}}};", indent));
+            },
+            Stmt::Loop(body) => {
+                out.push_str(&format!("{}loop {{\n", indent));
+                format_stmts(out, body, depth + 1);
+                out.push_str(&format!("{}}};", indent));
+            },
+            Stmt::While(cond, body) => {
+                out.push_str(&format!("{}while ({}) {{\n", indent, cond.render(0)));
+                format_stmts(out, body, depth + 1);
+                out.push_str(&format!("{}}};", indent));
+            },
+            Stmt::Break => out.push_str(&format!("{}break;", indent)),
+            Stmt::Continue => out.push_str(&format!("{}continue;", indent)),
+        }
+        out.push('\n');
+    }
+}
+
+/// Makes the statements read like source: conditions without an empty branch, `while` loops,
+/// and no redundant trailing `continue`.
+fn simplify(stmts: Vec<Stmt>) -> Vec<Stmt> {
+    stmts
+        .into_iter()
+        .map(|stmt| match stmt {
+            Stmt::If(cond, then_stmts, else_stmts) => {
+                let (then_stmts, else_stmts) = (simplify(then_stmts), simplify(else_stmts));
+                if then_stmts.is_empty() && !else_stmts.is_empty() {
+                    Stmt::If(cond.negate(), else_stmts, then_stmts)
+                } else {
+                    Stmt::If(cond, then_stmts, else_stmts)
+                }
+            },
+            Stmt::Loop(body) => {
+                let mut body = simplify(body);
+                if matches!(body.last(), Some(Stmt::Continue)) {
+                    body.pop();
+                }
+                match body.first() {
+                    Some(Stmt::If(cond, then_stmts, else_stmts))
+                        if matches!(then_stmts.as_slice(), [Stmt::Break])
+                            && else_stmts.is_empty() =>
+                    {
+                        let cond = cond.clone().negate();
+                        body.remove(0);
+                        Stmt::While(cond, body)
+                    },
+                    _ => Stmt::Loop(body),
+                }
+            },
+            stmt => stmt,
+        })
+        .collect()
+}
+
+//**************************************************************************************************
+// Function bodies
+//**************************************************************************************************
+
+type BlockId = CodeOffset;
+
+/// How a basic block ends.
+enum Terminator {
+    /// Continues at the given block.
+    Goto(BlockId),
+    /// Continues at the first block if the condition holds, at the second otherwise.
+    Cond(Exp, BlockId, BlockId),
+    /// Returns or aborts.
+    Exit,
+}
+
+struct LoopContext {
+    head: BlockId,
+    exit: Option<BlockId>,
+}
+
+struct FunctionDecompiler<'a, 'b> {
+    decompiler: &'b Decompiler<'a>,
+    code: &'b [Bytecode],
+    cfg: VMControlFlowGraph,
+    predecessors: BTreeMap<BlockId, Vec<BlockId>>,
+    num_params: usize,
+    num_returns: usize,
+    /// The height of the operand stack when entering each block.
+    entry_heights: BTreeMap<BlockId, usize>,
+    next_temp: usize,
+    /// Guards against exponential duplication of blocks in unstructured control flow.
+    remaining_blocks: usize,
+}
+
+impl<'a, 'b> FunctionDecompiler<'a, 'b> {
+    fn new(
+        decompiler: &'b Decompiler<'a>,
+        code: &'b [Bytecode],
+        num_params: usize,
+        num_returns: usize,
+    ) -> Self {
+        let cfg = VMControlFlowGraph::new(code);
+        let mut predecessors: BTreeMap<BlockId, Vec<BlockId>> = BTreeMap::new();
+        for block in cfg.blocks() {
+            for successor in cfg.successors(block) {
+                predecessors.entry(*successor).or_default().push(block);
+            }
+        }
+        let remaining_blocks = 8 * cfg.num_blocks() as usize + 64;
+        Self {
+            decompiler,
+            code,
+            cfg,
+            predecessors,
+            num_params,
+            num_returns,
+            entry_heights: BTreeMap::new(),
+            next_temp: 0,
+            remaining_blocks,
+        }
+    }
+
+    fn decompile(mut self) -> Result<Vec<Stmt>> {
+        // Values left on the stack at the end of a block are carried to its successors in stack
+        // variables, so the stack heights at the start of the blocks are needed first.
+        let entry = self.cfg.entry_block_id();
+        self.entry_heights.insert(entry, 0);
+        let mut worklist = vec![entry];
+        let mut max_height = 0;
+        while let Some(block) = worklist.pop() {
+            let (_, terminator, height) = self.translate_block(block)?;
+            max_height = max_height.max(height);
+            let successors = match terminator {
+                Terminator::Goto(next) => vec![next],
+                Terminator::Cond(_, if_true, if_false) => vec![if_true, if_false],
+                Terminator::Exit => vec![],
+            };
+            for successor in successors {
+                match self.entry_heights.get(&successor) {
+                    Some(entry_height) if *entry_height != height => bail!(
+                        "inconsistent stack height at offset {}: {} and {}",
+                        successor,
+                        entry_height,
+                        height
+                    ),
+                    Some(_) => (),
+                    None => {
+                        self.entry_heights.insert(successor, height);
+                        worklist.push(successor);
+                    },
+                }
+            }
+        }
+        self.next_temp = 0;
+
+        let mut stmts: Vec<_> = (0..max_height)
+            .map(|idx| Stmt::Line(format!("let {};", stack_var(idx))))
+            .collect();
+        stmts.extend(simplify(self.region(entry, None, &mut vec![])?));
+        // The value of the function is the value of the body.
+        match stmts.last() {
+            Some(Stmt::Return(exps)) if exps.is_empty() => {
+                stmts.pop();
+            },
+            Some(Stmt::Return(exps)) => {
+                let value = if exps.len() == 1 {
+                    exps[0].render(0)
+                } else {
+                    format!("({})", render_list(exps))
+                };
+                stmts.pop();
+                stmts.push(Stmt::Value(value));
+            },
+            _ => (),
+        }
+        Ok(stmts)
+    }
+
+    /// Structures the control flow starting at `start` until `stop`, the block where the
+    /// enclosing construct continues.
+    fn region(
+        &mut self,
+        start: BlockId,
+        stop: Option<BlockId>,
+        loops: &mut Vec<LoopContext>,
+    ) -> Result<Vec<Stmt>> {
+        let mut stmts = vec![];
+        let mut current = start;
+        loop {
+            if Some(current) == stop {
+                return Ok(stmts);
+            }
+            if let Some(innermost) = loops.last() {
+                if innermost.head == current {
+                    stmts.push(Stmt::Continue);
+                    return Ok(stmts);
+                }
+                if innermost.exit == Some(current) {
+                    stmts.push(Stmt::Break);
+                    return Ok(stmts);
+                }
+            }
+            if loops
+                .iter()
+                .any(|ctx| ctx.head == current || ctx.exit == Some(current))
+            {
+                bail!("jump out of nested loops at offset {}", current);
+            }
+
+            let next = if self.cfg.is_loop_head(current) {
+                let exit = self.loop_exit(current, loops);
+                loops.push(LoopContext {
+                    head: current,
+                    exit,
+                });
+                let mut body = vec![];
+                let next = self.block(current, loops, &mut body)?;
+                if let Some(next) = next {
+                    body.extend(self.region(next, None, loops)?);
+                }
+                loops.pop();
+                stmts.push(Stmt::Loop(body));
+                exit
+            } else {
+                self.block(current, loops, &mut stmts)?
+            };
+            match next {
+                Some(next) => current = next,
+                None => return Ok(stmts),
+            }
+        }
+    }
+
+    /// Emits a block and the conditional it ends with, if any, and returns the block where the
+    /// control flow continues.
+    fn block(
+        &mut self,
+        block: BlockId,
+        loops: &mut Vec<LoopContext>,
+        stmts: &mut Vec<Stmt>,
+    ) -> Result<Option<BlockId>> {
+        if self.remaining_blocks == 0 {
+            bail!("control flow is too complex");
+        }
+        self.remaining_blocks -= 1;
+
+        let (block_stmts, terminator, _) = self.translate_block(block)?;
+        stmts.extend(block_stmts);
+        let (cond, if_true, if_false) = match terminator {
+            Terminator::Exit => return Ok(None),
+            Terminator::Goto(next) => return Ok(Some(next)),
+            Terminator::Cond(cond, if_true, if_false) => (cond, if_true, if_false),
+        };
+
+        let loop_blocks: BTreeSet<BlockId> = loops
+            .iter()
+            .flat_map(|ctx| std::iter::once(ctx.head).chain(ctx.exit))
+            .collect();
+        let reach_true = self.reachable(if_true, &loop_blocks);
+        let reach_false = self.reachable(if_false, &loop_blocks);
+        if reach_true.contains(&if_false) && !loop_blocks.contains(&if_false) {
+            // `if (cond) { .. }`
+            let then_stmts = self.region(if_true, Some(if_false), loops)?;
+            stmts.push(Stmt::If(cond, then_stmts, vec![]));
+            return Ok(Some(if_false));
+        }
+        if reach_false.contains(&if_true) && !loop_blocks.contains(&if_true) {
+            let then_stmts = self.region(if_false, Some(if_true), loops)?;
+            stmts.push(Stmt::If(cond.negate(), then_stmts, vec![]));
+            return Ok(Some(if_true));
+        }
+        // Where both branches join: the code is laid out in order, so it is the first block
+        // reachable from both.
+        let join = reach_true
+            .intersection(&reach_false)
+            .find(|block| !loop_blocks.contains(block))
+            .copied();
+        match join {
+            Some(join) => {
+                let then_stmts = self.region(if_true, Some(join), loops)?;
+                let else_stmts = self.region(if_false, Some(join), loops)?;
+                stmts.push(Stmt::If(cond, then_stmts, else_stmts));
+                Ok(Some(join))
+            },
+            // At least one branch does not continue after the conditional, e.g. it returns,
+            // aborts, or leaves the loop: nest the smaller one, and continue with the other.
+            None if reach_true.len() <= reach_false.len() => {
+                let then_stmts = self.region(if_true, None, loops)?;
+                stmts.push(Stmt::If(cond, then_stmts, vec![]));
+                Ok(Some(if_false))
+            },
+            None => {
+                let then_stmts = self.region(if_false, None, loops)?;
+                stmts.push(Stmt::If(cond.negate(), then_stmts, vec![]));
+                Ok(Some(if_true))
+            },
+        }
+    }
+
+    /// The blocks reachable from `start`, without going past the blocks in `barriers`.
+    fn reachable(&self, start: BlockId, barriers: &BTreeSet<BlockId>) -> BTreeSet<BlockId> {
+        let mut reached = BTreeSet::new();
+        let mut worklist = vec![start];
+        while let Some(block) = worklist.pop() {
+            if !reached.insert(block) || barriers.contains(&block) {
+                continue;
+            }
+            worklist.extend(self.cfg.successors(block).iter().copied());
+        }
+        reached
+    }
+
+    /// The block a loop continues at when it is left. Blocks jumped to from within the loop
+    /// that do not continue after it, e.g. an abort of an assertion, are nested in the loop, so
+    /// exits reaching an enclosing loop are preferred, then exits which may return. Of these,
+    /// it is the first block they all reach, e.g. where a `break` and the loop condition meet,
+    /// else the last one, since the code is laid out in order.
+    fn loop_exit(&self, head: BlockId, loops: &[LoopContext]) -> Option<BlockId> {
+        let mut body = BTreeSet::from([head]);
+        let mut worklist: Vec<BlockId> = self
+            .predecessors
+            .get(&head)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|pred| self.cfg.is_back_edge(*pred, head))
+            .collect();
+        while let Some(block) = worklist.pop() {
+            if body.insert(block) {
+                worklist.extend(self.predecessors.get(&block).into_iter().flatten().copied());
+            }
+        }
+        let enclosing: BTreeSet<BlockId> = loops
+            .iter()
+            .flat_map(|ctx| std::iter::once(ctx.head).chain(ctx.exit))
+            .collect();
+        let mut barriers = enclosing.clone();
+        barriers.insert(head);
+        let exits: BTreeMap<BlockId, (bool, bool, BTreeSet<BlockId>)> = body
+            .iter()
+            .flat_map(|block| self.cfg.successors(*block).iter().copied())
+            .filter(|successor| !body.contains(successor))
+            .map(|exit| {
+                let reached = self.reachable(exit, &barriers);
+                let continues = reached.iter().any(|block| enclosing.contains(block));
+                let returns = reached
+                    .iter()
+                    .any(|block| self.code[self.cfg.block_end(*block) as usize] == Bytecode::Ret);
+                (exit, (continues, returns, reached))
+            })
+            .collect();
+        let best = exits
+            .values()
+            .map(|(continues, returns, _)| (*continues, *returns))
+            .max()?;
+        let mut candidates = exits
+            .iter()
+            .filter(|(_, (continues, returns, _))| (*continues, *returns) == best);
+        let (last, (_, _, reached)) = candidates.next_back()?;
+        let mut common = reached.clone();
+        let mut single = true;
+        for (_, (_, _, reached)) in candidates {
+            common.retain(|block| reached.contains(block));
+            single = false;
+        }
+        if single {
+            return Some(*last);
+        }
+        common
+            .into_iter()
+            .find(|block| !barriers.contains(block))
+            .or(Some(*last))
+    }
+
+    fn fresh_temp(&mut self) -> String {
+        self.next_temp += 1;
+        format!("t{}", self.next_temp)
+    }
+
+    /// Moves the values on the stack which satisfy `pred` into temporaries, so that they keep
+    /// the values they had at this point.
+    fn flush(&mut self, stack: &mut [Exp], stmts: &mut Vec<Stmt>, pred: impl Fn(&Exp) -> bool) {
+        for exp in stack.iter_mut() {
+            if !matches!(exp, Exp::Var(_) | Exp::Value(_)) && pred(exp) {
+                let temp = self.fresh_temp();
+                stmts.push(Stmt::Line(format!("let {} = {};", temp, exp.render(0))));
+                *exp = Exp::Var(temp);
+            }
+        }
+    }
+
+    /// Emits a statement with side effects, after the effects of the values on the stack.
+    fn effect(&mut self, stack: &mut [Exp], stmts: &mut Vec<Stmt>, stmt: Stmt) {
+        self.flush(stack, stmts, |exp| !exp.is_pure());
+        stmts.push(stmt);
+    }
+
+    /// Translates the instructions of a block into statements, and returns how it ends and the
+    /// height of the stack at its end.
+    fn translate_block(&mut self, block: BlockId) -> Result<(Vec<Stmt>, Terminator, usize)> {
+        let d = self.decompiler;
+        let height = *self
+            .entry_heights
+            .get(&block)
+            .ok_or_else(|| format_err!("unreachable block at offset {}", block))?;
+        let mut stack: Vec<Exp> = (0..height).map(|idx| Exp::Var(stack_var(idx))).collect();
+        let mut stmts = vec![];
+        let end = self.cfg.block_end(block);
+        let code = self.code;
+
+        macro_rules! pop {
+            () => {
+                stack
+                    .pop()
+                    .ok_or_else(|| format_err!("stack underflow in block at offset {}", block))?
+            };
+        }
+        macro_rules! pop_n {
+            ($n:expr) => {{
+                let n = $n;
+                if stack.len() < n {
+                    bail!("stack underflow in block at offset {}", block);
+                }
+                stack.split_off(stack.len() - n)
+            }};
+        }
+
+        let mut terminator = Terminator::Goto(end + 1);
+        for offset in self.cfg.block_start(block)..=end {
+            let instr = &code[offset as usize];
+            match instr {
+                Bytecode::Nop => (),
+                Bytecode::Pop => {
+                    let exp = pop!();
+                    if !exp.is_pure() {
+                        let line = format!("let _ = {};", exp.render(0));
+                        self.effect(&mut stack, &mut stmts, Stmt::Line(line));
+                    }
+                },
+                Bytecode::Ret => {
+                    let exps = pop_n!(self.num_returns);
+                    self.effect(&mut stack, &mut stmts, Stmt::Return(exps));
+                    terminator = Terminator::Exit;
+                },
+                Bytecode::Abort => {
+                    let line = format!("abort {};", pop!().render(0));
+                    self.effect(&mut stack, &mut stmts, Stmt::Line(line));
+                    terminator = Terminator::Exit;
+                },
+                Bytecode::Branch(target) => terminator = Terminator::Goto(*target),
+                Bytecode::BrTrue(target) => {
+                    terminator = Terminator::Cond(pop!(), *target, offset + 1)
+                },
+                Bytecode::BrFalse(target) => {
+                    terminator = Terminator::Cond(pop!(), offset + 1, *target)
+                },
+                Bytecode::LdU8(value) => stack.push(Exp::Value(format!("{}u8", value))),
+                Bytecode::LdU16(value) => stack.push(Exp::Value(format!("{}u16", value))),
+                Bytecode::LdU32(value) => stack.push(Exp::Value(format!("{}u32", value))),
+                Bytecode::LdU64(value) => stack.push(Exp::Value(value.to_string())),
+                Bytecode::LdU128(value) => stack.push(Exp::Value(format!("{}u128", value))),
+                Bytecode::LdU256(value) => stack.push(Exp::Value(format!("{}u256", value))),
+                Bytecode::LdTrue => stack.push(Exp::Value("true".to_string())),
+                Bytecode::LdFalse => stack.push(Exp::Value("false".to_string())),
+                Bytecode::LdConst(idx) => {
+                    let constant = d.module.constant_at(*idx);
+                    let value = constant
+                        .deserialize_constant()
+                        .ok_or_else(|| format_err!("unable to deserialize constant {}", idx))?;
+                    stack.push(Exp::Value(d.format_value(&value, &constant.type_)));
+                },
+                Bytecode::CastU8 => {
+                    let exp = pop!();
+                    stack.push(Exp::Cast(Box::new(exp), "u8"))
+                },
+                Bytecode::CastU16 => {
+                    let exp = pop!();
+                    stack.push(Exp::Cast(Box::new(exp), "u16"))
+                },
+                Bytecode::CastU32 => {
+                    let exp = pop!();
+                    stack.push(Exp::Cast(Box::new(exp), "u32"))
+                },
+                Bytecode::CastU64 => {
+                    let exp = pop!();
+                    stack.push(Exp::Cast(Box::new(exp), "u64"))
+                },
+                Bytecode::CastU128 => {
+                    let exp = pop!();
+                    stack.push(Exp::Cast(Box::new(exp), "u128"))
+                },
+                Bytecode::CastU256 => {
+                    let exp = pop!();
+                    stack.push(Exp::Cast(Box::new(exp), "u256"))
+                },
+                Bytecode::CopyLoc(idx) | Bytecode::MoveLoc(idx) => {
+                    stack.push(Exp::Var(self.local(*idx)))
+                },
+                Bytecode::StLoc(idx) => {
+                    let exp = pop!();
+                    let local = self.local(*idx);
+                    self.flush(&mut stack, &mut stmts, |exp| {
+                        !exp.is_pure() || exp.reads(&local)
+                    });
+                    stmts.push(Stmt::Line(format!("{} = {};", local, exp.render(0))));
+                },
+                Bytecode::MutBorrowLoc(idx) => {
+                    stack.push(Exp::Unary("&mut ", Box::new(Exp::Var(self.local(*idx)))))
+                },
+                Bytecode::ImmBorrowLoc(idx) => {
+                    stack.push(Exp::Unary("&", Box::new(Exp::Var(self.local(*idx)))))
+                },
+                Bytecode::MutBorrowField(idx) => {
+                    let field = Exp::Field(Box::new(pop!().field_base()), d.field_name(*idx)?);
+                    stack.push(Exp::Unary("&mut ", Box::new(field)));
+                },
+                Bytecode::MutBorrowFieldGeneric(idx) => {
+                    let field = Exp::Field(Box::new(pop!().field_base()), d.field_inst_name(*idx)?);
+                    stack.push(Exp::Unary("&mut ", Box::new(field)));
+                },
+                Bytecode::ImmBorrowField(idx) => {
+                    let field = Exp::Field(Box::new(pop!().field_base()), d.field_name(*idx)?);
+                    stack.push(Exp::Unary("&", Box::new(field)));
+                },
+                Bytecode::ImmBorrowFieldGeneric(idx) => {
+                    let field = Exp::Field(Box::new(pop!().field_base()), d.field_inst_name(*idx)?);
+                    stack.push(Exp::Unary("&", Box::new(field)));
+                },
+                Bytecode::ReadRef => {
+                    let exp = pop!();
+                    stack.push(exp.deref())
+                },
+                Bytecode::WriteRef => {
+                    let target = pop!().deref();
+                    let value = pop!();
+                    let line = format!("{} = {};", target.render(0), value.render(0));
+                    self.effect(&mut stack, &mut stmts, Stmt::Line(line));
+                },
+                // Mutable references are used as immutable ones implicitly.
+                Bytecode::FreezeRef => (),
+                Bytecode::Call(idx) => {
+                    let name = d.function_name(*idx);
+                    self.call(name, *idx, &mut stack, &mut stmts)?;
+                },
+                Bytecode::CallGeneric(idx) => {
+                    let name = d.function_inst_name(*idx);
+                    let handle = d.module.function_instantiation_at(*idx).handle;
+                    self.call(name, handle, &mut stack, &mut stmts)?;
+                },
+                Bytecode::Pack(idx) => {
+                    let name = d.struct_def_name(*idx);
+                    self.pack(name, *idx, &mut stack, block)?;
+                },
+                Bytecode::PackGeneric(idx) => {
+                    let name = d.struct_inst_name(*idx);
+                    let def = d.module.struct_instantiation_at(*idx).def;
+                    self.pack(name, def, &mut stack, block)?;
+                },
+                Bytecode::Unpack(idx) => {
+                    let name = d.struct_def_name(*idx);
+                    self.unpack(name, *idx, &mut stack, &mut stmts, block)?;
+                },
+                Bytecode::UnpackGeneric(idx) => {
+                    let name = d.struct_def_name(d.module.struct_instantiation_at(*idx).def);
+                    let def = d.module.struct_instantiation_at(*idx).def;
+                    self.unpack(name, def, &mut stack, &mut stmts, block)?;
+                },
+                Bytecode::MutBorrowGlobal(idx) => {
+                    let name = format!("borrow_global_mut<{}>", d.struct_def_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::MutBorrowGlobalGeneric(idx) => {
+                    let name = format!("borrow_global_mut<{}>", d.struct_inst_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::ImmBorrowGlobal(idx) => {
+                    let name = format!("borrow_global<{}>", d.struct_def_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::ImmBorrowGlobalGeneric(idx) => {
+                    let name = format!("borrow_global<{}>", d.struct_inst_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::Exists(idx) => {
+                    let name = format!("exists<{}>", d.struct_def_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::ExistsGeneric(idx) => {
+                    let name = format!("exists<{}>", d.struct_inst_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::MoveFrom(idx) => {
+                    let name = format!("move_from<{}>", d.struct_def_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::MoveFromGeneric(idx) => {
+                    let name = format!("move_from<{}>", d.struct_inst_name(*idx));
+                    {
+                        let exp = pop!();
+                        stack.push(Exp::Call(name, vec![exp]))
+                    };
+                },
+                Bytecode::MoveTo(idx) => {
+                    let name = format!("move_to<{}>", d.struct_def_name(*idx));
+                    self.call_stmt(name, 2, &mut stack, &mut stmts, block)?;
+                },
+                Bytecode::MoveToGeneric(idx) => {
+                    let name = format!("move_to<{}>", d.struct_inst_name(*idx));
+                    self.call_stmt(name, 2, &mut stack, &mut stmts, block)?;
+                },
+                Bytecode::Add
+                | Bytecode::Sub
+                | Bytecode::Mul
+                | Bytecode::Mod
+                | Bytecode::Div
+                | Bytecode::BitOr
+                | Bytecode::BitAnd
+                | Bytecode::Xor
+                | Bytecode::Or
+                | Bytecode::And
+                | Bytecode::Eq
+                | Bytecode::Neq
+                | Bytecode::Lt
+                | Bytecode::Gt
+                | Bytecode::Le
+                | Bytecode::Ge
+                | Bytecode::Shl
+                | Bytecode::Shr => {
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    stack.push(Exp::Binary(
+                        binary_operator(instr),
+                        Box::new(lhs),
+                        Box::new(rhs),
+                    ));
+                },
+                Bytecode::Not => {
+                    let exp = pop!();
+                    stack.push(exp.negate())
+                },
+                Bytecode::VecPack(idx, num) => {
+                    let ty = d.format_type(&d.module.signature_at(*idx).0[0]);
+                    let exps = pop_n!(*num as usize);
+                    stack.push(Exp::Vector(ty, exps));
+                },
+                Bytecode::VecLen(_) => {
+                    let exp = pop!();
+                    stack.push(Exp::Call(d.vector_function("length"), vec![exp]))
+                },
+                Bytecode::VecImmBorrow(_) => {
+                    let args = pop_n!(2);
+                    stack.push(Exp::Call(d.vector_function("borrow"), args));
+                },
+                Bytecode::VecMutBorrow(_) => {
+                    let args = pop_n!(2);
+                    stack.push(Exp::Call(d.vector_function("borrow_mut"), args));
+                },
+                Bytecode::VecPushBack(_) => self.call_stmt(
+                    d.vector_function("push_back"),
+                    2,
+                    &mut stack,
+                    &mut stmts,
+                    block,
+                )?,
+                Bytecode::VecPopBack(_) => {
+                    let exp = pop!();
+                    stack.push(Exp::Call(d.vector_function("pop_back"), vec![exp]))
+                },
+                Bytecode::VecSwap(_) => {
+                    self.call_stmt(d.vector_function("swap"), 3, &mut stack, &mut stmts, block)?
+                },
+                Bytecode::VecUnpack(_, num) => {
+                    // There is no source for unpacking a vector into its elements.
+                    let exp = pop!();
+                    let temps: Vec<_> = (0..*num).map(|_| self.fresh_temp()).collect();
+                    let line = format!(
+                        "let ({}) = /* unpack */ {};",
+                        temps.join(", "),
+                        exp.render(0)
+                    );
+                    self.effect(&mut stack, &mut stmts, Stmt::Line(line));
+                    stack.extend(temps.into_iter().map(Exp::Var));
+                },
+            }
+        }
+
+        // Carry the values left on the stack to the successors in stack variables.
+        if !matches!(terminator, Terminator::Exit) {
+            let num_vars = stack.len();
+            self.flush(&mut stack, &mut stmts, |exp| {
+                (0..num_vars).any(|idx| exp.reads(&stack_var(idx)))
+            });
+            for (idx, exp) in stack.iter().enumerate() {
+                let var = stack_var(idx);
+                if *exp != Exp::Var(var.clone()) {
+                    stmts.push(Stmt::Line(format!("{} = {};", var, exp.render(0))));
+                }
+            }
+        }
+        Ok((stmts, terminator, stack.len()))
+    }
+
+    fn local(&self, idx: u8) -> String {
+        local_name(idx as usize, self.num_params)
+    }
+
+    fn call(
+        &mut self,
+        name: String,
+        handle: FunctionHandleIndex,
+        stack: &mut Vec<Exp>,
+        stmts: &mut Vec<Stmt>,
+    ) -> Result<()> {
+        let module = self.decompiler.module;
+        let handle = module.function_handle_at(handle);
+        let num_args = module.signature_at(handle.parameters).len();
+        let num_returns = module.signature_at(handle.return_).len();
+        if stack.len() < num_args {
+            bail!("stack underflow calling {}", name);
+        }
+        let args = stack.split_off(stack.len() - num_args);
+        let call = Exp::Call(name, args);
+        match num_returns {
+            0 => {
+                let line = format!("{};", call.render(0));
+                self.effect(stack, stmts, Stmt::Line(line));
+            },
+            1 => stack.push(call),
+            _ => {
+                let temps: Vec<_> = (0..num_returns).map(|_| self.fresh_temp()).collect();
+                let line = format!("let ({}) = {};", temps.join(", "), call.render(0));
+                self.effect(stack, stmts, Stmt::Line(line));
+                stack.extend(temps.into_iter().map(Exp::Var));
+            },
+        }
+        Ok(())
+    }
+
+    /// Emits a call to a function, or a builtin, which returns nothing.
+    fn call_stmt(
+        &mut self,
+        name: String,
+        num_args: usize,
+        stack: &mut Vec<Exp>,
+        stmts: &mut Vec<Stmt>,
+        block: BlockId,
+    ) -> Result<()> {
+        if stack.len() < num_args {
+            bail!("stack underflow in block at offset {}", block);
+        }
+        let args = stack.split_off(stack.len() - num_args);
+        let line = format!("{};", Exp::Call(name, args).render(0));
+        self.effect(stack, stmts, Stmt::Line(line));
+        Ok(())
+    }
+
+    fn pack(
+        &mut self,
+        name: String,
+        def: StructDefinitionIndex,
+        stack: &mut Vec<Exp>,
+        block: BlockId,
+    ) -> Result<()> {
+        let fields = self.decompiler.field_names(def);
+        if stack.len() < fields.len() {
+            bail!("stack underflow in block at offset {}", block);
+        }
+        let exps = stack.split_off(stack.len() - fields.len());
+        stack.push(Exp::Pack(name, fields.into_iter().zip(exps).collect()));
+        Ok(())
+    }
+
+    fn unpack(
+        &mut self,
+        name: String,
+        def: StructDefinitionIndex,
+        stack: &mut Vec<Exp>,
+        stmts: &mut Vec<Stmt>,
+        block: BlockId,
+    ) -> Result<()> {
+        let exp = stack
+            .pop()
+            .ok_or_else(|| format_err!("stack underflow in block at offset {}", block))?;
+        let fields = self.decompiler.field_names(def);
+        let temps: Vec<_> = fields.iter().map(|_| self.fresh_temp()).collect();
+        let line = format!(
+            "let {} {{ {} }} = {};",
+            name,
+            fields
+                .iter()
+                .zip(&temps)
+                .map(|(field, temp)| format!("{}: {}", field, temp))
+                .collect::<Vec<_>>()
+                .join(", "),
+            exp.render(0)
+        );
+        self.effect(stack, stmts, Stmt::Line(line));
+        stack.extend(temps.into_iter().map(Exp::Var));
+        Ok(())
+    }
+}
+
+fn stack_var(idx: usize) -> String {
+    format!("s{}", idx)
+}
+
+fn binary_operator(instr: &Bytecode) -> &'static str {
+    match instr {
+        Bytecode::Add => "+",
+        Bytecode::Sub => "-",
+        Bytecode::Mul => "*",
+        Bytecode::Mod => "%",
+        Bytecode::Div => "/",
+        Bytecode::BitOr => "|",
+        Bytecode::BitAnd => "&",
+        Bytecode::Xor => "^",
+        Bytecode::Or => "||",
+        Bytecode::And => "&&",
+        Bytecode::Eq => "==",
+        Bytecode::Neq => "!=",
+        Bytecode::Lt => "<",
+        Bytecode::Gt => ">",
+        Bytecode::Le => "<=",
+        Bytecode::Ge => ">=",
+        Bytecode::Shl => "<<",
+        _ => ">>",
+    }
+}
+
+impl<'a> Decompiler<'a> {
+    fn format_value(&self, value: &MoveValue, ty: &SignatureToken) -> String {
+        match (value, ty) {
+            (MoveValue::U8(value), _) => format!("{}u8", value),
+            (MoveValue::U16(value), _) => format!("{}u16", value),
+            (MoveValue::U32(value), _) => format!("{}u32", value),
+            (MoveValue::U64(value), _) => value.to_string(),
+            (MoveValue::U128(value), _) => format!("{}u128", value),
+            (MoveValue::U256(value), _) => format!("{}u256", value),
+            (MoveValue::Bool(value), _) => value.to_string(),
+            (MoveValue::Address(address), _) | (MoveValue::Signer(address), _) => {
+                format!("@{}", address.to_hex_literal())
+            },
+            (MoveValue::Vector(values), SignatureToken::Vector(elem_ty)) => {
+                if **elem_ty == SignatureToken::U8 {
+                    let bytes: Vec<u8> = values
+                        .iter()
+                        .filter_map(|value| match value {
+                            MoveValue::U8(byte) => Some(*byte),
+                            _ => None,
+                        })
+                        .collect();
+                    return format_bytes(&bytes);
+                }
+                format!(
+                    "vector<{}>[{}]",
+                    self.format_type(elem_ty),
+                    values
+                        .iter()
+                        .map(|value| self.format_value(value, elem_ty))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            },
+            (value, _) => format!("/* {:?} */", value),
+        }
+    }
+}
+
+/// Formats bytes as a byte string if they are printable, as a hex string otherwise.
+fn format_bytes(bytes: &[u8]) -> String {
+    if !bytes.is_empty()
+        && bytes
+            .iter()
+            .all(|byte| byte.is_ascii_graphic() || *byte == b' ')
+    {
+        let mut out = String::from("b\"");
+        for byte in bytes {
+            if *byte == b'"' || *byte == b'\\' {
+                out.push('\\');
+            }
+            out.push(*byte as char);
+        }
+        out.push('"');
+        out
+    } else {
+        format!(
+            "x\"{}\"",
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        )
+    }
+}
@@ -0,0 +1,21 @@
+module 0x42::early_exit {
+    const E_DIVIDE_BY_ZERO: u64 = 1;
+
+    public fun checked_div(a: u64, b: u64): u64 {
+        if (b == 0) abort E_DIVIDE_BY_ZERO;
+        a / b
+    }
+
+    public fun is_square(x: u64): bool {
+        let i = 0;
+        while (i <= x) {
+            if (i * i == x) return true;
+            i = i + 1;
+        };
+        false
+    }
+
+    public fun assert_positive(x: u64) {
+        assert!(x > 0, 2);
+    }
+}
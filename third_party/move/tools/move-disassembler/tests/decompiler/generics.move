@@ -0,0 +1,27 @@
+module 0x42::generics {
+    struct Box<T: copy + drop> has copy, drop {
+        value: T,
+    }
+
+    struct Pair<A, B> has copy, drop, store {
+        first: A,
+        second: B,
+    }
+
+    public fun new<T: copy + drop>(value: T): Box<T> {
+        Box { value }
+    }
+
+    public fun get<T: copy + drop>(box: &Box<T>): T {
+        box.value
+    }
+
+    public fun swap<A, B>(pair: Pair<A, B>): Pair<B, A> {
+        let Pair { first, second } = pair;
+        Pair { first: second, second: first }
+    }
+
+    public fun boxed_pair(x: u64, y: bool): Box<Pair<u64, bool>> {
+        new(Pair { first: x, second: y })
+    }
+}
@@ -0,0 +1,23 @@
+module 0x42::if_else {
+    public fun max(a: u64, b: u64): u64 {
+        if (a > b) a else b
+    }
+
+    public fun compare(x: u64, y: u64): u8 {
+        if (x < y) {
+            0
+        } else if (x == y) {
+            1
+        } else {
+            2
+        }
+    }
+
+    public fun clamp(x: u64, limit: u64): u64 {
+        let result = x;
+        if (x > limit) {
+            result = limit;
+        };
+        result
+    }
+}
@@ -0,0 +1,22 @@
+module 0x42::loops {
+    public fun sum_odd_until(n: u64, limit: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while (i < n) {
+            i = i + 1;
+            if (i % 2 == 0) continue;
+            if (sum + i > limit) break;
+            sum = sum + i;
+        };
+        sum
+    }
+
+    public fun first_power_above(base: u64, limit: u64): u64 {
+        let x = 1;
+        loop {
+            x = x * base;
+            if (x > limit) break
+        };
+        x
+    }
+}
@@ -0,0 +1,35 @@
+module 0x42::nested_loops {
+    public fun count_pairs(n: u64): u64 {
+        let count = 0;
+        let i = 0;
+        while (i < n) {
+            let j = 0;
+            while (j < i) {
+                if ((i + j) % 3 == 0) {
+                    count = count + 1;
+                };
+                j = j + 1;
+            };
+            i = i + 1;
+        };
+        count
+    }
+
+    public fun find_pair(n: u64, target: u64): u64 {
+        let i = 0;
+        let found = 0;
+        while (i < n) {
+            let j = 0;
+            while (j < n) {
+                if (i * j == target) break;
+                j = j + 1;
+            };
+            if (j < n) {
+                found = i;
+                break
+            };
+            i = i + 1;
+        };
+        found
+    }
+}
@@ -0,0 +1,13 @@
+module 0x42::stack_values {
+    fun combine(a: u64, b: u64, c: u64): u64 {
+        a * b + c
+    }
+
+    public fun add_choice(x: u64, c: bool): u64 {
+        x + if (c) 1 else 2
+    }
+
+    public fun call_with_choices(x: u64, y: u64, c: bool): u64 {
+        combine(x, if (c) y else x, if (y > x) 3 else 4)
+    }
+}
@@ -0,0 +1,37 @@
+module 0x42::structs {
+    struct Point has copy, drop {
+        x: u64,
+        y: u64,
+    }
+
+    struct Balance has key {
+        value: u64,
+    }
+
+    public fun origin(): Point {
+        Point { x: 0, y: 0 }
+    }
+
+    public fun translate(p: &mut Point, dx: u64, dy: u64) {
+        p.x = p.x + dx;
+        p.y = p.y + dy;
+    }
+
+    public fun publish(account: &signer, value: u64) {
+        move_to(account, Balance { value })
+    }
+
+    public fun deposit(addr: address, value: u64) acquires Balance {
+        let balance = borrow_global_mut<Balance>(addr);
+        balance.value = balance.value + value;
+    }
+
+    public fun balance_of(addr: address): u64 acquires Balance {
+        if (exists<Balance>(addr)) borrow_global<Balance>(addr).value else 0
+    }
+
+    public fun destroy(addr: address): u64 acquires Balance {
+        let Balance { value } = move_from<Balance>(addr);
+        value
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::bail;
+use move_binary_format::CompiledModule;
+use move_command_line_common::{
+    address::NumericalAddress,
+    testing::{add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT},
+};
+use move_compiler::{
+    compiled_unit::{CompiledUnit, NamedCompiledModule},
+    diagnostics::report_diagnostics_to_buffer,
+    shared::Flags,
+    Compiler,
+};
+use move_disassembler::decompiler::Decompiler;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+/// Fixtures whose decompiled source is not expected to compile have this line.
+const NO_RECOMPILE: &str = "// decompiled: no-recompile";
+
+fn compile(path: &Path) -> anyhow::Result<Vec<CompiledModule>> {
+    let (files, units) = Compiler::from_files(
+        vec![path.to_str().unwrap().to_string()],
+        vec![],
+        BTreeMap::<String, NumericalAddress>::new(),
+        Flags::empty(),
+        &BTreeSet::new(),
+    )
+    .build()?;
+    let (units, _warnings) = match units {
+        Ok(units) => units,
+        Err(diags) => bail!(
+            "{}",
+            String::from_utf8_lossy(&report_diagnostics_to_buffer(&files, diags))
+        ),
+    };
+    Ok(units
+        .into_iter()
+        .filter_map(|unit| match unit.into_compiled_unit() {
+            CompiledUnit::Module(NamedCompiledModule { module, .. }) => Some(module),
+            CompiledUnit::Script(_) => None,
+        })
+        .collect())
+}
+
+fn decompile(modules: &[CompiledModule]) -> anyhow::Result<String> {
+    let sources = modules
+        .iter()
+        .map(|module| Decompiler::new(module).decompile())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(sources.join("\n"))
+}
+
+// Decompiles the modules of each source file under tests/decompiler, compares the result against
+// the expected output, and compiles it again.
+fn run_test(path: &Path) -> datatest_stable::Result<()> {
+    let decompiled = decompile(&compile(path)?)?;
+
+    let exp_path = path.with_extension(EXP_EXT);
+    if read_env_update_baseline() {
+        fs::write(&exp_path, &decompiled)?;
+    }
+    if !exp_path.is_file() {
+        let msg = format!("No expected output found for {:?}", path);
+        return Err(add_update_baseline_fix(msg).into());
+    }
+    let expected = fs::read_to_string(&exp_path)?;
+    if expected != decompiled {
+        let msg = format!(
+            "Expected outputs differ for {:?}:\n{}",
+            exp_path,
+            format_diff(expected, decompiled)
+        );
+        return Err(add_update_baseline_fix(msg).into());
+    }
+
+    if fs::read_to_string(path)?.contains(NO_RECOMPILE) {
+        return Ok(());
+    }
+    let dir = tempfile::tempdir()?;
+    let decompiled_path = dir.path().join(path.file_name().unwrap());
+    fs::write(&decompiled_path, &decompiled)?;
+    let recompiled = compile(&decompiled_path)
+        .map_err(|err| format!("Decompiled {:?} does not compile:\n{}", path, err))?;
+    // The recompiled modules decompile to the same source.
+    let redecompiled = decompile(&recompiled)?;
+    if redecompiled != decompiled {
+        return Err(format!(
+            "Recompiled {:?} decompiles differently:\n{}",
+            path,
+            format_diff(decompiled, redecompiled)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+datatest_stable::harness!(run_test, "tests/decompiler", r".*\.move$");
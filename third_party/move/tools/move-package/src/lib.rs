@@ -14,7 +14,10 @@ use crate::{
         build_plan::BuildPlan, compiled_package::CompiledPackage, model_builder::ModelBuilder,
     },
     package_lock::PackageLock,
-    resolution::resolution_graph::{ResolutionGraph, ResolvedGraph},
+    resolution::{
+        lockfile::Lockfile,
+        resolution_graph::{ResolutionGraph, ResolvedGraph},
+    },
     source_package::manifest_parser,
 };
 use anyhow::{bail, Result};
@@ -270,16 +273,41 @@ impl BuildConfig {
         let path = SourcePackageLayout::try_find_root(path)?;
         let toml_manifest =
             self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))?;
+        let lockfile = Lockfile::read(&path)?;
         let mutx = PackageLock::lock();
         // This should be locked as it inspects the environment for `MOVE_HOME` which could
         // possibly be set by a different process in parallel.
         let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
-        let resolution_graph = ResolutionGraph::new(manifest, path, self, writer)?;
+        let resolution_graph =
+            ResolutionGraph::new_with_lockfile(manifest, path, self, lockfile.as_ref(), writer)?;
         let ret = resolution_graph.resolve();
         mutx.unlock();
         ret
     }
 
+    /// Resolves the dependencies of the package at `path` or the containing Move package afresh,
+    /// ignoring its lockfile, and writes the lockfile pinning them. Dev dependencies are pinned
+    /// too, so the lockfile also applies to tests.
+    pub fn update_lockfile<W: Write>(mut self, path: &Path, writer: &mut W) -> Result<Lockfile> {
+        self.dev_mode = true;
+        self.skip_fetch_latest_git_deps = false;
+        let path = SourcePackageLayout::try_find_root(path)?;
+        let toml_manifest =
+            self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))?;
+        let mutx = PackageLock::lock();
+        // This should be locked as it inspects the environment for `MOVE_HOME` which could
+        // possibly be set by a different process in parallel.
+        let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
+        let resolution_graph = ResolutionGraph::new(manifest, path.clone(), self, writer)?;
+        let ret = resolution_graph
+            .resolve()
+            .and_then(|resolved_graph| Lockfile::from_resolved_graph(&resolved_graph));
+        mutx.unlock();
+        let lockfile = ret?;
+        lockfile.write(&path)?;
+        Ok(lockfile)
+    }
+
     fn parse_toml_manifest(&self, path: PathBuf) -> Result<toml::Value> {
         let manifest_string = std::fs::read_to_string(path)?;
         manifest_parser::parse_move_manifest_string(manifest_string)
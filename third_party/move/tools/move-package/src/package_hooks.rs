@@ -27,7 +27,27 @@ pub trait PackageHooks {
         dep_name: Symbol,
         info: &CustomDepInfo,
     ) -> anyhow::Result<()>;
+
+    /// Returns the exact version of a custom dependency, as resolved by
+    /// `resolve_custom_dependency`, to pin it in the lockfile of a package. Returns `None` if
+    /// custom dependencies are not versioned.
+    fn custom_dependency_version(
+        &self,
+        _info: &CustomDepInfo,
+    ) -> anyhow::Result<Option<CustomDepVersion>> {
+        Ok(None)
+    }
 }
+
+/// The exact version of a custom dependency, e.g. of an on-chain package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomDepVersion {
+    /// The version number, e.g. the upgrade number of an on-chain package.
+    pub version: u64,
+    /// A hash of the bytecode of the dependency.
+    pub bytecode_hash: String,
+}
+
 static HOOKS: Lazy<Mutex<Option<Box<dyn PackageHooks + Send + Sync>>>> =
     Lazy::new(|| Mutex::new(None));
 
@@ -48,6 +68,17 @@ pub(crate) fn resolve_custom_dependency(
     }
 }
 
+/// Calls any registered hook to return the version of a custom dependency.
+pub(crate) fn custom_dependency_version(
+    info: &CustomDepInfo,
+) -> anyhow::Result<Option<CustomDepVersion>> {
+    if let Some(hooks) = &*HOOKS.lock().unwrap() {
+        hooks.custom_dependency_version(info)
+    } else {
+        Ok(None)
+    }
+}
+
 pub(crate) fn custom_dependency_key() -> Option<String> {
     if let Some(hooks) = &*HOOKS.lock().unwrap() {
         hooks.custom_dependency_key()
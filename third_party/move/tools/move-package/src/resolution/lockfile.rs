@@ -0,0 +1,215 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    package_hooks,
+    resolution::resolution_graph::{git_head, ResolvedGraph, ResolvingPackage},
+    source_package::parsed_manifest::{Dependency, PackageName},
+    BuildConfig,
+};
+use anyhow::{bail, format_err, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The name of the lockfile, next to the manifest of the root package.
+pub const LOCKFILE_NAME: &str = "Move.lock";
+
+const UPDATE_HINT: &str =
+    "If the change is intended, update the lockfile, e.g. with `aptos move update-deps`";
+
+const LOCKFILE_HEADER: &str =
+    "# This file pins the remote dependencies of the package. It is generated, do not edit it.\n\n";
+
+/// Pins the remote (git and custom) dependencies of a package, transitively, to the exact
+/// versions they were resolved at. When a package has a lockfile, its builds check out the
+/// pinned git commits rather than the latest ones, and fail if any dependency does not match the
+/// lockfile, so that they are reproducible.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, LockedDependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedDependency {
+    /// The git URL of a git dependency, as declared in the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The git revision of a git dependency, as declared in the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// The git commit the revision was resolved to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The node URL of a custom dependency, as declared in the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_url: Option<String>,
+    /// The address of a custom dependency, as declared in the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// The version a custom dependency was resolved at, e.g. an upgrade number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// A hash of the bytecode of a custom dependency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytecode_hash: Option<String>,
+    /// The digest of the manifest and sources of the dependency.
+    pub digest: String,
+}
+
+impl Lockfile {
+    /// Reads the lockfile of the package at `package_path`, if it has one.
+    pub fn read(package_path: &Path) -> Result<Option<Self>> {
+        let path = package_path.join(LOCKFILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Unable to parse {}", path.display()))
+    }
+
+    pub fn write(&self, package_path: &Path) -> Result<()> {
+        let contents = format!("{}{}", LOCKFILE_HEADER, toml::to_string(self)?);
+        fs::write(package_path.join(LOCKFILE_NAME), contents)?;
+        Ok(())
+    }
+
+    /// Pins the remote dependencies of all packages in a resolved graph, as resolved.
+    pub fn from_resolved_graph(resolved_graph: &ResolvedGraph) -> Result<Self> {
+        let mut dependencies = BTreeMap::new();
+        for package in resolved_graph.package_table.values() {
+            let manifest = &package.source_package;
+            for (dep_name, dep) in manifest
+                .dependencies
+                .iter()
+                .chain(manifest.dev_dependencies.iter())
+            {
+                let dep_package = match resolved_graph.package_table.get(dep_name) {
+                    Some(dep_package) => dep_package,
+                    // Dev dependencies of dependencies are not resolved.
+                    None => continue,
+                };
+                let locked = match LockedDependency::new(dep, &dep_package.package_path)? {
+                    Some(locked) => locked,
+                    None => continue,
+                };
+                match dependencies.get(dep_name.as_str()) {
+                    Some(other) if *other != locked => bail!(
+                        "Dependency '{}' is declared with different sources in the package graph",
+                        dep_name
+                    ),
+                    _ => dependencies.insert(dep_name.to_string(), locked),
+                };
+            }
+        }
+        Ok(Self { dependencies })
+    }
+
+    /// The git commit a git dependency is pinned at, unless the dependency is not in the lockfile
+    /// or is declared differently.
+    pub(crate) fn locked_commit(&self, dep_name: PackageName, dep: &Dependency) -> Option<&str> {
+        let git_info = dep.git_info.as_ref()?;
+        let locked = self.dependencies.get(dep_name.as_str())?;
+        if locked.git.as_deref() != Some(git_info.git_url.as_str())
+            || locked.rev.as_deref() != Some(git_info.git_rev.as_str())
+        {
+            return None;
+        }
+        locked.commit.as_deref()
+    }
+
+    /// Checks that a dependency, resolved at `package_path`, matches the lockfile.
+    pub(crate) fn verify(
+        &self,
+        dep_name: PackageName,
+        dep: &Dependency,
+        package_path: &Path,
+    ) -> Result<()> {
+        let resolved = match LockedDependency::new(dep, package_path)? {
+            Some(resolved) => resolved,
+            None => return Ok(()),
+        };
+        let locked = self.dependencies.get(dep_name.as_str()).ok_or_else(|| {
+            format_err!(
+                "Dependency '{}' is not pinned in {}. {}",
+                dep_name,
+                LOCKFILE_NAME,
+                UPDATE_HINT
+            )
+        })?;
+        let mismatch = if (&locked.git, &locked.rev, &locked.node_url, &locked.address)
+            != (
+                &resolved.git,
+                &resolved.rev,
+                &resolved.node_url,
+                &resolved.address,
+            ) {
+            "is declared differently in the manifest".to_string()
+        } else if locked.commit != resolved.commit {
+            format!(
+                "is checked out at commit {} rather than the pinned {}",
+                resolved.commit.as_deref().unwrap_or("?"),
+                locked.commit.as_deref().unwrap_or("?")
+            )
+        } else if locked.version != resolved.version {
+            format!(
+                "resolved to version {} rather than the pinned {}",
+                resolved.version.map_or("?".to_string(), |v| v.to_string()),
+                locked.version.map_or("?".to_string(), |v| v.to_string())
+            )
+        } else if locked.bytecode_hash != resolved.bytecode_hash {
+            "has different bytecode".to_string()
+        } else if locked.digest != resolved.digest {
+            "has different sources".to_string()
+        } else {
+            return Ok(());
+        };
+        bail!(
+            "Dependency '{}' does not match {}: it {}. {}",
+            dep_name,
+            LOCKFILE_NAME,
+            mismatch,
+            UPDATE_HINT
+        )
+    }
+}
+
+impl LockedDependency {
+    /// Pins a dependency resolved at `package_path`. Returns `None` for local dependencies, which
+    /// are not pinned.
+    fn new(dep: &Dependency, package_path: &Path) -> Result<Option<Self>> {
+        // Only the sources compiled outside of dev mode are pinned, so that the digest does not
+        // depend on the build mode.
+        let digest =
+            ResolvingPackage::get_package_digest_for_config(package_path, &BuildConfig::default())?
+                .to_string();
+        if let Some(git_info) = &dep.git_info {
+            Ok(Some(Self {
+                git: Some(git_info.git_url.to_string()),
+                rev: Some(git_info.git_rev.to_string()),
+                commit: Some(git_head(&git_info.download_to)?),
+                node_url: None,
+                address: None,
+                version: None,
+                bytecode_hash: None,
+                digest,
+            }))
+        } else if let Some(node_info) = &dep.node_info {
+            let version = package_hooks::custom_dependency_version(node_info)?;
+            Ok(Some(Self {
+                git: None,
+                rev: None,
+                commit: None,
+                node_url: Some(node_info.node_url.to_string()),
+                address: Some(node_info.package_address.to_string()),
+                version: version.as_ref().map(|version| version.version),
+                bytecode_hash: version.map(|version| version.bytecode_hash),
+                digest,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
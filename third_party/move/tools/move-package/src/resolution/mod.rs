@@ -3,4 +3,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod digest;
+pub mod lockfile;
 pub mod resolution_graph;
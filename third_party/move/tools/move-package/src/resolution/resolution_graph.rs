@@ -4,7 +4,7 @@
 
 use crate::{
     package_hooks,
-    resolution::digest::compute_digest,
+    resolution::{digest::compute_digest, lockfile::Lockfile},
     source_package::{
         layout::SourcePackageLayout,
         manifest_parser::{parse_move_manifest_string, parse_source_manifest},
@@ -45,7 +45,7 @@ pub type GraphIndex = PackageName;
 type ResolutionTable<T> = BTreeMap<NamedAddress, T>;
 type ResolvingTable = ResolutionTable<ResolvingNamedAddress>;
 type ResolvingGraph = ResolutionGraph<ResolvingNamedAddress>;
-type ResolvingPackage = ResolutionPackage<ResolvingNamedAddress>;
+pub(crate) type ResolvingPackage = ResolutionPackage<ResolvingNamedAddress>;
 
 #[derive(Debug, Clone)]
 pub struct ResolvingNamedAddress {
@@ -95,9 +95,21 @@ pub struct ResolutionPackage<T> {
 
 impl ResolvingGraph {
     pub fn new<W: Write>(
+        root_package: SourceManifest,
+        root_package_path: PathBuf,
+        build_options: BuildConfig,
+        writer: &mut W,
+    ) -> Result<ResolvingGraph> {
+        Self::new_with_lockfile(root_package, root_package_path, build_options, None, writer)
+    }
+
+    /// Resolves the package graph like `new`, pinning and verifying its remote dependencies
+    /// against `lockfile`, if any.
+    pub fn new_with_lockfile<W: Write>(
         root_package: SourceManifest,
         root_package_path: PathBuf,
         mut build_options: BuildConfig,
+        lockfile: Option<&Lockfile>,
         writer: &mut W,
     ) -> Result<ResolvingGraph> {
         if build_options.architecture.is_none() {
@@ -114,7 +126,13 @@ impl ResolvingGraph {
         };
 
         resolution_graph
-            .build_resolution_graph(root_package.clone(), root_package_path, true, writer)
+            .build_resolution_graph(
+                root_package.clone(),
+                root_package_path,
+                true,
+                lockfile,
+                writer,
+            )
             .with_context(|| {
                 format!(
                     "Unable to resolve packages for package '{}'",
@@ -199,6 +217,7 @@ impl ResolvingGraph {
         package: SourceManifest,
         package_path: PathBuf,
         is_root_package: bool,
+        lockfile: Option<&Lockfile>,
         writer: &mut W,
     ) -> Result<()> {
         let package_name = package.package.name;
@@ -252,7 +271,7 @@ impl ResolvingGraph {
             self.graph.add_edge(package_node_id, dep_node_id, ());
 
             let (dep_renaming, dep_resolution_table) = self
-                .process_dependency(dep_name, dep, package_path.clone(), writer)
+                .process_dependency(dep_name, dep, package_path.clone(), lockfile, writer)
                 .with_context(|| {
                     format!(
                         "While resolving dependency '{}' in package '{}'",
@@ -393,21 +412,31 @@ impl ResolvingGraph {
         dep_name_in_pkg: PackageName,
         dep: Dependency,
         root_path: PathBuf,
+        lockfile: Option<&Lockfile>,
         writer: &mut W,
     ) -> Result<(Renaming, ResolvingTable)> {
         Self::download_and_update_if_remote(
             dep_name_in_pkg,
             &dep,
             self.build_options.skip_fetch_latest_git_deps,
+            lockfile.and_then(|lockfile| lockfile.locked_commit(dep_name_in_pkg, &dep)),
             writer,
         )?;
         let (dep_package, dep_package_dir) =
             Self::parse_package_manifest(&dep, &dep_name_in_pkg, root_path)
                 .with_context(|| format!("While processing dependency '{}'", dep_name_in_pkg))?;
-        self.build_resolution_graph(dep_package.clone(), dep_package_dir, false, writer)
-            .with_context(|| {
-                format!("Unable to resolve package dependency '{}'", dep_name_in_pkg)
-            })?;
+        self.build_resolution_graph(
+            dep_package.clone(),
+            dep_package_dir.clone(),
+            false,
+            lockfile,
+            writer,
+        )
+        .with_context(|| format!("Unable to resolve package dependency '{}'", dep_name_in_pkg))?;
+
+        if let Some(lockfile) = lockfile {
+            lockfile.verify(dep_name_in_pkg, &dep, &dep_package_dir)?;
+        }
 
         if dep_name_in_pkg != dep_package.package.name {
             bail!("Name of dependency declared in package '{}' does not match dependency's package name '{}'",
@@ -543,6 +572,7 @@ impl ResolvingGraph {
                 *dep_name,
                 dep,
                 build_options.skip_fetch_latest_git_deps,
+                None,
                 writer,
             )?;
 
@@ -559,15 +589,17 @@ impl ResolvingGraph {
         dep_name: PackageName,
         dep: &Dependency,
         skip_fetch_latest_git_deps: bool,
+        locked_commit: Option<&str>,
         writer: &mut W,
     ) -> Result<()> {
         if let Some(git_info) = &dep.git_info {
             let git_url = git_info.git_url.as_str();
             let git_rev = git_info.git_rev.as_str();
             let git_path = &git_info.download_to.display().to_string();
+            let is_cached = git_info.download_to.exists();
 
             // If there is no cached dependency, download it
-            if !git_info.download_to.exists() {
+            if !is_cached {
                 writeln!(
                     writer,
                     "{} {}",
@@ -595,7 +627,13 @@ impl ResolvingGraph {
                             dep_name
                         )
                     })?;
-            } else if !skip_fetch_latest_git_deps {
+            }
+
+            if let Some(commit) = locked_commit {
+                // The lockfile pins the dependency, so check out the pinned commit rather than the
+                // latest one.
+                Self::checkout_locked_commit(dep_name, git_path, commit, writer)?;
+            } else if is_cached && !skip_fetch_latest_git_deps {
                 // Confirm git is available.
                 confirm_git_available()?;
 
@@ -694,6 +732,53 @@ impl ResolvingGraph {
         }
         Ok(())
     }
+
+    fn checkout_locked_commit<W: Write>(
+        dep_name: PackageName,
+        git_path: &str,
+        commit: &str,
+        writer: &mut W,
+    ) -> Result<()> {
+        if git_head(Path::new(git_path))? == commit {
+            return Ok(());
+        }
+        let checkout = || {
+            Command::new("git")
+                .args(["-C", git_path, "checkout", "--quiet", commit])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false)
+        };
+        if checkout() {
+            return Ok(());
+        }
+
+        // The commit is not in the cached repository yet
+        writeln!(
+            writer,
+            "{} {} {}",
+            "FETCHING LOCKED GIT COMMIT".bold().green(),
+            commit,
+            dep_name,
+        )?;
+        let fetched = Command::new("git")
+            .args(["-C", git_path, "fetch", "origin"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if !fetched || !checkout() {
+            bail!(
+                "Failed to checkout commit '{}' pinned in the lockfile for package '{}'",
+                commit,
+                dep_name
+            )
+        }
+        Ok(())
+    }
 }
 
 impl ResolvingPackage {
@@ -789,7 +874,7 @@ impl ResolvingPackage {
         Ok(places_to_look)
     }
 
-    fn get_package_digest_for_config(
+    pub(crate) fn get_package_digest_for_config(
         package_path: &Path,
         config: &BuildConfig,
     ) -> Result<PackageDigest> {
@@ -946,6 +1031,23 @@ impl ResolvedPackage {
     }
 }
 
+/// Returns the commit checked out in the git repository at `git_path`.
+pub(crate) fn git_head(git_path: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(git_path)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .with_context(|| format!("Failed to run git in {}", git_path.display()))?;
+    if !output.status.success() {
+        bail!(
+            "Failed to get the git commit checked out in {}",
+            git_path.display()
+        )
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
 fn confirm_git_available() -> Result<()> {
     match Command::new("git").arg("--version").output() {
         Ok(_) => Ok(()),
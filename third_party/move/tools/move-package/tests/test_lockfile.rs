@@ -0,0 +1,99 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{resolution::lockfile::Lockfile, BuildConfig};
+use std::{fs, path::Path, process::Command};
+use tempfile::tempdir;
+
+fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=test", "-c", "user.email=test@test"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn write_dep_module(repo: &Path, value: u64) {
+    fs::write(
+        repo.join("sources/dep.move"),
+        format!(
+            "module 0x42::dep {{ public fun value(): u64 {{ {} }} }}",
+            value
+        ),
+    )
+    .unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "--quiet", "-m", "update"]);
+}
+
+fn resolve(root: &Path) -> anyhow::Result<()> {
+    BuildConfig {
+        install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+        ..Default::default()
+    }
+    .resolution_graph_for_package(root, &mut Vec::new())
+    .map(|_| ())
+}
+
+#[test]
+fn test_lockfile_pins_git_dependencies() {
+    let dir = tempdir().unwrap();
+    // Git dependencies are downloaded to MOVE_HOME.
+    std::env::set_var("MOVE_HOME", dir.path().join("move_home"));
+
+    let repo = dir.path().join("dep");
+    fs::create_dir_all(repo.join("sources")).unwrap();
+    fs::write(
+        repo.join("Move.toml"),
+        "[package]\nname = \"Dep\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+    git(&repo, &["init", "--quiet", "--initial-branch=main"]);
+    write_dep_module(&repo, 1);
+    let pinned_commit = git(&repo, &["rev-parse", "HEAD"]);
+
+    let root = dir.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        format!(
+            "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n[dependencies]\n\
+             Dep = {{ git = \"{}\", rev = \"main\" }}\n",
+            repo.display()
+        ),
+    )
+    .unwrap();
+
+    let lockfile = BuildConfig::default()
+        .update_lockfile(&root, &mut Vec::new())
+        .unwrap();
+    assert_eq!(
+        lockfile.dependencies["Dep"].commit.as_deref(),
+        Some(pinned_commit.as_str())
+    );
+    assert_eq!(Lockfile::read(&root).unwrap(), Some(lockfile.clone()));
+
+    // New upstream commits are not picked up while the lockfile pins the dependency.
+    write_dep_module(&repo, 2);
+    resolve(&root).unwrap();
+
+    // A lockfile which does not match the dependency fails the build.
+    let mut stale_lockfile = lockfile.clone();
+    stale_lockfile.dependencies.get_mut("Dep").unwrap().digest = "0".to_string();
+    stale_lockfile.write(&root).unwrap();
+    assert!(resolve(&root).is_err());
+
+    // Updating the lockfile picks up the new commit.
+    let updated_lockfile = BuildConfig::default()
+        .update_lockfile(&root, &mut Vec::new())
+        .unwrap();
+    assert_ne!(
+        updated_lockfile.dependencies["Dep"].commit,
+        Some(pinned_commit)
+    );
+    resolve(&root).unwrap();
+}
@@ -22,8 +22,10 @@ use move_vm_runtime::native_functions::NativeFunctionTable;
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
     collections::BTreeMap,
+    fs::File,
     io::{Result, Write},
     marker::Send,
+    path::PathBuf,
     sync::Mutex,
 };
 
@@ -67,6 +69,10 @@ pub struct UnitTestingConfig {
     #[clap(name = "report_statistics", short = 's', long = "statistics")]
     pub report_statistics: bool,
 
+    /// Write a JSON report of the outcome and gas used of each test to this file
+    #[clap(name = "json_report", long = "json_report")]
+    pub json_report: Option<PathBuf>,
+
     /// Show the storage state at the end of execution of a failing test
     #[clap(name = "global_state_on_error", short = 'g', long = "state_on_error")]
     pub report_storage_on_error: bool,
@@ -130,6 +136,7 @@ impl UnitTestingConfig {
             filter: None,
             num_threads: 8,
             report_statistics: false,
+            json_report: None,
             report_storage_on_error: false,
             report_stacktrace_on_abort: false,
             ignore_compile_warnings: false,
@@ -258,6 +265,10 @@ impl UnitTestingConfig {
             test_results.report_statistics(&shared_writer)?;
         }
 
+        if let Some(json_report) = &self.json_report {
+            test_results.report_json(&mut File::create(json_report)?)?;
+        }
+
         if self.verbose {
             test_results.report_goldens(&shared_writer)?;
         }
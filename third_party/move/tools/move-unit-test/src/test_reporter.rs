@@ -19,6 +19,7 @@ use move_core_types::{effects::ChangeSet, language_storage::ModuleId, vm_status:
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     io::{Result, Write},
@@ -81,6 +82,24 @@ pub struct TestResults {
     test_plan: TestPlan,
 }
 
+/// The outcome of a test, as reported by `TestResults::report_json`.
+#[derive(Debug, Serialize)]
+struct TestReport {
+    name: String,
+    passed: bool,
+    gas_used: u64,
+    elapsed_secs: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TestsReport {
+    passed: usize,
+    failed: usize,
+    tests: Vec<TestReport>,
+}
+
 impl TestRunInfo {
     pub fn new(function_ident: String, elapsed_time: Duration, instructions_executed: u64) -> Self {
         Self {
@@ -160,6 +179,10 @@ impl TestFailure {
     }
 
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
+        self.render_error_impl(test_plan, control::SHOULD_COLORIZE.should_colorize())
+    }
+
+    fn render_error_impl(&self, test_plan: &TestPlan, colorize: bool) -> String {
         let error_string = match &self.failure_reason {
             FailureReason::NoError(message) => message.to_string(),
             FailureReason::Timeout(message) => message.to_string(),
@@ -169,7 +192,7 @@ impl TestFailure {
                     expected.verbiage(/* is_past_tense */ false),
                     actual.verbiage(/* is_past_tense */ true),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, colorize)
             },
             FailureReason::WrongAbortDEPRECATED(message, expected_code, actual) => {
                 let base_message = format!(
@@ -179,7 +202,7 @@ impl TestFailure {
                     expected_code,
                     actual.verbiage(/* is_past_tense */ true),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, colorize)
             },
             FailureReason::UnexpectedError(message, error) => {
                 let prefix = match error.0.status_type() {
@@ -203,7 +226,7 @@ impl TestFailure {
                     message,
                     error.verbiage(/* is_past_tense */ true)
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, colorize)
             },
             FailureReason::Mismatch {
                 move_vm_return_values,
@@ -333,8 +356,9 @@ impl TestFailure {
         test_plan: &TestPlan,
         base_message: String,
         vm_error: &Option<VMError>,
+        colorize: bool,
     ) -> String {
-        let report_diagnostics = if control::SHOULD_COLORIZE.should_colorize() {
+        let report_diagnostics = if colorize {
             diagnostics::report_diagnostics_to_color_buffer
         } else {
             diagnostics::report_diagnostics_to_buffer
@@ -557,6 +581,50 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// Writes the outcome, gas used and duration of each test as a JSON document, for CI tools.
+    pub fn report_json<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let qualified_name = |module_id: &ModuleId, test_run_info: &TestRunInfo| {
+            format!(
+                "{}::{}",
+                format_module_id(module_id),
+                test_run_info.function_ident
+            )
+        };
+        let mut tests = vec![];
+        for (module_id, test_results) in &self.final_statistics.passed {
+            for test_result in test_results {
+                tests.push(TestReport {
+                    name: qualified_name(module_id, test_result),
+                    passed: true,
+                    gas_used: test_result.instructions_executed,
+                    elapsed_secs: test_result.elapsed_time.as_secs_f64(),
+                    failure: None,
+                });
+            }
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            for test_failure in test_failures {
+                let test_run_info = &test_failure.test_run_info;
+                tests.push(TestReport {
+                    name: qualified_name(module_id, test_run_info),
+                    passed: false,
+                    gas_used: test_run_info.instructions_executed,
+                    elapsed_secs: test_run_info.elapsed_time.as_secs_f64(),
+                    failure: Some(test_failure.render_error_impl(&self.test_plan, false)),
+                });
+            }
+        }
+        tests.sort_by(|test1, test2| test1.name.cmp(&test2.name));
+
+        let report = TestsReport {
+            passed: tests.iter().filter(|test| test.passed).count(),
+            failed: tests.iter().filter(|test| !test.passed).count(),
+            tests,
+        };
+        serde_json::to_writer_pretty(&mut *writer, &report)?;
+        writeln!(writer)
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
@@ -245,12 +245,27 @@ pub fn empty_rw_set() -> (Vec<StorageLocation>, Vec<StorageLocation>) {
     (vec![], vec![])
 }
 
+/// Returns the read and write hints of the transaction, or None if hints cannot be derived
+/// for its payload (currently, only coin transfers and account creation are supported).
+/// Unlike AnalyzedTransaction::new, never panics, so can be used for best-effort predictions.
+pub fn try_get_read_write_hints(
+    txn: &SignatureVerifiedTransaction,
+) -> Option<(Vec<StorageLocation>, Vec<StorageLocation>)> {
+    txn.try_get_read_write_hints()
+}
+
 trait AnalyzedTransactionProvider {
-    fn get_read_write_hints(&self) -> (Vec<StorageLocation>, Vec<StorageLocation>);
+    fn try_get_read_write_hints(&self) -> Option<(Vec<StorageLocation>, Vec<StorageLocation>)>;
+
+    fn get_read_write_hints(&self) -> (Vec<StorageLocation>, Vec<StorageLocation>) {
+        self.try_get_read_write_hints().unwrap_or_else(|| {
+            todo!("Only coin transfer and create account entry function transactions are supported for now")
+        })
+    }
 }
 
 impl AnalyzedTransactionProvider for Transaction {
-    fn get_read_write_hints(&self) -> (Vec<StorageLocation>, Vec<StorageLocation>) {
+    fn try_get_read_write_hints(&self) -> Option<(Vec<StorageLocation>, Vec<StorageLocation>)> {
         match self {
             Transaction::UserTransaction(signed_txn) => match signed_txn.payload() {
                 TransactionPayload::EntryFunction(func) => {
@@ -261,48 +276,45 @@ impl AnalyzedTransactionProvider for Transaction {
                     ) {
                         (AccountAddress::ONE, "coin", "transfer") => {
                             let sender_address = signed_txn.sender();
-                            let receiver_address = bcs::from_bytes(&func.args()[0]).unwrap();
-                            rw_set_for_coin_transfer(
+                            let receiver_address = bcs::from_bytes(func.args().first()?).ok()?;
+                            Some(rw_set_for_coin_transfer(
                                 sender_address,
                                 receiver_address,
                                 true,
-                            )
+                            ))
                         },
                         (AccountAddress::ONE, "aptos_account", "transfer") => {
                             let sender_address = signed_txn.sender();
-                            let receiver_address = bcs::from_bytes(&func.args()[0]).unwrap();
-                            rw_set_for_coin_transfer(
+                            let receiver_address = bcs::from_bytes(func.args().first()?).ok()?;
+                            Some(rw_set_for_coin_transfer(
                                 sender_address,
                                 receiver_address,
                                 false,
-                            )
+                            ))
                         },
                         (AccountAddress::ONE, "aptos_account", "create_account") => {
                             let sender_address = signed_txn.sender();
-                            let receiver_address = bcs::from_bytes(&func.args()[0]).unwrap();
-                            rw_set_for_create_account(
-                                sender_address,
-                                receiver_address,
-                            )
+                            let receiver_address = bcs::from_bytes(func.args().first()?).ok()?;
+                            Some(rw_set_for_create_account(sender_address, receiver_address))
                         },
-                        _ => todo!("Only coin transfer and create account transactions are supported for now")
+                        _ => None,
                     }
                 },
-                _ => todo!("Only entry function transactions are supported for now"),
+                _ => None,
             },
-            _ => empty_rw_set(),
+            _ => Some(empty_rw_set()),
         }
     }
 }
 
 impl AnalyzedTransactionProvider for SignatureVerifiedTransaction {
-    fn get_read_write_hints(&self) -> (Vec<StorageLocation>, Vec<StorageLocation>) {
+    fn try_get_read_write_hints(&self) -> Option<(Vec<StorageLocation>, Vec<StorageLocation>)> {
         match self {
-            SignatureVerifiedTransaction::Valid(txn) => txn.get_read_write_hints(),
+            SignatureVerifiedTransaction::Valid(txn) => txn.try_get_read_write_hints(),
             SignatureVerifiedTransaction::Invalid(_) => {
                 // Invalid transactions are not execute by the VM, so we don't need to provide
                 // read/write hints for them.
-                empty_rw_set()
+                Some(empty_rw_set())
             },
         }
     }
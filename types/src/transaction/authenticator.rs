@@ -4,14 +4,16 @@
 
 use crate::{
     account_address::AccountAddress,
-    transaction::{RawTransaction, RawTransactionWithData},
+    transaction::{
+        webauthn::PartialAuthenticatorAssertionResponse, RawTransaction, RawTransactionWithData,
+    },
 };
 use anyhow::{bail, ensure, Error, Result};
 use aptos_crypto::{
     ed25519::{Ed25519PublicKey, Ed25519Signature},
     hash::CryptoHash,
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
-    secp256k1_ecdsa,
+    p256_ecdsa, secp256k1_ecdsa,
     traits::Signature,
     CryptoMaterialError, HashValue, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
@@ -283,6 +285,15 @@ impl TransactionAuthenticator {
         }
     }
 
+    /// Return the authenticators of all signers, i.e., the sender, the secondary signers and the
+    /// fee payer (if any).
+    pub fn all_signers(&self) -> Vec<AccountAuthenticator> {
+        let mut signers = vec![self.sender()];
+        signers.extend(self.secondary_signers());
+        signers.extend(self.fee_payer_signer());
+        signers
+    }
+
     pub fn fee_payer_signer(&self) -> Option<AccountAuthenticator> {
         match self {
             Self::Ed25519 { .. }
@@ -531,6 +542,19 @@ impl AccountAuthenticator {
             Self::MultiKey { authenticator } => authenticator.signatures.len(),
         }
     }
+
+    /// Return the number of WebAuthn signatures included in this account authenticator.
+    pub fn number_of_webauthn_signatures(&self) -> usize {
+        match self {
+            Self::Ed25519 { .. } | Self::MultiEd25519 { .. } => 0,
+            Self::SingleKey { authenticator } => authenticator.signature.is_webauthn() as usize,
+            Self::MultiKey { authenticator } => authenticator
+                .signatures
+                .iter()
+                .filter(|signature| signature.is_webauthn())
+                .count(),
+        }
+    }
 }
 
 /// A struct that represents an account authentication key. An account's address is the last 32
@@ -884,6 +908,9 @@ pub enum AnySignature {
     Secp256k1Ecdsa {
         signature: secp256k1_ecdsa::Signature,
     },
+    WebAuthn {
+        signature: PartialAuthenticatorAssertionResponse,
+    },
 }
 
 impl AnySignature {
@@ -895,6 +922,14 @@ impl AnySignature {
         Self::Secp256k1Ecdsa { signature }
     }
 
+    pub fn webauthn(signature: PartialAuthenticatorAssertionResponse) -> Self {
+        Self::WebAuthn { signature }
+    }
+
+    pub fn is_webauthn(&self) -> bool {
+        matches!(self, Self::WebAuthn { .. })
+    }
+
     pub fn verify<T: Serialize + CryptoHash>(
         &self,
         public_key: &AnyPublicKey,
@@ -907,6 +942,7 @@ impl AnySignature {
             (Self::Secp256k1Ecdsa { signature }, AnyPublicKey::Secp256k1Ecdsa { public_key }) => {
                 signature.verify(message, public_key)
             },
+            (Self::WebAuthn { signature }, _) => signature.verify(message, public_key),
             _ => bail!("Invalid key, signature pairing"),
         }
     }
@@ -920,6 +956,9 @@ pub enum AnyPublicKey {
     Secp256k1Ecdsa {
         public_key: secp256k1_ecdsa::PublicKey,
     },
+    Secp256r1Ecdsa {
+        public_key: p256_ecdsa::PublicKey,
+    },
 }
 
 impl AnyPublicKey {
@@ -931,6 +970,10 @@ impl AnyPublicKey {
         Self::Secp256k1Ecdsa { public_key }
     }
 
+    pub fn secp256r1_ecdsa(public_key: p256_ecdsa::PublicKey) -> Self {
+        Self::Secp256r1Ecdsa { public_key }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         bcs::to_bytes(self).expect("Only unhandleable errors happen here.")
     }
@@ -939,7 +982,10 @@ impl AnyPublicKey {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::transaction::SignedTransaction;
+    use crate::transaction::{
+        webauthn::{self, AssertionSignature},
+        SignedTransaction,
+    };
     use aptos_crypto::{
         ed25519::Ed25519PrivateKey, secp256k1_ecdsa, PrivateKey, SigningKey, Uniform,
     };
@@ -1018,6 +1064,55 @@ mod tests {
         signed_txn.verify_signature().unwrap();
     }
 
+    #[test]
+    fn verify_webauthn_single_key_auth() {
+        let fake_sender = Ed25519PrivateKey::generate_for_testing();
+        let fake_sender_pub = fake_sender.public_key();
+
+        let sender = p256_ecdsa::PrivateKey::generate_for_testing();
+        let sender_pub = sender.public_key();
+
+        let single_sender_auth =
+            AuthenticationKey::any_key(AnyPublicKey::secp256r1_ecdsa(sender_pub.clone()));
+        let single_sender_addr = single_sender_auth.account_address();
+
+        let raw_txn = crate::test_helpers::transaction_test_helpers::get_test_signed_transaction(
+            single_sender_addr,
+            0,
+            &fake_sender,
+            fake_sender_pub,
+            None,
+            0,
+            0,
+            None,
+        )
+        .into_raw_transaction();
+
+        let challenge = webauthn::challenge(&raw_txn).unwrap();
+        let authenticator_data = vec![0u8; 37];
+        let client_data_json = format!(
+            r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://aptoslabs.com"}}"#,
+            base64::encode_config(challenge, base64::URL_SAFE_NO_PAD)
+        )
+        .into_bytes();
+        let signature = sender.sign_arbitrary_message(&webauthn::verification_data(
+            &authenticator_data,
+            &client_data_json,
+        ));
+        let sk_auth = SingleKeyAuthenticator::new(
+            AnyPublicKey::secp256r1_ecdsa(sender_pub),
+            AnySignature::webauthn(PartialAuthenticatorAssertionResponse::new(
+                AssertionSignature::Secp256r1Ecdsa { signature },
+                authenticator_data,
+                client_data_json,
+            )),
+        );
+        let account_auth = AccountAuthenticator::single_key(sk_auth);
+        assert_eq!(account_auth.number_of_webauthn_signatures(), 1);
+        let signed_txn = SignedTransaction::new_single_sender(raw_txn, account_auth);
+        signed_txn.verify_signature().unwrap();
+    }
+
     #[test]
     fn verify_multi_key_auth() {
         let sender0 = Ed25519PrivateKey::generate_for_testing();
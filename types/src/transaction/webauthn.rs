@@ -0,0 +1,205 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for [WebAuthn](https://www.w3.org/TR/webauthn-2/) assertions (e.g., produced by device
+//! passkeys) as transaction signatures.
+//!
+//! The WebAuthn challenge of an assertion is the SHA3-256 hash of the signing message of the
+//! transaction. The authenticator signs `authenticator_data || SHA2-256(client_data_json)`, where
+//! `client_data_json` embeds the base64url-encoded challenge.
+
+use crate::transaction::authenticator::AnyPublicKey;
+use anyhow::{bail, ensure, Context, Result};
+use aptos_crypto::{
+    hash::CryptoHash,
+    p256_ecdsa,
+    traits::{signing_message, Signature},
+    HashValue,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The `type` of the client data of a WebAuthn assertion.
+pub const WEBAUTHN_GET_CLIENT_DATA_TYPE: &str = "webauthn.get";
+
+/// The signature of a WebAuthn assertion.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum AssertionSignature {
+    Secp256r1Ecdsa { signature: p256_ecdsa::Signature },
+}
+
+/// The parts of a WebAuthn `AuthenticatorAssertionResponse` that are required to verify it,
+/// see <https://www.w3.org/TR/webauthn-2/#authenticatorassertionresponse>.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct PartialAuthenticatorAssertionResponse {
+    signature: AssertionSignature,
+    #[serde(with = "serde_bytes")]
+    authenticator_data: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    client_data_json: Vec<u8>,
+}
+
+/// The fields of the client data of a WebAuthn assertion that are checked during verification,
+/// see <https://www.w3.org/TR/webauthn-2/#dictionary-client-data>.
+#[derive(Deserialize)]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    ty: String,
+    challenge: String,
+}
+
+impl PartialAuthenticatorAssertionResponse {
+    pub fn new(
+        signature: AssertionSignature,
+        authenticator_data: Vec<u8>,
+        client_data_json: Vec<u8>,
+    ) -> Self {
+        Self {
+            signature,
+            authenticator_data,
+            client_data_json,
+        }
+    }
+
+    pub fn signature(&self) -> &AssertionSignature {
+        &self.signature
+    }
+
+    pub fn authenticator_data(&self) -> &[u8] {
+        &self.authenticator_data
+    }
+
+    pub fn client_data_json(&self) -> &[u8] {
+        &self.client_data_json
+    }
+
+    /// Return Ok if the assertion signs the challenge of `message` under `public_key`, Err
+    /// otherwise.
+    pub fn verify<T: Serialize + CryptoHash>(
+        &self,
+        message: &T,
+        public_key: &AnyPublicKey,
+    ) -> Result<()> {
+        let client_data: CollectedClientData = serde_json::from_slice(&self.client_data_json)
+            .context("Failed to parse the WebAuthn client data")?;
+        ensure!(
+            client_data.ty == WEBAUTHN_GET_CLIENT_DATA_TYPE,
+            "Invalid WebAuthn client data type, {}",
+            client_data.ty
+        );
+        ensure!(
+            client_data.challenge == encode_challenge(&challenge(message)?),
+            "The WebAuthn challenge does not match the signing message"
+        );
+
+        let verification_data = verification_data(&self.authenticator_data, &self.client_data_json);
+        match (&self.signature, public_key) {
+            (
+                AssertionSignature::Secp256r1Ecdsa { signature },
+                AnyPublicKey::Secp256r1Ecdsa { public_key },
+            ) => signature.verify_arbitrary_msg(&verification_data, public_key),
+            _ => bail!("Invalid key, WebAuthn signature pairing"),
+        }
+    }
+}
+
+/// Returns the WebAuthn challenge for `message`, i.e., the SHA3-256 hash of its signing message.
+/// The challenge must be passed to `navigator.credentials.get()` when requesting the assertion.
+pub fn challenge<T: Serialize + CryptoHash>(message: &T) -> Result<Vec<u8>> {
+    Ok(HashValue::sha3_256_of(&signing_message(message)?).to_vec())
+}
+
+/// Returns the data that is signed by the authenticator for an assertion, i.e.,
+/// `authenticator_data || SHA2-256(client_data_json)`.
+pub fn verification_data(authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    let mut data = authenticator_data.to_vec();
+    data.extend_from_slice(Sha256::digest(client_data_json).as_slice());
+    data
+}
+
+/// Encodes the challenge as in the client data, i.e., base64url without padding.
+fn encode_challenge(challenge: &[u8]) -> String {
+    base64::encode_config(challenge, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{RawTransaction, Script, TransactionPayload},
+    };
+    use aptos_crypto::{PrivateKey, SigningKey, Uniform};
+
+    fn test_raw_txn() -> RawTransaction {
+        RawTransaction::new(
+            AccountAddress::random(),
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            0,
+            0,
+            0,
+            ChainId::new(10),
+        )
+    }
+
+    fn assertion(
+        private_key: &p256_ecdsa::PrivateKey,
+        client_data_json: String,
+    ) -> PartialAuthenticatorAssertionResponse {
+        let authenticator_data = vec![0x49, 0x96, 0x0D, 0xE5, 0x88, 0x0E, 0x8C, 0x68, 0x74];
+        let signature = private_key.sign_arbitrary_message(&verification_data(
+            &authenticator_data,
+            client_data_json.as_bytes(),
+        ));
+        PartialAuthenticatorAssertionResponse::new(
+            AssertionSignature::Secp256r1Ecdsa { signature },
+            authenticator_data,
+            client_data_json.into_bytes(),
+        )
+    }
+
+    fn client_data_json(ty: &str, challenge: &[u8]) -> String {
+        format!(
+            r#"{{"type":"{}","challenge":"{}","origin":"https://aptoslabs.com","crossOrigin":false}}"#,
+            ty,
+            encode_challenge(challenge)
+        )
+    }
+
+    #[test]
+    fn verify_webauthn_assertion() {
+        let private_key = p256_ecdsa::PrivateKey::generate_for_testing();
+        let public_key = AnyPublicKey::secp256r1_ecdsa(private_key.public_key());
+        let raw_txn = test_raw_txn();
+        let challenge = challenge(&raw_txn).unwrap();
+
+        let valid = assertion(
+            &private_key,
+            client_data_json(WEBAUTHN_GET_CLIENT_DATA_TYPE, &challenge),
+        );
+        valid.verify(&raw_txn, &public_key).unwrap();
+        // The assertion doesn't verify for another transaction.
+        valid.verify(&test_raw_txn(), &public_key).unwrap_err();
+        // Nor under another key.
+        let other_private_key = p256_ecdsa::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let other_public_key = AnyPublicKey::secp256r1_ecdsa(other_private_key.public_key());
+        valid.verify(&raw_txn, &other_public_key).unwrap_err();
+
+        let wrong_type = assertion(
+            &private_key,
+            client_data_json("webauthn.create", &challenge),
+        );
+        wrong_type.verify(&raw_txn, &public_key).unwrap_err();
+
+        let wrong_challenge = assertion(
+            &private_key,
+            client_data_json(WEBAUTHN_GET_CLIENT_DATA_TYPE, &[0u8; 32]),
+        );
+        wrong_challenge.verify(&raw_txn, &public_key).unwrap_err();
+
+        let malformed = assertion(&private_key, "not json".to_string());
+        malformed.verify(&raw_txn, &public_key).unwrap_err();
+    }
+}